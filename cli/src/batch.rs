@@ -0,0 +1,180 @@
+//! `create-batch`: mass grant creation from a CSV file.
+//!
+//! Each row is `beneficiary,amount,template`, after an optional header row. The beneficiary is a wallet, paid
+//! through its associated token account of the mint. The template is one of
+//! * `cliff:RELEASE_TIME` - the whole amount released at once
+//! * `linear:START:INTERVAL:COUNT` - COUNT tranches every INTERVAL seconds after START, floored like the program's
+//!   linear curve with the remainder in the last tranche
+//!
+//! Grants are packed into as few transactions as fit, every transaction is retried, and the outcome of every row
+//! is written to a CSV report.
+
+use anyhow::{anyhow, bail, Context, Result};
+use solana_sdk::hash::hashv;
+use solana_sdk::packet::PACKET_DATA_SIZE;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::Transaction;
+use std::fs;
+use std::str::FromStr;
+use token_vesting_client::token_vesting::math::linear_tranches;
+use token_vesting_client::{find_vesting_address, Grant, Schedule, VestingClient};
+
+pub struct Row {
+    pub beneficiary: Pubkey,
+    pub amount: u64,
+    pub template: String,
+}
+
+pub struct BatchConfig {
+    pub mint: Pubkey,
+    pub source_token: Pubkey,
+    // Distinguishes the vesting accounts of different batches paying the same beneficiaries
+    pub seed_prefix: String,
+    pub retries: u32,
+}
+
+enum Outcome {
+    Created(Signature),
+    AlreadyExists,
+    Failed(String),
+}
+
+/// Reads the grant rows of `path`, skipping a header row
+pub fn read_rows(path: &str) -> Result<Vec<Row>> {
+    let content = fs::read_to_string(path).with_context(|| format!("reading {}", path))?;
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .filter(|(i, line)| !(*i == 0 && line.to_lowercase().starts_with("beneficiary")))
+        .map(|(i, line)| parse_row(line).with_context(|| format!("{} line {}", path, i + 1)))
+        .collect()
+}
+
+fn parse_row(line: &str) -> Result<Row> {
+    let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+    match fields.as_slice() {
+        [beneficiary, amount, template] => Ok(Row {
+            beneficiary: Pubkey::from_str(beneficiary).map_err(|e| anyhow!("beneficiary {}: {}", beneficiary, e))?,
+            amount: amount.parse().with_context(|| format!("amount {}", amount))?,
+            template: template.to_string(),
+        }),
+        _ => bail!("expected beneficiary,amount,template, got {}", line),
+    }
+}
+
+/// Expands a schedule template for `amount`
+pub fn schedules(template: &str, amount: u64) -> Result<Vec<Schedule>> {
+    let parts: Vec<&str> = template.split(':').collect();
+    let tranches: Vec<(u64, u64)> = match parts.as_slice() {
+        ["cliff", release_time] => vec![(release_time.parse()?, amount)],
+        ["linear", start, interval, count] => {
+            let count: u64 = count.parse()?;
+            if count == 0 {
+                bail!("template {} has no tranche", template);
+            }
+            linear_tranches(amount, start.parse()?, interval.parse()?, count).collect()
+        }
+        _ => bail!("unknown schedule template {}", template),
+    };
+    Ok(tranches
+        .into_iter()
+        .map(|(release_time, amount)| Schedule { release_time, amount, condition: None })
+        .collect())
+}
+
+/// Returns the seeds of the grant of row `index`, unique per batch prefix, beneficiary and row
+pub fn grant_seeds(prefix: &str, beneficiary: &Pubkey, index: usize) -> [u8; 31] {
+    let hash = hashv(&[prefix.as_bytes(), beneficiary.as_ref(), &(index as u64).to_le_bytes()]);
+    let mut seeds = [0u8; 31];
+    seeds.copy_from_slice(&hash.as_ref()[..31]);
+    seeds
+}
+
+/// Creates the grants of `rows` and writes the outcome of every row to `report`
+pub fn create_batch(client: &VestingClient, keypair: &Keypair, config: &BatchConfig, rows: &[Row], report: &str) -> Result<()> {
+    let grants = rows
+        .iter()
+        .enumerate()
+        .map(|(i, row)| {
+            Ok(Grant {
+                seeds: grant_seeds(&config.seed_prefix, &row.beneficiary, i),
+                source_token: config.source_token,
+                mint: config.mint,
+                destination_token: token_vesting_client::get_associated_token_address(&row.beneficiary, &config.mint),
+                schedules: schedules(&row.template, row.amount).with_context(|| format!("row {}", i + 1))?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut outcomes = Vec::with_capacity(grants.len());
+    for chunk in chunk_grants(&grants, &keypair.pubkey())? {
+        let chunk_outcomes = send_chunk(client, keypair, config.retries, chunk);
+        outcomes.extend(chunk_outcomes);
+    }
+
+    let mut lines = vec!["beneficiary,amount,template,vesting,destination,status,detail".to_string()];
+    for ((row, grant), outcome) in rows.iter().zip(&grants).zip(&outcomes) {
+        let (status, detail) = match outcome {
+            Outcome::Created(signature) => ("created", signature.to_string()),
+            Outcome::AlreadyExists => ("exists", String::new()),
+            Outcome::Failed(error) => ("failed", error.replace(',', ";").replace('\n', " ")),
+        };
+        lines.push(format!("{},{},{},{},{},{},{}", row.beneficiary, row.amount, row.template,
+                           find_vesting_address(&grant.seeds).0, grant.destination_token, status, detail));
+    }
+    fs::write(report, lines.join("\n") + "\n").with_context(|| format!("writing {}", report))?;
+
+    let failed = outcomes.iter().filter(|o| matches!(o, Outcome::Failed(_))).count();
+    println!("{} grants, {} failed, report written to {}", grants.len(), failed, report);
+    Ok(())
+}
+
+/// Packs consecutive grants into chunks whose transaction fits in a packet
+fn chunk_grants<'a>(grants: &'a [Grant], payer: &Pubkey) -> Result<Vec<&'a [Grant]>> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < grants.len() {
+        let mut end = start + 1;
+        if transaction_size(&grants[start..end], payer)? > PACKET_DATA_SIZE {
+            bail!("the grant of row {} doesn't fit in a transaction, split its schedule", start + 1);
+        }
+        while end < grants.len() && transaction_size(&grants[start..end + 1], payer)? <= PACKET_DATA_SIZE {
+            end += 1;
+        }
+        chunks.push(&grants[start..end]);
+        start = end;
+    }
+    Ok(chunks)
+}
+
+fn transaction_size(grants: &[Grant], payer: &Pubkey) -> Result<usize> {
+    let instructions: Vec<_> = grants.iter().flat_map(|g| g.instructions(payer, payer)).collect();
+    let transaction = Transaction::new_with_payer(&instructions, Some(payer));
+    Ok(bincode::serialized_size(&transaction)? as usize)
+}
+
+/// Sends the grants of `chunk` in one transaction, retried up to `retries` times. A retry first checks whether
+/// a previous attempt landed after all
+fn send_chunk(client: &VestingClient, keypair: &Keypair, retries: u32, chunk: &[Grant]) -> Vec<Outcome> {
+    let instructions: Vec<_> = chunk.iter().flat_map(|g| g.instructions(&keypair.pubkey(), &keypair.pubkey())).collect();
+    let mut error = String::new();
+    for attempt in 0..=retries {
+        if attempt > 0 && chunk.iter().all(|g| is_created(client, g)) {
+            return chunk.iter().map(|_| Outcome::AlreadyExists).collect();
+        }
+        match client.send(&instructions, keypair, &[keypair]) {
+            Ok(signature) => return chunk.iter().map(|_| Outcome::Created(signature)).collect(),
+            Err(e) => {
+                error = e.to_string();
+                eprintln!("attempt {} of {} failed: {}", attempt + 1, retries + 1, error);
+            }
+        }
+    }
+    chunk.iter().map(|_| Outcome::Failed(error.clone())).collect()
+}
+
+fn is_created(client: &VestingClient, grant: &Grant) -> bool {
+    matches!(client.fetch_vesting_by_seeds(&grant.seeds), Ok(vesting) if vesting.is_initialized)
+}
@@ -0,0 +1,63 @@
+//! `inspect`: human-readable state of vesting contracts, with a table of their tranches.
+
+use anyhow::Result;
+use chrono::{Local, TimeZone};
+use solana_sdk::pubkey::Pubkey;
+use token_vesting_client::token_vesting::math;
+use token_vesting_client::token_vesting::NextAction;
+use token_vesting_client::{claimable_at, locked_at, Vesting};
+
+/// Prints `vesting` as of the cluster time `now`
+pub fn print_vesting(address: &Pubkey, vesting: &Vesting, now: i64) -> Result<()> {
+    println!("Vesting account: {}", address);
+    println!("Mint:            {}", vesting.mint_address);
+    println!("Creator:         {}", vesting.creator);
+    println!("Destination:     {}", vesting.destination_address);
+    if let Some(owner) = vesting.destination_owner {
+        println!("Destination owner: {}", owner);
+    }
+    println!("State:           {}", state(vesting));
+    println!();
+
+    println!("  #  Release (local time)                     Amount  Status");
+    for (i, tranche) in vesting.schedule.iter().enumerate() {
+        println!("{:>3}  {:<25}  {:>20}  {}", i + 1, local_time(tranche.release_time as i64), tranche.amount,
+                 tranche_status(tranche, now));
+    }
+    println!();
+
+    println!("Remaining: {}", math::total_amount(&vesting.schedule).unwrap_or(u64::MAX));
+    println!("Locked:    {}", locked_at(vesting, now));
+    println!("Claimable: {}", claimable_at(vesting, now));
+    Ok(())
+}
+
+fn state(vesting: &Vesting) -> String {
+    match vesting.next_action {
+        NextAction::AcceptPending => "pending, waiting for the destination owner to accept".to_string(),
+        NextAction::Claimable => "claimable".to_string(),
+        NextAction::WaitUntil(release_time) => format!("vesting, next release {}", local_time(release_time)),
+        NextAction::ConditionPending => "waiting for a release condition".to_string(),
+        NextAction::Completed => "completed, everything claimed".to_string(),
+    }
+}
+
+// Claimed tranches are zeroed by the program, their original amount isn't recorded
+fn tranche_status(tranche: &token_vesting_client::Schedule, now: i64) -> &'static str {
+    if tranche.condition.is_some() {
+        "waiting for condition"
+    } else if !math::is_released(tranche, now) {
+        "locked"
+    } else if tranche.amount == 0 {
+        "claimed"
+    } else {
+        "claimable"
+    }
+}
+
+fn local_time(timestamp: i64) -> String {
+    match Local.timestamp_opt(timestamp, 0).single() {
+        Some(time) => time.format("%Y-%m-%d %H:%M:%S %Z").to_string(),
+        None => timestamp.to_string(),
+    }
+}
@@ -0,0 +1,189 @@
+//! `token-vesting` command line interface, operating vesting contracts with a keypair file over RPC.
+
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{App, AppSettings, Arg, ArgGroup, ArgMatches, SubCommand};
+use solana_client::rpc_client::RpcClient;
+use solana_sdk::commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{read_keypair_file, Keypair, Signer};
+use std::str::FromStr;
+use token_vesting_client::{find_vesting_address, integration, Grant, Schedule, Vesting, VestingClient};
+
+mod batch;
+mod inspect;
+
+fn main() -> Result<()> {
+    let seed = Arg::with_name("seed")
+        .long("seed")
+        .takes_value(true)
+        .required(true)
+        .help("The seed the vesting account is derived from, at most 31 bytes");
+    let matches = App::new("token-vesting")
+        .about("Operates token vesting contracts")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .arg(Arg::with_name("url").long("url").short("u").global(true).takes_value(true)
+            .default_value("http://localhost:8899").help("The RPC URL of the cluster"))
+        .arg(Arg::with_name("keypair").long("keypair").short("k").global(true).takes_value(true)
+            .help("The keypair paying and signing the transactions [default: ~/.config/solana/id.json]"))
+        .subcommand(SubCommand::with_name("init-create")
+            .about("Allocates, escrows and funds a vesting contract paid from a token account of the keypair")
+            .arg(seed.clone())
+            .arg(Arg::with_name("mint").long("mint").takes_value(true).required(true))
+            .arg(Arg::with_name("source-token").long("source-token").takes_value(true).required(true))
+            .arg(Arg::with_name("destination-token").long("destination-token").takes_value(true).required(true))
+            .arg(Arg::with_name("schedule").long("schedule").takes_value(true).required(true).multiple(true)
+                .number_of_values(1).help("A tranche as RELEASE_TIME:AMOUNT, the release time in unix seconds")))
+        .subcommand(SubCommand::with_name("create-batch")
+            .about("Creates the grants of a CSV file of beneficiary,amount,template rows, paid from a token account of the keypair")
+            .arg(Arg::with_name("csv").long("csv").takes_value(true).required(true))
+            .arg(Arg::with_name("mint").long("mint").takes_value(true).required(true))
+            .arg(Arg::with_name("source-token").long("source-token").takes_value(true).required(true))
+            .arg(Arg::with_name("seed-prefix").long("seed-prefix").takes_value(true).required(true)
+                .help("Distinguishes the vesting accounts of this batch from other batches"))
+            .arg(Arg::with_name("retries").long("retries").takes_value(true).default_value("3"))
+            .arg(Arg::with_name("report").long("report").takes_value(true).default_value("grants-report.csv")))
+        .subcommand(SubCommand::with_name("unlock")
+            .about("Unlocks the released amount of a vesting contract")
+            .arg(seed.clone()))
+        .subcommand(SubCommand::with_name("change-destination")
+            .about("Changes the destination of a vesting contract, signed by the owner of the current destination")
+            .arg(seed.clone())
+            .arg(Arg::with_name("new-destination").long("new-destination").takes_value(true).required(true)))
+        .subcommand(SubCommand::with_name("show")
+            .about("Prints a vesting contract")
+            .arg(seed))
+        .subcommand(SubCommand::with_name("inspect")
+            .about("Prints vesting contracts with a table of their tranches")
+            .arg(Arg::with_name("address").long("address").takes_value(true).help("A vesting account"))
+            .arg(Arg::with_name("creator").long("creator").takes_value(true).help("The creator of the contracts"))
+            .arg(Arg::with_name("beneficiary").long("beneficiary").takes_value(true)
+                .help("The wallet owning the destinations of the contracts"))
+            .group(ArgGroup::with_name("contracts").args(&["address", "creator", "beneficiary"]).required(true)))
+        .get_matches();
+
+    let client = VestingClient::new(RpcClient::new_with_commitment(
+        matches.value_of("url").unwrap().to_string(),
+        CommitmentConfig::confirmed(),
+    ));
+    let keypair = || load_keypair(matches.value_of("keypair"));
+
+    match matches.subcommand() {
+        ("init-create", Some(m)) => init_create(&client, &keypair()?, m),
+        ("create-batch", Some(m)) => create_batch(&client, &keypair()?, m),
+        ("unlock", Some(m)) => unlock(&client, &keypair()?, m),
+        ("change-destination", Some(m)) => change_destination(&client, &keypair()?, m),
+        ("show", Some(m)) => show(&client, m),
+        ("inspect", Some(m)) => inspect(&client, m),
+        _ => unreachable!(),
+    }
+}
+
+fn init_create(client: &VestingClient, keypair: &Keypair, matches: &ArgMatches) -> Result<()> {
+    let grant = Grant {
+        seeds: parse_seeds(matches.value_of("seed").unwrap())?,
+        source_token: parse_pubkey(matches, "source-token")?,
+        mint: parse_pubkey(matches, "mint")?,
+        destination_token: parse_pubkey(matches, "destination-token")?,
+        schedules: matches.values_of("schedule").unwrap().map(parse_schedule).collect::<Result<_>>()?,
+    };
+    let signature = client.create_grant(keypair, keypair, &grant)?;
+    println!("Vesting account: {}", find_vesting_address(&grant.seeds).0);
+    println!("Signature: {}", signature);
+    Ok(())
+}
+
+fn create_batch(client: &VestingClient, keypair: &Keypair, matches: &ArgMatches) -> Result<()> {
+    let config = batch::BatchConfig {
+        mint: parse_pubkey(matches, "mint")?,
+        source_token: parse_pubkey(matches, "source-token")?,
+        seed_prefix: matches.value_of("seed-prefix").unwrap().to_string(),
+        retries: matches.value_of("retries").unwrap().parse().context("--retries")?,
+    };
+    let rows = batch::read_rows(matches.value_of("csv").unwrap())?;
+    batch::create_batch(client, keypair, &config, &rows, matches.value_of("report").unwrap())
+}
+
+fn unlock(client: &VestingClient, keypair: &Keypair, matches: &ArgMatches) -> Result<()> {
+    let seeds = parse_seeds(matches.value_of("seed").unwrap())?;
+    let (address, vesting) = fetch(client, &seeds)?;
+    let escrow = client.find_escrow(&address, &vesting.mint_address)?;
+    let ix = integration::unlock(seeds, escrow, vesting.mint_address, vesting.destination_address);
+    println!("Signature: {}", client.send(&[ix], keypair, &[keypair])?);
+    Ok(())
+}
+
+fn change_destination(client: &VestingClient, keypair: &Keypair, matches: &ArgMatches) -> Result<()> {
+    let seeds = parse_seeds(matches.value_of("seed").unwrap())?;
+    let (_, vesting) = fetch(client, &seeds)?;
+    let ix = integration::change_destination(seeds, vesting.destination_address, keypair.pubkey(),
+                                             parse_pubkey(matches, "new-destination")?);
+    println!("Signature: {}", client.send(&[ix], keypair, &[keypair])?);
+    Ok(())
+}
+
+fn show(client: &VestingClient, matches: &ArgMatches) -> Result<()> {
+    let seeds = parse_seeds(matches.value_of("seed").unwrap())?;
+    let (address, vesting) = fetch(client, &seeds)?;
+    inspect::print_vesting(&address, &vesting, client.cluster_time()?)
+}
+
+fn inspect(client: &VestingClient, matches: &ArgMatches) -> Result<()> {
+    let vestings = if matches.is_present("address") {
+        let address = parse_pubkey(matches, "address")?;
+        vec![(address, client.fetch_vesting(&address).with_context(|| format!("fetching vesting account {}", address))?)]
+    } else if matches.is_present("creator") {
+        client.fetch_vestings_by_creator(&parse_pubkey(matches, "creator")?)?
+    } else {
+        client.fetch_vestings_by_beneficiary(&parse_pubkey(matches, "beneficiary")?)?
+    };
+    if vestings.is_empty() {
+        println!("No vesting contract found");
+    }
+
+    let now = client.cluster_time()?;
+    for (i, (address, vesting)) in vestings.iter().enumerate() {
+        if i > 0 {
+            println!();
+        }
+        inspect::print_vesting(address, vesting, now)?;
+    }
+    Ok(())
+}
+
+fn fetch(client: &VestingClient, seeds: &[u8; 31]) -> Result<(Pubkey, Vesting)> {
+    let address = find_vesting_address(seeds).0;
+    let vesting = client.fetch_vesting(&address).with_context(|| format!("fetching vesting account {}", address))?;
+    Ok((address, vesting))
+}
+
+fn load_keypair(path: Option<&str>) -> Result<Keypair> {
+    let path = match path {
+        Some(path) => path.to_string(),
+        None => format!("{}/.config/solana/id.json", std::env::var("HOME")?),
+    };
+    read_keypair_file(&path).map_err(|e| anyhow!("reading keypair {}: {}", path, e))
+}
+
+/// Zero pads the bytes of `seed` to the 31 byte seeds of a vesting account
+fn parse_seeds(seed: &str) -> Result<[u8; 31]> {
+    if seed.len() > 31 {
+        bail!("seed {} is longer than 31 bytes", seed);
+    }
+    let mut seeds = [0u8; 31];
+    seeds[..seed.len()].copy_from_slice(seed.as_bytes());
+    Ok(seeds)
+}
+
+fn parse_pubkey(matches: &ArgMatches, name: &str) -> Result<Pubkey> {
+    let value = matches.value_of(name).unwrap();
+    Pubkey::from_str(value).map_err(|e| anyhow!("--{} {}: {}", name, value, e))
+}
+
+fn parse_schedule(tranche: &str) -> Result<Schedule> {
+    let (release_time, amount) = tranche.split_once(':').ok_or_else(|| anyhow!("tranche {} isn't RELEASE_TIME:AMOUNT", tranche))?;
+    Ok(Schedule {
+        release_time: release_time.parse().with_context(|| format!("release time of {}", tranche))?,
+        amount: amount.parse().with_context(|| format!("amount of {}", tranche))?,
+        condition: None,
+    })
+}
@@ -0,0 +1,108 @@
+//! `getProgramAccounts` filters selecting vesting accounts, for apps querying grants without an indexer.
+//!
+//! The destination, mint and creator of a contract sit at fixed offsets right after the account discriminator, so
+//! a memcmp filter on them leaves the scan to the RPC node. Combine the filters of one query in the same `Vec`, the
+//! node returning the accounts matching all of them.
+
+use anchor_lang::Discriminator;
+use solana_client::rpc_filter::{Memcmp, MemcmpEncodedBytes, RpcFilterType};
+use solana_sdk::pubkey::Pubkey;
+use token_vesting::{Vesting, VESTING_ACCOUNT_BASE_SIZE, VESTING_SCHEDULE_SIZE};
+
+/// Offset of `Vesting::destination_address`, right after the account discriminator
+pub const DESTINATION_ADDRESS_OFFSET: usize = 8;
+/// Offset of `Vesting::mint_address`
+pub const MINT_ADDRESS_OFFSET: usize = 8 + 32;
+/// Offset of `Vesting::creator`, after the one byte `layout_version`
+pub const CREATOR_OFFSET: usize = 8 + 32 + 32 + 1;
+
+/// Selects the vesting accounts among the program accounts
+pub fn is_vesting() -> RpcFilterType {
+    memcmp(0, Vesting::discriminator().to_vec())
+}
+
+/// Selects the vesting accounts paying `destination_token`
+pub fn paying(destination_token: &Pubkey) -> Vec<RpcFilterType> {
+    vec![is_vesting(), memcmp(DESTINATION_ADDRESS_OFFSET, destination_token.to_bytes().to_vec())]
+}
+
+/// Selects the vesting accounts of `mint`
+pub fn of_mint(mint: &Pubkey) -> Vec<RpcFilterType> {
+    vec![is_vesting(), memcmp(MINT_ADDRESS_OFFSET, mint.to_bytes().to_vec())]
+}
+
+/// Selects the vesting accounts created by `creator`
+pub fn created_by(creator: &Pubkey) -> Vec<RpcFilterType> {
+    vec![is_vesting(), memcmp(CREATOR_OFFSET, creator.to_bytes().to_vec())]
+}
+
+/// Selects the accounts allocated for `number_of_schedules` schedules, to add to the filters above
+pub fn with_schedules(number_of_schedules: u32) -> RpcFilterType {
+    RpcFilterType::DataSize(VESTING_ACCOUNT_BASE_SIZE + number_of_schedules as u64 * VESTING_SCHEDULE_SIZE)
+}
+
+fn memcmp(offset: usize, bytes: Vec<u8>) -> RpcFilterType {
+    RpcFilterType::Memcmp(Memcmp { offset, bytes: MemcmpEncodedBytes::Bytes(bytes), encoding: None })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AccountSerialize;
+    use token_vesting::{NextAction, UnlockPolicy};
+
+    #[test]
+    fn filters_match_the_serialized_account() {
+        let (destination, mint, creator) = (Pubkey::new_unique(), Pubkey::new_unique(), Pubkey::new_unique());
+        let vesting = Vesting {
+            destination_address: destination,
+            mint_address: mint,
+            layout_version: token_vesting::VESTING_LAYOUT_VERSION,
+            creator,
+            is_initialized: true,
+            min_claim_amount: 0,
+            voter_weight: None,
+            yield_config: None,
+            guarantor: None,
+            vote_delegate: None,
+            revoker: None,
+            receipt_mint: None,
+            transfer_approval_required: false,
+            bridge_target: None,
+            claim_delegate: None,
+            permit_nonce: 0,
+            is_accepted: true,
+            destination_owner: None,
+            unlock_policy: UnlockPolicy::Anyone,
+            destination_change_count: 0,
+            claim_count: 0,
+            next_action: NextAction::Completed,
+            claim_hook: None,
+            shared_vault: None,
+            cohort: None,
+            is_paused: false,
+            payroll_payer: None,
+            terminated_at: None,
+            probation_end: None,
+            salary_price_feed: None,
+            round: None,
+            rollup: None,
+            partner: None,
+            is_rent_sponsored: false,
+            schedule: vec![],
+        };
+        let mut data = vec![0; VESTING_ACCOUNT_BASE_SIZE as usize];
+        vesting.try_serialize(&mut data.as_mut_slice()).unwrap();
+
+        let matches = |filter: &RpcFilterType| match filter {
+            RpcFilterType::Memcmp(memcmp) => memcmp.bytes_match(&data),
+            RpcFilterType::DataSize(size) => *size as usize == data.len(),
+            RpcFilterType::TokenAccountState => false,
+        };
+        for filters in [paying(&destination), of_mint(&mint), created_by(&creator)] {
+            assert!(filters.iter().chain(Some(&with_schedules(0))).all(matches));
+        }
+        assert!(!paying(&mint).iter().all(matches));
+        assert!(!matches(&with_schedules(1)));
+    }
+}
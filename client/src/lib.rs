@@ -0,0 +1,295 @@
+//! Off-chain Rust client of the token vesting program over `solana-client`.
+//!
+//! Wraps the address derivation, claim math and instruction builders of `token_vesting::integration` with
+//! account fetching and transaction sending, so bots and backends don't hand-roll the Borsh layouts.
+
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Result};
+use solana_address_lookup_table_program::instruction::{create_lookup_table, extend_lookup_table};
+use solana_address_lookup_table_program::state::AddressLookupTable;
+use solana_client::rpc_client::RpcClient;
+use solana_client::rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig};
+use solana_client::rpc_filter::RpcFilterType;
+use solana_client::rpc_request::{RpcRequest, TokenAccountsFilter};
+use solana_sdk::instruction::Instruction;
+use solana_sdk::message::VersionedMessage;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::transaction::{Transaction, VersionedTransaction};
+use spl_associated_token_account::instruction::create_associated_token_account;
+use std::str::FromStr;
+use std::thread::sleep;
+use std::time::Duration;
+
+use lookup_table::{compile_v0_message, ADDRESSES_PER_EXTENSION, UNLOCKS_PER_V0_TRANSACTION};
+
+pub mod filters;
+pub mod lookup_table;
+
+pub use spl_associated_token_account::get_associated_token_address;
+pub use token_vesting;
+pub use token_vesting::integration::{self, claimable_at, find_vesting_address, locked_at, next_unlock_at};
+pub use token_vesting::{Cohort, GrantIndex, PartnerStats, ProgramConfig, Rollup, Round, Schedule, Vesting};
+
+/// The unlocks `claim_all` packs into one transaction
+pub const UNLOCKS_PER_TRANSACTION: usize = 4;
+
+/// An irrevocable contract paid from `source_token` to `destination_token`
+#[derive(Clone)]
+pub struct Grant {
+    pub seeds: [u8; 31],
+    pub source_token: Pubkey,
+    pub mint: Pubkey,
+    pub destination_token: Pubkey,
+    pub schedules: Vec<Schedule>,
+}
+
+impl Grant {
+    /// Returns the instructions allocating, escrowing and funding the contract. The escrow is the associated token
+    /// account of the vesting account
+    pub fn instructions(&self, payer: &Pubkey, source_authority: &Pubkey) -> Vec<Instruction> {
+        let vesting = find_vesting_address(&self.seeds).0;
+        let vesting_token = get_associated_token_address(&vesting, &self.mint);
+        vec![
+            integration::init(self.seeds, self.schedules.len() as u32, *payer),
+            create_associated_token_account(payer, &vesting, &self.mint),
+            integration::create(self.seeds, vesting_token, self.source_token, *source_authority, self.mint,
+                                self.destination_token, self.schedules.clone()),
+        ]
+    }
+}
+
+pub struct VestingClient {
+    rpc: RpcClient,
+}
+
+impl VestingClient {
+    pub fn new(rpc: RpcClient) -> Self {
+        Self { rpc }
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    /// Fetches and deserializes the vesting account at `address`
+    pub fn fetch_vesting(&self, address: &Pubkey) -> Result<Vesting> {
+        let data = self.rpc.get_account_data(address)?;
+        Ok(Vesting::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// Fetches the vesting account derived from `seeds`
+    pub fn fetch_vesting_by_seeds(&self, seeds: &[u8; 31]) -> Result<Vesting> {
+        self.fetch_vesting(&find_vesting_address(seeds).0)
+    }
+
+    /// Fetches the program config
+    pub fn fetch_config(&self) -> Result<ProgramConfig> {
+        let address = Pubkey::find_program_address(&[token_vesting::CONFIG_SEED.as_bytes()], &token_vesting::ID).0;
+        let data = self.rpc.get_account_data(&address)?;
+        Ok(ProgramConfig::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// Fails unless the program config records `expected` as the verifiable build hash of the deployed program
+    pub fn verify_build_hash(&self, expected: &[u8; 32]) -> Result<()> {
+        match self.fetch_config()?.build_hash {
+            Some(build_hash) if build_hash == *expected => Ok(()),
+            Some(_) => Err(anyhow!("the program config records another build hash")),
+            None => Err(anyhow!("the program config records no build hash")),
+        }
+    }
+
+    /// Fetches the vesting accounts paying `destination_token`
+    pub fn fetch_vestings_by_destination(&self, destination_token: &Pubkey) -> Result<Vec<(Pubkey, Vesting)>> {
+        self.fetch_vestings(filters::paying(destination_token))
+    }
+
+    /// Fetches the vesting accounts of `mint`
+    pub fn fetch_vestings_by_mint(&self, mint: &Pubkey) -> Result<Vec<(Pubkey, Vesting)>> {
+        self.fetch_vestings(filters::of_mint(mint))
+    }
+
+    /// Fetches the vesting accounts created by `creator`
+    pub fn fetch_vestings_by_creator(&self, creator: &Pubkey) -> Result<Vec<(Pubkey, Vesting)>> {
+        self.fetch_vestings(filters::created_by(creator))
+    }
+
+    /// Fetches the cohort at `address`
+    pub fn fetch_cohort(&self, address: &Pubkey) -> Result<Cohort> {
+        let data = self.rpc.get_account_data(address)?;
+        Ok(Cohort::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// Fetches the contracts of `cohort`, among those its admin created as the cohort isn't at a fixed offset
+    pub fn fetch_cohort_members(&self, cohort: &Pubkey) -> Result<Vec<(Pubkey, Vesting)>> {
+        let admin = self.fetch_cohort(cohort)?.admin;
+        let mut vestings = self.fetch_vestings_by_creator(&admin)?;
+        vestings.retain(|(_, vesting)| vesting.cohort == Some(*cohort));
+        Ok(vestings)
+    }
+
+    /// Fetches the round at `address`, its tallies giving the unlock overhang of the round
+    pub fn fetch_round(&self, address: &Pubkey) -> Result<Round> {
+        let data = self.rpc.get_account_data(address)?;
+        Ok(Round::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// Fetches the rollup of the contracts of `org` for `beneficiary`, their compensation statement
+    pub fn fetch_rollup(&self, org: &Pubkey, beneficiary: &Pubkey) -> Result<Rollup> {
+        let data = self.rpc.get_account_data(&integration::find_rollup_address(org, beneficiary).0)?;
+        Ok(Rollup::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// Fetches the stats of the partner config `partner_config` in `mint`, for the partner dashboards
+    pub fn fetch_partner_stats(&self, partner_config: &Pubkey, mint: &Pubkey) -> Result<PartnerStats> {
+        let data = self.rpc.get_account_data(&integration::find_partner_stats_address(partner_config, mint).0)?;
+        Ok(PartnerStats::try_deserialize(&mut data.as_slice())?)
+    }
+
+    /// Fetches the contracts `creator` issued to `beneficiary` listed in their grant index, with their grant ids
+    pub fn fetch_indexed_grants(&self, creator: &Pubkey, beneficiary: &Pubkey) -> Result<Vec<(u64, Pubkey, Vesting)>> {
+        let data = self.rpc.get_account_data(&integration::find_grant_index_address(creator, beneficiary).0)?;
+        let grant_index = GrantIndex::try_deserialize(&mut data.as_slice())?;
+        grant_index.grant_ids
+            .iter()
+            .map(|&grant_id| {
+                let seeds = integration::grant_seeds(creator, beneficiary, grant_id);
+                Ok((grant_id, find_vesting_address(&seeds).0, self.fetch_vesting_by_seeds(&seeds)?))
+            })
+            .collect()
+    }
+
+    /// Fetches the vesting accounts paying any token account of the `beneficiary` wallet
+    pub fn fetch_vestings_by_beneficiary(&self, beneficiary: &Pubkey) -> Result<Vec<(Pubkey, Vesting)>> {
+        let token_accounts = self.rpc.get_token_accounts_by_owner(beneficiary, TokenAccountsFilter::ProgramId(anchor_spl::token::ID))?;
+        let mut vestings = Vec::new();
+        for token_account in token_accounts {
+            vestings.extend(self.fetch_vestings_by_destination(&Pubkey::from_str(&token_account.pubkey)?)?);
+        }
+        Ok(vestings)
+    }
+
+    /// Fetches the vesting accounts matching `filters`, built with the `filters` module
+    pub fn fetch_vestings(&self, filters: Vec<RpcFilterType>) -> Result<Vec<(Pubkey, Vesting)>> {
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig::default(),
+            with_context: None,
+        };
+        self.rpc
+            .get_program_accounts_with_config(&token_vesting::ID, config)?
+            .into_iter()
+            .map(|(address, account)| Ok((address, Vesting::try_deserialize(&mut account.data.as_slice())?)))
+            .collect()
+    }
+
+    /// Returns the escrow token account of the vesting account at `vesting`
+    pub fn find_escrow(&self, vesting: &Pubkey, mint: &Pubkey) -> Result<Pubkey> {
+        let accounts = self.rpc.get_token_accounts_by_owner(vesting, TokenAccountsFilter::Mint(*mint))?;
+        let escrow = accounts.first().ok_or_else(|| anyhow!("vesting account {} has no escrow", vesting))?;
+        Ok(Pubkey::from_str(&escrow.pubkey)?)
+    }
+
+    /// Returns the cluster time, the timestamp the program compares release times with
+    pub fn cluster_time(&self) -> Result<i64> {
+        Ok(self.rpc.get_block_time(self.rpc.get_slot()?)?)
+    }
+
+    /// Allocates, escrows and funds `grant` in one transaction
+    pub fn create_grant(&self, payer: &Keypair, source_authority: &Keypair, grant: &Grant) -> Result<Signature> {
+        let instructions = grant.instructions(&payer.pubkey(), &source_authority.pubkey());
+        self.send(&instructions, payer, &[payer, source_authority])
+    }
+
+    /// Unlocks every contract of `seeds` with something claimable now, `UNLOCKS_PER_TRANSACTION` per transaction.
+    /// Returns the signatures of the sent transactions
+    pub fn claim_all(&self, payer: &Keypair, seeds: &[[u8; 31]]) -> Result<Vec<Signature>> {
+        self.claimable_unlocks(seeds)?
+            .chunks(UNLOCKS_PER_TRANSACTION)
+            .map(|chunk| self.send(chunk, payer, &[payer]))
+            .collect()
+    }
+
+    /// Unlocks every contract of `seeds` with something claimable now in v0 transactions loading their accounts from
+    /// the lookup table at `lookup_table`, `UNLOCKS_PER_V0_TRANSACTION` per transaction
+    pub fn claim_all_with_lookup_table(&self, payer: &Keypair, seeds: &[[u8; 31]], lookup_table: &Pubkey) -> Result<Vec<Signature>> {
+        let table_addresses = self.fetch_lookup_table(lookup_table)?;
+        self.claimable_unlocks(seeds)?
+            .chunks(UNLOCKS_PER_V0_TRANSACTION)
+            .map(|chunk| self.send_v0(chunk, payer, &[payer], lookup_table, &table_addresses))
+            .collect()
+    }
+
+    fn claimable_unlocks(&self, seeds: &[[u8; 31]]) -> Result<Vec<Instruction>> {
+        let now = self.cluster_time()?;
+        let mut unlocks = Vec::new();
+        for seeds in seeds {
+            let address = find_vesting_address(seeds).0;
+            let vesting = self.fetch_vesting(&address)?;
+            if claimable_at(&vesting, now) == 0 {
+                continue;
+            }
+            let escrow = self.find_escrow(&address, &vesting.mint_address)?;
+            unlocks.push(integration::unlock(*seeds, escrow, vesting.mint_address, vesting.destination_address));
+        }
+        Ok(unlocks)
+    }
+
+    /// Creates a lookup table of `addresses`, `lookup_table::cohort_addresses` listing those of a cohort, owned and
+    /// paid by `payer`. Returns its address once its addresses can be loaded
+    pub fn create_lookup_table(&self, payer: &Keypair, addresses: &[Pubkey]) -> Result<Pubkey> {
+        let (create, lookup_table) = create_lookup_table(payer.pubkey(), payer.pubkey(), self.rpc.get_slot()?);
+        self.send(&[create], payer, &[payer])?;
+        self.extend_lookup_table(payer, &lookup_table, addresses)?;
+        Ok(lookup_table)
+    }
+
+    /// Appends `addresses` to the lookup table at `lookup_table`, `ADDRESSES_PER_EXTENSION` per transaction, signed
+    /// and paid by its authority. Returns once the new addresses can be loaded, the slot after the last extension
+    pub fn extend_lookup_table(&self, authority: &Keypair, lookup_table: &Pubkey, addresses: &[Pubkey]) -> Result<()> {
+        for chunk in addresses.chunks(ADDRESSES_PER_EXTENSION) {
+            let extend = extend_lookup_table(*lookup_table, authority.pubkey(), Some(authority.pubkey()), chunk.to_vec());
+            self.send(&[extend], authority, &[authority])?;
+        }
+        let extended_at = self.rpc.get_slot()?;
+        while self.rpc.get_slot()? <= extended_at {
+            sleep(Duration::from_millis(400));
+        }
+        Ok(())
+    }
+
+    /// Fetches the addresses of the lookup table at `lookup_table`
+    pub fn fetch_lookup_table(&self, lookup_table: &Pubkey) -> Result<Vec<Pubkey>> {
+        let data = self.rpc.get_account_data(lookup_table)?;
+        let table = AddressLookupTable::deserialize(&data).map_err(|e| anyhow!("invalid lookup table {}: {}", lookup_table, e))?;
+        Ok(table.addresses.to_vec())
+    }
+
+    /// Sends `instructions` in one transaction paid by `payer` and waits for its confirmation
+    pub fn send(&self, instructions: &[Instruction], payer: &Keypair, signers: &[&Keypair]) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let transaction = Transaction::new_signed_with_payer(instructions, Some(&payer.pubkey()), &signers.to_vec(), blockhash);
+        Ok(self.rpc.send_and_confirm_transaction(&transaction)?)
+    }
+
+    /// Sends `instructions` in one v0 transaction loading the accounts found in the lookup table at `lookup_table`,
+    /// whose addresses are `table_addresses`, and waits for its confirmation
+    pub fn send_v0(
+        &self,
+        instructions: &[Instruction],
+        payer: &Keypair,
+        signers: &[&Keypair],
+        lookup_table: &Pubkey,
+        table_addresses: &[Pubkey],
+    ) -> Result<Signature> {
+        let blockhash = self.rpc.get_latest_blockhash()?;
+        let message = compile_v0_message(&payer.pubkey(), instructions, lookup_table, table_addresses, blockhash)?;
+        let transaction = VersionedTransaction::try_new(VersionedMessage::V0(message), &signers.to_vec())?;
+        // The RPC client of solana 1.10 only sends legacy transactions
+        let encoded = base64::encode(bincode::serialize(&transaction)?);
+        let signature: String = self.rpc.send(RpcRequest::SendTransaction, serde_json::json!([encoded, { "encoding": "base64" }]))?;
+        let signature = Signature::from_str(&signature)?;
+        self.rpc.poll_for_signature(&signature)?;
+        Ok(signature)
+    }
+}
@@ -0,0 +1,12 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use token_vesting::program_test::DeferredFreeAllocator;
+use token_vesting_fuzz::{run, Scenario};
+
+#[global_allocator]
+static ALLOCATOR: DeferredFreeAllocator = DeferredFreeAllocator;
+
+fuzz_target!(|scenario: Scenario| {
+    run(scenario);
+});
@@ -0,0 +1,219 @@
+//! Fuzzing harness of the token vesting program.
+//!
+//! A `Scenario` is a random contract and a random sequence of instructions on it, played on a `solana-program-test`
+//! bank through the `test-fixture` of the program while the clock only moves forward. The custody invariants are
+//! checked after every instruction, whether the program accepted it or not:
+//! * the escrow holds at least what the schedule still owes
+//! * no token is created or lost between the creator, the escrow and the destinations
+//! * the destinations never received more than what was released by then, plus tokens sent to the escrow directly
+//!
+//! Run with `cargo fuzz run instruction_sequences` from the repository root, on a nightly toolchain.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use arbitrary::Arbitrary;
+use solana_program_test::tokio;
+use solana_sdk::instruction::Instruction;
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use token_vesting::integration::{self, find_vesting_address};
+use token_vesting::program_test::VestingFixture;
+use token_vesting::{Schedule, UnlockPolicy};
+
+/// The most tranches and instructions a scenario plays, keeping runs short
+pub const MAX_TRANCHES: usize = 8;
+pub const MAX_ACTIONS: usize = 16;
+
+const SEEDS: [u8; 31] = [42; 31];
+
+#[derive(Arbitrary, Debug)]
+pub struct Tranche {
+    /// Seconds after the creation
+    pub offset: u32,
+    pub amount: u32,
+}
+
+#[derive(Arbitrary, Debug)]
+pub enum Action {
+    /// Moves the clock forward by the given seconds
+    Warp(u32),
+    Accept,
+    Unlock,
+    /// Revoked by the creator, who is the revoker of revocable contracts
+    Revoke,
+    Cancel,
+    /// Moves the destination to the token account of the other beneficiary
+    ChangeDestination,
+    /// Sends tokens to the escrow directly
+    Donate(u16),
+}
+
+#[derive(Arbitrary, Debug)]
+pub struct Scenario {
+    pub tranches: Vec<Tranche>,
+    pub min_claim_amount: u32,
+    pub revocable: bool,
+    pub actions: Vec<Action>,
+}
+
+struct Harness {
+    fixture: VestingFixture,
+    mint: Pubkey,
+    creator: Keypair,
+    beneficiaries: [Keypair; 2],
+    source: Pubkey,
+    escrow: Pubkey,
+    destinations: [Pubkey; 2],
+    // Index of the beneficiary the contract currently pays
+    current: usize,
+    schedule: Vec<Schedule>,
+    supply: u64,
+    donated: u64,
+}
+
+/// Plays `scenario`, panicking on a broken invariant
+pub fn run(scenario: Scenario) {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(play(scenario));
+}
+
+async fn play(scenario: Scenario) {
+    let mut harness = match Harness::create(&scenario).await {
+        Some(harness) => harness,
+        None => return,
+    };
+    for action in scenario.actions.iter().take(MAX_ACTIONS) {
+        harness.apply(action).await;
+        harness.check_invariants().await;
+    }
+}
+
+impl Harness {
+    /// Creates the contract of `scenario`, none when the program refuses it
+    async fn create(scenario: &Scenario) -> Option<Harness> {
+        let mut fixture = VestingFixture::start().await;
+        let start = fixture.now().await;
+        let schedule: Vec<Schedule> = scenario.tranches.iter().take(MAX_TRANCHES)
+            .map(|t| Schedule { release_time: (start + t.offset as i64) as u64, amount: t.amount as u64, condition: None })
+            .collect();
+        if schedule.is_empty() {
+            return None;
+        }
+        // The program requires a balance above the total amount
+        let supply = schedule.iter().map(|s| s.amount).sum::<u64>() + 1;
+
+        let mint = fixture.create_mint(0).await;
+        let creator = Keypair::new();
+        let beneficiaries = [Keypair::new(), Keypair::new()];
+        let source = fixture.create_associated_token_account(&creator.pubkey(), &mint).await;
+        let destinations = [
+            fixture.create_associated_token_account(&beneficiaries[0].pubkey(), &mint).await,
+            fixture.create_associated_token_account(&beneficiaries[1].pubkey(), &mint).await,
+        ];
+        fixture.mint_to(&mint, &source, supply).await;
+
+        let vesting = find_vesting_address(&SEEDS).0;
+        fixture.allocate_vesting(&SEEDS, schedule.len() as u32).await;
+        let escrow = fixture.create_associated_token_account(&vesting, &mint).await;
+        let create = Instruction {
+            program_id: token_vesting::ID,
+            accounts: token_vesting::accounts::Create {
+                vesting,
+                vesting_token: escrow,
+                source_token: source,
+                source_authority: creator.pubkey(),
+                mint,
+                token_program: spl_token::ID,
+            }.to_account_metas(None),
+            data: token_vesting::instruction::Create {
+                seeds: SEEDS,
+                mint_address: mint,
+                destination_token_address: destinations[0],
+                schedules: schedule.clone(),
+                min_claim_amount: scenario.min_claim_amount as u64,
+                revoker: if scenario.revocable { Some(creator.pubkey()) } else { None },
+                soulbound_receipt: false,
+                unlock_policy: UnlockPolicy::Anyone,
+            }.data(),
+        };
+        fixture.process(&[create], &[&creator]).await.ok()?;
+
+        let mut harness = Harness {
+            fixture, mint, creator, beneficiaries, source, escrow, destinations,
+            current: 0,
+            schedule,
+            supply,
+            donated: 0,
+        };
+        harness.check_invariants().await;
+        Some(harness)
+    }
+
+    async fn apply(&mut self, action: &Action) {
+        let destination = self.destinations[self.current];
+        // Refused instructions are part of the exploration, only the invariants matter
+        let _ = match action {
+            Action::Warp(seconds) => {
+                self.fixture.warp_forward(*seconds as i64).await;
+                Ok(())
+            }
+            Action::Accept => {
+                let accept = integration::accept(SEEDS, destination, self.beneficiaries[self.current].pubkey());
+                self.fixture.process(&[accept], &[&self.beneficiaries[self.current]]).await
+            }
+            Action::Unlock => {
+                let unlock = integration::unlock(SEEDS, self.escrow, self.mint, destination);
+                self.fixture.process(&[unlock], &[]).await
+            }
+            Action::Revoke => {
+                let revoke = Instruction {
+                    program_id: token_vesting::ID,
+                    accounts: token_vesting::accounts::Revoke {
+                        vesting: find_vesting_address(&SEEDS).0,
+                        vesting_token: self.escrow,
+                        mint: self.mint,
+                        refund_token: self.source,
+                        revoker: self.creator.pubkey(),
+                        token_program: spl_token::ID,
+                    }.to_account_metas(None),
+                    data: token_vesting::instruction::Revoke { seeds: SEEDS }.data(),
+                };
+                self.fixture.process(&[revoke], &[&self.creator]).await
+            }
+            Action::Cancel => {
+                let cancel = integration::cancel(SEEDS, self.escrow, self.mint, self.source, self.creator.pubkey());
+                self.fixture.process(&[cancel], &[&self.creator]).await
+            }
+            Action::ChangeDestination => {
+                let next = 1 - self.current;
+                let change = integration::change_destination(SEEDS, destination, self.beneficiaries[self.current].pubkey(),
+                                                             self.destinations[next]);
+                let result = self.fixture.process(&[change], &[&self.beneficiaries[self.current]]).await;
+                if result.is_ok() {
+                    self.current = next;
+                }
+                result
+            }
+            Action::Donate(amount) => {
+                let escrow = self.escrow;
+                self.fixture.mint_to(&self.mint, &escrow, *amount as u64).await;
+                self.donated += *amount as u64;
+                Ok(())
+            }
+        };
+    }
+
+    async fn check_invariants(&mut self) {
+        let now = self.fixture.now().await;
+        let vesting = self.fixture.vesting(&SEEDS).await;
+        let owed: u64 = vesting.schedule.iter().map(|s| s.amount).sum();
+        let source = self.fixture.token_balance(&self.source).await;
+        let escrow = self.fixture.token_balance(&self.escrow).await;
+        let paid = self.fixture.token_balance(&self.destinations[0]).await
+            + self.fixture.token_balance(&self.destinations[1]).await;
+        let released: u64 = self.schedule.iter().filter(|s| s.release_time as i64 <= now).map(|s| s.amount).sum();
+
+        assert!(escrow >= owed, "escrow holds {} but the schedule owes {}", escrow, owed);
+        assert_eq!(source + escrow + paid, self.supply + self.donated, "tokens created or lost");
+        assert!(paid <= released + self.donated, "paid {} with {} released at {}", paid, released, now);
+    }
+}
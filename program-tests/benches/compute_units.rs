@@ -0,0 +1,187 @@
+//! Compute units consumed by the claim instructions, compared with the recorded baseline.
+//!
+//! Only BPF programs are metered, so the bench runs against the BPF build:
+//! ```text
+//! anchor build && BPF_OUT_DIR=$PWD/target/deploy cargo bench -p token-vesting-program-tests --bench compute_units
+//! ```
+//! A case consuming more than `TOLERANCE_PERCENT` above its baseline, or missing from the baseline, fails the run.
+//! After an intended change, record the new consumption with `-- --save-baseline` and commit `compute_units.baseline`.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_program_test::tokio;
+use solana_sdk::compute_budget::ComputeBudgetInstruction;
+use solana_sdk::instruction::{AccountMeta, Instruction};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use std::collections::BTreeMap;
+use std::fs;
+use token_vesting::integration::{self, find_vesting_address};
+use token_vesting::program_test::{empty_vesting, DeferredFreeAllocator, VestingFixture};
+use token_vesting::Schedule;
+
+#[global_allocator]
+static ALLOCATOR: DeferredFreeAllocator = DeferredFreeAllocator;
+
+const BASELINE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/benches/compute_units.baseline");
+const TOLERANCE_PERCENT: u64 = 5;
+
+const SCHEDULE_COUNTS: [usize; 4] = [1, 10, 100, 500];
+const BATCH_SIZES: [usize; 2] = [2, 8];
+// The schedules of every contract of a batch
+const BATCH_SCHEDULES: usize = 4;
+const TRANCHE_AMOUNT: u64 = 1_000;
+
+struct Bench {
+    fixture: VestingFixture,
+    mint: Pubkey,
+    owner: Keypair,
+    destination: Pubkey,
+}
+
+impl Bench {
+    async fn start() -> Self {
+        let mut fixture = VestingFixture::start().await;
+        let mint = fixture.create_mint(6).await;
+        let owner = Keypair::new();
+        let destination = fixture.create_associated_token_account(&owner.pubkey(), &mint).await;
+        Bench { fixture, mint, owner, destination }
+    }
+
+    /// Writes an accepted contract of `schedules` tranches, all released, and funds its escrow. Returns the escrow
+    async fn released_contract(&mut self, seeds: [u8; 31], schedules: usize) -> Pubkey {
+        let now = self.fixture.now().await;
+        let mut vesting = empty_vesting();
+        vesting.destination_address = self.destination;
+        vesting.mint_address = self.mint;
+        vesting.creator = self.fixture.payer();
+        vesting.layout_version = token_vesting::VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.is_accepted = true;
+        vesting.schedule = (0..schedules)
+            .map(|i| Schedule { release_time: (now - 1 - i as i64) as u64, amount: TRANCHE_AMOUNT, condition: None })
+            .collect();
+        self.fixture.set_vesting(&seeds, &vesting).await;
+
+        let escrow = self.fixture.create_associated_token_account(&find_vesting_address(&seeds).0, &self.mint).await;
+        self.fixture.mint_to(&self.mint, &escrow, TRANCHE_AMOUNT * schedules as u64).await;
+        escrow
+    }
+}
+
+/// Returns the compute units of `instruction`, given the largest compute budget
+async fn measure(fixture: &mut VestingFixture, instruction: Instruction, signers: &[&Keypair]) -> u64 {
+    let instructions = [ComputeBudgetInstruction::set_compute_unit_limit(1_400_000), instruction];
+    fixture.simulate_compute_units(&instructions, signers).await.unwrap()
+}
+
+fn seeds(i: usize) -> [u8; 31] {
+    [i as u8 + 1; 31]
+}
+
+async fn unlock(schedules: usize) -> u64 {
+    let mut bench = Bench::start().await;
+    let escrow = bench.released_contract(seeds(0), schedules).await;
+    let unlock = integration::unlock(seeds(0), escrow, bench.mint, bench.destination);
+    measure(&mut bench.fixture, unlock, &[]).await
+}
+
+async fn batch_unlock(contracts: usize) -> u64 {
+    let mut bench = Bench::start().await;
+    let mut accounts = token_vesting::accounts::BatchUnlock {
+        destination_token: bench.destination,
+        mint: bench.mint,
+        token_program: spl_token::ID,
+    }.to_account_metas(None);
+    for i in 0..contracts {
+        let escrow = bench.released_contract(seeds(i), BATCH_SCHEDULES).await;
+        accounts.push(AccountMeta::new(find_vesting_address(&seeds(i)).0, false));
+        accounts.push(AccountMeta::new(escrow, false));
+    }
+    let instruction = Instruction {
+        program_id: token_vesting::ID,
+        accounts,
+        data: token_vesting::instruction::BatchUnlock { seeds: (0..contracts).map(seeds).collect() }.data(),
+    };
+    measure(&mut bench.fixture, instruction, &[]).await
+}
+
+async fn unlock_all_for_destination(contracts: usize) -> u64 {
+    let mut bench = Bench::start().await;
+    let mut accounts = token_vesting::accounts::UnlockAllForDestination {
+        owner: bench.owner.pubkey(),
+        token_program: spl_token::ID,
+    }.to_account_metas(None);
+    for i in 0..contracts {
+        let escrow = bench.released_contract(seeds(i), BATCH_SCHEDULES).await;
+        accounts.push(AccountMeta::new(find_vesting_address(&seeds(i)).0, false));
+        accounts.push(AccountMeta::new(escrow, false));
+        accounts.push(AccountMeta::new_readonly(bench.mint, false));
+        accounts.push(AccountMeta::new(bench.destination, false));
+    }
+    let instruction = Instruction {
+        program_id: token_vesting::ID,
+        accounts,
+        data: token_vesting::instruction::UnlockAllForDestination { seeds: (0..contracts).map(seeds).collect() }.data(),
+    };
+    measure(&mut bench.fixture, instruction, &[&bench.owner]).await
+}
+
+fn read_baseline() -> BTreeMap<String, u64> {
+    fs::read_to_string(BASELINE)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| {
+            let (case, units) = line.split_once(' ')?;
+            Some((case.to_string(), units.trim().parse().ok()?))
+        })
+        .collect()
+}
+
+#[tokio::main]
+async fn main() {
+    if std::env::var("BPF_OUT_DIR").is_err() {
+        eprintln!("compute units are only metered for BPF programs, build the program and set BPF_OUT_DIR to its directory");
+        std::process::exit(1);
+    }
+
+    let mut results = BTreeMap::new();
+    for count in SCHEDULE_COUNTS {
+        results.insert(format!("unlock/{}_schedules", count), unlock(count).await);
+    }
+    for size in BATCH_SIZES {
+        results.insert(format!("batch_unlock/{}_contracts", size), batch_unlock(size).await);
+        results.insert(format!("unlock_all_for_destination/{}_contracts", size), unlock_all_for_destination(size).await);
+    }
+
+    if std::env::args().any(|arg| arg == "--save-baseline") {
+        let lines: Vec<String> = results.iter().map(|(case, units)| format!("{} {}", case, units)).collect();
+        fs::write(BASELINE, lines.join("\n") + "\n").unwrap();
+        println!("baseline written to {}", BASELINE);
+    }
+
+    let baseline = read_baseline();
+    let (mut regressions, mut missing) = (0, 0);
+    println!("{:<44} {:>10} {:>10}", "case", "units", "baseline");
+    for (case, units) in &results {
+        match baseline.get(case) {
+            Some(&expected) => {
+                let regressed = *units * 100 > expected * (100 + TOLERANCE_PERCENT);
+                println!("{:<44} {:>10} {:>10}{}", case, units, expected, if regressed { "  REGRESSED" } else { "" });
+                regressions += regressed as usize;
+            }
+            None => {
+                println!("{:<44} {:>10} {:>10}", case, units, "-");
+                missing += 1;
+            }
+        }
+    }
+    if missing > 0 {
+        eprintln!("{} cases have no baseline, record it with --save-baseline", missing);
+    }
+    if regressions > 0 {
+        eprintln!("{} cases consume more than {}% above their baseline", regressions, TOLERANCE_PERCENT);
+    }
+    if regressions > 0 || missing > 0 {
+        std::process::exit(1);
+    }
+}
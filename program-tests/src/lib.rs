@@ -0,0 +1,6 @@
+//! Native program tests and compute unit benches of the token vesting program, run on a `solana-program-test` bank
+//! through the `test-fixture` of the program.
+//!
+//! They live outside the program crate so that its own builds never enable the fixture: the program is built with
+//! exactly the features its build asks for. `cargo test -p token-vesting-program-tests --features test-clock` also
+//! runs the tests of the test clock.
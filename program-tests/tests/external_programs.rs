@@ -0,0 +1,650 @@
+//! Contracts calling the programs they integrate with, stood in by native programs registered at their addresses.
+
+use anchor_lang::prelude::{AccountInfo, ProgramError};
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::{AnchorDeserialize, InstructionData, ToAccountMetas};
+use solana_program_test::{processor, tokio, BanksClientError};
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::{ed25519_program, system_program};
+use solana_sdk::transaction::TransactionError;
+use spl_token::instruction::AuthorityType;
+use std::convert::TryInto;
+use token_vesting::bubblegum;
+use token_vesting::claim_hook::{claim_hook_authority, OnClaim};
+use token_vesting::integration::{self, find_vesting_address};
+use token_vesting::program_test::{program_test, DeferredFreeAllocator, VestingFixture};
+use token_vesting::wormhole::{self, core_bridge_program, token_bridge_program};
+use token_vesting::switchboard::attestation_program;
+use token_vesting::{BridgeTarget, ClaimHook, ReleaseCondition, Schedule, VestingError};
+
+#[global_allocator]
+static ALLOCATOR: DeferredFreeAllocator = DeferredFreeAllocator;
+
+const SEEDS: [u8; 31] = [9; 31];
+const DAY: i64 = 86_400;
+
+const HOOK_PROGRAM: Pubkey = Pubkey::new_from_array([42; 32]);
+// The error of a claim hook whose record has its `fail` flag set
+const HOOK_FAILURE: u32 = 0x4000;
+// fail u8, calls u8, signers u8, amount u64, destination
+const HOOK_RECORD_SIZE: usize = 43;
+
+// BridgeData of the core bridge, its fee at offset 16
+const BRIDGE_FEE: u64 = 1_000;
+
+// minted u8, leaf owner
+const TREE_RECORD_SIZE: usize = 33;
+
+const GOVERNANCE_PROGRAM: Pubkey = Pubkey::new_from_array([43; 32]);
+const VAULT_PROGRAM: Pubkey = Pubkey::new_from_array([44; 32]);
+
+/// A claim hook counting the `on_claim` calls it gets in the account registered with it, along with the signers and
+/// the last claim. It fails while the `fail` flag of the account is set
+fn record_claims(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data[..8] != hash(b"global:on_claim").to_bytes()[..8] {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if !accounts[0].is_signer || *accounts[0].key != claim_hook_authority().0 {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let claim = OnClaim::try_from_slice(&data[8..])?;
+    let mut record = accounts[1].try_borrow_mut_data()?;
+    if record[0] != 0 {
+        return Err(ProgramError::Custom(HOOK_FAILURE));
+    }
+    record[1] += 1;
+    record[2] = accounts.iter().filter(|a| a.is_signer).count() as u8;
+    record[3..11].copy_from_slice(&claim.amount.to_le_bytes());
+    record[11..43].copy_from_slice(claim.destination.as_ref());
+    Ok(())
+}
+
+/// The `transfer_native` of the Wormhole token bridge, pulling the amount out of `from` into custody through the
+/// `authority_signer` delegate. No message is posted
+fn transfer_native(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data.len() != 55 || data[0] != 5 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let amount = u64::from_le_bytes(data[5..13].try_into().unwrap());
+    let (from, custody, authority_signer, message, token_program) = (&accounts[2], &accounts[4], &accounts[5], &accounts[8], &accounts[16]);
+    if !message.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let bump = Pubkey::find_program_address(&[b"authority_signer"], program_id).1;
+    let instruction = spl_token::instruction::transfer(&spl_token::ID, from.key, custody.key, authority_signer.key, &[], amount)?;
+    invoke_signed(&instruction, &[from.clone(), custody.clone(), authority_signer.clone(), token_program.clone()],
+                  &[&[b"authority_signer", &[bump]]])
+}
+
+/// A vault minting a share per deposited token, whose reserve and share mint belong to the vault, its `["vault"]` PDA.
+/// Shares are worth their part of the reserve, growing with the yield minted to it
+fn vault(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    let (vault, reserve, share_mint, share_token, underlying, authority, token_program) =
+        (&accounts[0], &accounts[1], &accounts[2], &accounts[3], &accounts[4], &accounts[5], &accounts[6]);
+    let bump = Pubkey::find_program_address(&[b"vault"], program_id).1;
+    let vault_seeds: &[&[u8]] = &[b"vault", &[bump]];
+    let amount = u64::from_le_bytes(data[8..16].try_into().unwrap());
+    let reserve_balance = spl_token::state::Account::unpack(&reserve.try_borrow_data()?)?.amount as u128;
+    let supply = spl_token::state::Mint::unpack(&share_mint.try_borrow_data()?)?.supply as u128;
+    let is = |name: &str| data[..8] == hash(format!("global:{}", name).as_bytes()).to_bytes()[..8];
+
+    if is("deposit") {
+        let deposit = spl_token::instruction::transfer(&spl_token::ID, underlying.key, reserve.key, authority.key, &[], amount)?;
+        invoke(&deposit, &[underlying.clone(), reserve.clone(), authority.clone(), token_program.clone()])?;
+        let mint = spl_token::instruction::mint_to(&spl_token::ID, share_mint.key, share_token.key, vault.key, &[], amount)?;
+        return invoke_signed(&mint, &[share_mint.clone(), share_token.clone(), vault.clone(), token_program.clone()], &[vault_seeds]);
+    }
+    let (shares, amount) = if is("withdraw") {
+        ((amount as u128 * supply).div_ceil(reserve_balance) as u64, amount)
+    } else if is("redeem") {
+        (amount, (reserve_balance * amount as u128 / supply) as u64)
+    } else {
+        return Err(ProgramError::InvalidInstructionData);
+    };
+    let burn = spl_token::instruction::burn(&spl_token::ID, share_token.key, share_mint.key, authority.key, &[], shares)?;
+    invoke(&burn, &[share_token.clone(), share_mint.clone(), authority.clone(), token_program.clone()])?;
+    let withdraw = spl_token::instruction::transfer(&spl_token::ID, reserve.key, underlying.key, vault.key, &[], amount)?;
+    invoke_signed(&withdraw, &[reserve.clone(), underlying.clone(), vault.clone(), token_program.clone()], &[vault_seeds])
+}
+
+/// The `mint_v1` of Bubblegum, recording the mints into a tree and the owner of the last leaf in the tree account.
+/// The only creator of the leaf has to be verified by its signature
+fn mint_v1(_program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> ProgramResult {
+    if data[..8] != hash(b"global:mint_v1").to_bytes()[..8] {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    let (leaf_owner, merkle_tree, tree_delegate, creator) = (&accounts[1], &accounts[3], &accounts[5], &accounts[9]);
+    let verified_creator = &data[data.len() - 34..];
+    if verified_creator[..32] != creator.key.to_bytes() || verified_creator[32] != 1 {
+        return Err(ProgramError::InvalidInstructionData);
+    }
+    if !creator.is_signer || !tree_delegate.is_signer {
+        return Err(ProgramError::MissingRequiredSignature);
+    }
+    let mut record = merkle_tree.try_borrow_mut_data()?;
+    record[0] += 1;
+    record[1..33].copy_from_slice(leaf_owner.key.as_ref());
+    Ok(())
+}
+
+/// SPL Governance, whose realms and proposals tests write directly
+fn governance(_program_id: &Pubkey, _accounts: &[AccountInfo], _data: &[u8]) -> ProgramResult {
+    Ok(())
+}
+
+/// A RealmV2 without council mint whose authority is `authority`
+fn realm_data(authority: &Pubkey) -> Vec<u8> {
+    let mut data = vec![16];
+    data.extend_from_slice(&[0; 57]);
+    data.push(0);
+    data.extend_from_slice(&[0; 8]);
+    data.push(1);
+    data.extend_from_slice(authority.as_ref());
+    data
+}
+
+struct Setup {
+    fixture: VestingFixture,
+    mint: Pubkey,
+    creator: Keypair,
+    beneficiary: Keypair,
+    source: Pubkey,
+    destination: Pubkey,
+    start: i64,
+}
+
+/// The vault of `VAULT_PROGRAM`, its reserve and its share mint
+struct Vault {
+    vault: Pubkey,
+    reserve: Pubkey,
+    share_mint: Pubkey,
+}
+
+/// Starts a bank running the claim hook, the token bridge, Bubblegum, SPL Governance and a vault, with a creator
+/// holding 1001 tokens and an empty token account of the beneficiary
+async fn setup() -> Setup {
+    let mut program_test = program_test();
+    program_test.add_program("claim_hook", HOOK_PROGRAM, processor!(record_claims));
+    program_test.add_program("token_bridge", token_bridge_program::ID, processor!(transfer_native));
+    program_test.add_program("bubblegum", bubblegum::ID, processor!(mint_v1));
+    program_test.add_program("governance", GOVERNANCE_PROGRAM, processor!(governance));
+    program_test.add_program("vault", VAULT_PROGRAM, processor!(vault));
+    let mut fixture = VestingFixture::start_with(program_test).await;
+
+    let mint = fixture.create_mint(6).await;
+    let creator = Keypair::new();
+    let beneficiary = Keypair::new();
+    let source = fixture.create_associated_token_account(&creator.pubkey(), &mint).await;
+    let destination = fixture.create_associated_token_account(&beneficiary.pubkey(), &mint).await;
+    fixture.mint_to(&mint, &source, 1_001).await;
+    let start = fixture.now().await;
+    Setup { fixture, mint, creator, beneficiary, source, destination, start }
+}
+
+fn tranche(release_time: i64, amount: u64) -> Schedule {
+    Schedule { release_time: release_time as u64, amount, condition: None }
+}
+
+/// 300 at the cliff a day in, then 300 and 400 a day apart
+fn cliff_schedule(start: i64) -> Vec<Schedule> {
+    vec![tranche(start + DAY, 300), tranche(start + 2 * DAY, 300), tranche(start + 3 * DAY, 400)]
+}
+
+impl Setup {
+    /// Grants `schedules`, left pending, and returns the escrow
+    async fn grant(&mut self, schedules: Vec<Schedule>) -> Pubkey {
+        self.fixture.create_grant(SEEDS, &self.source, &self.creator, &self.mint, &self.destination, schedules).await.unwrap()
+    }
+
+    async fn accept(&mut self) {
+        let instruction = integration::accept(SEEDS, self.destination, self.beneficiary.pubkey());
+        self.fixture.process(&[instruction], &[&self.beneficiary]).await.unwrap();
+    }
+
+    async fn unlock(&mut self, escrow: Pubkey, remaining_accounts: Vec<AccountMeta>) -> Result<(), BanksClientError> {
+        let mut instruction = integration::unlock(SEEDS, escrow, self.mint, self.destination);
+        instruction.accounts.extend(remaining_accounts);
+        self.fixture.process(&[instruction], &[]).await
+    }
+
+    async fn claim_and_bridge(&mut self, escrow: Pubkey, beneficiary: &Keypair) -> Result<(), BanksClientError> {
+        let message = Keypair::new();
+        let payer = self.fixture.payer();
+        let instruction = integration::claim_and_bridge(SEEDS, escrow, self.mint, self.destination, beneficiary.pubkey(),
+                                                        payer, message.pubkey(), 7);
+        self.fixture.process(&[instruction], &[beneficiary, &message]).await
+    }
+
+    /// Creates the empty vault of `VAULT_PROGRAM` for the mint
+    async fn create_vault(&mut self) -> Vault {
+        let vault = Pubkey::find_program_address(&[b"vault"], &VAULT_PROGRAM).0;
+        let reserve = Pubkey::new_unique();
+        self.fixture.create_token_account(&reserve, &vault, &self.mint).await;
+        let share_mint = self.fixture.create_mint(6).await;
+        let payer = self.fixture.payer();
+        let handover = spl_token::instruction::set_authority(&spl_token::ID, &share_mint, Some(&vault), AuthorityType::MintTokens,
+                                                             &payer, &[]).unwrap();
+        self.fixture.process(&[handover], &[]).await.unwrap();
+        Vault { vault, reserve, share_mint }
+    }
+
+    /// Creates the contract of `seeds` granting `schedules` from the source through `create_with_yield`, paying the
+    /// yield to the source, and returns the escrow and the share account of the contract
+    async fn grant_with_yield(&mut self, seeds: [u8; 31], schedules: Vec<Schedule>, vault: &Vault) -> (Pubkey, Pubkey) {
+        let vesting = find_vesting_address(&seeds).0;
+        self.fixture.allocate_vesting(&seeds, schedules.len() as u32).await;
+        let escrow = self.fixture.create_associated_token_account(&vesting, &self.mint).await;
+        let shares = self.fixture.create_associated_token_account(&vesting, &vault.share_mint).await;
+        let create = integration::create_with_yield(seeds, escrow, self.source, self.creator.pubkey(), self.mint, self.destination,
+                                                    schedules, self.source, VAULT_PROGRAM, vault.vault, vault.reserve, vault.share_mint,
+                                                    shares);
+        self.fixture.process(&[create], &[&self.creator]).await.unwrap();
+        (escrow, shares)
+    }
+}
+
+fn assert_error<T: std::fmt::Debug>(result: Result<T, BanksClientError>, expected: u32) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) =>
+            assert_eq!(code, expected, "unexpected error code"),
+        other => panic!("expected a program error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn claim_hooks_hear_of_claims_while_allowlisted() {
+    let mut s = setup().await;
+    let escrow = s.grant(cliff_schedule(s.start)).await;
+    let admin = Keypair::new();
+    s.fixture.set_program_config(&admin.pubkey()).await;
+    let record = Pubkey::new_unique();
+    s.fixture.set_foreign_account(&record, &HOOK_PROGRAM, vec![0; HOOK_RECORD_SIZE]).await;
+    let hook = ClaimHook { program: HOOK_PROGRAM, accounts: vec![record] };
+
+    // Only the program config admin keeps the allowlist, and contracts only register allowlisted programs
+    let result = s.fixture.allowlist_claim_hooks(&s.creator, vec![HOOK_PROGRAM]).await;
+    assert_error(result, VestingError::InvalidConfigAdmin.into());
+    let unlisted = ClaimHook { program: Pubkey::new_unique(), accounts: vec![record] };
+    s.fixture.allowlist_claim_hooks(&admin, vec![HOOK_PROGRAM]).await.unwrap();
+    let instruction = integration::set_claim_hook(SEEDS, s.creator.pubkey(), Some(unlisted));
+    assert_error(s.fixture.process(&[instruction], &[&s.creator]).await, VestingError::ClaimHookNotAllowlisted.into());
+    let instruction = integration::set_claim_hook(SEEDS, s.creator.pubkey(), Some(hook.clone()));
+    s.fixture.process(&[instruction], &[&s.creator]).await.unwrap();
+    s.accept().await;
+
+    // Claims have to pass the hook accounts, and are notified signed by the claim hook authority alone
+    s.fixture.warp_to_timestamp(s.start + DAY).await;
+    let without_hook = vec![AccountMeta::new_readonly(integration::find_claim_hook_allowlist_address().0, false)];
+    assert_error(s.unlock(escrow, without_hook).await, VestingError::InvalidClaimHookAccounts.into());
+    s.unlock(escrow, integration::claim_hook_accounts(&hook)).await.unwrap();
+    let data = s.fixture.context.banks_client.get_account(record).await.unwrap().unwrap().data;
+    assert_eq!(data[1..3], [1, 1]);
+    assert_eq!(data[3..11], 300u64.to_le_bytes());
+    assert_eq!(data[11..43], s.destination.to_bytes());
+
+    // Hooks hold off bridging, which couldn't notify them
+    let beneficiary = Keypair::from_bytes(&s.beneficiary.to_bytes()).unwrap();
+    let target = BridgeTarget { chain: 2, address: [1; 32] };
+    let instruction = integration::set_bridge_target(SEEDS, s.destination, beneficiary.pubkey(), Some(target));
+    s.fixture.process(&[instruction], &[&beneficiary]).await.unwrap();
+    assert_error(s.claim_and_bridge(escrow, &beneficiary).await, VestingError::ClaimHookEnabled.into());
+
+    // A failing hook fails the claim until the admin delists it, after which claims skip it
+    let mut failing = data.clone();
+    failing[0] = 1;
+    s.fixture.set_foreign_account(&record, &HOOK_PROGRAM, failing.clone()).await;
+    s.fixture.warp_to_timestamp(s.start + 2 * DAY).await;
+    assert_error(s.unlock(escrow, integration::claim_hook_accounts(&hook)).await, HOOK_FAILURE);
+    s.fixture.allowlist_claim_hooks(&admin, vec![]).await.unwrap();
+    s.unlock(escrow, integration::claim_hook_accounts(&hook)).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 600);
+    assert_eq!(s.fixture.context.banks_client.get_account(record).await.unwrap().unwrap().data, failing);
+}
+
+#[tokio::test]
+async fn voters_count_each_contract_once() {
+    let mut s = setup().await;
+    s.grant(cliff_schedule(s.start)).await;
+    s.accept().await;
+    let (realm, other_realm, authority) = (Pubkey::new_unique(), Pubkey::new_unique(), Keypair::new());
+    s.fixture.set_foreign_account(&realm, &GOVERNANCE_PROGRAM, realm_data(&authority.pubkey())).await;
+    s.fixture.set_foreign_account(&other_realm, &GOVERNANCE_PROGRAM, realm_data(&authority.pubkey())).await;
+    let registrar = s.fixture.create_registrar(&realm, &GOVERNANCE_PROGRAM, &s.mint).await.unwrap();
+    let other_registrar = s.fixture.create_registrar(&other_realm, &GOVERNANCE_PROGRAM, &s.mint).await.unwrap();
+    let beneficiary = s.beneficiary.pubkey();
+    let record = s.fixture.create_voter_weight_record(&registrar, &beneficiary).await.unwrap();
+    s.fixture.create_voter_weight_record(&other_registrar, &beneficiary).await.unwrap();
+    let stranger = Pubkey::new_unique();
+    s.fixture.create_voter_weight_record(&registrar, &stranger).await.unwrap();
+    let contract = [(find_vesting_address(&SEEDS).0, s.destination)];
+
+    // The destination owner votes with the locked tokens, the contract recording the record counting it
+    let instruction = integration::update_voter_weight_record(registrar, stranger, &contract);
+    assert_error(s.fixture.process(&[instruction], &[]).await, VestingError::InvalidDestinationAuthority.into());
+    let instruction = integration::update_voter_weight_record(registrar, beneficiary, &contract);
+    s.fixture.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(s.fixture.voter_weight_record(&record).await.voter_weight, 1_000);
+    assert_eq!(s.fixture.vesting(&SEEDS).await.voter_weight.unwrap().voter_weight_record, record);
+
+    // Updates recount what is still locked, in one realm at a time
+    s.fixture.warp_to_timestamp(s.start + DAY).await;
+    let instruction = integration::update_voter_weight_record(registrar, beneficiary, &contract);
+    s.fixture.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(s.fixture.voter_weight_record(&record).await.voter_weight, 700);
+    let instruction = integration::update_voter_weight_record(other_registrar, beneficiary, &contract);
+    assert_error(s.fixture.process(&[instruction], &[]).await, VestingError::VoterWeightCountedElsewhere.into());
+}
+
+#[tokio::test]
+async fn delegates_vote_with_the_locked_tokens_instead_of_the_owner() {
+    let mut s = setup().await;
+    s.grant(cliff_schedule(s.start)).await;
+    s.accept().await;
+    let (realm, authority) = (Pubkey::new_unique(), Keypair::new());
+    s.fixture.set_foreign_account(&realm, &GOVERNANCE_PROGRAM, realm_data(&authority.pubkey())).await;
+    let registrar = s.fixture.create_registrar(&realm, &GOVERNANCE_PROGRAM, &s.mint).await.unwrap();
+    let delegate = Pubkey::new_unique();
+    let beneficiary_record = s.fixture.create_voter_weight_record(&registrar, &s.beneficiary.pubkey()).await.unwrap();
+    let delegate_record = s.fixture.create_voter_weight_record(&registrar, &delegate).await.unwrap();
+    let contract = [(find_vesting_address(&SEEDS).0, s.destination)];
+    let instruction = integration::update_voter_weight_record(registrar, s.beneficiary.pubkey(), &contract);
+    s.fixture.process(&[instruction], &[]).await.unwrap();
+
+    // Delegating takes the weight off the record counting it, which has to be passed
+    let result = s.fixture.delegate_votes(SEEDS, &s.destination, &s.beneficiary, &delegate).await;
+    assert_error(result, VestingError::MissingVoterWeightRecord.into());
+    let payer = s.fixture.payer();
+    let mut instruction = integration::set_vote_delegate(SEEDS, s.destination, s.beneficiary.pubkey(), delegate, payer);
+    instruction.accounts.push(AccountMeta::new(beneficiary_record, false));
+    s.fixture.process(&[instruction], &[&s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.voter_weight_record(&beneficiary_record).await.voter_weight, 0);
+    assert!(s.fixture.vesting(&SEEDS).await.voter_weight.is_none());
+
+    // The delegate now votes for it instead of the owner
+    let instruction = integration::update_voter_weight_record(registrar, s.beneficiary.pubkey(), &contract);
+    assert_error(s.fixture.process(&[instruction], &[]).await, VestingError::InvalidDestinationAuthority.into());
+    let instruction = integration::update_voter_weight_record(registrar, delegate, &contract);
+    s.fixture.process(&[instruction], &[]).await.unwrap();
+    assert_eq!(s.fixture.voter_weight_record(&delegate_record).await.voter_weight, 1_000);
+}
+
+#[tokio::test]
+async fn realm_authorities_set_the_max_voter_weight_to_the_locked_supply() {
+    let mut s = setup().await;
+    s.grant(cliff_schedule(s.start)).await;
+    s.accept().await;
+    let (realm, authority) = (Pubkey::new_unique(), Keypair::new());
+    s.fixture.set_foreign_account(&realm, &GOVERNANCE_PROGRAM, realm_data(&authority.pubkey())).await;
+    let registrar = s.fixture.create_registrar(&realm, &GOVERNANCE_PROGRAM, &s.mint).await.unwrap();
+    let max_record = s.fixture.create_max_voter_weight_record(&registrar).await.unwrap();
+    let vesting = find_vesting_address(&SEEDS).0;
+    s.fixture.warp_to_timestamp(s.start + DAY).await;
+
+    let instruction = integration::update_max_voter_weight_record(registrar, realm, s.creator.pubkey(), &[vesting]);
+    assert_error(s.fixture.process(&[instruction], &[&s.creator]).await, VestingError::InvalidRealmAuthority.into());
+    let instruction = integration::update_max_voter_weight_record(registrar, realm, authority.pubkey(), &[vesting]);
+    s.fixture.process(&[instruction], &[&authority]).await.unwrap();
+    assert_eq!(s.fixture.max_voter_weight_record(&max_record).await.max_voter_weight, 700);
+}
+
+#[tokio::test]
+async fn claims_bridge_to_the_target_the_beneficiary_recorded() {
+    let mut s = setup().await;
+    let escrow = s.grant(cliff_schedule(s.start)).await;
+    s.accept().await;
+    let mut bridge = vec![0; 24];
+    bridge[16..24].copy_from_slice(&BRIDGE_FEE.to_le_bytes());
+    s.fixture.set_foreign_account(&wormhole::bridge(), &core_bridge_program::ID, bridge).await;
+    s.fixture.set_foreign_account(&wormhole::fee_collector(), &system_program::ID, vec![]).await;
+    let custody = wormhole::custody(&s.mint);
+    s.fixture.create_token_account(&custody, &wormhole::custody_signer(), &s.mint).await;
+    s.fixture.warp_to_timestamp(s.start + DAY).await;
+
+    let beneficiary = Keypair::from_bytes(&s.beneficiary.to_bytes()).unwrap();
+    assert_error(s.claim_and_bridge(escrow, &beneficiary).await, VestingError::NoBridgeTarget.into());
+
+    // Only the owner of the destination records the target and bridges
+    let target = BridgeTarget { chain: 2, address: [1; 32] };
+    let instruction = integration::set_bridge_target(SEEDS, s.destination, s.creator.pubkey(), Some(target.clone()));
+    assert_error(s.fixture.process(&[instruction], &[&s.creator]).await, VestingError::InvalidDestinationAuthority.into());
+    let instruction = integration::set_bridge_target(SEEDS, s.destination, beneficiary.pubkey(), Some(target.clone()));
+    s.fixture.process(&[instruction], &[&beneficiary]).await.unwrap();
+    let recorded = s.fixture.vesting(&SEEDS).await.bridge_target.unwrap();
+    assert_eq!((recorded.chain, recorded.address), (target.chain, target.address));
+    let creator = Keypair::from_bytes(&s.creator.to_bytes()).unwrap();
+    assert_error(s.claim_and_bridge(escrow, &creator).await, VestingError::InvalidDestinationAuthority.into());
+
+    // The bridge pulls the unlocked tokens into custody through the delegate, left cleared, and the fee is paid
+    let fees = s.fixture.lamports(&wormhole::fee_collector()).await;
+    s.claim_and_bridge(escrow, &beneficiary).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&custody).await, 300);
+    assert_eq!(s.fixture.token_balance(&escrow).await, 700);
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 0);
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1);
+    assert_eq!(s.fixture.lamports(&wormhole::fee_collector()).await, fees + BRIDGE_FEE);
+    let escrow_account = s.fixture.context.banks_client.get_account(escrow).await.unwrap().unwrap();
+    assert!(spl_token::state::Account::unpack(&escrow_account.data).unwrap().delegate.is_none());
+}
+
+/// A Switchboard function whose verified enclave, valid until `valid_until`, signs with `enclave_signer`
+fn function_data(enclave_signer: &Pubkey, valid_until: i64) -> Vec<u8> {
+    let mut data = vec![0; 473];
+    data[..8].copy_from_slice(&[76, 139, 47, 44, 240, 182, 148, 200]);
+    data[392..424].copy_from_slice(enclave_signer.as_ref());
+    data[424..456].copy_from_slice(&[1; 32]);
+    data[456] = 1 << 2;
+    data[465..473].copy_from_slice(&valid_until.to_le_bytes());
+    data
+}
+
+/// An ed25519 program instruction verifying the signature of `signer` over `message`
+fn ed25519_instruction(signer: &Keypair, message: &[u8]) -> Instruction {
+    let (public_key_offset, signature_offset, message_offset) = (16u16, 48u16, 112u16);
+    let mut data = vec![1, 0];
+    for offset in [signature_offset, u16::MAX, public_key_offset, u16::MAX, message_offset, message.len() as u16, u16::MAX] {
+        data.extend_from_slice(&offset.to_le_bytes());
+    }
+    data.extend_from_slice(signer.pubkey().as_ref());
+    data.extend_from_slice(signer.sign_message(message).as_ref());
+    data.extend_from_slice(message);
+    Instruction { program_id: ed25519_program::ID, accounts: vec![], data }
+}
+
+fn trigger_release_condition(schedule_index: u32, remaining_accounts: &[Pubkey]) -> Instruction {
+    let mut accounts = token_vesting::accounts::TriggerReleaseCondition { vesting: find_vesting_address(&SEEDS).0 }.to_account_metas(None);
+    accounts.extend(remaining_accounts.iter().map(|account| AccountMeta::new_readonly(*account, false)));
+    Instruction {
+        program_id: token_vesting::ID,
+        accounts,
+        data: token_vesting::instruction::TriggerReleaseCondition { _seeds: SEEDS, schedule_index }.data(),
+    }
+}
+
+fn fulfill_function_condition(schedule_index: u32, function: Pubkey, enclave_signer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: token_vesting::ID,
+        accounts: token_vesting::accounts::FulfillFunctionCondition {
+            vesting: find_vesting_address(&SEEDS).0,
+            function,
+            enclave_signer,
+        }.to_account_metas(None),
+        data: token_vesting::instruction::FulfillFunctionCondition { _seeds: SEEDS, schedule_index }.data(),
+    }
+}
+
+fn fulfill_signature_condition(schedule_index: u32, deadline: i64) -> Instruction {
+    Instruction {
+        program_id: token_vesting::ID,
+        accounts: token_vesting::accounts::FulfillSignatureCondition {
+            vesting: find_vesting_address(&SEEDS).0,
+            instructions: solana_sdk::sysvar::instructions::ID,
+        }.to_account_metas(None),
+        data: token_vesting::instruction::FulfillSignatureCondition { _seeds: SEEDS, schedule_index, deadline }.data(),
+    }
+}
+
+#[tokio::test]
+async fn price_conditions_clear_once_the_pinned_feed_reaches_the_target() {
+    let mut s = setup().await;
+    // The token at $1.50 against a $2 target
+    let feed = s.fixture.set_pyth_price(150, -2, s.start).await;
+    let condition = ReleaseCondition::Price { price_feed: feed, target_price: 200, target_expo: -2, is_above: true, max_staleness: 3_600 };
+    let escrow = s.grant(vec![Schedule { condition: Some(condition), ..tranche(s.start, 1_000) }]).await;
+    s.accept().await;
+    assert_error(s.unlock(escrow, vec![]).await, VestingError::ReleaseTimeNotYetReached.into());
+
+    let other_feed = s.fixture.set_pyth_price(250, -2, s.start).await;
+    assert_error(s.fixture.process(&[trigger_release_condition(0, &[other_feed])], &[]).await, VestingError::InvalidPriceFeed.into());
+    assert_error(s.fixture.process(&[trigger_release_condition(0, &[feed])], &[]).await, VestingError::PriceConditionNotMet.into());
+    let mut feed_account = s.fixture.context.banks_client.get_account(feed).await.unwrap().unwrap();
+    feed_account.data[208..216].copy_from_slice(&250i64.to_le_bytes());
+    s.fixture.set_foreign_account(&feed, &feed_account.owner, feed_account.data).await;
+    s.fixture.process(&[trigger_release_condition(0, &[feed])], &[]).await.unwrap();
+
+    s.unlock(escrow, vec![]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_000);
+}
+
+#[tokio::test]
+async fn function_conditions_clear_once_the_verified_enclave_signs() {
+    let mut s = setup().await;
+    let (function, enclave) = (Pubkey::new_unique(), Keypair::new());
+    let condition = ReleaseCondition::SwitchboardFunction { function };
+    let escrow = s.grant(vec![Schedule { condition: Some(condition), ..tranche(s.start, 1_000) }]).await;
+    s.accept().await;
+
+    s.fixture.set_foreign_account(&function, &attestation_program::ID, function_data(&enclave.pubkey(), s.start + DAY)).await;
+    assert_error(s.fixture.process(&[trigger_release_condition(0, &[])], &[]).await, VestingError::FunctionRequiresEnclaveSigner.into());
+    let instruction = fulfill_function_condition(0, function, s.creator.pubkey());
+    assert_error(s.fixture.process(&[instruction], &[&s.creator]).await, VestingError::InvalidEnclaveSigner.into());
+    s.fixture.process(&[fulfill_function_condition(0, function, enclave.pubkey())], &[&enclave]).await.unwrap();
+
+    s.unlock(escrow, vec![]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_000);
+}
+
+#[tokio::test]
+async fn signature_conditions_clear_once_the_oracle_signs_before_the_deadline() {
+    let mut s = setup().await;
+    let oracle = Keypair::new();
+    let condition = ReleaseCondition::OracleSignature { oracle: oracle.pubkey() };
+    let escrow = s.grant(vec![Schedule { condition: Some(condition), ..tranche(s.start, 1_000) }]).await;
+    s.accept().await;
+
+    // Oracles sign the tranche until a deadline
+    let deadline = s.start + DAY;
+    let mut message = find_vesting_address(&SEEDS).0.to_bytes().to_vec();
+    message.extend_from_slice(&0u32.to_le_bytes());
+    message.extend_from_slice(&deadline.to_le_bytes());
+    let result = s.fixture.process(&[fulfill_signature_condition(0, deadline)], &[]).await;
+    assert_error(result, VestingError::MissingOracleSignature.into());
+    let result = s.fixture.process(&[ed25519_instruction(&s.creator, &message), fulfill_signature_condition(0, deadline)], &[]).await;
+    assert_error(result, VestingError::MissingOracleSignature.into());
+    s.fixture.warp_to_timestamp(deadline + 1).await;
+    let result = s.fixture.process(&[ed25519_instruction(&oracle, &message), fulfill_signature_condition(0, deadline)], &[]).await;
+    assert_error(result, VestingError::SignatureExpired.into());
+    s.fixture.warp_to_timestamp(deadline).await;
+    s.fixture.process(&[ed25519_instruction(&oracle, &message), fulfill_signature_condition(0, deadline)], &[]).await.unwrap();
+
+    s.unlock(escrow, vec![]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_000);
+}
+
+#[tokio::test]
+async fn governance_conditions_clear_once_the_proposal_succeeds() {
+    let mut s = setup().await;
+    let proposal = Pubkey::new_unique();
+    let condition = ReleaseCondition::GovernanceProposal { governance_program: GOVERNANCE_PROGRAM, proposal };
+    let escrow = s.grant(vec![Schedule { condition: Some(condition), ..tranche(s.start, 1_000) }]).await;
+    s.accept().await;
+
+    // Proposals have to be voted through
+    let mut proposal_data = vec![0; 66];
+    proposal_data[0] = 14;
+    s.fixture.set_foreign_account(&proposal, &GOVERNANCE_PROGRAM, proposal_data.clone()).await;
+    assert_error(s.fixture.process(&[trigger_release_condition(0, &[])], &[]).await, VestingError::InvalidProposal.into());
+    assert_error(s.fixture.process(&[trigger_release_condition(0, &[proposal])], &[]).await, VestingError::ProposalNotSucceeded.into());
+    proposal_data[65] = 3;
+    s.fixture.set_foreign_account(&proposal, &GOVERNANCE_PROGRAM, proposal_data).await;
+    s.fixture.process(&[trigger_release_condition(0, &[proposal])], &[]).await.unwrap();
+
+    s.unlock(escrow, vec![]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_000);
+}
+
+#[tokio::test]
+async fn vaults_earn_on_escrows_until_the_last_claim() {
+    let mut s = setup().await;
+    let vault = s.create_vault().await;
+
+    // The escrow of a yielding contract is deposited as it is created
+    let (escrow, shares) = s.grant_with_yield(SEEDS, cliff_schedule(s.start), &vault).await;
+    s.accept().await;
+    assert_eq!(s.fixture.token_balance(&escrow).await, 0);
+    assert_eq!(s.fixture.token_balance(&shares).await, 1_000);
+
+    // Claims withdraw from the vault, the last one redeeming the yield to the recipient
+    s.fixture.mint_to(&s.mint, &vault.reserve, 100).await;
+    s.fixture.warp_to_timestamp(s.start + DAY).await;
+    assert_error(s.unlock(escrow, vec![]).await, VestingError::YieldVaultEnabled.into());
+    let (destination, mint, source) = (s.destination, s.mint, s.source);
+    let unlock = |yield_recipient| integration::unlock_from_yield(SEEDS, destination, mint, yield_recipient, VAULT_PROGRAM, vault.vault,
+                                                                  vault.reserve, vault.share_mint, shares);
+    assert_error(s.fixture.process(&[unlock(destination)], &[]).await, VestingError::InvalidYieldRecipient.into());
+    s.fixture.process(&[unlock(source)], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+    s.fixture.warp_to_timestamp(s.start + 3 * DAY).await;
+    s.fixture.process(&[unlock(source)], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_000);
+    assert_eq!(s.fixture.token_balance(&s.source).await, 101);
+    assert_eq!(s.fixture.token_balance(&shares).await, 0);
+}
+
+#[tokio::test]
+async fn claims_are_staked_for_the_owner_of_the_destination() {
+    const YIELDING_SEEDS: [u8; 31] = [10; 31];
+    let mut s = setup().await;
+    let vault = s.create_vault().await;
+    let escrow = s.grant(cliff_schedule(s.start)).await;
+    s.accept().await;
+    s.fixture.mint_to(&s.mint, &s.source, 1_000).await;
+    let (yielding_escrow, _) = s.grant_with_yield(YIELDING_SEEDS, cliff_schedule(s.start), &vault).await;
+    let pool_token = s.fixture.create_associated_token_account(&s.beneficiary.pubkey(), &vault.share_mint).await;
+    s.fixture.warp_to_timestamp(s.start + 3 * DAY).await;
+
+    // Claims of a plain contract are staked for the owner of its destination, yielding contracts already earn
+    let destination = s.destination;
+    let stake = |seeds, vesting_token, destination_authority| integration::claim_and_stake(
+        seeds, vesting_token, destination, destination_authority, VAULT_PROGRAM, vault.vault, vault.reserve, vault.share_mint, pool_token);
+    let instruction = stake(YIELDING_SEEDS, yielding_escrow, s.beneficiary.pubkey());
+    assert_error(s.fixture.process(&[instruction], &[&s.beneficiary]).await, VestingError::YieldVaultEnabled.into());
+    let instruction = stake(SEEDS, escrow, s.creator.pubkey());
+    assert_error(s.fixture.process(&[instruction], &[&s.creator]).await, VestingError::InvalidDestinationAuthority.into());
+    let instruction = stake(SEEDS, escrow, s.beneficiary.pubkey());
+    s.fixture.process(&[instruction], &[&s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&pool_token).await, 1_000);
+    assert_eq!(s.fixture.token_balance(&escrow).await, 0);
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 0);
+}
+
+#[tokio::test]
+async fn compressed_receipts_are_minted_to_the_beneficiary_by_the_contract() {
+    let mut s = setup().await;
+    s.grant(cliff_schedule(s.start)).await;
+    let tree = Pubkey::new_unique();
+    s.fixture.set_foreign_account(&tree, &bubblegum::ID, vec![0; TREE_RECORD_SIZE]).await;
+    let (destination, beneficiary, payer) = (s.destination, s.beneficiary.pubkey(), s.fixture.payer());
+    let mint = |creator| integration::mint_compressed_receipt(SEEDS, destination, beneficiary, tree, creator, payer, "Grant".to_string(),
+                                                              "VEST".to_string(), "https://example.com/grant.json".to_string());
+
+    assert_error(s.fixture.process(&[mint(beneficiary)], &[&s.beneficiary]).await, VestingError::InvalidCreator.into());
+    s.fixture.process(&[mint(s.creator.pubkey())], &[&s.creator]).await.unwrap();
+    let record = s.fixture.context.banks_client.get_account(tree).await.unwrap().unwrap().data;
+    assert_eq!(record[0], 1);
+    assert_eq!(record[1..33], beneficiary.to_bytes());
+}
@@ -0,0 +1,1444 @@
+//! Contracts driven through their lifetime on a test bank, the clock warped across their release times.
+
+use anchor_lang::{InstructionData, ToAccountMetas};
+use solana_program_test::{tokio, BanksClientError};
+use solana_sdk::instruction::{AccountMeta, Instruction, InstructionError};
+use solana_sdk::pubkey::Pubkey;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::TransactionError;
+use token_vesting::integration::{self, airdrop_position_seeds, find_airdrop_address, find_budget_address, find_distributor_address,
+                                 find_funding_escrow_address, find_partner_config_address, find_shared_vault_address,
+                                 find_vesting_address, locked_at};
+use token_vesting::program_test::{DeferredFreeAllocator, VestingFixture};
+use token_vesting::{merkle, BridgeTarget, MerkleTranche, NextAction, PartnerConfig, ReleaseCondition, Schedule,
+                    TemplateTranche, UnlockPolicy, VestingError, MAX_PARTNER_FEE_BPS, MIN_TWAP_OBSERVATIONS};
+
+#[global_allocator]
+static ALLOCATOR: DeferredFreeAllocator = DeferredFreeAllocator;
+
+const SEEDS: [u8; 31] = [7; 31];
+
+struct Setup {
+    fixture: VestingFixture,
+    mint: Pubkey,
+    creator: Keypair,
+    beneficiary: Keypair,
+    source: Pubkey,
+    destination: Pubkey,
+    start: i64,
+}
+
+/// Starts a bank with a creator holding `balance` tokens and an empty token account of the beneficiary
+async fn setup(balance: u64) -> Setup {
+    let mut fixture = VestingFixture::start().await;
+    let mint = fixture.create_mint(6).await;
+    let creator = Keypair::new();
+    let beneficiary = Keypair::new();
+    let source = fixture.create_associated_token_account(&creator.pubkey(), &mint).await;
+    let destination = fixture.create_associated_token_account(&beneficiary.pubkey(), &mint).await;
+    fixture.mint_to(&mint, &source, balance).await;
+    let start = fixture.now().await;
+    Setup { fixture, mint, creator, beneficiary, source, destination, start }
+}
+
+fn tranche(release_time: i64, amount: u64) -> Schedule {
+    Schedule { release_time: release_time as u64, amount, condition: None }
+}
+
+/// 300 at the cliff a day in, then 300 and 400 a day apart
+fn cliff_schedule(start: i64) -> Vec<Schedule> {
+    vec![tranche(start + 86_400, 300), tranche(start + 2 * 86_400, 300), tranche(start + 3 * 86_400, 400)]
+}
+
+impl Setup {
+    async fn create(&mut self, schedules: Vec<Schedule>) -> Pubkey {
+        let escrow = self.fixture.create_grant(SEEDS, &self.source, &self.creator, &self.mint, &self.destination, schedules)
+            .await.unwrap();
+        self.accept().await.unwrap();
+        escrow
+    }
+
+    /// Creates a contract with the settings `integration::create` leaves at their defaults
+    async fn create_with(&mut self, schedules: Vec<Schedule>, min_claim_amount: u64, revoker: Option<Pubkey>,
+                         unlock_policy: UnlockPolicy) -> Pubkey {
+        let vesting = find_vesting_address(&SEEDS).0;
+        self.fixture.allocate_vesting(&SEEDS, schedules.len() as u32).await;
+        let escrow = self.fixture.create_associated_token_account(&vesting, &self.mint).await;
+        let instruction = Instruction {
+            program_id: token_vesting::ID,
+            accounts: token_vesting::accounts::Create {
+                vesting,
+                vesting_token: escrow,
+                mint: self.mint,
+                source_token: self.source,
+                source_authority: self.creator.pubkey(),
+                token_program: spl_token::ID,
+            }.to_account_metas(None),
+            data: token_vesting::instruction::Create {
+                seeds: SEEDS,
+                mint_address: self.mint,
+                destination_token_address: self.destination,
+                schedules,
+                min_claim_amount,
+                revoker,
+                soulbound_receipt: false,
+                unlock_policy,
+            }.data(),
+        };
+        self.fixture.process(&[instruction], &[&self.creator]).await.unwrap();
+        self.accept().await.unwrap();
+        escrow
+    }
+
+    async fn accept(&mut self) -> Result<(), BanksClientError> {
+        let instruction = integration::accept(SEEDS, self.destination, self.beneficiary.pubkey());
+        self.fixture.process(&[instruction], &[&self.beneficiary]).await
+    }
+
+    async fn unlock(&mut self, escrow: Pubkey) -> Result<(), BanksClientError> {
+        let instruction = integration::unlock(SEEDS, escrow, self.mint, self.destination);
+        self.fixture.process(&[instruction], &[]).await
+    }
+
+    async fn revoke(&mut self, escrow: Pubkey, revoker: &Keypair) -> Result<(), BanksClientError> {
+        let instruction = Instruction {
+            program_id: token_vesting::ID,
+            accounts: token_vesting::accounts::Revoke {
+                vesting: find_vesting_address(&SEEDS).0,
+                vesting_token: escrow,
+                mint: self.mint,
+                refund_token: self.source,
+                revoker: revoker.pubkey(),
+                token_program: spl_token::ID,
+            }.to_account_metas(None),
+            data: token_vesting::instruction::Revoke { seeds: SEEDS }.data(),
+        };
+        self.fixture.process(&[instruction], &[revoker]).await
+    }
+
+    async fn balances(&mut self, escrow: Pubkey) -> (u64, u64, u64) {
+        (
+            self.fixture.token_balance(&self.source).await,
+            self.fixture.token_balance(&escrow).await,
+            self.fixture.token_balance(&self.destination).await,
+        )
+    }
+}
+
+fn assert_error<T: std::fmt::Debug>(result: Result<T, BanksClientError>, expected: VestingError) {
+    match result {
+        Err(BanksClientError::TransactionError(TransactionError::InstructionError(_, InstructionError::Custom(code)))) =>
+            assert_eq!(code, u32::from(expected), "unexpected error code"),
+        other => panic!("expected a program error, got {:?}", other),
+    }
+}
+
+#[tokio::test]
+async fn unlock_releases_tranches_as_the_clock_crosses_them() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    assert_eq!(s.balances(escrow).await, (1, 1_000, 0));
+
+    assert_error(s.unlock(escrow).await, VestingError::ReleaseTimeNotYetReached);
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 700, 300));
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!(vesting.schedule[0].amount, 0);
+    assert_eq!(locked_at(&vesting, s.start + 86_400), 700);
+    assert_error(s.unlock(escrow).await, VestingError::ReleaseTimeNotYetReached);
+
+    // Two tranches released by the time anyone claims again
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400 + 1).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 0, 1_000));
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert!(vesting.schedule.iter().all(|tranche| tranche.amount == 0));
+    assert!(vesting.next_action == NextAction::Completed);
+    assert_error(s.unlock(escrow).await, VestingError::ReleaseTimeNotYetReached);
+}
+
+#[tokio::test]
+async fn unlock_waits_for_acceptance() {
+    let mut s = setup(1_001).await;
+    let escrow = s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+        .await.unwrap();
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    assert_error(s.unlock(escrow).await, VestingError::NotAccepted);
+    assert!(s.fixture.vesting(&SEEDS).await.next_action == NextAction::AcceptPending);
+
+    s.accept().await.unwrap();
+    assert_error(s.accept().await, VestingError::AlreadyAccepted);
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 700, 300));
+}
+
+#[tokio::test]
+async fn final_unlock_sweeps_the_escrow() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    s.fixture.mint_to(&s.mint, &escrow, 5).await;
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 705, 300));
+
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 0, 1_005));
+}
+
+#[tokio::test]
+async fn create_requires_a_funded_source() {
+    let mut s = setup(1_000).await;
+    let result = s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+        .await;
+    assert_error(result, VestingError::InsufficientFunds);
+}
+
+#[tokio::test]
+async fn cancel_refunds_a_pending_contract() {
+    let mut s = setup(1_001).await;
+    let escrow = s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+        .await.unwrap();
+
+    let cancel = integration::cancel(SEEDS, escrow, s.mint, s.source, s.creator.pubkey());
+    s.fixture.process(&[cancel], &[&s.creator]).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1_001, 0, 0));
+    assert!(s.fixture.vesting(&SEEDS).await.schedule.iter().all(|tranche| tranche.amount == 0));
+}
+
+#[tokio::test]
+async fn cancel_is_refused_once_accepted() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+
+    let cancel = integration::cancel(SEEDS, escrow, s.mint, s.source, s.creator.pubkey());
+    assert_error(s.fixture.process(&[cancel], &[&s.creator]).await, VestingError::AlreadyAccepted);
+    assert_eq!(s.balances(escrow).await, (1, 1_000, 0));
+}
+
+#[tokio::test]
+async fn contracts_of_the_first_release_migrate_to_the_current_layout() {
+    let mut s = setup(0).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&SEEDS).0, &s.mint).await;
+    s.fixture.mint_to(&s.mint, &escrow, 1_000).await;
+    let first_tranche = (s.start + 86_400) as u64;
+    s.fixture.write_legacy_vesting(&SEEDS, &s.destination, &s.mint, &[(first_tranche, 300), (first_tranche + 86_400, 700)]).await;
+    assert!(s.unlock(escrow).await.is_err());
+
+    let migrate = integration::migrate_vesting_layout(SEEDS, s.fixture.payer());
+    s.fixture.process(std::slice::from_ref(&migrate), &[]).await.unwrap();
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!((vesting.destination_address, vesting.mint_address), (s.destination, s.mint));
+    assert!(vesting.is_accepted && vesting.unlock_policy == UnlockPolicy::Anyone);
+    assert_eq!(locked_at(&vesting, s.start), 1_000);
+    s.fixture.warp_forward(1).await;
+    assert_error(s.fixture.process(&[migrate], &[]).await, VestingError::VestingLayoutCurrent);
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (0, 700, 300));
+}
+
+#[tokio::test]
+async fn revoke_refunds_the_locked_tranches() {
+    let mut s = setup(1_001).await;
+    let revoker = Keypair::new();
+    let escrow = s.create_with(cliff_schedule(s.start), 0, Some(revoker.pubkey()), UnlockPolicy::Anyone).await;
+
+    assert_error(s.revoke(escrow, &Keypair::new()).await, VestingError::InvalidRevoker);
+
+    // The released tranche stays claimable after the revocation
+    s.fixture.warp_to_timestamp(s.start + 86_400 + 60).await;
+    s.revoke(escrow, &revoker).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (701, 300, 0));
+    assert_error(s.revoke(escrow, &revoker).await, VestingError::NothingToRevoke);
+
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (701, 0, 300));
+    assert!(s.fixture.vesting(&SEEDS).await.next_action == NextAction::Completed);
+}
+
+#[tokio::test]
+async fn min_claim_amount_holds_back_small_claims() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create_with(cliff_schedule(s.start), 500, None, UnlockPolicy::Anyone).await;
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    assert_error(s.unlock(escrow).await, VestingError::ClaimBelowMinimum);
+
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 400, 600));
+
+    // The final claim goes through below the minimum
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 0, 1_000));
+}
+
+#[tokio::test]
+async fn restricted_contracts_only_unlock_for_their_authorities() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create_with(cliff_schedule(s.start), 0, None, UnlockPolicy::DestinationOwner).await;
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+
+    assert_error(s.unlock(escrow).await, VestingError::UnlockRestricted);
+
+    let stranger = Keypair::new();
+    let unlock = integration::authorized_unlock(SEEDS, escrow, s.mint, s.destination, stranger.pubkey());
+    assert!(s.fixture.process(&[unlock], &[&stranger]).await.is_err());
+
+    let unlock = integration::authorized_unlock(SEEDS, escrow, s.mint, s.destination, s.beneficiary.pubkey());
+    s.fixture.process(&[unlock], &[&s.beneficiary]).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 700, 300));
+}
+
+#[tokio::test]
+async fn change_destination_redirects_later_claims() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(escrow).await.unwrap();
+
+    s.fixture.delegate_votes(SEEDS, &s.destination, &s.beneficiary, &Pubkey::new_unique()).await.unwrap();
+
+    let new_owner = Keypair::new();
+    let new_destination = s.fixture.create_associated_token_account(&new_owner.pubkey(), &s.mint).await;
+    let change = integration::change_destination(SEEDS, s.destination, s.beneficiary.pubkey(), new_destination);
+    s.fixture.process(&[change], &[&s.beneficiary]).await.unwrap();
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!(vesting.destination_address, new_destination);
+    assert_eq!(vesting.vote_delegate, None, "the previous owner's delegate must not carry over");
+
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400).await;
+    assert_error(s.unlock(escrow).await, VestingError::InvalidDestination);
+    let unlock = integration::unlock(SEEDS, escrow, s.mint, new_destination);
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&new_destination).await, 700);
+    assert_eq!(s.balances(escrow).await, (1, 0, 300));
+}
+
+#[tokio::test]
+async fn warp_moves_the_clock_the_program_reads() {
+    let mut s = setup(1_001).await;
+    s.fixture.warp_forward(86_400).await;
+    assert_eq!(s.fixture.now().await, s.start + 86_400);
+
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    assert!(s.fixture.vesting(&SEEDS).await.next_action == NextAction::Claimable);
+    s.unlock(escrow).await.unwrap();
+    assert!(s.fixture.vesting(&SEEDS).await.next_action == NextAction::WaitUntil(s.start + 2 * 86_400));
+}
+
+#[cfg(feature = "test-clock")]
+#[tokio::test]
+async fn unlock_reads_the_test_clock_passed_last() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    s.fixture.set_test_clock(s.start + 2 * 86_400).await;
+
+    let mut unlock = integration::unlock(SEEDS, escrow, s.mint, s.destination);
+    unlock.accounts.push(AccountMeta::new_readonly(token_vesting::test_clock::test_clock_address().0, false));
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 400, 600));
+
+    // Without the test clock the program reads the cluster clock, still before the cliff
+    assert_error(s.unlock(escrow).await, VestingError::ReleaseTimeNotYetReached);
+}
+
+#[cfg(feature = "test-clock")]
+#[tokio::test]
+async fn only_the_config_admin_sets_the_test_clock() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    let admin = Keypair::new();
+    s.fixture.set_program_config(&admin.pubkey()).await;
+    s.fixture.set_test_clock(s.start).await;
+
+    let payer = s.fixture.payer();
+    let forged = integration::set_test_clock(s.creator.pubkey(), payer, s.start + 3 * 86_400);
+    assert_error(s.fixture.process(&[forged], &[&s.creator]).await, VestingError::InvalidConfigAdmin);
+
+    let set = integration::set_test_clock(admin.pubkey(), payer, s.start + 3 * 86_400);
+    s.fixture.process(&[set], &[&admin]).await.unwrap();
+    let mut unlock = integration::unlock(SEEDS, escrow, s.mint, s.destination);
+    unlock.accounts.push(AccountMeta::new_readonly(token_vesting::test_clock::test_clock_address().0, false));
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1, 0, 1_000));
+}
+
+#[tokio::test]
+async fn distributor_pays_each_leaf_as_it_releases() {
+    let mut s = setup(1_500).await;
+    let other = Keypair::new();
+    let leaf_tranches = vec![
+        MerkleTranche { release_time: (s.start + 86_400) as u64, amount: 300 },
+        MerkleTranche { release_time: (s.start + 2 * 86_400) as u64, amount: 700 },
+    ];
+    let other_tranches = vec![MerkleTranche { release_time: s.start as u64, amount: 500 }];
+    let leaves = [merkle::leaf(&s.beneficiary.pubkey(), &leaf_tranches), merkle::leaf(&other.pubkey(), &other_tranches)];
+
+    let distributor = find_distributor_address(&SEEDS).0;
+    let escrow = s.fixture.create_associated_token_account(&distributor, &s.mint).await;
+    s.fixture.create_distributor(SEEDS, &s.source, &s.creator, &s.mint, &escrow, merkle::root(&leaves), 1_500).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.source).await, 0);
+    s.fixture.prepare_claim_status(&distributor, &s.beneficiary.pubkey()).await;
+
+    let claim = |tranches: &[MerkleTranche], index: usize| integration::claim_from_distributor(
+        SEEDS, escrow, s.mint, s.beneficiary.pubkey(), s.destination, tranches.to_vec(), merkle::proof(&leaves, index));
+    let (claim_leaf, claim_other_leaf) = ([claim(&leaf_tranches, 0)], [claim(&other_tranches, 1)]);
+
+    assert_error(s.fixture.process(&claim_leaf, &[&s.beneficiary]).await, VestingError::ReleaseTimeNotYetReached);
+    // The leaf of another claimant doesn't pay the beneficiary
+    assert_error(s.fixture.process(&claim_other_leaf, &[&s.beneficiary]).await, VestingError::InvalidMerkleProof);
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.fixture.process(&claim_leaf, &[&s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    s.fixture.process(&claim_leaf, &[&s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_000);
+    assert_eq!(s.fixture.claim_status(&distributor, &s.beneficiary.pubkey()).await.claimed_amount, 1_000);
+    assert_eq!(s.fixture.token_balance(&escrow).await, 500);
+
+    s.fixture.warp_forward(60).await;
+    assert_error(s.fixture.process(&claim_leaf, &[&s.beneficiary]).await, VestingError::ReleaseTimeNotYetReached);
+}
+
+#[tokio::test]
+async fn shared_vault_pays_each_contract_its_own_schedule() {
+    let mut s = setup(0).await;
+    let (other_seeds, authority) = ([8; 31], s.creator.pubkey());
+    let shared_vault = find_shared_vault_address(&authority, &s.mint).0;
+    let vault = s.fixture.create_associated_token_account(&shared_vault, &s.mint).await;
+    s.fixture.create_shared_vault(&s.creator, &s.mint, &vault).await.unwrap();
+
+    // One deposit funds the cohort, each contract committing its total out of it
+    s.fixture.mint_to(&s.mint, &vault, 1_500).await;
+    for (seeds, schedules) in [(SEEDS, cliff_schedule(s.start)), (other_seeds, vec![tranche(s.start + 86_400, 500)])] {
+        s.fixture.allocate_vesting(&seeds, schedules.len() as u32).await;
+        let create = integration::create_in_shared_vault(seeds, vault, s.mint, authority, s.destination, schedules);
+        s.fixture.process(&[create], &[&s.creator]).await.unwrap();
+        let accept = integration::accept(seeds, s.destination, s.beneficiary.pubkey());
+        s.fixture.process(&[accept], &[&s.beneficiary]).await.unwrap();
+    }
+    s.fixture.allocate_vesting(&[9; 31], 1).await;
+    let overcommit = integration::create_in_shared_vault([9; 31], vault, s.mint, authority, s.destination, vec![tranche(s.start, 1)]);
+    assert_error(s.fixture.process(&[overcommit], &[&s.creator]).await, VestingError::InsufficientFunds);
+
+    // The contracts have no escrow of their own
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&SEEDS).0, &s.mint).await;
+    assert_error(s.unlock(escrow).await, VestingError::SharedVaultContract);
+
+    let unlock = |seeds| integration::unlock_from_shared_vault(seeds, vault, s.mint, authority, s.destination);
+    let (unlock_first, unlock_other) = ([unlock(SEEDS)], [unlock(other_seeds)]);
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.fixture.process(&unlock_first, &[]).await.unwrap();
+    s.fixture.process(&unlock_other, &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 800);
+
+    // The final claim of the first contract takes its schedule, not the vault
+    s.fixture.mint_to(&s.mint, &vault, 5).await;
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400).await;
+    s.fixture.process(&unlock_first, &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_500);
+    assert_eq!(s.fixture.token_balance(&vault).await, 5);
+    assert_error(s.fixture.process(&unlock_other, &[]).await, VestingError::ReleaseTimeNotYetReached);
+}
+
+#[tokio::test]
+async fn initializers_refuse_escrows_and_terms_they_cant_hold() {
+    let mut s = setup(1_000).await;
+    let other_mint = s.fixture.create_mint(6).await;
+
+    // Each escrow must belong to the account it funds and hold its mint
+    let creator_owned = s.source;
+    let result = s.fixture.create_shared_vault(&s.creator, &s.mint, &creator_owned).await;
+    assert_error(result, VestingError::InvalidVestingTokenAuthority);
+    let distributor = find_distributor_address(&SEEDS).0;
+    let other_escrow = s.fixture.create_associated_token_account(&distributor, &other_mint).await;
+    let result = s.fixture.create_distributor(SEEDS, &s.source, &s.creator, &s.mint, &other_escrow, [0; 32], 1_000).await;
+    assert_error(result, VestingError::InvalidMint);
+    let budget_token = s.fixture.create_associated_token_account(&find_budget_address(&s.creator.pubkey(), "2024").0, &other_mint).await;
+    let result = s.fixture.create_budget(&s.creator, "2024", &s.mint, &budget_token, 1_000, vec![]).await;
+    assert_error(result, VestingError::InvalidMint);
+
+    // Templates need a weighted tranche, partners a fee under the cap
+    let unweighted = vec![TemplateTranche { offset: 86_400, weight: 0 }];
+    assert_error(s.fixture.create_schedule_template(&s.creator, "empty", unweighted).await, VestingError::InvalidScheduleTemplate);
+    assert_error(s.fixture.create_schedule_template(&s.creator, "none", vec![]).await, VestingError::InvalidScheduleTemplate);
+    let partner = Keypair::new();
+    let config = PartnerConfig {
+        authority: partner.pubkey(),
+        fee_recipient: s.source,
+        fee_bps: MAX_PARTNER_FEE_BPS + 1,
+        branding: Pubkey::new_unique(),
+        default_min_claim_amount: 0,
+        default_unlock_policy: UnlockPolicy::DestinationOwner,
+        default_revocable: false,
+    };
+    assert_error(s.fixture.create_partner_config(&partner, &config).await, VestingError::InvalidPartnerConfig);
+
+    // Nothing of the refused distributor sticks, it's created once given an escrow of its mint
+    let escrow = s.fixture.create_associated_token_account(&distributor, &s.mint).await;
+    s.fixture.create_distributor(SEEDS, &s.source, &s.creator, &s.mint, &escrow, [0; 32], 1_000).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 1_000);
+}
+
+#[tokio::test]
+async fn template_contracts_split_their_total_by_weight() {
+    let mut s = setup(2_000).await;
+    // A 1 day cliff of a quarter, then a quarter and a half a day apart
+    let tranches = [(86_400, 1), (2 * 86_400, 1), (3 * 86_400, 2)]
+        .iter()
+        .map(|&(offset, weight)| TemplateTranche { offset, weight })
+        .collect();
+    let template = s.fixture.create_schedule_template(&s.creator, "1d cliff", tranches).await.unwrap();
+
+    let create = |s: &Setup, seeds, escrow, total_amount| integration::create_from_template(
+        seeds, escrow, s.source, s.creator.pubkey(), template, s.mint, s.destination, total_amount, s.start as u64);
+    // The account must hold one schedule per template tranche
+    s.fixture.allocate_vesting(&[9; 31], 2).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&[9; 31]).0, &s.mint).await;
+    let short = [create(&s, [9; 31], escrow, 1_000)];
+    assert_error(s.fixture.process(&short, &[&s.creator]).await, VestingError::InvalidScheduleLen);
+
+    s.fixture.allocate_vesting(&SEEDS, 3).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&SEEDS).0, &s.mint).await;
+    let instruction = [create(&s, SEEDS, escrow, 1_001)];
+    s.fixture.process(&instruction, &[&s.creator]).await.unwrap();
+    s.accept().await.unwrap();
+    let amounts: Vec<(i64, u64)> = s.fixture.vesting(&SEEDS).await.schedule.iter()
+        .map(|t| (t.release_time as i64 - s.start, t.amount))
+        .collect();
+    // The last tranche takes the rounding rest
+    assert_eq!(amounts, vec![(86_400, 250), (2 * 86_400, 250), (3 * 86_400, 501)]);
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 250);
+}
+
+#[tokio::test]
+async fn clones_scale_and_move_the_source_schedule() {
+    let mut s = setup(2_000).await;
+    s.create(cliff_schedule(s.start)).await;
+    let (clone_seeds, source_vesting) = ([9; 31], find_vesting_address(&SEEDS).0);
+    s.fixture.allocate_vesting(&clone_seeds, 3).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&clone_seeds).0, &s.mint).await;
+
+    let clone = |s: &Setup, source_start: i64| [integration::clone_contract(
+        clone_seeds, source_vesting, escrow, s.mint, s.source, s.creator.pubkey(), s.destination, 500, source_start as u64,
+        (s.start + 86_400) as u64)];
+    // The source contract doesn't release before the start it's cloned from
+    let late_start = clone(&s, s.start + 2 * 86_400);
+    assert_error(s.fixture.process(&late_start, &[&s.creator]).await, VestingError::InvalidCloneSource);
+
+    let instruction = clone(&s, s.start);
+    s.fixture.process(&instruction, &[&s.creator]).await.unwrap();
+    let accept = integration::accept(clone_seeds, s.destination, s.beneficiary.pubkey());
+    s.fixture.process(&[accept], &[&s.beneficiary]).await.unwrap();
+    let amounts: Vec<(i64, u64)> = s.fixture.vesting(&clone_seeds).await.schedule.iter()
+        .map(|t| (t.release_time as i64 - s.start, t.amount))
+        .collect();
+    assert_eq!(amounts, vec![(2 * 86_400, 150), (3 * 86_400, 150), (4 * 86_400, 200)]);
+
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    let unlock = integration::unlock(clone_seeds, escrow, s.mint, s.destination);
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 150);
+}
+
+#[tokio::test]
+async fn cohorts_tally_the_contracts_of_their_admin() {
+    let mut s = setup(2_000).await;
+    s.create(cliff_schedule(s.start)).await;
+    let cohort = s.fixture.create_cohort(&s.creator, "seed round", &s.mint).await.unwrap();
+
+    // Only the admin adds its contracts, once
+    let stranger = Keypair::new();
+    let by_stranger = [integration::add_to_cohort(SEEDS, cohort, stranger.pubkey())];
+    assert_error(s.fixture.process(&by_stranger, &[&stranger]).await, VestingError::InvalidCohort);
+    let add = [integration::add_to_cohort(SEEDS, cohort, s.creator.pubkey())];
+    s.fixture.process(&add, &[&s.creator]).await.unwrap();
+    s.fixture.warp_forward(1).await;
+    assert_error(s.fixture.process(&add, &[&s.creator]).await, VestingError::InvalidCohort);
+
+    assert_eq!(s.fixture.vesting(&SEEDS).await.cohort, Some(cohort));
+    let tally = s.fixture.cohort(&cohort).await;
+    assert_eq!((tally.member_count, tally.total_amount), (1, 1_000));
+}
+
+#[tokio::test]
+async fn cohort_admin_pauses_and_revokes_its_members() {
+    let mut s = setup(2_000).await;
+    let admin = s.creator.pubkey();
+    let escrow = s.create_with(cliff_schedule(s.start), 0, Some(admin), UnlockPolicy::Anyone).await;
+    let cohort = s.fixture.create_cohort(&s.creator, "employees", &s.mint).await.unwrap();
+    s.fixture.process(&[integration::add_to_cohort(SEEDS, cohort, admin)], &[&s.creator]).await.unwrap();
+
+    s.fixture.process(&[integration::set_cohort_paused(cohort, admin, &[SEEDS], true)], &[&s.creator]).await.unwrap();
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    assert_error(s.unlock(escrow).await, VestingError::ContractPaused);
+    let cranker = Keypair::new();
+    let crank = [integration::crank_unlock(SEEDS, escrow, s.mint, s.destination, cranker.pubkey())];
+    s.fixture.process(&crank, &[&cranker]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 0);
+    s.fixture.process(&[integration::set_cohort_paused(cohort, admin, &[SEEDS], false)], &[&s.creator]).await.unwrap();
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+
+    let revoke = [integration::revoke_cohort(cohort, admin, s.mint, s.source, &[(SEEDS, escrow)])];
+    s.fixture.process(&revoke, &[&s.creator]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1_700);
+    assert_eq!(s.fixture.token_balance(&escrow).await, 0);
+    assert_eq!(locked_at(&s.fixture.vesting(&SEEDS).await, s.start + 3 * 86_400), 0);
+}
+
+#[tokio::test]
+async fn airdrop_claims_become_vesting_contracts() {
+    let mut s = setup(1_000).await;
+    let tranches = vec![TemplateTranche { offset: 86_400, weight: 1 }, TemplateTranche { offset: 2 * 86_400, weight: 3 }];
+    let template = s.fixture.create_schedule_template(&s.creator, "airdrop", tranches).await.unwrap();
+    let other = Pubkey::new_unique();
+    let leaves = [merkle::allocation_leaf(&s.beneficiary.pubkey(), 400), merkle::allocation_leaf(&other, 600)];
+
+    let airdrop = find_airdrop_address(&SEEDS).0;
+    let airdrop_token = s.fixture.create_associated_token_account(&airdrop, &s.mint).await;
+    s.fixture.create_airdrop(SEEDS, &s.source, &s.creator, &s.mint, &airdrop_token, &template, merkle::root(&leaves),
+                             s.start as u64, 1_000).await.unwrap();
+    // The claim creates the position, only its escrow is written ahead
+    let position = airdrop_position_seeds(&airdrop, &s.beneficiary.pubkey());
+    s.fixture.prepare_vesting(&position, 2).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&position).0, &s.mint).await;
+
+    let (mint, claimant, destination) = (s.mint, s.beneficiary.pubkey(), s.destination);
+    let claim = |amount, index| [integration::claim_airdrop(
+        SEEDS, airdrop_token, template, mint, claimant, destination, amount, merkle::proof(&leaves, index))];
+    // The amount of another leaf isn't the beneficiary's
+    assert_error(s.fixture.process(&claim(600, 1), &[&s.beneficiary]).await, VestingError::InvalidMerkleProof);
+    s.fixture.process(&claim(400, 0), &[&s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 400);
+    s.fixture.warp_forward(1).await;
+    assert_error(s.fixture.process(&claim(400, 0), &[&s.beneficiary]).await, VestingError::AlreadyInitialized);
+
+    // The position unlocks like any contract
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    let unlock = integration::unlock(position, escrow, s.mint, s.destination);
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 100);
+}
+
+#[tokio::test]
+async fn payroll_periods_grow_the_schedule() {
+    let mut s = setup(2_000).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    s.fixture.reserve_schedules(&SEEDS, 1).await;
+    let payer = s.fixture.payer();
+    let payroll_token = s.fixture.create_associated_token_account(&payer, &s.mint).await;
+    s.fixture.mint_to(&s.mint, &payroll_token, 1_000).await;
+    let fund = |s: &Setup, days: i64| [integration::fund_next_period(
+        SEEDS, escrow, s.mint, payroll_token, payer, 500, (s.start + days * 86_400) as u64)];
+
+    let next_period = fund(&s, 4);
+    assert_error(s.fixture.process(&next_period, &[]).await, VestingError::InvalidPayrollPayer);
+    let set_payer = [integration::set_payroll_payer(SEEDS, s.creator.pubkey(), Some(payer))];
+    s.fixture.process(&set_payer, &[&s.creator]).await.unwrap();
+    // Periods only extend the schedule
+    let overlapping = fund(&s, 3);
+    assert_error(s.fixture.process(&overlapping, &[]).await, VestingError::InvalidPayPeriod);
+
+    s.fixture.process(&next_period, &[]).await.unwrap();
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!(vesting.schedule.len(), 4);
+    assert_eq!(locked_at(&vesting, s.start + 3 * 86_400), 500);
+
+    s.fixture.warp_to_timestamp(s.start + 4 * 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_500);
+}
+
+#[tokio::test]
+async fn termination_pays_the_vested_part_and_refunds_the_rest() {
+    let mut s = setup(2_000).await;
+    let escrow = s.create_with(cliff_schedule(s.start), 0, Some(s.creator.pubkey()), UnlockPolicy::Anyone).await;
+    s.fixture.mint_to(&s.mint, &escrow, 5).await;
+
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    let terminate = [integration::terminate(SEEDS, escrow, s.mint, s.destination, s.source, s.creator.pubkey())];
+    s.fixture.process(&terminate, &[&s.creator]).await.unwrap();
+    // The escrow surplus returns with the unvested tranche
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 600);
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1_405);
+    assert_eq!(s.fixture.token_balance(&escrow).await, 0);
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!(vesting.terminated_at, Some(s.start + 2 * 86_400));
+    assert!(vesting.next_action == NextAction::Completed);
+
+    s.fixture.warp_forward(1).await;
+    assert_error(s.fixture.process(&terminate, &[&s.creator]).await, VestingError::AlreadyTerminated);
+}
+
+#[tokio::test]
+async fn probation_adjusts_only_the_cliff() {
+    let mut s = setup(2_000).await;
+    let escrow = s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+        .await.unwrap();
+    let probation_end = s.start + 12 * 3_600;
+    let set_probation = [integration::set_probation(SEEDS, s.creator.pubkey(), Some(probation_end))];
+    s.fixture.process(&set_probation, &[&s.creator]).await.unwrap();
+    s.accept().await.unwrap();
+    let adjust = |s: &Setup, amount: u64, hours: i64| [integration::adjust_cliff(
+        SEEDS, escrow, s.mint, s.source, s.destination, s.creator.pubkey(), s.beneficiary.pubkey(), amount, (s.start + hours * 3_600) as u64)];
+
+    // The cliff can't pass the next tranche
+    let past_next = adjust(&s, 500, 49);
+    assert_error(s.fixture.process(&past_next, &[&s.creator, &s.beneficiary]).await, VestingError::InvalidCliff);
+    let longer_cliff = adjust(&s, 500, 36);
+    s.fixture.process(&longer_cliff, &[&s.creator, &s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 1_200);
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!(vesting.schedule[0].release_time, (s.start + 36 * 3_600) as u64);
+    let later: Vec<(u64, u64)> = vesting.schedule[1..].iter().map(|t| (t.release_time, t.amount)).collect();
+    assert_eq!(later, vec![((s.start + 2 * 86_400) as u64, 300), ((s.start + 3 * 86_400) as u64, 400)]);
+
+    let smaller_cliff = adjust(&s, 100, 24);
+    s.fixture.process(&smaller_cliff, &[&s.creator, &s.beneficiary]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 800);
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1_200);
+
+    s.fixture.warp_to_timestamp(probation_end + 1).await;
+    let after_probation = adjust(&s, 200, 30);
+    assert_error(s.fixture.process(&after_probation, &[&s.creator, &s.beneficiary]).await, VestingError::NotInProbation);
+}
+
+#[tokio::test]
+async fn twap_conditions_need_observations_spread_across_the_window() {
+    let mut s = setup(1_001).await;
+    // The token at $2.50 against a $2 target, the feed staying fresh throughout
+    let feed = s.fixture.set_pyth_price(250, -2, s.start).await;
+    let condition = ReleaseCondition::Twap {
+        price_feed: feed, target_price: 200, target_expo: -2, is_above: true, max_staleness: 86_400, window: 1_000,
+        window_start: 0, last_observation_time: 0, last_price: 0, cumulative_price: 0,
+    };
+    s.create(vec![Schedule { condition: Some(condition), ..tranche(s.start, 1_000) }]).await;
+    let observe = Instruction {
+        program_id: token_vesting::ID,
+        accounts: [
+            token_vesting::accounts::TriggerReleaseCondition { vesting: find_vesting_address(&SEEDS).0 }.to_account_metas(None),
+            vec![AccountMeta::new_readonly(feed, false)],
+        ].concat(),
+        data: token_vesting::instruction::RecordObservation { _seeds: SEEDS, schedule_index: 0 }.data(),
+    };
+
+    // Observations 200 seconds apart are more than a tenth of the window apart, each restarting it
+    for step in 0..=6 {
+        s.fixture.warp_to_timestamp(s.start + step * 200).await;
+        s.fixture.process(std::slice::from_ref(&observe), &[]).await.unwrap();
+    }
+    assert!(s.fixture.vesting(&SEEDS).await.schedule[0].condition.is_some());
+
+    let resumed = s.start + 1_200;
+    for step in 1..=MIN_TWAP_OBSERVATIONS as i64 {
+        assert!(s.fixture.vesting(&SEEDS).await.schedule[0].condition.is_some(), "met after {} observations", step);
+        s.fixture.warp_to_timestamp(resumed + step * 100).await;
+        s.fixture.process(std::slice::from_ref(&observe), &[]).await.unwrap();
+    }
+    assert!(s.fixture.vesting(&SEEDS).await.schedule[0].condition.is_none());
+}
+
+#[tokio::test]
+async fn salary_periods_convert_usd_at_the_feed_price() {
+    let mut s = setup(2_000).await;
+    let escrow = s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+        .await.unwrap();
+    // The token at $2.50
+    let feed = s.fixture.set_pyth_price(250_000_000, -8, s.start).await;
+    let set_feed = [integration::set_salary_price_feed(SEEDS, s.creator.pubkey(), Some(feed))];
+    s.fixture.process(&set_feed, &[&s.creator]).await.unwrap();
+    s.accept().await.unwrap();
+    s.fixture.reserve_schedules(&SEEDS, 1).await;
+    s.fixture.mint_to(&s.mint, &s.source, 1_000_000_000).await;
+
+    // $1,000 is 400 tokens of 6 decimals
+    let fund = |s: &Setup, feed: Pubkey| [integration::fund_salary_period(
+        SEEDS, escrow, s.mint, s.source, feed, s.creator.pubkey(), 1_000_000_000, (s.start + 4 * 86_400) as u64)];
+    let other_feed = s.fixture.set_pyth_price(100_000_000, -8, s.start).await;
+    let wrong_feed = fund(&s, other_feed);
+    assert_error(s.fixture.process(&wrong_feed, &[&s.creator]).await, VestingError::InvalidPriceFeed);
+    let salary = fund(&s, feed);
+    s.fixture.process(&salary, &[&s.creator]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 1_000 + 400_000_000);
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert_eq!(vesting.schedule[3].amount, 400_000_000);
+
+    // A stale price converts nothing
+    s.fixture.warp_to_timestamp(s.start + 61).await;
+    let next_salary = [integration::fund_salary_period(
+        SEEDS, escrow, s.mint, s.source, feed, s.creator.pubkey(), 1_000_000_000, (s.start + 5 * 86_400) as u64)];
+    assert_error(s.fixture.process(&next_salary, &[&s.creator]).await, VestingError::StalePrice);
+}
+
+#[tokio::test]
+async fn funding_escrows_fund_the_terms_of_their_issuer() {
+    let mut s = setup(2_000).await;
+    let issuer = Keypair::new();
+    let mut escrows = Vec::new();
+    for seeds in [[1; 31], [2; 31]] {
+        let address = find_funding_escrow_address(&seeds).0;
+        let escrow = s.fixture.create_associated_token_account(&address, &s.mint).await;
+        s.fixture.open_funding_escrow(seeds, &s.source, &s.creator, &s.mint, &escrow, &issuer.pubkey(), 1_000).await.unwrap();
+        escrows.push(escrow);
+    }
+
+    // The issuer backs out of the first deal, the deposit returns to the funder
+    let abort = [integration::abort_funding_escrow([1; 31], escrows[0], s.mint, s.source, s.creator.pubkey(), issuer.pubkey())];
+    s.fixture.process(&abort, &[&issuer]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1_000);
+
+    let vesting = find_vesting_address(&SEEDS).0;
+    s.fixture.allocate_vesting(&SEEDS, 3).await;
+    let vesting_token = s.fixture.create_associated_token_account(&vesting, &s.mint).await;
+    let activate = |s: &Setup, signer: Pubkey, schedules: Vec<Schedule>| [integration::activate_funding_escrow(
+        [2; 31], escrows[1], s.mint, SEEDS, vesting_token, signer, s.destination, schedules, None)];
+    let by_funder = activate(&s, s.creator.pubkey(), cliff_schedule(s.start));
+    assert_error(s.fixture.process(&by_funder, &[&s.creator]).await, VestingError::InvalidIssuer);
+    let mut short = cliff_schedule(s.start);
+    short[2].amount = 300;
+    let short = activate(&s, issuer.pubkey(), short);
+    assert_error(s.fixture.process(&short, &[&issuer]).await, VestingError::FundingAmountMismatch);
+
+    let terms = activate(&s, issuer.pubkey(), cliff_schedule(s.start));
+    s.fixture.process(&terms, &[&issuer]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&vesting_token).await, 1_000);
+    assert_eq!(s.fixture.vesting(&SEEDS).await.creator, issuer.pubkey());
+    // Once active, neither party can pull the deposit
+    let late_abort = [integration::abort_funding_escrow([2; 31], escrows[1], s.mint, s.source, s.creator.pubkey(), s.creator.pubkey())];
+    assert_error(s.fixture.process(&late_abort, &[&s.creator]).await, VestingError::FundingEscrowActivated);
+
+    s.accept().await.unwrap();
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(vesting_token).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+}
+
+#[tokio::test]
+async fn sale_settlement_creates_the_buyer_contract() {
+    let mut s = setup(0).await;
+    // Stands in for the PDA of a sale program, which signs its CPI with `invoke_signed`
+    let sale = Keypair::new();
+    let tranches = vec![TemplateTranche { offset: 86_400, weight: 1 }, TemplateTranche { offset: 2 * 86_400, weight: 1 }];
+    let template = s.fixture.create_schedule_template(&sale, "sale", tranches.clone()).await.unwrap();
+    let other_template = s.fixture.create_schedule_template(&s.creator, "sale", tranches).await.unwrap();
+    let proceeds = s.fixture.create_associated_token_account(&sale.pubkey(), &s.mint).await;
+    s.fixture.mint_to(&s.mint, &proceeds, 1_000).await;
+    s.fixture.allocate_vesting(&SEEDS, 2).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&SEEDS).0, &s.mint).await;
+
+    let payer = s.fixture.payer();
+    let settle = |s: &Setup, template| [integration::create_for(
+        SEEDS, sale.pubkey(), template, proceeds, s.mint, payer, s.beneficiary.pubkey(), 600, s.start as u64)];
+    // Only the sale owning the template creates along it
+    let foreign_terms = settle(&s, other_template);
+    assert_error(s.fixture.process(&foreign_terms, &[&sale]).await, VestingError::InvalidScheduleTemplate);
+    let settlement = settle(&s, template);
+    s.fixture.process(&settlement, &[&sale]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 600);
+    assert_eq!(s.fixture.token_balance(&proceeds).await, 400);
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert!(vesting.is_accepted);
+    assert_eq!(vesting.destination_address, s.destination);
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    s.unlock(escrow).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+}
+
+#[tokio::test]
+async fn rounds_tally_the_claims_and_revocations_of_their_contracts() {
+    let mut s = setup(2_000).await;
+    let escrow = s.fixture.create_revocable_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start),
+                                                  &s.creator.pubkey()).await.unwrap();
+    let round = s.fixture.create_round(&s.creator, "seed", &s.mint).await.unwrap();
+    let tag = [integration::tag_round(SEEDS, round, s.creator.pubkey())];
+    s.fixture.process(&tag, &[&s.creator]).await.unwrap();
+    s.accept().await.unwrap();
+    let tallies = |round: token_vesting::Round| (round.contract_count, round.total_granted, round.total_claimed, round.total_revoked);
+    assert_eq!(tallies(s.fixture.round(&round).await), (1, 1_000, 0, 0));
+
+    // Claims of a tagged contract report to its round
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    assert_error(s.unlock(escrow).await, VestingError::MissingRoundAccount);
+    let mut unlock = integration::unlock(SEEDS, escrow, s.mint, s.destination);
+    unlock.accounts.push(AccountMeta::new(round, false));
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(tallies(s.fixture.round(&round).await), (1, 1_000, 300, 0));
+
+    let mut revoke = integration::revoke(SEEDS, escrow, s.mint, s.source, s.creator.pubkey());
+    revoke.accounts.push(AccountMeta::new(round, false));
+    s.fixture.process(&[revoke], &[&s.creator]).await.unwrap();
+    assert_eq!(tallies(s.fixture.round(&round).await), (1, 1_000, 300, 700));
+}
+
+#[tokio::test]
+async fn custodians_migrate_destinations_in_batches() {
+    let mut s = setup(3_001).await;
+    let contracts = [[1; 31], [2; 31]];
+    for seeds in contracts {
+        s.fixture.create_grant(seeds, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start)).await.unwrap();
+    }
+    let other_destination = s.fixture.create_associated_token_account(&Pubkey::new_unique(), &s.mint).await;
+    s.fixture.create_grant([3; 31], &s.source, &s.creator, &s.mint, &other_destination, cliff_schedule(s.start)).await.unwrap();
+    let new_wallet = Pubkey::new_unique();
+    let new_destination = s.fixture.create_associated_token_account(&new_wallet, &s.mint).await;
+
+    // Every contract of the batch must pay the omnibus wallet signing
+    let foreign = [integration::batch_change_destination(s.beneficiary.pubkey(), &[
+        ([1; 31], s.destination, new_destination), ([3; 31], other_destination, new_destination),
+    ])];
+    assert_error(s.fixture.process(&foreign, &[&s.beneficiary]).await, VestingError::InvalidDestinationAuthority);
+
+    let migration: Vec<_> = contracts.iter().map(|seeds| (*seeds, s.destination, new_destination)).collect();
+    let migrate = [integration::batch_change_destination(s.beneficiary.pubkey(), &migration)];
+    s.fixture.process(&migrate, &[&s.beneficiary]).await.unwrap();
+    for seeds in contracts {
+        let vesting = s.fixture.vesting(&seeds).await;
+        assert_eq!(vesting.destination_address, new_destination);
+        assert_eq!(vesting.destination_change_count, 1);
+    }
+    assert_eq!(s.fixture.vesting(&[3; 31]).await.destination_address, other_destination);
+}
+
+#[tokio::test]
+async fn revokers_claw_back_many_contracts_at_once() {
+    let mut s = setup(3_001).await;
+    let mut contracts = Vec::new();
+    for (seeds, revoker) in [([1; 31], s.creator.pubkey()), ([2; 31], s.creator.pubkey()), ([3; 31], Pubkey::new_unique())] {
+        let escrow = s.fixture.create_revocable_grant(seeds, &s.source, &s.creator, &s.mint, &s.destination,
+                                                      cliff_schedule(s.start), &revoker).await.unwrap();
+        contracts.push((seeds, escrow));
+    }
+
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    // A contract of another revoker fails the batch
+    let foreign = [integration::batch_revoke(s.creator.pubkey(), s.mint, s.source, &contracts)];
+    assert_error(s.fixture.process(&foreign, &[&s.creator]).await, VestingError::InvalidRevoker);
+    let layoff = [integration::batch_revoke(s.creator.pubkey(), s.mint, s.source, &contracts[..2])];
+    s.fixture.process(&layoff, &[&s.creator]).await.unwrap();
+    // The cliffs passed stay with the escrows
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1 + 2 * 700);
+    for (seeds, escrow) in &contracts[..2] {
+        assert_eq!(s.fixture.token_balance(escrow).await, 300);
+        assert_eq!(locked_at(&s.fixture.vesting(seeds).await, s.start + 86_400), 0);
+    }
+}
+
+#[tokio::test]
+async fn shared_vaults_never_give_away_committed_capacity() {
+    let mut s = setup(1).await;
+    let authority = s.creator.pubkey();
+    let shared_vault = find_shared_vault_address(&authority, &s.mint).0;
+    let vault = s.fixture.create_associated_token_account(&shared_vault, &s.mint).await;
+    s.fixture.create_shared_vault(&s.creator, &s.mint, &vault).await.unwrap();
+    s.fixture.mint_to(&s.mint, &vault, 1_200).await;
+    for seeds in [SEEDS, [8; 31]] {
+        s.fixture.allocate_vesting(&seeds, 3).await;
+        let create = integration::create_in_shared_vault(seeds, vault, s.mint, authority, s.destination, cliff_schedule(s.start));
+        let result = s.fixture.process(&[create], &[&s.creator]).await;
+        if seeds == SEEDS {
+            result.unwrap();
+        } else {
+            // 200 free tokens don't cover another contract
+            assert_error(result, VestingError::InsufficientFunds);
+        }
+    }
+
+    // Only the free capacity can be withdrawn
+    let (mint, source) = (s.mint, s.source);
+    let withdraw = |amount| integration::withdraw_from_shared_vault(vault, mint, authority, source, amount);
+    let (too_much, free) = ([withdraw(201)], [withdraw(200)]);
+    assert_error(s.fixture.process(&too_much, &[&s.creator]).await, VestingError::InsufficientFunds);
+    s.fixture.process(&free, &[&s.creator]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&vault).await, 1_000);
+
+    // Cancelling the pending contract frees its commitment, which `cancel` can't reach
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&SEEDS).0, &s.mint).await;
+    let cancel = integration::cancel(SEEDS, escrow, s.mint, s.source, authority);
+    assert_error(s.fixture.process(&[cancel], &[&s.creator]).await, VestingError::SharedVaultContract);
+    let cancel = integration::cancel_in_shared_vault(SEEDS, vault, s.mint, authority);
+    s.fixture.process(&[cancel], &[&s.creator]).await.unwrap();
+    s.fixture.process(&[withdraw(1_000)], &[&s.creator]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.source).await, 1_201);
+}
+
+#[tokio::test]
+async fn budgets_cap_what_their_admins_lock() {
+    let mut s = setup(1).await;
+    let (org, hr) = (s.creator.pubkey(), Keypair::new());
+    let budget_token = s.fixture.create_associated_token_account(&find_budget_address(&org, "2024").0, &s.mint).await;
+    let budget = s.fixture.create_budget(&s.creator, "2024", &s.mint, &budget_token, 1_500, vec![hr.pubkey()]).await.unwrap();
+    s.fixture.mint_to(&s.mint, &budget_token, 2_000).await;
+
+    let mut escrows = Vec::new();
+    for seeds in [SEEDS, [8; 31]] {
+        s.fixture.allocate_vesting(&seeds, 3).await;
+        escrows.push(s.fixture.create_associated_token_account(&find_vesting_address(&seeds).0, &s.mint).await);
+    }
+    let create = |seeds, escrow, admin| integration::create_from_budget(seeds, escrow, budget, budget_token, s.mint, admin,
+        s.destination, cliff_schedule(s.start), true);
+    let (by_org, by_hr, over_cap) = ([create(SEEDS, escrows[0], org)], [create(SEEDS, escrows[0], hr.pubkey())],
+        [create([8; 31], escrows[1], hr.pubkey())]);
+    // Only admins draw on the budget, and not beyond its cap
+    assert_error(s.fixture.process(&by_org, &[&s.creator]).await, VestingError::InvalidBudgetAdmin);
+    s.fixture.process(&by_hr, &[&hr]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrows[0]).await, 1_000);
+    assert_eq!(s.fixture.vesting(&SEEDS).await.revoker, Some(org));
+    assert_error(s.fixture.process(&over_cap, &[&hr]).await, VestingError::BudgetExceeded);
+
+    // Raising the cap lets the admins allocate the rest of the funds
+    let raise = integration::set_budget(budget, org, 2_000, vec![hr.pubkey()]);
+    s.fixture.process(&[raise], &[&s.creator]).await.unwrap();
+    s.fixture.process(&over_cap, &[&hr]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&budget_token).await, 0);
+}
+
+#[tokio::test]
+async fn rollups_sum_the_contracts_of_a_beneficiary() {
+    let mut s = setup(2_001).await;
+    let (org, beneficiary) = (s.creator.pubkey(), s.beneficiary.pubkey());
+    let rollup = s.fixture.create_rollup(&s.creator, &beneficiary, &s.mint).await.unwrap();
+    let mut escrows = Vec::new();
+    for seeds in [SEEDS, [8; 31]] {
+        escrows.push(s.fixture.create_grant(seeds, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+            .await.unwrap());
+        let add = integration::add_to_rollup(seeds, org, beneficiary, s.destination);
+        s.fixture.process(&[add], &[&s.creator]).await.unwrap();
+        let accept = integration::accept(seeds, s.destination, beneficiary);
+        s.fixture.process(&[accept], &[&s.beneficiary]).await.unwrap();
+    }
+    let amounts = |rollup: token_vesting::Rollup|
+        (rollup.contract_count, rollup.locked_amount, rollup.vested_unclaimed_amount, rollup.claimed_amount);
+    assert_eq!(amounts(s.fixture.rollup(&rollup).await), (2, 2_000, 0, 0));
+
+    // Releases show after a refresh covering every contract, claims right away
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    let partial = [integration::refresh_rollup(rollup, &[SEEDS])];
+    assert_error(s.fixture.process(&partial, &[]).await, VestingError::InvalidRemainingAccounts);
+    let refresh = [integration::refresh_rollup(rollup, &[SEEDS, [8; 31]])];
+    s.fixture.process(&refresh, &[]).await.unwrap();
+    assert_eq!(amounts(s.fixture.rollup(&rollup).await), (2, 1_400, 600, 0));
+
+    assert_error(s.unlock(escrows[0]).await, VestingError::MissingRollupAccount);
+    let mut unlock = integration::unlock(SEEDS, escrows[0], s.mint, s.destination);
+    unlock.accounts.push(AccountMeta::new(rollup, false));
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(amounts(s.fixture.rollup(&rollup).await), (2, 1_400, 300, 300));
+}
+
+#[tokio::test]
+async fn unlock_queues_crank_the_due_contracts() {
+    let mut s = setup(2_001).await;
+    let queue = s.fixture.create_unlock_queue(&s.mint).await.unwrap();
+    let other_beneficiary = Keypair::new();
+    let other_destination = s.fixture.create_associated_token_account(&other_beneficiary.pubkey(), &s.mint).await;
+    let mut contracts = Vec::new();
+    for (seeds, start, destination, beneficiary) in [
+        ([8; 31], s.start + 86_400, other_destination, &other_beneficiary), (SEEDS, s.start, s.destination, &s.beneficiary),
+    ] {
+        let escrow = s.fixture.create_grant(seeds, &s.source, &s.creator, &s.mint, &destination, cliff_schedule(start))
+            .await.unwrap();
+        s.fixture.process(&[integration::register_unlock(seeds, s.mint)], &[]).await.unwrap();
+        let accept = integration::accept(seeds, destination, beneficiary.pubkey());
+        s.fixture.process(&[accept], &[beneficiary]).await.unwrap();
+        contracts.push((seeds, escrow, destination));
+    }
+    let times = |queue: token_vesting::UnlockQueue| queue.entries.iter().map(|e| (e.seeds, e.release_time)).collect::<Vec<_>>();
+    let start = s.start as i64;
+    let day = |n: i64| start + n * 86_400;
+    assert_eq!(times(s.fixture.unlock_queue(&queue).await), vec![(SEEDS, day(1)), ([8; 31], day(2))]);
+
+    // Only the head is due, and it is queued again at its next release, after the contracts due then
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    let crank = [integration::process_queue(s.mint, &[contracts[1], contracts[0]])];
+    s.fixture.process(&crank, &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+    assert_eq!(times(s.fixture.unlock_queue(&queue).await), vec![([8; 31], day(2)), (SEEDS, day(2))]);
+
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    let crank = [integration::process_queue(s.mint, &[contracts[0], contracts[1]])];
+    s.fixture.process(&crank, &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 600);
+    assert_eq!(s.fixture.token_balance(&other_destination).await, 300);
+    assert_eq!(times(s.fixture.unlock_queue(&queue).await), vec![([8; 31], day(3)), (SEEDS, day(3))]);
+}
+
+#[tokio::test]
+async fn partners_take_their_fee_and_meter_the_contracts_created_under_them() {
+    let mut s = setup(1_051).await;
+    let (partner, fee_recipient) = (Keypair::new(), Pubkey::new_unique());
+    let fee_token = s.fixture.create_associated_token_account(&fee_recipient, &s.mint).await;
+    let mut config = PartnerConfig {
+        authority: partner.pubkey(),
+        fee_recipient,
+        fee_bps: 50,
+        branding: Pubkey::new_unique(),
+        default_min_claim_amount: 0,
+        default_unlock_policy: UnlockPolicy::DestinationOwner,
+        default_revocable: true,
+    };
+    s.fixture.create_partner_config(&partner, &config).await.unwrap();
+
+    // Fees above the cap are refused
+    config.fee_bps = MAX_PARTNER_FEE_BPS + 1;
+    let greedy = [integration::set_partner_config(partner.pubkey(), &config)];
+    assert_error(s.fixture.process(&greedy, &[&partner]).await, VestingError::InvalidPartnerConfig);
+
+    let partner_config = find_partner_config_address(&partner.pubkey()).0;
+    let stats = s.fixture.prepare_partner_stats(&partner_config, &s.mint).await;
+    s.fixture.allocate_vesting(&SEEDS, 3).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&SEEDS).0, &s.mint).await;
+    let create = integration::create_with_partner(SEEDS, escrow, s.source, s.creator.pubkey(), s.mint, partner.pubkey(), fee_token,
+                                                  s.fixture.payer(), s.destination, cliff_schedule(s.start));
+    s.fixture.process(&[create], &[&s.creator]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&escrow).await, 1_000);
+    assert_eq!(s.fixture.token_balance(&fee_token).await, 5);
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert!(vesting.unlock_policy == UnlockPolicy::DestinationOwner);
+    assert_eq!(vesting.revoker, Some(s.creator.pubkey()));
+    assert_eq!(vesting.partner, Some(partner_config));
+    s.accept().await.unwrap();
+    let usage = |stats: token_vesting::PartnerStats| (stats.contract_count, stats.total_value_locked, stats.claim_count, stats.claimed_amount);
+    assert_eq!(usage(s.fixture.partner_stats(&stats).await), (1, 1_000, 0, 0));
+
+    // Claims are metered whichever instruction makes them, and can't skip the stats
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    let unlock = integration::authorized_unlock(SEEDS, escrow, s.mint, s.destination, s.beneficiary.pubkey());
+    assert_error(s.fixture.process(std::slice::from_ref(&unlock), &[&s.beneficiary]).await, VestingError::MissingPartnerStatsAccount);
+    let mut metered = unlock;
+    metered.accounts.push(AccountMeta::new(stats, false));
+    s.fixture.process(&[metered], &[&s.beneficiary]).await.unwrap();
+    assert_eq!(usage(s.fixture.partner_stats(&stats).await), (1, 400, 1, 600));
+}
+
+#[tokio::test]
+async fn rent_sponsorship_pools_pay_the_accounts_claiming_creates() {
+    let mut s = setup(1_001).await;
+    s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start)).await.unwrap();
+    let pool = s.fixture.create_rent_sponsorship_pool(&s.creator, 3_000_000).await.unwrap();
+    let rent = s.fixture.lamports(&pool).await - 3_000_000;
+
+    // Existing accounts aren't funded
+    let existing = integration::sponsor_destination_rent(SEEDS, s.creator.pubkey(), s.beneficiary.pubkey(), s.mint);
+    let pay_beneficiary = integration::set_destination_owner(SEEDS, s.creator.pubkey(), s.beneficiary.pubkey());
+    s.fixture.process(&[pay_beneficiary], &[&s.creator]).await.unwrap();
+    assert_error(s.fixture.process(&[existing], &[]).await, VestingError::SponsoredAccountExists);
+
+    // The associated token account of the destination owner is funded before it exists, once per contract
+    let owner = Pubkey::new_unique();
+    s.fixture.process(&[integration::set_destination_owner(SEEDS, s.creator.pubkey(), owner)], &[&s.creator]).await.unwrap();
+    let sponsor = integration::sponsor_destination_rent(SEEDS, s.creator.pubkey(), owner, s.mint);
+    s.fixture.process(std::slice::from_ref(&sponsor), &[]).await.unwrap();
+    let destination_token = anchor_spl::associated_token::get_associated_token_address(&owner, &s.mint);
+    let token_account_rent = s.fixture.lamports(&destination_token).await;
+    assert!(token_account_rent > 0);
+    assert!(s.fixture.vesting(&SEEDS).await.is_rent_sponsored);
+    s.fixture.warp_forward(1).await;
+    assert_error(s.fixture.process(&[sponsor], &[]).await, VestingError::RentAlreadySponsored);
+    assert_eq!(s.fixture.lamports(&destination_token).await, token_account_rent);
+    let sponsored = s.fixture.rent_sponsorship_pool(&pool).await;
+    assert_eq!((sponsored.sponsored_count, sponsored.sponsored_lamports), (1, token_account_rent));
+
+    // The pool can't be drawn below its rent, by claimants or its creator
+    let claimant = Pubkey::new_unique();
+    let distributor_escrow = s.fixture.create_associated_token_account(&find_distributor_address(&SEEDS).0, &s.mint).await;
+    s.fixture.create_distributor(SEEDS, &s.source, &s.creator, &s.mint, &distributor_escrow, [0; 32], 0).await.unwrap();
+    let left = 3_000_000 - token_account_rent;
+    let withdraw = integration::withdraw_from_rent_sponsorship_pool(s.creator.pubkey(), left - 1);
+    s.fixture.process(&[withdraw], &[&s.creator]).await.unwrap();
+    assert_error(s.fixture.process(&[integration::sponsor_claim_status_rent(SEEDS, s.creator.pubkey(), claimant)], &[]).await,
+                 VestingError::RentSponsorshipPoolEmpty);
+    let withdraw = integration::withdraw_from_rent_sponsorship_pool(s.creator.pubkey(), 2);
+    assert_error(s.fixture.process(&[withdraw], &[&s.creator]).await, VestingError::InsufficientFunds);
+
+    let fund = integration::fund_rent_sponsorship_pool(s.creator.pubkey(), s.fixture.payer(), 2_000_000);
+    s.fixture.process(&[fund], &[]).await.unwrap();
+    s.fixture.process(&[integration::sponsor_claim_status_rent(SEEDS, s.creator.pubkey(), claimant)], &[]).await.unwrap();
+    let distributor = find_distributor_address(&SEEDS).0;
+    let claim_status = integration::find_claim_status_address(&distributor, &claimant).0;
+    assert!(s.fixture.lamports(&claim_status).await > 0);
+    assert!(s.fixture.lamports(&pool).await >= rent);
+}
+
+#[tokio::test]
+async fn grant_indexes_list_the_contracts_of_a_creator_for_a_beneficiary() {
+    let mut s = setup(3_001).await;
+    let (creator, beneficiary) = (s.creator.pubkey(), s.beneficiary.pubkey());
+    let grant_index = s.fixture.create_grant_index(&s.creator, &beneficiary).await.unwrap();
+
+    let mut escrows = vec![];
+    for grant_id in 0..2 {
+        let seeds = integration::grant_seeds(&creator, &beneficiary, grant_id);
+        s.fixture.allocate_vesting(&seeds, 3).await;
+        let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&seeds).0, &s.mint).await;
+        let mut create = integration::create(seeds, escrow, s.source, creator, s.mint, s.destination, cliff_schedule(s.start));
+        create.accounts.push(AccountMeta::new(grant_index, false));
+        s.fixture.process(&[create], &[&s.creator]).await.unwrap();
+        s.fixture.process(&[integration::accept(seeds, s.destination, beneficiary)], &[&s.beneficiary]).await.unwrap();
+        escrows.push(escrow);
+    }
+    let index = s.fixture.grant_index(&grant_index).await;
+    assert_eq!((index.next_grant_id, index.grant_ids), (2, vec![0, 1]));
+
+    // Only the next grant seeds are indexed
+    let seeds = integration::grant_seeds(&creator, &beneficiary, 3);
+    s.fixture.allocate_vesting(&seeds, 3).await;
+    let escrow = s.fixture.create_associated_token_account(&find_vesting_address(&seeds).0, &s.mint).await;
+    let mut create = integration::create(seeds, escrow, s.source, creator, s.mint, s.destination, cliff_schedule(s.start));
+    create.accounts.push(AccountMeta::new(grant_index, false));
+    assert_error(s.fixture.process(&[create], &[&s.creator]).await, VestingError::InvalidVestingSeeds);
+
+    // Grants leave the index once fully released
+    let close = integration::close_indexed_grant(creator, beneficiary, 0);
+    assert_error(s.fixture.process(std::slice::from_ref(&close), &[&s.creator]).await, VestingError::ContractNotFullyReleased);
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400).await;
+    let seeds = integration::grant_seeds(&creator, &beneficiary, 0);
+    s.fixture.process(&[integration::unlock(seeds, escrows[0], s.mint, s.destination)], &[]).await.unwrap();
+    s.fixture.process(&[close], &[&s.creator]).await.unwrap();
+    let index = s.fixture.grant_index(&grant_index).await;
+    assert_eq!((index.next_grant_id, index.grant_ids), (2, vec![1]));
+}
+
+#[tokio::test]
+async fn guarantors_cover_the_top_ups_a_granter_misses() {
+    let mut s = setup(1_201).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    let (guarantor, deadline) = (Keypair::new(), s.start + 86_400);
+    let guarantor_token = s.fixture.create_associated_token_account(&guarantor.pubkey(), &s.mint).await;
+    s.fixture.mint_to(&s.mint, &guarantor_token, 500).await;
+    let guarantee = integration::find_guarantee_address(&find_vesting_address(&SEEDS).0).0;
+    let collateral = s.fixture.create_associated_token_account(&guarantee, &s.mint).await;
+
+    // Only the creator or revoker promises top-ups, once per contract
+    let stranger = Keypair::new();
+    let result = s.fixture.create_guarantee(SEEDS, &stranger, &guarantor, &guarantor_token, &collateral, &s.mint, 400, deadline, 500).await;
+    assert_error(result, VestingError::InvalidGuaranteeGranter);
+    s.fixture.create_guarantee(SEEDS, &s.creator, &guarantor, &guarantor_token, &collateral, &s.mint, 400, deadline, 500).await.unwrap();
+    let result = s.fixture.create_guarantee(SEEDS, &s.creator, &guarantor, &guarantor_token, &collateral, &s.mint, 400, deadline, 500).await;
+    assert!(result.is_err());
+    assert_eq!(s.fixture.token_balance(&collateral).await, 500);
+
+    // Top-ups come from the granter into an existing tranche
+    let (mint, source, destination) = (s.mint, s.source, s.destination);
+    let top_up = |granter: &Keypair, schedule_index, amount| [integration::top_up(SEEDS, escrow, mint, source, granter.pubkey(), schedule_index, amount)];
+    assert_error(s.fixture.process(&top_up(&stranger, 2, 100), &[&stranger]).await, VestingError::InvalidGranter);
+    assert_error(s.fixture.process(&top_up(&s.creator, 3, 100), &[&s.creator]).await, VestingError::InvalidScheduleIndex);
+    s.fixture.process(&top_up(&s.creator, 2, 100), &[&s.creator]).await.unwrap();
+    assert_eq!(s.fixture.vesting(&SEEDS).await.schedule[2].amount, 500);
+
+    // The shortfall is only paid once the deadline passed, to the destination, and only to the guarantor's account
+    let settle = |guarantor_token| [integration::settle_guarantee(SEEDS, collateral, mint, destination, guarantor_token)];
+    assert_error(s.fixture.process(&settle(guarantor_token), &[]).await, VestingError::GuaranteeNotDue);
+    s.fixture.warp_to_timestamp(deadline + 1).await;
+    assert_error(s.fixture.process(&settle(s.source), &[]).await, VestingError::InvalidGuarantorToken);
+    s.fixture.process(&settle(guarantor_token), &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+    assert_eq!(s.fixture.token_balance(&guarantor_token).await, 200);
+    assert_eq!(s.fixture.token_balance(&collateral).await, 0);
+    assert_error(s.fixture.process(&settle(guarantor_token), &[]).await, VestingError::GuaranteeSettled);
+    assert_error(s.fixture.process(&top_up(&s.creator, 2, 100), &[&s.creator]).await, VestingError::GuaranteeSettled);
+}
+
+#[tokio::test]
+async fn kept_promises_release_the_collateral_before_the_deadline() {
+    let mut s = setup(1_401).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    let guarantor = Keypair::new();
+    let guarantor_token = s.fixture.create_associated_token_account(&guarantor.pubkey(), &s.mint).await;
+    s.fixture.mint_to(&s.mint, &guarantor_token, 500).await;
+    let guarantee = integration::find_guarantee_address(&find_vesting_address(&SEEDS).0).0;
+    let collateral = s.fixture.create_associated_token_account(&guarantee, &s.mint).await;
+    s.fixture.create_guarantee(SEEDS, &s.creator, &guarantor, &guarantor_token, &collateral, &s.mint, 400, s.start + 86_400, 500)
+        .await.unwrap();
+
+    for schedule_index in [0, 1] {
+        let top_up = integration::top_up(SEEDS, escrow, s.mint, s.source, s.creator.pubkey(), schedule_index, 200);
+        s.fixture.process(&[top_up], &[&s.creator]).await.unwrap();
+    }
+    let settle = integration::settle_guarantee(SEEDS, collateral, s.mint, s.destination, guarantor_token);
+    s.fixture.process(&[settle], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&guarantor_token).await, 500);
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 0);
+    assert_eq!(s.balances(escrow).await, (1, 1_400, 0));
+}
+
+#[tokio::test]
+async fn settled_guarantees_stop_needing_the_guarantor_to_revoke() {
+    let mut s = setup(1_401).await;
+    let revoker = Keypair::new();
+    let escrow = s.create_with(cliff_schedule(s.start), 0, Some(revoker.pubkey()), UnlockPolicy::Anyone).await;
+    let guarantor = Keypair::new();
+    let guarantor_token = s.fixture.create_associated_token_account(&guarantor.pubkey(), &s.mint).await;
+    s.fixture.mint_to(&s.mint, &guarantor_token, 500).await;
+    let guarantee = integration::find_guarantee_address(&find_vesting_address(&SEEDS).0).0;
+    let collateral = s.fixture.create_associated_token_account(&guarantee, &s.mint).await;
+    s.fixture.create_guarantee(SEEDS, &s.creator, &guarantor, &guarantor_token, &collateral, &s.mint, 0, s.start + 86_400, 500)
+        .await.unwrap();
+    assert_error(s.revoke(escrow, &revoker).await, VestingError::MissingGuarantorSignature);
+
+    let settle = integration::settle_guarantee(SEEDS, collateral, s.mint, s.destination, guarantor_token);
+    s.fixture.process(&[settle], &[]).await.unwrap();
+    assert_eq!(s.fixture.vesting(&SEEDS).await.guarantor, None);
+    s.revoke(escrow, &revoker).await.unwrap();
+    assert_eq!(s.balances(escrow).await, (1_401, 0, 0));
+}
+
+#[tokio::test]
+async fn receipts_make_their_holder_the_beneficiary() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    let beneficiary = s.beneficiary.pubkey();
+
+    // Only the creator mints the receipt, and only once as its mint then exists
+    let result = s.fixture.create_receipt(SEEDS, &s.destination, &beneficiary, &s.beneficiary).await;
+    assert_error(result, VestingError::InvalidCreator);
+    let receipt_token = s.fixture.create_receipt(SEEDS, &s.destination, &beneficiary, &s.creator).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&receipt_token).await, 1);
+    assert!(s.fixture.create_receipt(SEEDS, &s.destination, &beneficiary, &s.creator).await.is_err());
+
+    // From then on whoever holds the receipt is paid
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    assert_error(s.unlock(escrow).await, VestingError::ReceiptHolderIsBeneficiary);
+    let holder = Keypair::new();
+    let receipt_mint = integration::find_receipt_mint_address(&find_vesting_address(&SEEDS).0).0;
+    let holder_receipt = s.fixture.create_associated_token_account(&holder.pubkey(), &receipt_mint).await;
+    let holder_token = s.fixture.create_associated_token_account(&holder.pubkey(), &s.mint).await;
+    let transfer = spl_token::instruction::transfer(&spl_token::ID, &receipt_token, &holder_receipt, &beneficiary, &[], 1).unwrap();
+    s.fixture.process(&[transfer], &[&s.beneficiary]).await.unwrap();
+    let unlock = integration::unlock_to_holder(SEEDS, escrow, s.mint, receipt_token, beneficiary);
+    assert_error(s.fixture.process(&[unlock], &[]).await, VestingError::InvalidReceipt);
+    let unlock = integration::unlock_to_holder(SEEDS, escrow, s.mint, holder_receipt, holder.pubkey());
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&holder_token).await, 300);
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 0);
+}
+
+#[tokio::test]
+async fn listed_positions_sell_at_the_price_and_with_the_approval_agreed() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    let set_approval = integration::set_transfer_approval_required(SEEDS, s.creator.pubkey(), true);
+    s.fixture.process(&[set_approval], &[&s.creator]).await.unwrap();
+    let payment_mint = s.fixture.create_mint(6).await;
+    let buyer = Keypair::new();
+    let seller_payment = s.fixture.create_associated_token_account(&s.beneficiary.pubkey(), &payment_mint).await;
+    let buyer_payment = s.fixture.create_associated_token_account(&buyer.pubkey(), &payment_mint).await;
+    let buyer_destination = s.fixture.create_associated_token_account(&buyer.pubkey(), &s.mint).await;
+    s.fixture.mint_to(&payment_mint, &buyer_payment, 100).await;
+    let (destination, beneficiary) = (s.destination, s.beneficiary.pubkey());
+    let accept = |expected_price| integration::accept_listing(SEEDS, destination, beneficiary, seller_payment, buyer_payment,
+                                                              payment_mint, buyer_destination, buyer.pubkey(), expected_price);
+
+    // The seller's claim delegate and bridge target don't carry over to the buyer
+    let seller_settings = [
+        integration::set_claim_delegate(SEEDS, s.destination, beneficiary, Pubkey::new_unique(), s.start + 86_400),
+        integration::set_bridge_target(SEEDS, s.destination, beneficiary, Some(BridgeTarget { chain: 2, address: [1; 32] })),
+    ];
+    s.fixture.process(&seller_settings, &[&s.beneficiary]).await.unwrap();
+
+    // Only the owner of the destination lists, and the creator approves
+    let result = s.fixture.list_position(SEEDS, &s.destination, &buyer, &buyer_payment, 50).await;
+    assert_error(result, VestingError::InvalidDestinationAuthority);
+    s.fixture.list_position(SEEDS, &s.destination, &s.beneficiary, &seller_payment, 50).await.unwrap();
+    assert_error(s.fixture.process(&[accept(50)], &[&buyer]).await, VestingError::ListingNotApproved);
+    let approve = integration::approve_listing(SEEDS, buyer.pubkey());
+    assert_error(s.fixture.process(&[approve], &[&buyer]).await, VestingError::InvalidCreator);
+
+    // A relisted position needs the buyer to agree to the new price, and a new approval
+    let cancel = integration::cancel_listing(SEEDS, s.beneficiary.pubkey());
+    s.fixture.process(&[cancel], &[&s.beneficiary]).await.unwrap();
+    s.fixture.list_position(SEEDS, &s.destination, &s.beneficiary, &seller_payment, 80).await.unwrap();
+    s.fixture.process(&[integration::approve_listing(SEEDS, s.creator.pubkey())], &[&s.creator]).await.unwrap();
+    assert_error(s.fixture.process(&[accept(50)], &[&buyer]).await, VestingError::ListingPriceChanged);
+    s.fixture.process(&[accept(80)], &[&buyer]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&seller_payment).await, 80);
+    assert_eq!(s.fixture.token_balance(&buyer_payment).await, 20);
+    let vesting = s.fixture.vesting(&SEEDS).await;
+    assert!(vesting.claim_delegate.is_none() && vesting.bridge_target.is_none() && vesting.claim_hook.is_none());
+
+    // The buyer claims from then on
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    let unlock = integration::unlock(SEEDS, escrow, s.mint, buyer_destination);
+    s.fixture.process(&[unlock], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&buyer_destination).await, 300);
+    assert_error(s.unlock(escrow).await, VestingError::InvalidDestination);
+}
+
+#[tokio::test]
+async fn batch_unlocks_pay_the_due_contracts_of_a_destination() {
+    const LATER_SEEDS: [u8; 31] = [8; 31];
+    let mut s = setup(2_001).await;
+    let escrow = s.fixture.create_grant(SEEDS, &s.source, &s.creator, &s.mint, &s.destination, cliff_schedule(s.start))
+        .await.unwrap();
+    let round = s.fixture.create_round(&s.creator, "payroll", &s.mint).await.unwrap();
+    s.fixture.process(&[integration::tag_round(SEEDS, round, s.creator.pubkey())], &[&s.creator]).await.unwrap();
+    s.accept().await.unwrap();
+    let later_escrow = s.fixture.create_grant(LATER_SEEDS, &s.source, &s.creator, &s.mint, &s.destination,
+                                              vec![tranche(s.start + 2 * 86_400, 1_000)]).await.unwrap();
+    let accept = integration::accept(LATER_SEEDS, s.destination, s.beneficiary.pubkey());
+    s.fixture.process(&[accept], &[&s.beneficiary]).await.unwrap();
+    let contracts = [(SEEDS, escrow), (LATER_SEEDS, later_escrow)];
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+
+    // The pairs follow the seeds, and the rounds of tagged contracts come after them
+    let mut swapped = integration::batch_unlock(s.destination, s.mint, &[(LATER_SEEDS, later_escrow), (SEEDS, escrow)]);
+    swapped.accounts.swap(3, 5);
+    swapped.accounts.swap(4, 6);
+    assert_error(s.fixture.process(&[swapped], &[]).await, VestingError::InvalidVestingSeeds);
+    let mut short = integration::batch_unlock(s.destination, s.mint, &contracts);
+    short.accounts.pop();
+    assert_error(s.fixture.process(&[short], &[]).await, VestingError::InvalidRemainingAccounts);
+    let batch = integration::batch_unlock(s.destination, s.mint, &contracts);
+    assert_error(s.fixture.process(std::slice::from_ref(&batch), &[]).await, VestingError::MissingRoundAccount);
+
+    // Contracts with nothing due are skipped
+    let mut tallied = batch;
+    tallied.accounts.push(AccountMeta::new(round, false));
+    s.fixture.process(std::slice::from_ref(&tallied), &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 300);
+    assert_eq!(s.fixture.round(&round).await.total_claimed, 300);
+
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    s.fixture.process(&[tallied], &[]).await.unwrap();
+    assert_eq!(s.fixture.token_balance(&s.destination).await, 1_600);
+    assert_eq!(s.fixture.round(&round).await.total_claimed, 600);
+}
+
+#[tokio::test]
+async fn claim_receipts_record_each_claim_to_the_destination() {
+    let mut s = setup(1_001).await;
+    let escrow = s.create(cliff_schedule(s.start)).await;
+    let beneficiary = s.beneficiary.pubkey();
+
+    // Each claim with a receipt records its counter, amount and destination
+    s.fixture.warp_to_timestamp(s.start + 86_400).await;
+    let creator_token = s.fixture.create_associated_token_account(&s.creator.pubkey(), &s.mint).await;
+    let result = s.fixture.unlock_with_receipt(SEEDS, &escrow, &s.mint, &creator_token).await;
+    assert_error(result, VestingError::InvalidDestination);
+    let first = s.fixture.unlock_with_receipt(SEEDS, &escrow, &s.mint, &s.destination).await.unwrap();
+    s.fixture.warp_to_timestamp(s.start + 2 * 86_400).await;
+    let second = s.fixture.unlock_with_receipt(SEEDS, &escrow, &s.mint, &s.destination).await.unwrap();
+    assert_eq!(s.fixture.claim_receipt(&first).await.counter, 0);
+    let receipt = s.fixture.claim_receipt(&second).await;
+    assert_eq!((receipt.counter, receipt.amount, receipt.destination), (1, 300, s.destination));
+
+    // Once a transferable receipt stands for the position, its holder claims instead
+    s.fixture.create_receipt(SEEDS, &s.destination, &beneficiary, &s.creator).await.unwrap();
+    s.fixture.warp_to_timestamp(s.start + 3 * 86_400).await;
+    let result = s.fixture.unlock_with_receipt(SEEDS, &escrow, &s.mint, &s.destination).await;
+    assert_error(result, VestingError::ReceiptHolderIsBeneficiary);
+}
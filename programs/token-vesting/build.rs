@@ -0,0 +1,16 @@
+//! Declares the program id the crate is built for, `TOKEN_VESTING_PROGRAM_ID` when set, so a deployment on any cluster
+//! builds the program and its CPI clients from the same source. Defaults to the localnet address of Anchor.toml.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+const LOCALNET_PROGRAM_ID: &str = "Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS";
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=TOKEN_VESTING_PROGRAM_ID");
+    let program_id = env::var("TOKEN_VESTING_PROGRAM_ID").unwrap_or_else(|_| LOCALNET_PROGRAM_ID.to_string());
+    // declare_id! checks the address when the crate compiles
+    let declaration = format!("declare_id!({:?});\n", program_id.trim());
+    fs::write(Path::new(&env::var("OUT_DIR").unwrap()).join("program_id.rs"), declaration).unwrap();
+}
@@ -0,0 +1,181 @@
+//! Reader and CPI helpers of Bonfida's token vesting program, whose contracts `migrate_from_bonfida` imports.
+//!
+//! Bonfida packs its accounts by hand: a header of the destination token address, the mint address and
+//! `is_initialized`, followed by the schedules as little endian `release_time` and `amount`. A vesting account lives at
+//! the address created from its 32 bytes seeds, the last one being the bump. Only Bonfida's `unlock` moves tokens out
+//! of its escrow, transferring every released tranche to the destination and zeroing their amounts.
+//!
+//! With the `bonfida-compat` feature the program also accepts Bonfida's `init`, `create` and `unlock` instruction
+//! data and accounts, so clients built against Bonfida's ABI can target it. The 31 first bytes of the Bonfida seeds
+//! are the seeds here, which derive the same vesting address. The contracts they create still wait for the
+//! destination owner to `accept` them, and Bonfida's `unlock` has to be followed by the mint account.
+
+use anchor_lang::prelude::*;
+use anchor_lang::InstructionData;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke;
+use std::convert::TryInto;
+
+anchor_lang::declare_id!("CChTq6PthWU82YZkbveA3WDf7s97BWhBK4Vx9bmsT743");
+
+const HEADER_LEN: usize = 65;
+const SCHEDULE_LEN: usize = 16;
+
+const INIT_TAG: u8 = 0;
+const CREATE_TAG: u8 = 1;
+const UNLOCK_TAG: u8 = 2;
+const CHANGE_DESTINATION_TAG: u8 = 3;
+
+pub struct BonfidaVesting {
+    pub destination_address: Pubkey,
+    pub mint_address: Pubkey,
+    pub is_initialized: bool,
+    /// Release time and amount of each tranche, 0 once unlocked
+    pub schedules: Vec<(u64, u64)>,
+}
+
+impl BonfidaVesting {
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        let (header, schedules) = (data.get(..HEADER_LEN)?, &data[HEADER_LEN..]);
+        if schedules.len() % SCHEDULE_LEN != 0 {
+            return None;
+        }
+        Some(BonfidaVesting {
+            destination_address: Pubkey::new(&header[..32]),
+            mint_address: Pubkey::new(&header[32..64]),
+            is_initialized: header[64] == 1,
+            schedules: schedules
+                .chunks_exact(SCHEDULE_LEN)
+                .map(|s| (u64::from_le_bytes(s[..8].try_into().unwrap()), u64::from_le_bytes(s[8..].try_into().unwrap())))
+                .collect(),
+        })
+    }
+
+    /// Returns whether Bonfida's `unlock` would transfer anything at `timestamp`, it fails otherwise
+    pub fn has_released(&self, timestamp: i64) -> bool {
+        self.schedules.iter().any(|&(release_time, amount)| release_time as i64 <= timestamp && amount > 0)
+    }
+}
+
+/// Returns the address of the Bonfida vesting account of `seeds`, none when they don't lead off the curve
+pub fn vesting_address(seeds: &[u8; 32]) -> Option<Pubkey> {
+    Pubkey::create_program_address(&[seeds.as_ref()], &ID).ok()
+}
+
+/// Builds Bonfida's `unlock`, transferring the released tranches from its escrow to the destination. Anyone can send it
+pub fn unlock_instruction(seeds: [u8; 32], vesting_token: Pubkey, destination_token: Pubkey) -> Instruction {
+    let mut data = vec![UNLOCK_TAG];
+    data.extend_from_slice(&seeds);
+    Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new_readonly(spl_token::ID, false),
+            AccountMeta::new_readonly(anchor_lang::solana_program::sysvar::clock::ID, false),
+            AccountMeta::new(vesting_address(&seeds).unwrap_or_default(), false),
+            AccountMeta::new(vesting_token, false),
+            AccountMeta::new(destination_token, false),
+        ],
+        data,
+    }
+}
+
+pub struct BonfidaAccounts<'info> {
+    pub bonfida_program: AccountInfo<'info>,
+    pub vesting: AccountInfo<'info>,
+    pub vesting_token: AccountInfo<'info>,
+    pub destination_token: AccountInfo<'info>,
+    /// The owner of the destination token account, signing
+    pub destination_authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+}
+
+/// Points the Bonfida contract of `seeds` to `new_destination_token`, signed by the current destination owner
+pub fn change_destination<'info>(accounts: &BonfidaAccounts<'info>, seeds: [u8; 32], new_destination_token: &AccountInfo<'info>) -> Result<()> {
+    let mut data = vec![CHANGE_DESTINATION_TAG];
+    data.extend_from_slice(&seeds);
+    let ix = Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(accounts.vesting.key(), false),
+            AccountMeta::new_readonly(accounts.destination_authority.key(), true),
+            AccountMeta::new_readonly(accounts.destination_token.key(), false),
+            AccountMeta::new_readonly(new_destination_token.key(), false),
+        ],
+        data,
+    };
+    invoke(
+        &ix,
+        &[
+            accounts.vesting.clone(),
+            accounts.destination_authority.clone(),
+            accounts.destination_token.clone(),
+            new_destination_token.clone(),
+            accounts.bonfida_program.clone(),
+        ],
+    ).map_err(Into::into)
+}
+
+/// Unlocks the released tranches of the Bonfida contract of `seeds` to `destination_token`, its current destination
+pub fn unlock<'info>(accounts: &BonfidaAccounts<'info>, seeds: [u8; 32], destination_token: &AccountInfo<'info>) -> Result<()> {
+    invoke(
+        &unlock_instruction(seeds, accounts.vesting_token.key(), destination_token.key()),
+        &[
+            accounts.token_program.clone(),
+            accounts.clock.clone(),
+            accounts.vesting.clone(),
+            accounts.vesting_token.clone(),
+            destination_token.clone(),
+            accounts.bonfida_program.clone(),
+        ],
+    ).map_err(Into::into)
+}
+
+/// Translates a Bonfida `init`, `create` or `unlock` into the instruction data and accounts of the instruction here,
+/// none when `data` isn't one of them
+pub fn translate_instruction<'info>(accounts: &[AccountInfo<'info>], data: &[u8]) -> Option<Result<(Vec<u8>, Vec<AccountInfo<'info>>)>> {
+    let (&tag, rest) = data.split_first()?;
+    let seeds: [u8; 31] = rest.get(..31)?.try_into().unwrap();
+    let rest = rest.get(32..)?;
+    let translated = match tag {
+        INIT_TAG if rest.len() == 4 => {
+            let number_of_schedules = u32::from_le_bytes(rest.try_into().unwrap());
+            // system_program, rent, payer, vesting
+            reorder(accounts, &[3, 2, 0]).map(|accounts| (crate::instruction::Init { seeds, number_of_schedules }.data(), accounts))
+        }
+        CREATE_TAG if rest.len() >= 64 && (rest.len() - 64) % SCHEDULE_LEN == 0 => {
+            let schedules = rest[64..]
+                .chunks_exact(SCHEDULE_LEN)
+                .map(|s| crate::Schedule {
+                    release_time: u64::from_le_bytes(s[..8].try_into().unwrap()),
+                    amount: u64::from_le_bytes(s[8..].try_into().unwrap()),
+                    condition: None,
+                })
+                .collect();
+            let create = crate::instruction::Create {
+                seeds,
+                mint_address: Pubkey::new(&rest[..32]),
+                destination_token_address: Pubkey::new(&rest[32..64]),
+                schedules,
+                min_claim_amount: 0,
+                revoker: None,
+                soulbound_receipt: false,
+                unlock_policy: crate::UnlockPolicy::Anyone,
+            };
+            // token_program, vesting, vesting_token, source_authority, source_token
+            reorder(accounts, &[1, 2, 4, 3, 0]).map(|accounts| (create.data(), accounts))
+        }
+        UNLOCK_TAG if rest.is_empty() => {
+            // token_program, clock, vesting, vesting_token, destination_token, followed by the mint
+            reorder(accounts, &[2, 3, 5, 4, 0]).map(|accounts| (crate::instruction::Unlock { seeds, memo: None }.data(), accounts))
+        }
+        _ => return None,
+    };
+    Some(translated)
+}
+
+fn reorder<'info>(accounts: &[AccountInfo<'info>], order: &[usize]) -> Result<Vec<AccountInfo<'info>>> {
+    order.iter()
+        .map(|&i| accounts.get(i).cloned().ok_or_else(|| anchor_lang::error::ErrorCode::AccountNotEnoughKeys.into()))
+        .collect()
+}
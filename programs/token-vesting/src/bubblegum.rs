@@ -0,0 +1,88 @@
+//! CPI helper minting compressed NFT receipts through Metaplex Bubblegum.
+//!
+//! `mpl-bubblegum` targets a newer Anchor, so `mint_v1` is packed by hand following its
+//! `MetadataArgs` borsh layout. The receipt lists the vesting account as its only, verified creator.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+anchor_lang::declare_id!("BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY");
+
+pub mod noop_program {
+    anchor_lang::declare_id!("noopb9bkMVfRPU8AsbpTUg8AQkHtKwMYZiFUjNRtMmV");
+}
+
+pub mod compression_program {
+    anchor_lang::declare_id!("cmtDvXumGCrqC1Age74AVPhSRVXJMd8PJS91L8KbNCK");
+}
+
+pub struct MintAccounts<'info> {
+    pub bubblegum_program: AccountInfo<'info>,
+    /// The tree config PDA `[merkle_tree]` of the bubblegum program
+    pub tree_authority: AccountInfo<'info>,
+    pub leaf_owner: AccountInfo<'info>,
+    pub merkle_tree: AccountInfo<'info>,
+    pub payer: AccountInfo<'info>,
+    /// The tree creator or delegate, any signer for a public tree
+    pub tree_delegate: AccountInfo<'info>,
+    /// The vesting account, signing as the verified creator of the receipt
+    pub creator: AccountInfo<'info>,
+    pub log_wrapper: AccountInfo<'info>,
+    pub compression_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+/// Mints a non-fungible compressed NFT to `leaf_owner`, verified as created by `creator`
+pub fn mint_v1(accounts: &MintAccounts, name: String, symbol: String, uri: String, creator_signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    let mut data = hash(b"global:mint_v1").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&name.try_to_vec()?);
+    data.extend_from_slice(&symbol.try_to_vec()?);
+    data.extend_from_slice(&uri.try_to_vec()?);
+    data.extend_from_slice(&0u16.to_le_bytes()); // seller_fee_basis_points
+    data.push(0); // primary_sale_happened
+    data.push(0); // is_mutable
+    data.push(0); // edition_nonce: None
+    data.extend_from_slice(&[1, 0]); // token_standard: Some(NonFungible)
+    data.push(0); // collection: None
+    data.push(0); // uses: None
+    data.push(0); // token_program_version: Original
+    data.extend_from_slice(&1u32.to_le_bytes()); // creators
+    data.extend_from_slice(accounts.creator.key.as_ref());
+    data.push(1); // verified
+    data.push(100); // share
+
+    let ix = Instruction {
+        program_id: ID,
+        accounts: vec![
+            AccountMeta::new(accounts.tree_authority.key(), false),
+            AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+            AccountMeta::new_readonly(accounts.leaf_owner.key(), false),
+            AccountMeta::new(accounts.merkle_tree.key(), false),
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.tree_delegate.key(), true),
+            AccountMeta::new_readonly(accounts.log_wrapper.key(), false),
+            AccountMeta::new_readonly(accounts.compression_program.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.creator.key(), true),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.tree_authority.clone(),
+            accounts.leaf_owner.clone(),
+            accounts.merkle_tree.clone(),
+            accounts.payer.clone(),
+            accounts.tree_delegate.clone(),
+            accounts.log_wrapper.clone(),
+            accounts.compression_program.clone(),
+            accounts.system_program.clone(),
+            accounts.creator.clone(),
+            accounts.bubblegum_program.clone(),
+        ],
+        creator_signer_seeds,
+    ).map_err(Into::into)
+}
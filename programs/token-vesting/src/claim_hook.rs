@@ -0,0 +1,63 @@
+//! CPI notifying the claim hook program registered on a contract after each claim.
+//!
+//! The hook receives an Anchor style `on_claim` instruction: the sighash of `global:on_claim` followed by the borsh
+//! encoded `OnClaim`. Its first account is the `claim_hook_authority` PDA of this program, signing so the hook can
+//! tell a claim from a spoofed call, followed by the accounts registered with the hook. Only the authority signs:
+//! the signatures of the claim are not forwarded, and the vesting account never signs as its signature would let the
+//! hook move the escrow.
+//!
+//! Hooks are restricted to the programs of the claim hook allowlist kept by the program config admin. Claims skip the
+//! hook of a program removed from the allowlist, so a failing hook can't lock a contract.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+pub const CLAIM_HOOK_AUTHORITY_SEED: &[u8] = b"claim_hook_authority";
+
+/// The claim details passed to the hook
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct OnClaim {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// Returns the claim hook authority PDA and its bump
+pub fn claim_hook_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_HOOK_AUTHORITY_SEED], &crate::ID)
+}
+
+/// Invokes `on_claim` of `hook_program` with `accounts`, signed by the claim hook authority
+pub fn on_claim<'info>(
+    hook_program: &AccountInfo<'info>,
+    authority: &AccountInfo<'info>,
+    accounts: &[AccountInfo<'info>],
+    args: &OnClaim,
+) -> Result<()> {
+    let (authority_address, bump) = claim_hook_authority();
+    require!(authority.key() == authority_address, crate::VestingError::InvalidClaimHookAccounts);
+
+    let mut data = hash(b"global:on_claim").to_bytes()[..8].to_vec();
+    data.extend_from_slice(&args.try_to_vec()?);
+
+    let mut metas = vec![AccountMeta::new_readonly(authority_address, true)];
+    metas.extend(accounts.iter().map(|a| if a.is_writable {
+        AccountMeta::new(a.key(), false)
+    } else {
+        AccountMeta::new_readonly(a.key(), false)
+    }));
+    let ix = Instruction {
+        program_id: hook_program.key(),
+        accounts: metas,
+        data,
+    };
+
+    let mut infos = vec![authority.clone()];
+    infos.extend_from_slice(accounts);
+    infos.push(hook_program.clone());
+    invoke_signed(&ix, &infos, &[&[CLAIM_HOOK_AUTHORITY_SEED, &[bump]]]).map_err(Into::into)
+}
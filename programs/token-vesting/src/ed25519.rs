@@ -0,0 +1,54 @@
+//! Verification of ed25519 signatures through instruction introspection.
+//!
+//! The ed25519 native program fails the whole transaction on an invalid signature, so finding
+//! an ed25519 instruction over the expected key and message in the transaction proves the signature.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use std::convert::TryInto;
+
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_SIZE: usize = 14;
+const PUBKEY_SIZE: usize = 32;
+// Instruction index meaning the data lives in the ed25519 instruction itself
+const CURRENT_INSTRUCTION: u16 = u16::MAX;
+
+/// Checks that an instruction preceding the current one verifies a signature of `signer` over `message`
+pub fn verify_signed_message(instructions_sysvar: &AccountInfo, signer: &Pubkey, message: &[u8]) -> Result<bool> {
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    for index in 0..current_index {
+        let ix = load_instruction_at_checked(index as usize, instructions_sysvar)?;
+        if ix.program_id == ed25519_program::ID && is_signed_message(&ix.data, signer, message) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+fn is_signed_message(data: &[u8], signer: &Pubkey, message: &[u8]) -> bool {
+    let num_signatures = match data.first() {
+        Some(n) => *n as usize,
+        None => return false,
+    };
+
+    (0..num_signatures).any(|i| {
+        let start = SIGNATURE_OFFSETS_START + i * SIGNATURE_OFFSETS_SIZE;
+        let offsets = match data.get(start..start + SIGNATURE_OFFSETS_SIZE) {
+            Some(offsets) => offsets,
+            None => return false,
+        };
+        let read_u16 = |at: usize| u16::from_le_bytes(offsets[at..at + 2].try_into().unwrap());
+
+        let public_key_offset = read_u16(4) as usize;
+        let message_offset = read_u16(8) as usize;
+        let message_size = read_u16(10) as usize;
+        let in_current_instruction = read_u16(2) == CURRENT_INSTRUCTION
+            && read_u16(6) == CURRENT_INSTRUCTION
+            && read_u16(12) == CURRENT_INSTRUCTION;
+
+        in_current_instruction
+            && data.get(public_key_offset..public_key_offset + PUBKEY_SIZE) == Some(signer.as_ref())
+            && data.get(message_offset..message_offset + message_size) == Some(message)
+    })
+}
@@ -0,0 +1,65 @@
+//! Event delivery through a self-CPI, enabled by the `event-cpi` feature.
+//!
+//! Program logs are truncated once they exceed the log limit, which drops events emitted under heavy
+//! CPI stacks. Following Anchor's event CPI convention the program instead invokes itself with
+//! `EVENT_IX_TAG (u64 LE) || event discriminator || event` as instruction data, signed by the
+//! `__event_authority` PDA, and indexers read the event from the inner instruction.
+//! Anchor 0.22 can't add the event accounts to every instruction, so the event authority and this
+//! program are passed as the last two remaining accounts, only followed by the test clock of the `test-clock`
+//! feature. Without them events are logged as usual.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+pub const EVENT_IX_TAG: u64 = 0x1d9a_cb51_2ea5_45e4;
+pub const EVENT_AUTHORITY_SEED: &[u8] = b"__event_authority";
+
+/// Returns the event authority PDA and its bump
+pub fn event_authority() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[EVENT_AUTHORITY_SEED], &crate::ID)
+}
+
+/// Returns the remaining accounts of an instruction without the trailing event authority and program
+pub fn instruction_accounts<'a, 'info>(accounts: &'a [AccountInfo<'info>]) -> &'a [AccountInfo<'info>] {
+    let accounts = crate::test_clock::instruction_accounts(accounts);
+    match accounts {
+        [rest @ .., authority, program] if is_event_accounts(authority, program) => rest,
+        _ => accounts,
+    }
+}
+
+/// Emits `event` through a self-CPI when the event accounts are passed, otherwise logs it like `emit!`
+pub fn emit<E: anchor_lang::Event>(remaining_accounts: &[AccountInfo], event: &E) -> Result<()> {
+    let (authority, program) = match crate::test_clock::instruction_accounts(remaining_accounts) {
+        [.., authority, program] if is_event_accounts(authority, program) => (authority, program),
+        _ => {
+            emit!(*event);
+            return Ok(());
+        }
+    };
+
+    let mut data = EVENT_IX_TAG.to_le_bytes().to_vec();
+    data.extend_from_slice(&event.data());
+    let ix = Instruction {
+        program_id: crate::ID,
+        accounts: vec![AccountMeta::new_readonly(authority.key(), true)],
+        data,
+    };
+    let bump = event_authority().1;
+    invoke_signed(&ix, &[authority.clone(), program.clone()], &[&[EVENT_AUTHORITY_SEED, &[bump]]])
+        .map_err(Into::into)
+}
+
+/// Accepts the self-CPI carrying an event, which only the event authority can sign
+pub fn handle_event_instruction(accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+    require!(cfg!(feature = "event-cpi") && data.starts_with(&EVENT_IX_TAG.to_le_bytes()),
+             anchor_lang::error::ErrorCode::InstructionFallbackNotFound);
+    let authority = accounts.first().ok_or(anchor_lang::error::ErrorCode::AccountNotEnoughKeys)?;
+    require!(authority.is_signer && authority.key() == event_authority().0, crate::VestingError::InvalidEventAuthority);
+    Ok(())
+}
+
+fn is_event_accounts(authority: &AccountInfo, program: &AccountInfo) -> bool {
+    cfg!(feature = "event-cpi") && program.key() == crate::ID && authority.key() == event_authority().0
+}
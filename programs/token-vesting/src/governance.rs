@@ -0,0 +1,150 @@
+//! SPL Governance voter weight addin accounts and proposal state reading.
+//!
+//! The record layouts mirror `spl-governance-addin-api`. Its account discriminators are the Anchor
+//! discriminators of the same struct names, so the records can be declared as regular Anchor accounts.
+
+use anchor_lang::prelude::*;
+
+/// Links a realm and its governing token mint to this program acting as the voter weight addin
+#[account]
+pub struct Registrar {
+    pub governance_program_id: Pubkey,
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+}
+
+#[account]
+pub struct VoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub voter_weight: u64,
+    // The slot the voter weight is valid for, the record must be updated in the same slot it is used
+    pub voter_weight_expiry: Option<u64>,
+    pub weight_action: Option<VoterWeightAction>,
+    pub weight_action_target: Option<Pubkey>,
+    pub reserved: [u8; 8],
+}
+
+/// The maximum voter weight of the realm for the governing token mint, used by quorum calculations
+#[account]
+pub struct MaxVoterWeightRecord {
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub max_voter_weight: u64,
+    // The slot the max voter weight is valid for, the record must be updated in the same slot it is used
+    pub max_voter_weight_expiry: Option<u64>,
+    pub reserved: [u8; 8],
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq)]
+pub enum VoterWeightAction {
+    CastVote,
+    CommentProposal,
+    CreateGovernance,
+    CreateProposal,
+    SignOffProposal,
+}
+
+// GovernanceAccountType of ProposalV1 and ProposalV2, which share the layout prefix read below
+const PROPOSAL_V1: u8 = 5;
+const PROPOSAL_V2: u8 = 14;
+const PROPOSAL_STATE_OFFSET: usize = 65;
+
+// GovernanceAccountType of RealmV1 and RealmV2, which share the layout prefix read below
+const REALM_V1: u8 = 1;
+const REALM_V2: u8 = 16;
+// The council mint option of the realm config, after the account type, the community mint, the addin flags,
+// reserved bytes, the min weight to create a governance and the max vote weight source
+const REALM_COUNCIL_MINT_OFFSET: usize = 1 + 32 + 2 + 6 + 8 + 9;
+// The reserved bytes and the voting proposal count between the council mint and the authority
+const REALM_AUTHORITY_GAP: usize = 6 + 2;
+
+// ProposalState variants of a successful vote
+const PROPOSAL_SUCCEEDED: u8 = 3;
+const PROPOSAL_EXECUTING: u8 = 4;
+const PROPOSAL_COMPLETED: u8 = 5;
+
+/// Returns whether the proposal, owned by `governance_program`, was voted through, i.e. is Succeeded, Executing or Completed
+pub fn is_proposal_succeeded(proposal: &AccountInfo, governance_program: &Pubkey) -> Result<bool> {
+    require!(proposal.owner == governance_program, crate::VestingError::InvalidProposal);
+    let data = proposal.try_borrow_data()?;
+    require!(data.len() > PROPOSAL_STATE_OFFSET, crate::VestingError::InvalidProposal);
+    require!(data[0] == PROPOSAL_V1 || data[0] == PROPOSAL_V2, crate::VestingError::InvalidProposal);
+
+    Ok(matches!(data[PROPOSAL_STATE_OFFSET], PROPOSAL_SUCCEEDED | PROPOSAL_EXECUTING | PROPOSAL_COMPLETED))
+}
+
+/// Returns the authority of the realm, owned by `governance_program`, none once the realm has no authority
+pub fn realm_authority(realm: &AccountInfo, governance_program: &Pubkey) -> Result<Option<Pubkey>> {
+    require!(realm.owner == governance_program, crate::VestingError::InvalidRealm);
+    let data = realm.try_borrow_data()?;
+    require!(data.first() == Some(&REALM_V1) || data.first() == Some(&REALM_V2), crate::VestingError::InvalidRealm);
+
+    let council_mint_len = match data.get(REALM_COUNCIL_MINT_OFFSET) {
+        Some(0) => 1,
+        Some(1) => 1 + 32,
+        _ => return err!(crate::VestingError::InvalidRealm),
+    };
+    let authority_offset = REALM_COUNCIL_MINT_OFFSET + council_mint_len + REALM_AUTHORITY_GAP;
+    match data.get(authority_offset) {
+        Some(0) => Ok(None),
+        Some(1) => data.get(authority_offset + 1..authority_offset + 33)
+            .map(|key| Some(Pubkey::new(key)))
+            .ok_or_else(|| crate::VestingError::InvalidRealm.into()),
+        _ => err!(crate::VestingError::InvalidRealm),
+    }
+}
+
+pub const REGISTRAR_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 3 * std::mem::size_of::<Pubkey>(); // governance_program_id, realm, governing_token_mint
+
+pub const VOTER_WEIGHT_RECORD_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 3 * std::mem::size_of::<Pubkey>() // realm, governing_token_mint, governing_token_owner
+    + std::mem::size_of::<u64>() // voter_weight
+    + 1 + std::mem::size_of::<u64>() // voter_weight_expiry
+    + 1 + 1 // weight_action
+    + 1 + std::mem::size_of::<Pubkey>() // weight_action_target
+    + 8; // reserved
+
+pub const MAX_VOTER_WEIGHT_RECORD_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // realm, governing_token_mint
+    + std::mem::size_of::<u64>() // max_voter_weight
+    + 1 + std::mem::size_of::<u64>() // max_voter_weight_expiry
+    + 8; // reserved
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A RealmV2 account prefix with the given council mint and authority
+    fn realm_data(council_mint: Option<Pubkey>, authority: Option<Pubkey>) -> Vec<u8> {
+        let mut data = vec![REALM_V2];
+        data.extend_from_slice(&[0; REALM_COUNCIL_MINT_OFFSET - 1]);
+        data.extend(council_mint.try_to_vec().unwrap());
+        data.extend_from_slice(&[0; REALM_AUTHORITY_GAP]);
+        data.extend(authority.try_to_vec().unwrap());
+        data.extend("realm".to_string().try_to_vec().unwrap());
+        data
+    }
+
+    fn read_authority(mut data: Vec<u8>, owner: &Pubkey, governance_program: &Pubkey) -> Result<Option<Pubkey>> {
+        let (key, mut lamports) = (Pubkey::new_unique(), 0);
+        let realm = AccountInfo::new(&key, false, false, &mut lamports, &mut data, owner, false, 0);
+        realm_authority(&realm, governance_program)
+    }
+
+    #[test]
+    fn realm_authority_follows_the_council_mint() {
+        let (governance_program, authority) = (Pubkey::new_unique(), Pubkey::new_unique());
+        for council_mint in [None, Some(Pubkey::new_unique())] {
+            let read = |authority| read_authority(realm_data(council_mint, authority), &governance_program, &governance_program).unwrap();
+            assert_eq!(read(Some(authority)), Some(authority));
+            assert_eq!(read(None), None);
+        }
+        assert!(read_authority(realm_data(None, Some(authority)), &Pubkey::new_unique(), &governance_program).is_err());
+    }
+}
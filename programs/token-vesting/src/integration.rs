@@ -0,0 +1,1956 @@
+//! Helpers for programs creating and claiming vesting positions, enabled by the `cpi` feature.
+//!
+//! Depend on the crate with the `cpi` feature, which implies `no-entrypoint` so its entrypoint symbols don't
+//! collide with the dependent program's, and `no-idl` to leave out the IDL instructions. Anchor generates the
+//! `cpi` calls and the `cpi::accounts` structs, re-exported here. This module adds the address derivation,
+//! the claim math and instruction builders so integrators don't copy them.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+pub use crate::cpi::accounts;
+
+use crate::{BridgeTarget, ClaimHook, MerkleTranche, Schedule, TemplateTranche, UnlockPolicy, Vesting, AIRDROP_SEED, CLAIM_STATUS_SEED, COHORT_SEED,
+            BUDGET_SEED, DISTRIBUTOR_SEED, FUNDING_ESCROW_SEED, GRANT_INDEX_SEED, GUARANTEE_SEED, PARTNER_SEED, PARTNER_STATS_SEED, RENT_SPONSORSHIP_SEED, ROLLUP_SEED, ROUND_SEED, SCHEDULE_TEMPLATE_SEED, SHARED_VAULT_SEED, UNLOCK_QUEUE_SEED,
+            VOTE_DELEGATION_SEED, CLAIM_HOOK_ALLOWLIST_SEED, REGISTRAR_SEED, VOTER_WEIGHT_RECORD_SEED, MAX_VOTER_WEIGHT_RECORD_SEED, LISTING_SEED, RECEIPT_SEED, CLAIM_RECEIPT_SEED,
+            FEE_POOL_SEED};
+
+/// Returns the vesting account address and bump of `seeds`
+pub fn find_vesting_address(seeds: &[u8; 31]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[seeds.as_ref()], &crate::ID)
+}
+
+/// Returns the merkle distributor address and bump of `seeds`
+pub fn find_distributor_address(seeds: &[u8; 31]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[DISTRIBUTOR_SEED.as_bytes(), seeds.as_ref()], &crate::ID)
+}
+
+/// Returns the claim status address and bump of `claimant` in `distributor`
+pub fn find_claim_status_address(distributor: &Pubkey, claimant: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_STATUS_SEED.as_bytes(), distributor.as_ref(), claimant.as_ref()], &crate::ID)
+}
+
+/// Returns the shared vault address and bump of `authority` for `mint`
+pub fn find_shared_vault_address(authority: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SHARED_VAULT_SEED.as_bytes(), authority.as_ref(), mint.as_ref()], &crate::ID)
+}
+
+/// Returns the schedule template address and bump of `authority` named `name`
+pub fn find_schedule_template_address(authority: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[SCHEDULE_TEMPLATE_SEED.as_bytes(), authority.as_ref(), name.as_bytes()], &crate::ID)
+}
+
+/// Returns the cohort address and bump of `admin` named `name`
+pub fn find_cohort_address(admin: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[COHORT_SEED.as_bytes(), admin.as_ref(), name.as_bytes()], &crate::ID)
+}
+
+/// Returns the vested airdrop address and bump of `seeds`
+pub fn find_airdrop_address(seeds: &[u8; 31]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[AIRDROP_SEED.as_bytes(), seeds.as_ref()], &crate::ID)
+}
+
+/// Returns the seeds of the contract `claim_airdrop` creates for `claimant` in `airdrop`
+pub fn airdrop_position_seeds(airdrop: &Pubkey, claimant: &Pubkey) -> [u8; 31] {
+    crate::airdrop_position_seeds(airdrop, claimant)
+}
+
+/// Returns the funding escrow address and bump of `seeds`
+pub fn find_funding_escrow_address(seeds: &[u8; 31]) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FUNDING_ESCROW_SEED.as_bytes(), seeds.as_ref()], &crate::ID)
+}
+
+/// Returns the round address and bump of the round `name` of `creator`
+pub fn find_round_address(creator: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROUND_SEED.as_bytes(), creator.as_ref(), name.as_bytes()], &crate::ID)
+}
+
+/// Returns the address of the budget `name` of `org`
+pub fn find_budget_address(org: &Pubkey, name: &str) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[BUDGET_SEED.as_bytes(), org.as_ref(), name.as_bytes()], &crate::ID)
+}
+
+/// Returns the address of the rollup of the contracts of `org` for `beneficiary`
+pub fn find_rollup_address(org: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[ROLLUP_SEED.as_bytes(), org.as_ref(), beneficiary.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the unlock queue of `mint`
+pub fn find_unlock_queue_address(mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[UNLOCK_QUEUE_SEED.as_bytes(), mint.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the partner config of `authority`
+pub fn find_partner_config_address(authority: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PARTNER_SEED.as_bytes(), authority.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the stats of the partner config `partner_config` in `mint`
+pub fn find_partner_stats_address(partner_config: &Pubkey, mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[PARTNER_STATS_SEED.as_bytes(), partner_config.as_ref(), mint.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the rent sponsorship pool of `creator`
+pub fn find_rent_sponsorship_pool_address(creator: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RENT_SPONSORSHIP_SEED.as_bytes(), creator.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the grant index of `creator` for `beneficiary`
+pub fn find_grant_index_address(creator: &Pubkey, beneficiary: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GRANT_INDEX_SEED.as_bytes(), creator.as_ref(), beneficiary.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the vote delegation of the contract at `vesting`
+pub fn find_vote_delegation_address(vesting: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[VOTE_DELEGATION_SEED.as_bytes(), vesting.as_ref()], &crate::ID)
+}
+
+/// Returns the address of the guarantee of the contract at `vesting`
+pub fn find_guarantee_address(vesting: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[GUARANTEE_SEED.as_bytes(), vesting.as_ref()], &crate::ID)
+}
+
+/// Returns the seeds of the contract of `grant_id` in the grant index of `creator` for `beneficiary`
+pub fn grant_seeds(creator: &Pubkey, beneficiary: &Pubkey, grant_id: u64) -> [u8; 31] {
+    crate::grant_seeds(creator, beneficiary, grant_id)
+}
+
+/// Returns the amount `unlock` would transfer at `timestamp`, 0 when nothing can be claimed
+pub fn claimable_at(vesting: &Vesting, timestamp: i64) -> u64 {
+    crate::claimable_amount(vesting, timestamp).unwrap_or(0)
+}
+
+/// Returns the release time and amount of the next tranche locked at `timestamp`, none once everything is released.
+/// Tranches awaiting their release condition are left out
+pub fn next_unlock_at(vesting: &Vesting, timestamp: i64) -> Option<(u64, u64)> {
+    crate::next_locked_tranche(&vesting.schedule, timestamp)
+}
+
+/// Returns the amount still locked at `timestamp`
+pub fn locked_at(vesting: &Vesting, timestamp: i64) -> u64 {
+    crate::locked_amount(&vesting.schedule, timestamp)
+}
+
+/// Builds an `init` instruction allocating a vesting account of `number_of_schedules` schedules
+pub fn init(seeds: [u8; 31], number_of_schedules: u32, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Initialize {
+            vesting: find_vesting_address(&seeds).0,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::Init { seeds, number_of_schedules }.data(),
+    }
+}
+
+/// Builds a `create` instruction of an irrevocable, permissionlessly unlocked contract without receipt, funded from `source_token`
+pub fn create(
+    seeds: [u8; 31],
+    vesting_token: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    mint_address: Pubkey,
+    destination_token_address: Pubkey,
+    schedules: Vec<Schedule>,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Create {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            source_token,
+            source_authority,
+            mint: mint_address,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::Create {
+            seeds,
+            mint_address,
+            destination_token_address,
+            schedules,
+            min_claim_amount: 0,
+            revoker: None,
+            soulbound_receipt: false,
+            unlock_policy: UnlockPolicy::Anyone,
+        }.data(),
+    }
+}
+
+/// Builds an `unlock` instruction paying the released amount to the contract destination
+pub fn unlock(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, destination_token: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Unlock {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            destination_token,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::Unlock { seeds, memo: None }.data(),
+    }
+}
+
+/// Builds an `accept` instruction activating a pending contract, signed by the destination owner
+pub fn accept(seeds: [u8; 31], destination_token: Pubkey, destination_authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Accept {
+            vesting: find_vesting_address(&seeds).0,
+            destination_token,
+            destination_authority,
+        }.to_account_metas(None),
+        data: crate::instruction::Accept { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `cancel` instruction refunding a pending contract to `refund_token`, signed by the creator
+pub fn cancel(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, refund_token: Pubkey, creator: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Cancel {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            refund_token,
+            creator,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::Cancel { seeds }.data(),
+    }
+}
+
+/// Builds an `authorized_unlock` instruction signed by the destination owner, its claim delegate or an allowlisted key
+pub fn authorized_unlock(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, destination_token: Pubkey, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::AuthorizedUnlock {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            destination_token,
+            authority,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::AuthorizedUnlock { seeds }.data(),
+    }
+}
+
+/// Builds a `migrate_vesting_layout` instruction converting the contract of `seeds`, written by the first release of
+/// the program, to the current layout. `payer` pays the rent of the grown account
+pub fn migrate_vesting_layout(seeds: [u8; 31], payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::MigrateVestingLayout {
+            vesting: find_vesting_address(&seeds).0,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::MigrateVestingLayout { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `migrate_from_bonfida` instruction importing the Bonfida contract of `bonfida_seeds` into the allocated
+/// vesting account of `seeds`, escrowed by `vesting_token`
+pub fn migrate_from_bonfida(
+    seeds: [u8; 31],
+    bonfida_seeds: [u8; 32],
+    vesting_token: Pubkey,
+    bonfida_vesting_token: Pubkey,
+    destination_token: Pubkey,
+    destination_authority: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::MigrateFromBonfida {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            bonfida_vesting: crate::bonfida::vesting_address(&bonfida_seeds).unwrap_or_default(),
+            bonfida_vesting_token,
+            destination_token,
+            destination_authority,
+            bonfida_program: crate::bonfida::ID,
+            token_program: spl_token::ID,
+            clock: anchor_lang::solana_program::sysvar::clock::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::MigrateFromBonfida { _seeds: seeds, bonfida_seeds }.data(),
+    }
+}
+
+/// Builds a `set_destination_owner` instruction paying the pending contract of `seeds` to the associated token account
+/// of `owner`, signed by its creator
+pub fn set_destination_owner(seeds: [u8; 31], creator: Pubkey, owner: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetDestinationOwner {
+            vesting: find_vesting_address(&seeds).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::SetDestinationOwner { _seeds: seeds, owner }.data(),
+    }
+}
+
+/// Builds a `set_vote_delegate` instruction delegating the governance power of the contract of `seeds` to `delegate`,
+/// signed by the owner of its destination
+pub fn set_vote_delegate(seeds: [u8; 31], destination_token: Pubkey, destination_authority: Pubkey, delegate: Pubkey,
+                         payer: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetVoteDelegate {
+            vesting,
+            vote_delegation: find_vote_delegation_address(&vesting).0,
+            destination_token,
+            destination_authority,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::SetVoteDelegate { _seeds: seeds, delegate }.data(),
+    }
+}
+
+/// Builds a `change_destination` instruction signed by the owner of the current destination
+pub fn change_destination(
+    seeds: [u8; 31],
+    current_destination_token: Pubkey,
+    destination_authority: Pubkey,
+    new_destination_token: Pubkey,
+) -> Instruction {
+    let mut accounts = crate::accounts::ChangeDestination {
+        vesting: find_vesting_address(&seeds).0,
+        current_destination_token,
+        destination_authority,
+        new_destination_token,
+    }.to_account_metas(None);
+    // The handler verifies the signature, the accounts struct can't express it
+    accounts[2].is_signer = true;
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::ChangeDestination { seeds }.data(),
+    }
+}
+
+/// Returns the fee pool address and bump of `vesting`
+pub fn find_fee_pool_address(vesting: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[FEE_POOL_SEED.as_bytes(), vesting.as_ref()], &crate::ID)
+}
+
+/// Builds a `crank_unlock` instruction paying the released amount to the contract destination and tipping `cranker`
+/// from the contract fee pool
+pub fn crank_unlock(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, destination_token: Pubkey, cranker: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CrankUnlock {
+            vesting,
+            vesting_token,
+            mint,
+            destination_token,
+            fee_pool: find_fee_pool_address(&vesting).0,
+            cranker,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CrankUnlock { seeds }.data(),
+    }
+}
+
+/// Builds a `set_test_clock` instruction setting the test clock to `unix_timestamp`, signed by the admin of the
+/// program config. The test clock is created at the expense of `payer` on its first use
+#[cfg(feature = "test-clock")]
+pub fn set_test_clock(admin: Pubkey, payer: Pubkey, unix_timestamp: i64) -> Instruction {
+    let mut data = crate::test_clock::set_test_clock_discriminator().to_vec();
+    data.extend_from_slice(&unix_timestamp.to_le_bytes());
+    Instruction {
+        program_id: crate::ID,
+        accounts: vec![
+            AccountMeta::new(crate::test_clock::test_clock_address().0, false),
+            AccountMeta::new_readonly(Pubkey::find_program_address(&[crate::CONFIG_SEED.as_bytes()], &crate::ID).0, false),
+            AccountMeta::new_readonly(admin, true),
+            AccountMeta::new(payer, true),
+            AccountMeta::new_readonly(System::id(), false),
+        ],
+        data,
+    }
+}
+
+/// Builds a `create_distributor` instruction escrowing `total_amount` into `distributor_token`, owned by the
+/// distributor of `seeds`
+pub fn create_distributor(
+    seeds: [u8; 31],
+    root: [u8; 32],
+    total_amount: u64,
+    distributor_token: Pubkey,
+    mint: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    payer: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateDistributor {
+            distributor: find_distributor_address(&seeds).0,
+            distributor_token,
+            mint,
+            source_token,
+            source_authority,
+            payer,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateDistributor { seeds, root, total_amount }.data(),
+    }
+}
+
+/// Builds a `claim_from_distributor` instruction paying the claimant's leaf of `tranches` to `destination_token`
+pub fn claim_from_distributor(
+    seeds: [u8; 31],
+    distributor_token: Pubkey,
+    mint: Pubkey,
+    claimant: Pubkey,
+    destination_token: Pubkey,
+    tranches: Vec<MerkleTranche>,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let distributor = find_distributor_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ClaimFromDistributor {
+            distributor,
+            distributor_token,
+            claim_status: find_claim_status_address(&distributor, &claimant).0,
+            claimant,
+            destination_token,
+            mint,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::ClaimFromDistributor { tranches, proof }.data(),
+    }
+}
+
+/// Builds a `create_shared_vault` instruction escrowing the contracts of `authority` for `mint` in `vault_token`
+pub fn create_shared_vault(vault_token: Pubkey, mint: Pubkey, authority: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateSharedVault {
+            shared_vault: find_shared_vault_address(&authority, &mint).0,
+            vault_token,
+            mint,
+            authority,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateSharedVault {}.data(),
+    }
+}
+
+/// Builds a `create_in_shared_vault` instruction committing `schedules` out of the shared vault of `authority` for
+/// `mint`, held by `vault_token`
+pub fn create_in_shared_vault(
+    seeds: [u8; 31],
+    vault_token: Pubkey,
+    mint: Pubkey,
+    authority: Pubkey,
+    destination_token_address: Pubkey,
+    schedules: Vec<Schedule>,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateInSharedVault {
+            vesting: find_vesting_address(&seeds).0,
+            shared_vault: find_shared_vault_address(&authority, &mint).0,
+            vault_token,
+            authority,
+        }.to_account_metas(None),
+        data: crate::instruction::CreateInSharedVault {
+            _seeds: seeds,
+            destination_token_address,
+            schedules,
+            min_claim_amount: 0,
+        }.data(),
+    }
+}
+
+/// Builds an `unlock_from_shared_vault` instruction paying the released amount to the contract destination
+pub fn unlock_from_shared_vault(seeds: [u8; 31], vault_token: Pubkey, mint: Pubkey, authority: Pubkey, destination_token: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::UnlockFromSharedVault {
+            vesting: find_vesting_address(&seeds).0,
+            shared_vault: find_shared_vault_address(&authority, &mint).0,
+            vault_token,
+            mint,
+            destination_token,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::UnlockFromSharedVault { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `cancel_in_shared_vault` instruction releasing the commitment of a pending contract of the shared vault of
+/// `authority` for `mint`
+pub fn cancel_in_shared_vault(seeds: [u8; 31], vault_token: Pubkey, mint: Pubkey, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CancelInSharedVault {
+            vesting: find_vesting_address(&seeds).0,
+            shared_vault: find_shared_vault_address(&authority, &mint).0,
+            vault_token,
+            authority,
+        }.to_account_metas(None),
+        data: crate::instruction::CancelInSharedVault { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `withdraw_from_shared_vault` instruction moving `amount` of the free capacity of the shared vault of
+/// `authority` for `mint` to `refund_token`
+pub fn withdraw_from_shared_vault(vault_token: Pubkey, mint: Pubkey, authority: Pubkey, refund_token: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::WithdrawFromSharedVault {
+            shared_vault: find_shared_vault_address(&authority, &mint).0,
+            vault_token,
+            mint,
+            refund_token,
+            authority,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::WithdrawFromSharedVault { amount }.data(),
+    }
+}
+
+/// Builds a `create_schedule_template` instruction of the template `name` of `authority`
+pub fn create_schedule_template(authority: Pubkey, name: &str, tranches: Vec<TemplateTranche>, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateScheduleTemplate {
+            schedule_template: find_schedule_template_address(&authority, name).0,
+            authority,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateScheduleTemplate { name: name.to_string(), tranches }.data(),
+    }
+}
+
+/// Builds a `create_from_template` instruction of a contract of `total_amount` vesting along `schedule_template` from
+/// `start`, funded from `source_token`
+pub fn create_from_template(
+    seeds: [u8; 31],
+    vesting_token: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    schedule_template: Pubkey,
+    mint_address: Pubkey,
+    destination_token_address: Pubkey,
+    total_amount: u64,
+    start: u64,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateFromTemplate {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            source_token,
+            source_authority,
+            schedule_template,
+            mint: mint_address,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CreateFromTemplate {
+            _seeds: seeds,
+            mint_address,
+            destination_token_address,
+            total_amount,
+            start,
+        }.data(),
+    }
+}
+
+/// Builds a `clone_contract` instruction of a contract of `total_amount` on the terms of `source_vesting`, moved from
+/// `source_start` to `start` and funded from `source_token`
+pub fn clone_contract(
+    seeds: [u8; 31],
+    source_vesting: Pubkey,
+    vesting_token: Pubkey,
+    mint: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    destination_token_address: Pubkey,
+    total_amount: u64,
+    source_start: u64,
+    start: u64,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CloneContract {
+            vesting: find_vesting_address(&seeds).0,
+            source_vesting,
+            vesting_token,
+            mint,
+            source_token,
+            source_authority,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CloneContract {
+            _seeds: seeds,
+            destination_token_address,
+            total_amount,
+            source_start,
+            start,
+        }.data(),
+    }
+}
+
+/// Builds a `create_cohort` instruction of the cohort `name` of `admin` for `mint`
+pub fn create_cohort(admin: Pubkey, name: &str, mint: Pubkey, schedule_template: Option<Pubkey>, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateCohort {
+            cohort: find_cohort_address(&admin, name).0,
+            mint,
+            admin,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateCohort { name: name.to_string(), schedule_template }.data(),
+    }
+}
+
+/// Builds an `add_to_cohort` instruction adding the contract of `seeds` to `cohort`, signed by its admin
+pub fn add_to_cohort(seeds: [u8; 31], cohort: Pubkey, admin: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::AddToCohort {
+            vesting: find_vesting_address(&seeds).0,
+            cohort,
+            admin,
+        }.to_account_metas(None),
+        data: crate::instruction::AddToCohort { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `set_cohort_paused` instruction pausing or resuming the contracts of `seeds` in `cohort`
+pub fn set_cohort_paused(cohort: Pubkey, admin: Pubkey, seeds: &[[u8; 31]], is_paused: bool) -> Instruction {
+    let mut accounts = crate::accounts::CohortAction { cohort, admin }.to_account_metas(None);
+    accounts.extend(seeds.iter().map(|seeds| AccountMeta::new(find_vesting_address(seeds).0, false)));
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::SetCohortPaused { is_paused }.data(),
+    }
+}
+
+/// Builds a `revoke_cohort` instruction revoking the contracts of `cohort`, given as their seeds and escrows, into
+/// `refund_token`
+pub fn revoke_cohort(cohort: Pubkey, admin: Pubkey, mint: Pubkey, refund_token: Pubkey, contracts: &[([u8; 31], Pubkey)]) -> Instruction {
+    let mut accounts = crate::accounts::RevokeCohort {
+        cohort,
+        admin,
+        mint,
+        refund_token,
+        token_program: spl_token::ID,
+    }.to_account_metas(None);
+    for (seeds, vesting_token) in contracts {
+        accounts.push(AccountMeta::new(find_vesting_address(seeds).0, false));
+        accounts.push(AccountMeta::new(*vesting_token, false));
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::RevokeCohort { seeds: contracts.iter().map(|(seeds, _)| *seeds).collect() }.data(),
+    }
+}
+
+/// Builds a `create_airdrop` instruction escrowing `total_amount` in `airdrop_token`, the leaves of `root` vesting along
+/// `schedule_template` from `start`
+pub fn create_airdrop(
+    seeds: [u8; 31],
+    airdrop_token: Pubkey,
+    schedule_template: Pubkey,
+    mint: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    payer: Pubkey,
+    root: [u8; 32],
+    start: u64,
+    total_amount: u64,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateAirdrop {
+            airdrop: find_airdrop_address(&seeds).0,
+            airdrop_token,
+            schedule_template,
+            mint,
+            source_token,
+            source_authority,
+            payer,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateAirdrop { seeds, root, start, total_amount }.data(),
+    }
+}
+
+/// Builds a `claim_airdrop` instruction turning the leaf of `claimant` in the airdrop of `seeds` into a contract paying
+/// `destination_token`
+pub fn claim_airdrop(
+    seeds: [u8; 31],
+    airdrop_token: Pubkey,
+    schedule_template: Pubkey,
+    mint: Pubkey,
+    claimant: Pubkey,
+    destination_token: Pubkey,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Instruction {
+    let airdrop = find_airdrop_address(&seeds).0;
+    let vesting_seeds = airdrop_position_seeds(&airdrop, &claimant);
+    let vesting = find_vesting_address(&vesting_seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ClaimAirdrop {
+            airdrop,
+            airdrop_token,
+            schedule_template,
+            vesting,
+            vesting_token: anchor_spl::associated_token::get_associated_token_address(&vesting, &mint),
+            claimant,
+            destination_token,
+            mint,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: System::id(),
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::ClaimAirdrop { _vesting_seeds: vesting_seeds, amount, proof }.data(),
+    }
+}
+
+/// Builds a `fund_next_period` instruction appending a tranche of `amount` at `release_time` to the contract of `seeds`,
+/// paid from `source_token` by `payer`
+pub fn fund_next_period(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, source_token: Pubkey, payer: Pubkey, amount: u64, release_time: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::FundNextPeriod {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            source_token,
+            payer,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::FundNextPeriod { _seeds: seeds, amount, release_time }.data(),
+    }
+}
+
+/// Builds a `set_payroll_payer` instruction letting `payroll_payer` fund the pay periods of the contract of `seeds`
+pub fn set_payroll_payer(seeds: [u8; 31], creator: Pubkey, payroll_payer: Option<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreatorUpdate {
+            vesting: find_vesting_address(&seeds).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::SetPayrollPayer { _seeds: seeds, payroll_payer }.data(),
+    }
+}
+
+/// Builds a `terminate` instruction paying the vested amount to `destination_token` and refunding the rest of
+/// `vesting_token` to `refund_token`, signed by the revoker
+pub fn terminate(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, destination_token: Pubkey, refund_token: Pubkey, revoker: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Terminate {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            destination_token,
+            refund_token,
+            revoker,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::Terminate { seeds }.data(),
+    }
+}
+
+/// Builds a `set_probation` instruction, signed by the creator of the pending contract
+pub fn set_probation(seeds: [u8; 31], creator: Pubkey, probation_end: Option<i64>) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreatorUpdate {
+            vesting: find_vesting_address(&seeds).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::SetProbation { _seeds: seeds, probation_end }.data(),
+    }
+}
+
+/// Builds an `adjust_cliff` instruction, signed by the creator and the owner of `destination_token`. The creator pays
+/// or is refunded the change of the cliff amount through `creator_token`
+pub fn adjust_cliff(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, creator_token: Pubkey, destination_token: Pubkey, creator: Pubkey,
+                    destination_authority: Pubkey, amount: u64, release_time: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::AdjustCliff {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            creator_token,
+            destination_token,
+            creator,
+            destination_authority,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::AdjustCliff { seeds, amount, release_time }.data(),
+    }
+}
+
+/// Builds a `set_salary_price_feed` instruction, signed by the creator of the pending contract
+pub fn set_salary_price_feed(seeds: [u8; 31], creator: Pubkey, salary_price_feed: Option<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreatorUpdate {
+            vesting: find_vesting_address(&seeds).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::SetSalaryPriceFeed { _seeds: seeds, salary_price_feed }.data(),
+    }
+}
+
+/// Builds a `fund_salary_period` instruction converting `usd_amount` at `price_feed` and paying the tokens from
+/// `source_token`, owned by `payer`, the creator or the payroll payer of the contract
+pub fn fund_salary_period(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, source_token: Pubkey, price_feed: Pubkey, payer: Pubkey,
+                          usd_amount: u64, release_time: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::FundSalaryPeriod {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            source_token,
+            price_feed,
+            payer,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::FundSalaryPeriod { _seeds: seeds, usd_amount, release_time }.data(),
+    }
+}
+
+/// Builds an `open_funding_escrow` instruction depositing `amount` from `funder_token` into `escrow_token`, a token
+/// account of the funding escrow of `seeds`
+pub fn open_funding_escrow(seeds: [u8; 31], escrow_token: Pubkey, mint: Pubkey, funder_token: Pubkey, funder: Pubkey, issuer: Pubkey,
+                           amount: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::OpenFundingEscrow {
+            funding_escrow: find_funding_escrow_address(&seeds).0,
+            escrow_token,
+            mint,
+            funder_token,
+            funder,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::OpenFundingEscrow { seeds, issuer, amount }.data(),
+    }
+}
+
+/// Builds an `activate_funding_escrow` instruction funding the contract of `vesting_seeds` with the deposit of the
+/// funding escrow of `seeds`, signed by its issuer
+pub fn activate_funding_escrow(
+    seeds: [u8; 31],
+    escrow_token: Pubkey,
+    mint: Pubkey,
+    vesting_seeds: [u8; 31],
+    vesting_token: Pubkey,
+    issuer: Pubkey,
+    destination_token_address: Pubkey,
+    schedules: Vec<Schedule>,
+    revoker: Option<Pubkey>,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ActivateFundingEscrow {
+            funding_escrow: find_funding_escrow_address(&seeds).0,
+            escrow_token,
+            mint,
+            vesting: find_vesting_address(&vesting_seeds).0,
+            vesting_token,
+            issuer,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::ActivateFundingEscrow { _vesting_seeds: vesting_seeds, destination_token_address, schedules, revoker }.data(),
+    }
+}
+
+/// Builds an `abort_funding_escrow` instruction refunding the deposit of the funding escrow of `seeds` to
+/// `funder_token`, signed by `authority`, its funder or its issuer
+pub fn abort_funding_escrow(seeds: [u8; 31], escrow_token: Pubkey, mint: Pubkey, funder_token: Pubkey, funder: Pubkey, authority: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::AbortFundingEscrow {
+            funding_escrow: find_funding_escrow_address(&seeds).0,
+            escrow_token,
+            mint,
+            funder_token,
+            funder,
+            authority,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::AbortFundingEscrow {}.data(),
+    }
+}
+
+/// Builds a `create_for` instruction creating the contract of `vesting_seeds` for `buyer` from the proceeds of the sale
+/// of `sale_authority`, the instruction a sale program invokes at settlement. The contract pays the associated token
+/// account of the buyer
+pub fn create_for(
+    vesting_seeds: [u8; 31],
+    sale_authority: Pubkey,
+    schedule_template: Pubkey,
+    proceeds_token: Pubkey,
+    mint: Pubkey,
+    payer: Pubkey,
+    buyer: Pubkey,
+    amount: u64,
+    start: u64,
+) -> Instruction {
+    let vesting = find_vesting_address(&vesting_seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateFor {
+            sale_authority,
+            schedule_template,
+            proceeds_token,
+            vesting,
+            vesting_token: anchor_spl::associated_token::get_associated_token_address(&vesting, &mint),
+            destination_token: anchor_spl::associated_token::get_associated_token_address(&buyer, &mint),
+            mint,
+            payer,
+            token_program: spl_token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: System::id(),
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CreateFor { _vesting_seeds: vesting_seeds, buyer, amount, start }.data(),
+    }
+}
+
+/// Builds a `create_round` instruction creating the round `name` of `creator` for `mint`
+pub fn create_round(creator: Pubkey, name: &str, mint: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateRound {
+            round: find_round_address(&creator, name).0,
+            mint,
+            creator,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateRound { name: name.to_string() }.data(),
+    }
+}
+
+/// Builds a `tag_round` instruction tagging the pending contract of `seeds` with `round`, signed by its creator
+pub fn tag_round(seeds: [u8; 31], round: Pubkey, creator: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::TagRound {
+            vesting: find_vesting_address(&seeds).0,
+            round,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::TagRound { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `revoke` instruction refunding the locked amount of the contract of `seeds` to `refund_token`, signed by
+/// its revoker
+pub fn revoke(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, refund_token: Pubkey, revoker: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::Revoke {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            refund_token,
+            revoker,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::Revoke { seeds }.data(),
+    }
+}
+
+/// Builds a `batch_change_destination` instruction pointing the contracts of `contracts`, given as their seeds, current
+/// destination token and new destination token, to their new destinations, signed by the owner of the current ones
+pub fn batch_change_destination(destination_authority: Pubkey, contracts: &[([u8; 31], Pubkey, Pubkey)]) -> Instruction {
+    let mut accounts = crate::accounts::BatchChangeDestination { destination_authority }.to_account_metas(None);
+    // The handler verifies the signature, the accounts struct can't express it
+    accounts[0].is_signer = true;
+    for (seeds, current_destination_token, new_destination_token) in contracts {
+        accounts.push(AccountMeta::new(find_vesting_address(seeds).0, false));
+        accounts.push(AccountMeta::new_readonly(*current_destination_token, false));
+        accounts.push(AccountMeta::new_readonly(*new_destination_token, false));
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::BatchChangeDestination { seeds: contracts.iter().map(|(seeds, _, _)| *seeds).collect() }.data(),
+    }
+}
+
+/// Builds a `batch_revoke` instruction revoking the contracts of `revoker`, given as their seeds and escrows, into
+/// `refund_token`
+pub fn batch_revoke(revoker: Pubkey, mint: Pubkey, refund_token: Pubkey, contracts: &[([u8; 31], Pubkey)]) -> Instruction {
+    let mut accounts = crate::accounts::BatchRevoke {
+        revoker,
+        mint,
+        refund_token,
+        token_program: spl_token::ID,
+    }.to_account_metas(None);
+    for (seeds, vesting_token) in contracts {
+        accounts.push(AccountMeta::new(find_vesting_address(seeds).0, false));
+        accounts.push(AccountMeta::new(*vesting_token, false));
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::BatchRevoke { seeds: contracts.iter().map(|(seeds, _)| *seeds).collect() }.data(),
+    }
+}
+
+/// Builds a `create_budget` instruction creating the budget `name` of `org` for `mint`, escrowed in `budget_token`
+pub fn create_budget(org: Pubkey, name: &str, budget_token: Pubkey, mint: Pubkey, allocation_limit: u64, admins: Vec<Pubkey>, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateBudget {
+            budget: find_budget_address(&org, name).0,
+            budget_token,
+            mint,
+            org,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateBudget { name: name.to_string(), allocation_limit, admins }.data(),
+    }
+}
+
+/// Builds a `set_budget` instruction changing the allocation limit and the admins of `budget`, signed by its org
+pub fn set_budget(budget: Pubkey, org: Pubkey, allocation_limit: u64, admins: Vec<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetBudget { budget, org }.to_account_metas(None),
+        data: crate::instruction::SetBudget { allocation_limit, admins }.data(),
+    }
+}
+
+/// Builds a `create_from_budget` instruction creating the contract of `seeds` out of `budget`, signed by one of its
+/// admins
+pub fn create_from_budget(
+    seeds: [u8; 31],
+    vesting_token: Pubkey,
+    budget: Pubkey,
+    budget_token: Pubkey,
+    mint: Pubkey,
+    admin: Pubkey,
+    destination_token_address: Pubkey,
+    schedules: Vec<Schedule>,
+    revocable: bool,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateFromBudget {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            budget,
+            budget_token,
+            mint,
+            admin,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CreateFromBudget {
+            _seeds: seeds,
+            destination_token_address,
+            schedules,
+            min_claim_amount: 0,
+            revocable,
+        }.data(),
+    }
+}
+
+/// Builds a `withdraw_from_budget` instruction moving `amount` out of the token account of `budget` to
+/// `refund_token`, signed by its org
+pub fn withdraw_from_budget(budget: Pubkey, budget_token: Pubkey, mint: Pubkey, org: Pubkey, refund_token: Pubkey, amount: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::WithdrawFromBudget {
+            budget,
+            budget_token,
+            mint,
+            refund_token,
+            org,
+            token_program: spl_token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::WithdrawFromBudget { amount }.data(),
+    }
+}
+
+/// Builds a `create_rollup` instruction creating the rollup of the contracts of `org` for `beneficiary` in `mint`
+pub fn create_rollup(org: Pubkey, beneficiary: Pubkey, mint: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateRollup {
+            rollup: find_rollup_address(&org, &beneficiary).0,
+            mint,
+            org,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateRollup { beneficiary }.data(),
+    }
+}
+
+/// Builds an `add_to_rollup` instruction adding the pending contract of `seeds`, paying `destination_token` of
+/// `beneficiary`, to their rollup, signed by the org
+pub fn add_to_rollup(seeds: [u8; 31], org: Pubkey, beneficiary: Pubkey, destination_token: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::AddToRollup {
+            vesting: find_vesting_address(&seeds).0,
+            rollup: find_rollup_address(&org, &beneficiary).0,
+            destination_token,
+            org,
+        }.to_account_metas(None),
+        data: crate::instruction::AddToRollup { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `refresh_rollup` instruction bringing `rollup` to the current time from its contracts, given as their
+/// seeds
+pub fn refresh_rollup(rollup: Pubkey, contracts: &[[u8; 31]]) -> Instruction {
+    let mut accounts = crate::accounts::RefreshRollup { rollup }.to_account_metas(None);
+    accounts.extend(contracts.iter().map(|seeds| AccountMeta::new_readonly(find_vesting_address(seeds).0, false)));
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::RefreshRollup {}.data(),
+    }
+}
+
+/// Builds a `create_unlock_queue` instruction creating the unlock queue of `mint`
+pub fn create_unlock_queue(mint: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateUnlockQueue {
+            unlock_queue: find_unlock_queue_address(&mint).0,
+            mint,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateUnlockQueue {}.data(),
+    }
+}
+
+/// Builds a `register_unlock` instruction queueing the contract of `seeds` in the unlock queue of `mint`
+pub fn register_unlock(seeds: [u8; 31], mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::RegisterUnlock {
+            vesting: find_vesting_address(&seeds).0,
+            unlock_queue: find_unlock_queue_address(&mint).0,
+        }.to_account_metas(None),
+        data: crate::instruction::RegisterUnlock { seeds }.data(),
+    }
+}
+
+/// Builds a `process_queue` instruction unlocking the due contracts at the head of the unlock queue of `mint`, given
+/// in the queue order as their seeds, escrows and destinations
+pub fn process_queue(mint: Pubkey, contracts: &[([u8; 31], Pubkey, Pubkey)]) -> Instruction {
+    let mut accounts = crate::accounts::ProcessQueue {
+        unlock_queue: find_unlock_queue_address(&mint).0,
+        mint,
+        token_program: spl_token::ID,
+    }.to_account_metas(None);
+    for (seeds, vesting_token, destination_token) in contracts {
+        accounts.push(AccountMeta::new(find_vesting_address(seeds).0, false));
+        accounts.push(AccountMeta::new(*vesting_token, false));
+        accounts.push(AccountMeta::new(*destination_token, false));
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::ProcessQueue { n: contracts.len() as u8 }.data(),
+    }
+}
+
+/// Builds a `create_partner_config` instruction registering the partner config of `authority`
+pub fn create_partner_config(authority: Pubkey, fee_recipient: Pubkey, fee_bps: u16, branding: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreatePartnerConfig {
+            partner_config: find_partner_config_address(&authority).0,
+            authority,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreatePartnerConfig {
+            fee_recipient,
+            fee_bps,
+            branding,
+            default_min_claim_amount: 0,
+            default_unlock_policy: UnlockPolicy::Anyone,
+            default_revocable: false,
+        }.data(),
+    }
+}
+
+/// Builds a `set_partner_config` instruction replacing the fee and the default terms of the partner config of
+/// `authority`
+pub fn set_partner_config(authority: Pubkey, config: &crate::PartnerConfig) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetPartnerConfig {
+            partner_config: find_partner_config_address(&authority).0,
+            authority,
+        }.to_account_metas(None),
+        data: crate::instruction::SetPartnerConfig {
+            fee_recipient: config.fee_recipient,
+            fee_bps: config.fee_bps,
+            branding: config.branding,
+            default_min_claim_amount: config.default_min_claim_amount,
+            default_unlock_policy: config.default_unlock_policy.clone(),
+            default_revocable: config.default_revocable,
+        }.data(),
+    }
+}
+
+/// Builds a `create_with_partner` instruction creating the contract of `seeds` in `mint` under the partner config of
+/// `partner`, its fee paid to `fee_token`
+pub fn create_with_partner(
+    seeds: [u8; 31],
+    vesting_token: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    mint: Pubkey,
+    partner: Pubkey,
+    fee_token: Pubkey,
+    payer: Pubkey,
+    destination_token_address: Pubkey,
+    schedules: Vec<Schedule>,
+) -> Instruction {
+    let partner_config = find_partner_config_address(&partner).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateWithPartner {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            source_token,
+            partner_config,
+            fee_token,
+            partner_stats: find_partner_stats_address(&partner_config, &mint).0,
+            source_authority,
+            payer,
+            mint,
+            token_program: spl_token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateWithPartner { _seeds: seeds, destination_token_address, schedules }.data(),
+    }
+}
+
+/// Builds a `create_rent_sponsorship_pool` instruction creating the pool of `creator` with `lamports`
+pub fn create_rent_sponsorship_pool(creator: Pubkey, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateRentSponsorshipPool {
+            pool: find_rent_sponsorship_pool_address(&creator).0,
+            creator,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateRentSponsorshipPool { lamports }.data(),
+    }
+}
+
+/// Builds a `fund_rent_sponsorship_pool` instruction depositing `lamports` of `funder` into the pool of `creator`
+pub fn fund_rent_sponsorship_pool(creator: Pubkey, funder: Pubkey, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::FundRentSponsorshipPool {
+            pool: find_rent_sponsorship_pool_address(&creator).0,
+            funder,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::FundRentSponsorshipPool { lamports }.data(),
+    }
+}
+
+/// Builds a `withdraw_from_rent_sponsorship_pool` instruction returning `lamports` of the pool of `creator`
+pub fn withdraw_from_rent_sponsorship_pool(creator: Pubkey, lamports: u64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::WithdrawFromRentSponsorshipPool {
+            pool: find_rent_sponsorship_pool_address(&creator).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::WithdrawFromRentSponsorshipPool { lamports }.data(),
+    }
+}
+
+/// Builds a `sponsor_destination_rent` instruction funding the associated token account of `owner` in `mint`, the
+/// destination owner of the contract of `seeds` created by `creator`
+pub fn sponsor_destination_rent(seeds: [u8; 31], creator: Pubkey, owner: Pubkey, mint: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SponsorDestinationRent {
+            vesting: find_vesting_address(&seeds).0,
+            pool: find_rent_sponsorship_pool_address(&creator).0,
+            owner,
+            destination_token: anchor_spl::associated_token::get_associated_token_address(&owner, &mint),
+        }.to_account_metas(None),
+        data: crate::instruction::SponsorDestinationRent { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `sponsor_claim_status_rent` instruction funding the claim status of `claimant` in the distributor of
+/// `seeds` created by `creator`
+pub fn sponsor_claim_status_rent(seeds: [u8; 31], creator: Pubkey, claimant: Pubkey) -> Instruction {
+    let distributor = find_distributor_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SponsorClaimStatusRent {
+            distributor,
+            pool: find_rent_sponsorship_pool_address(&creator).0,
+            claimant,
+            claim_status: find_claim_status_address(&distributor, &claimant).0,
+        }.to_account_metas(None),
+        data: crate::instruction::SponsorClaimStatusRent {}.data(),
+    }
+}
+
+/// Builds a `create_grant_index` instruction creating the grant index of `creator` for `beneficiary`
+pub fn create_grant_index(creator: Pubkey, beneficiary: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateGrantIndex {
+            grant_index: find_grant_index_address(&creator, &beneficiary).0,
+            creator,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateGrantIndex { beneficiary }.data(),
+    }
+}
+
+/// Builds a `close_indexed_grant` instruction removing `grant_id` from the grant index of `creator` for `beneficiary`
+pub fn close_indexed_grant(creator: Pubkey, beneficiary: Pubkey, grant_id: u64) -> Instruction {
+    let seeds = grant_seeds(&creator, &beneficiary, grant_id);
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CloseIndexedGrant {
+            vesting: find_vesting_address(&seeds).0,
+            grant_index: find_grant_index_address(&creator, &beneficiary).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::CloseIndexedGrant { _seeds: seeds, grant_id }.data(),
+    }
+}
+
+/// Builds a `create_guarantee` instruction backing the contract of `seeds` with `collateral_amount` of
+/// `guarantor_token`, escrowed in `collateral_token` owned by the guarantee. Signed by the guarantor and the granter,
+/// the creator or revoker of the contract
+pub fn create_guarantee(
+    seeds: [u8; 31],
+    collateral_token: Pubkey,
+    mint: Pubkey,
+    guarantor_token: Pubkey,
+    guarantor: Pubkey,
+    granter: Pubkey,
+    payer: Pubkey,
+    promised_amount: u64,
+    deadline: i64,
+    collateral_amount: u64,
+) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateGuarantee {
+            vesting,
+            guarantee: find_guarantee_address(&vesting).0,
+            collateral_token,
+            mint,
+            guarantor_token,
+            guarantor,
+            granter,
+            payer,
+            token_program: anchor_spl::token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateGuarantee { _seeds: seeds, promised_amount, deadline, collateral_amount }.data(),
+    }
+}
+
+/// Builds a `top_up` instruction adding `amount` of `source_token` to the tranche `schedule_index` of a guaranteed
+/// contract, signed by the granter
+pub fn top_up(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, source_token: Pubkey, granter: Pubkey, schedule_index: u32,
+              amount: u64) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::TopUp {
+            vesting,
+            guarantee: find_guarantee_address(&vesting).0,
+            vesting_token,
+            mint,
+            source_token,
+            granter,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::TopUp { _seeds: seeds, schedule_index, amount }.data(),
+    }
+}
+
+/// Builds a `settle_guarantee` instruction paying the shortfall of the contract of `seeds` to its destination and the
+/// rest of the collateral back to `guarantor_token`
+pub fn settle_guarantee(seeds: [u8; 31], collateral_token: Pubkey, mint: Pubkey, destination_token: Pubkey,
+                        guarantor_token: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SettleGuarantee {
+            vesting,
+            guarantee: find_guarantee_address(&vesting).0,
+            collateral_token,
+            mint,
+            destination_token,
+            guarantor_token,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::SettleGuarantee { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `create_with_yield` instruction escrowing the total of `schedules` from `source_token` and depositing it
+/// into `vault` of `vault_program`, whose shares are held in `share_token` owned by the vesting account
+pub fn create_with_yield(
+    seeds: [u8; 31],
+    vesting_token: Pubkey,
+    source_token: Pubkey,
+    source_authority: Pubkey,
+    mint: Pubkey,
+    destination_token: Pubkey,
+    schedules: Vec<Schedule>,
+    yield_recipient: Pubkey,
+    vault_program: Pubkey,
+    vault: Pubkey,
+    vault_reserve: Pubkey,
+    share_mint: Pubkey,
+    share_token: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateWithYield {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            source_token,
+            source_authority,
+            vault_program,
+            vault,
+            vault_reserve,
+            share_mint,
+            share_token,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CreateWithYield {
+            seeds,
+            mint_address: mint,
+            destination_token_address: destination_token,
+            schedules,
+            min_claim_amount: 0,
+            yield_recipient,
+        }.data(),
+    }
+}
+
+/// Builds an `unlock_from_yield` instruction withdrawing the released amount of the contract of `seeds` from its
+/// vault to `destination_token`. The final claim redeems the remaining shares to `yield_recipient`
+pub fn unlock_from_yield(
+    seeds: [u8; 31],
+    destination_token: Pubkey,
+    mint: Pubkey,
+    yield_recipient: Pubkey,
+    vault_program: Pubkey,
+    vault: Pubkey,
+    vault_reserve: Pubkey,
+    share_mint: Pubkey,
+    share_token: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::UnlockFromYield {
+            vesting: find_vesting_address(&seeds).0,
+            destination_token,
+            mint,
+            yield_recipient,
+            vault_program,
+            vault,
+            vault_reserve,
+            share_mint,
+            share_token,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::UnlockFromYield { seeds }.data(),
+    }
+}
+
+/// Builds a `claim_and_stake` instruction depositing the claim of the contract of `seeds` into `stake_pool` of
+/// `stake_program`, the pool tokens going to `pool_token`. Signed by the owner of the destination
+pub fn claim_and_stake(
+    seeds: [u8; 31],
+    vesting_token: Pubkey,
+    destination_token: Pubkey,
+    destination_authority: Pubkey,
+    stake_program: Pubkey,
+    stake_pool: Pubkey,
+    stake_reserve: Pubkey,
+    pool_mint: Pubkey,
+    pool_token: Pubkey,
+) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ClaimAndStake {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            destination_token,
+            destination_authority,
+            stake_program,
+            stake_pool,
+            stake_reserve,
+            pool_mint,
+            pool_token,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::ClaimAndStake { seeds }.data(),
+    }
+}
+
+/// Returns the registrar address and bump of `realm` for `governing_token_mint`
+pub fn find_registrar_address(realm: &Pubkey, governing_token_mint: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[realm.as_ref(), REGISTRAR_SEED.as_bytes(), governing_token_mint.as_ref()], &crate::ID)
+}
+
+/// Returns the voter weight record address and bump of `governing_token_owner` in `registrar`
+pub fn find_voter_weight_record_address(registrar: &Pubkey, governing_token_owner: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[registrar.as_ref(), VOTER_WEIGHT_RECORD_SEED.as_bytes(), governing_token_owner.as_ref()], &crate::ID)
+}
+
+/// Returns the max voter weight record address and bump of `registrar`
+pub fn find_max_voter_weight_record_address(registrar: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[registrar.as_ref(), MAX_VOTER_WEIGHT_RECORD_SEED.as_bytes()], &crate::ID)
+}
+
+/// Builds a `create_registrar` instruction registering `realm`, owned by `governance_program`, and its governing token
+/// mint
+pub fn create_registrar(realm: Pubkey, governance_program: Pubkey, governing_token_mint: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateRegistrar {
+            registrar: find_registrar_address(&realm, &governing_token_mint).0,
+            realm,
+            governance_program,
+            governing_token_mint,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateRegistrar {}.data(),
+    }
+}
+
+/// Builds a `create_voter_weight_record` instruction for `governing_token_owner` in `registrar`
+pub fn create_voter_weight_record(registrar: Pubkey, governing_token_owner: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateVoterWeightRecord {
+            registrar,
+            voter_weight_record: find_voter_weight_record_address(&registrar, &governing_token_owner).0,
+            governing_token_owner,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateVoterWeightRecord {}.data(),
+    }
+}
+
+/// Builds an `update_voter_weight_record` instruction counting the `(vesting, destination token)` pairs of
+/// `contracts` for `governing_token_owner`
+pub fn update_voter_weight_record(registrar: Pubkey, governing_token_owner: Pubkey, contracts: &[(Pubkey, Pubkey)]) -> Instruction {
+    let mut accounts = crate::accounts::UpdateVoterWeightRecord {
+        registrar,
+        voter_weight_record: find_voter_weight_record_address(&registrar, &governing_token_owner).0,
+    }.to_account_metas(None);
+    for (vesting, destination_token) in contracts {
+        accounts.push(AccountMeta::new(*vesting, false));
+        accounts.push(AccountMeta::new_readonly(*destination_token, false));
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::UpdateVoterWeightRecord {}.data(),
+    }
+}
+
+/// Builds a `create_max_voter_weight_record` instruction for `registrar`
+pub fn create_max_voter_weight_record(registrar: Pubkey, payer: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateMaxVoterWeightRecord {
+            registrar,
+            max_voter_weight_record: find_max_voter_weight_record_address(&registrar).0,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::CreateMaxVoterWeightRecord {}.data(),
+    }
+}
+
+/// Builds an `update_max_voter_weight_record` instruction summing every contract of the registrar mint in `vestings`,
+/// signed by the authority of `realm`
+pub fn update_max_voter_weight_record(registrar: Pubkey, realm: Pubkey, realm_authority: Pubkey, vestings: &[Pubkey]) -> Instruction {
+    let mut accounts = crate::accounts::UpdateMaxVoterWeightRecord {
+        registrar,
+        max_voter_weight_record: find_max_voter_weight_record_address(&registrar).0,
+        realm,
+        realm_authority,
+    }.to_account_metas(None);
+    accounts.extend(vestings.iter().map(|vesting| AccountMeta::new_readonly(*vesting, false)));
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::UpdateMaxVoterWeightRecord {}.data(),
+    }
+}
+
+/// Returns the receipt mint address and bump of `vesting`
+pub fn find_receipt_mint_address(vesting: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[RECEIPT_SEED.as_bytes(), vesting.as_ref()], &crate::ID)
+}
+
+/// Returns the address and bump of the receipt of the `claim_count`-th claim of `vesting`
+pub fn find_claim_receipt_address(vesting: &Pubkey, claim_count: u64) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_RECEIPT_SEED.as_bytes(), vesting.as_ref(), &claim_count.to_le_bytes()], &crate::ID)
+}
+
+/// Builds a `create_receipt` instruction minting the receipt of the contract of `seeds` to `beneficiary`, the owner
+/// of its destination, signed by the creator
+pub fn create_receipt(seeds: [u8; 31], destination_token: Pubkey, beneficiary: Pubkey, creator: Pubkey, payer: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    let receipt_mint = find_receipt_mint_address(&vesting).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreateReceipt {
+            vesting,
+            receipt_mint,
+            receipt_token: anchor_spl::associated_token::get_associated_token_address(&beneficiary, &receipt_mint),
+            destination_token,
+            beneficiary,
+            creator,
+            payer,
+            token_program: anchor_spl::token::ID,
+            associated_token_program: anchor_spl::associated_token::ID,
+            system_program: System::id(),
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::CreateReceipt { seeds }.data(),
+    }
+}
+
+/// Builds an `unlock_to_holder` instruction paying the associated token account of the owner of `receipt_token`
+pub fn unlock_to_holder(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, receipt_token: Pubkey, holder: Pubkey) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::UnlockToHolder {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            mint,
+            receipt_token,
+            holder_token: anchor_spl::associated_token::get_associated_token_address(&holder, &mint),
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::UnlockToHolder { seeds }.data(),
+    }
+}
+
+/// Builds a `mint_compressed_receipt` instruction minting a receipt of the contract of `seeds` into `merkle_tree`,
+/// signed by the creator as the tree delegate
+pub fn mint_compressed_receipt(seeds: [u8; 31], destination_token: Pubkey, beneficiary: Pubkey, merkle_tree: Pubkey,
+                               creator: Pubkey, payer: Pubkey, name: String, symbol: String, uri: String) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::MintCompressedReceipt {
+            vesting: find_vesting_address(&seeds).0,
+            destination_token,
+            beneficiary,
+            tree_authority: Pubkey::find_program_address(&[merkle_tree.as_ref()], &crate::bubblegum::ID).0,
+            merkle_tree,
+            creator,
+            payer,
+            bubblegum_program: crate::bubblegum::ID,
+            log_wrapper: crate::bubblegum::noop_program::ID,
+            compression_program: crate::bubblegum::compression_program::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::MintCompressedReceipt { seeds, name, symbol, uri }.data(),
+    }
+}
+
+/// Builds a `set_bridge_target` instruction recording the foreign-chain address the contract of `seeds` is bridged
+/// to, signed by the owner of its destination
+pub fn set_bridge_target(seeds: [u8; 31], destination_token: Pubkey, beneficiary: Pubkey, bridge_target: Option<BridgeTarget>)
+                         -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetBridgeTarget {
+            vesting: find_vesting_address(&seeds).0,
+            destination_token,
+            beneficiary,
+        }.to_account_metas(None),
+        data: crate::instruction::SetBridgeTarget { _seeds: seeds, bridge_target }.data(),
+    }
+}
+
+/// Builds a `claim_and_bridge` instruction unlocking the contract of `seeds` into the Wormhole token bridge, signed by
+/// the owner of its destination and by `message`, a new keypair. `payer` pays the message fee
+pub fn claim_and_bridge(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, destination_token: Pubkey, beneficiary: Pubkey,
+                        payer: Pubkey, message: Pubkey, nonce: u32) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ClaimAndBridge {
+            vesting: find_vesting_address(&seeds).0,
+            vesting_token,
+            destination_token,
+            beneficiary,
+            payer,
+            mint,
+            config: crate::wormhole::config(),
+            custody: crate::wormhole::custody(&mint),
+            authority_signer: crate::wormhole::authority_signer(),
+            custody_signer: crate::wormhole::custody_signer(),
+            bridge: crate::wormhole::bridge(),
+            message,
+            emitter: crate::wormhole::emitter(),
+            sequence: crate::wormhole::sequence(),
+            fee_collector: crate::wormhole::fee_collector(),
+            clock: anchor_lang::solana_program::sysvar::clock::ID,
+            rent: anchor_lang::solana_program::sysvar::rent::ID,
+            system_program: System::id(),
+            token_program: anchor_spl::token::ID,
+            core_bridge_program: crate::wormhole::core_bridge_program::ID,
+            token_bridge_program: crate::wormhole::token_bridge_program::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::ClaimAndBridge { seeds, nonce }.data(),
+    }
+}
+
+/// Builds a `set_claim_delegate` instruction letting `delegate` claim the contract of `seeds` until `expires_at`,
+/// signed by the owner of its destination
+pub fn set_claim_delegate(seeds: [u8; 31], destination_token: Pubkey, destination_authority: Pubkey, delegate: Pubkey,
+                          expires_at: i64) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetClaimDelegate {
+            vesting: find_vesting_address(&seeds).0,
+            destination_token,
+            destination_authority,
+        }.to_account_metas(None),
+        data: crate::instruction::SetClaimDelegate { _seeds: seeds, delegate, expires_at }.data(),
+    }
+}
+
+/// Returns the listing address and bump of `vesting`
+pub fn find_listing_address(vesting: &Pubkey) -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[LISTING_SEED.as_bytes(), vesting.as_ref()], &crate::ID)
+}
+
+/// Builds a `list_position` instruction offering the contract of `seeds` for `price`, paid to `seller_payment_token`,
+/// signed by the owner of its destination
+pub fn list_position(seeds: [u8; 31], destination_token: Pubkey, seller_payment_token: Pubkey, seller: Pubkey, payer: Pubkey,
+                     price: u64) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ListPosition {
+            vesting,
+            listing: find_listing_address(&vesting).0,
+            destination_token,
+            seller_payment_token,
+            seller,
+            payer,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::ListPosition { _seeds: seeds, price }.data(),
+    }
+}
+
+/// Builds a `cancel_listing` instruction signed by the seller
+pub fn cancel_listing(seeds: [u8; 31], seller: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CancelListing {
+            vesting,
+            listing: find_listing_address(&vesting).0,
+            seller,
+        }.to_account_metas(None),
+        data: crate::instruction::CancelListing { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds a `set_transfer_approval_required` instruction signed by the creator
+pub fn set_transfer_approval_required(seeds: [u8; 31], creator: Pubkey, required: bool) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::CreatorUpdate {
+            vesting: find_vesting_address(&seeds).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::SetTransferApprovalRequired { _seeds: seeds, required }.data(),
+    }
+}
+
+/// Builds an `approve_listing` instruction signed by the creator
+pub fn approve_listing(seeds: [u8; 31], creator: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::ApproveListing {
+            vesting,
+            listing: find_listing_address(&vesting).0,
+            creator,
+        }.to_account_metas(None),
+        data: crate::instruction::ApproveListing { _seeds: seeds }.data(),
+    }
+}
+
+/// Builds an `accept_listing` instruction buying the contract of `seeds` at `expected_price` of `payment_mint`, paid
+/// from `buyer_payment_token` and redirecting the contract to `buyer_destination_token`, signed by the buyer
+pub fn accept_listing(
+    seeds: [u8; 31],
+    current_destination_token: Pubkey,
+    seller: Pubkey,
+    seller_payment_token: Pubkey,
+    buyer_payment_token: Pubkey,
+    payment_mint: Pubkey,
+    buyer_destination_token: Pubkey,
+    buyer: Pubkey,
+    expected_price: u64,
+) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::AcceptListing {
+            vesting,
+            listing: find_listing_address(&vesting).0,
+            current_destination_token,
+            seller,
+            seller_payment_token,
+            buyer_payment_token,
+            payment_mint,
+            buyer_destination_token,
+            buyer,
+            token_program: anchor_spl::token::ID,
+        }.to_account_metas(None),
+        data: crate::instruction::AcceptListing { _seeds: seeds, expected_price }.data(),
+    }
+}
+
+/// Builds a `batch_unlock` instruction unlocking the `(seeds, vesting token)` contracts paying `destination_token`
+pub fn batch_unlock(destination_token: Pubkey, mint: Pubkey, contracts: &[([u8; 31], Pubkey)]) -> Instruction {
+    let mut accounts = crate::accounts::BatchUnlock {
+        destination_token,
+        mint,
+        token_program: anchor_spl::token::ID,
+    }.to_account_metas(None);
+    for (seeds, vesting_token) in contracts {
+        accounts.push(AccountMeta::new(find_vesting_address(seeds).0, false));
+        accounts.push(AccountMeta::new(*vesting_token, false));
+    }
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::BatchUnlock { seeds: contracts.iter().map(|(seeds, _)| *seeds).collect() }.data(),
+    }
+}
+
+/// Builds an `unlock_with_receipt` instruction recording the `claim_count`-th claim of the contract of `seeds`
+pub fn unlock_with_receipt(seeds: [u8; 31], vesting_token: Pubkey, mint: Pubkey, destination_token: Pubkey, claim_count: u64,
+                           payer: Pubkey) -> Instruction {
+    let vesting = find_vesting_address(&seeds).0;
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::UnlockWithReceipt {
+            vesting,
+            vesting_token,
+            mint,
+            destination_token,
+            claim_receipt: find_claim_receipt_address(&vesting, claim_count).0,
+            payer,
+            token_program: anchor_spl::token::ID,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::UnlockWithReceipt { seeds }.data(),
+    }
+}
+
+/// Returns the claim hook allowlist address and bump
+pub fn find_claim_hook_allowlist_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[CLAIM_HOOK_ALLOWLIST_SEED.as_bytes()], &crate::ID)
+}
+
+/// Builds a `set_claim_hook_allowlist` instruction replacing the programs contracts can register as claim hook,
+/// signed by the admin of the program config, who pays the allowlist on its first use
+pub fn set_claim_hook_allowlist(admin: Pubkey, programs: Vec<Pubkey>) -> Instruction {
+    Instruction {
+        program_id: crate::ID,
+        accounts: crate::accounts::SetClaimHookAllowlist {
+            config: Pubkey::find_program_address(&[crate::CONFIG_SEED.as_bytes()], &crate::ID).0,
+            claim_hook_allowlist: find_claim_hook_allowlist_address().0,
+            admin,
+            system_program: System::id(),
+        }.to_account_metas(None),
+        data: crate::instruction::SetClaimHookAllowlist { programs }.data(),
+    }
+}
+
+/// Builds a `set_claim_hook` instruction registering `claim_hook` on the pending contract of `seeds`, signed by its
+/// creator. The claim hook allowlist is passed in the remaining accounts
+pub fn set_claim_hook(seeds: [u8; 31], creator: Pubkey, claim_hook: Option<ClaimHook>) -> Instruction {
+    let mut accounts = crate::accounts::CreatorUpdate {
+        vesting: find_vesting_address(&seeds).0,
+        creator,
+    }.to_account_metas(None);
+    accounts.push(AccountMeta::new_readonly(find_claim_hook_allowlist_address().0, false));
+    Instruction {
+        program_id: crate::ID,
+        accounts,
+        data: crate::instruction::SetClaimHook { _seeds: seeds, claim_hook }.data(),
+    }
+}
+
+/// Returns the remaining accounts the claims of a contract registering `claim_hook` pass: the claim hook allowlist,
+/// the hook program, its authority and the registered accounts, writable
+pub fn claim_hook_accounts(claim_hook: &ClaimHook) -> Vec<AccountMeta> {
+    let mut accounts = vec![
+        AccountMeta::new_readonly(find_claim_hook_allowlist_address().0, false),
+        AccountMeta::new_readonly(claim_hook.program, false),
+        AccountMeta::new_readonly(crate::claim_hook::claim_hook_authority().0, false),
+    ];
+    accounts.extend(claim_hook.accounts.iter().map(|account| AccountMeta::new(*account, false)));
+    accounts
+}
@@ -0,0 +1,37 @@
+//! Reader of the vesting accounts written by the first release of the program, which `migrate_vesting_layout`
+//! converts to the current layout.
+//!
+//! The first release stored the destination token address, the mint address, `is_initialized` and the schedule as
+//! `release_time` and `amount` pairs, after the same `Vesting` discriminator. The fields added since sit between
+//! `is_initialized` and the schedule, so such an account doesn't deserialize as a `Vesting` any more.
+
+use anchor_lang::prelude::*;
+use anchor_lang::Discriminator;
+
+use crate::Vesting;
+
+#[derive(AnchorDeserialize)]
+pub struct LegacySchedule {
+    pub release_time: u64,
+    pub amount: u64,
+}
+
+#[derive(AnchorDeserialize)]
+pub struct LegacyVesting {
+    pub destination_address: Pubkey,
+    pub mint_address: Pubkey,
+    pub is_initialized: bool,
+    pub schedule: Vec<LegacySchedule>,
+}
+
+impl LegacyVesting {
+    /// Reads the account data of a vesting account in the layout of the first release, none when it holds
+    /// something else
+    pub fn unpack(data: &[u8]) -> Option<Self> {
+        if data.get(..8)? != Vesting::discriminator() {
+            return None;
+        }
+        LegacyVesting::deserialize(&mut &data[8..]).ok()
+    }
+}
+
@@ -1,8 +1,53 @@
+// Anchor instruction handlers and their generated CPI wrappers take one argument per instruction argument
+#![allow(clippy::too_many_arguments)]
+
 use std::borrow::BorrowMut;
+use std::collections::BTreeMap;
+use std::convert::TryInto;
 use anchor_lang::prelude::*;
-use anchor_spl::token::{TokenAccount, Transfer, Token, transfer};
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::entrypoint::MAX_PERMITTED_DATA_INCREASE;
+use anchor_spl::associated_token::{AssociatedToken, get_associated_token_address};
+use anchor_spl::token::{TokenAccount, Token, Mint, MintTo, mint_to, SetAuthority, set_authority, Approve, approve};
+use token::{TransferChecked, transfer_checked};
+use math::{is_released, released_amount, locked_amount, next_locked_tranche};
+use verbose::verbose_log;
+use governance::{Registrar, VoterWeightRecord, MaxVoterWeightRecord, REGISTRAR_ACCOUNT_SIZE, VOTER_WEIGHT_RECORD_ACCOUNT_SIZE, MAX_VOTER_WEIGHT_RECORD_ACCOUNT_SIZE};
+
+pub mod bonfida;
+pub mod bubblegum;
+pub mod claim_hook;
+pub mod ed25519;
+pub mod event_cpi;
+pub mod governance;
+#[cfg(feature = "cpi")]
+pub mod integration;
+pub mod legacy;
+pub mod math;
+pub mod memo;
+pub mod merkle;
+#[cfg(feature = "test-fixture")]
+pub mod program_test;
+pub mod pyth;
+pub mod switchboard;
+pub mod test_clock;
+pub mod token;
+pub mod token2022;
+pub mod vault;
+mod verbose;
+pub mod wormhole;
 
-declare_id!("Fg6PaFpoGXkYsidMpWTK6W2BeZ7FEfcYkg476zPFsLnS");
+// The declare_id! of TOKEN_VESTING_PROGRAM_ID at build time, see build.rs
+include!(concat!(env!("OUT_DIR"), "/program_id.rs"));
+
+#[cfg(not(feature = "no-entrypoint"))]
+solana_security_txt::security_txt! {
+    name: "Token Vesting",
+    project_url: "https://github.com/TengizSharafievWeb3/token-vesting",
+    contacts: "link:https://github.com/TengizSharafievWeb3/token-vesting/security/advisories/new",
+    policy: "https://github.com/TengizSharafievWeb3/token-vesting/blob/master/SECURITY.md",
+    source_code: "https://github.com/TengizSharafievWeb3/token-vesting"
+}
 
 #[program]
 pub mod token_vesting {
@@ -13,47 +58,374 @@ pub mod token_vesting {
     /// # Arguments
     /// * `seeds` - The seed used to derive the vesting accounts address
     /// * `number_of_schedules` - The number of release schedules for this contract to hold
+    #[allow(unused_variables)]
     pub fn init(ctx: Context<Initialize>, seeds: [u8; 31], number_of_schedules: u32) -> Result<()> {
         let vesting = &mut ctx.accounts.vesting;
         vesting.is_initialized = false;
-        vesting.schedule = vec![Schedule{release_time: 0, amount: 0}; number_of_schedules as usize];
+        vesting.schedule = vec![Schedule{release_time: 0, amount: 0, condition: None}; number_of_schedules as usize];
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingInitialized {
+            vesting: ctx.accounts.vesting.key(),
+            number_of_schedules,
+        })?;
         Ok(())
     }
 
-    /// Creates a new vesting schedule contract
-    pub fn create(ctx: Context<Create>,
+    /// Creates a new vesting schedule contract, pending until the destination owner accepts it. A grant index of the
+    /// creator in the remaining accounts records the contract when `seeds` are its next grant seeds, see `grant_seeds`
+    ///
+    /// # Arguments
+    /// * `min_claim_amount` - The smallest amount `unlock` will release, except for the final claim. 0 disables the threshold
+    /// * `revoker` - The authority allowed to revoke the unvested part of the contract, none for an irrevocable contract
+    /// * `soulbound_receipt` - Whether to mint a non-transferable Token-2022 receipt to the destination owner.
+    ///   The remaining accounts are then the rent payer, the destination token account, its owner, the receipt mint PDA `["soulbound", vesting]`,
+    ///   the owner's Token-2022 associated token account, the system, Token-2022 and associated token programs
+    /// * `unlock_policy` - Who may unlock the contract: anyone, the destination owner and its claim delegate, or the keys of an allowlist
+    pub fn create<'info>(ctx: Context<'_, '_, '_, 'info, Create<'info>>,
                   seeds: [u8; 31],
                   mint_address: Pubkey,
                   destination_token_address: Pubkey,
-                  schedules: Vec<Schedule>) -> Result<()> {
+                  schedules: Vec<Schedule>,
+                  min_claim_amount: u64,
+                  revoker: Option<Pubkey>,
+                  soulbound_receipt: bool,
+                  unlock_policy: UnlockPolicy) -> Result<()> {
+
+        require!(is_valid_unlock_policy(&unlock_policy), VestingError::InvalidUnlockPolicy);
+        let total_amount = total_amount(&schedules)?;
+        verbose_log!("create.funds", total_amount = total_amount, source_balance = ctx.accounts.source_token.amount,
+                     schedules = schedules.len());
+        require!(expect_more_than("source token balance", total_amount, ctx.accounts.source_token.amount), VestingError::InsufficientFunds);
+        #[cfg(feature = "verbose-logs")]
+        for (index, schedule) in schedules.iter().enumerate() {
+            verbose_log!("create.tranche", index = index, release_time = schedule.release_time, amount = schedule.amount,
+                         conditional = schedule.condition.is_some());
+        }
+        verbose_log!("create.terms", min_claim_amount = min_claim_amount, revocable = revoker.is_some(),
+                     soulbound_receipt = soulbound_receipt);
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = mint_address;
+        vesting.creator = ctx.accounts.source_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = min_claim_amount;
+        vesting.revoker = revoker;
+        vesting.is_accepted = false;
+        vesting.unlock_policy = unlock_policy;
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.source_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: mint_address,
+            creator: ctx.accounts.source_authority.key(),
+            destination: destination_token_address,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+        enqueue_in_remaining(ctx.remaining_accounts, ctx.program_id, seeds.as_ref(), &ctx.accounts.vesting,
+                             test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+        index_grant_in_remaining(ctx.remaining_accounts, ctx.program_id, &seeds, &ctx.accounts.vesting,
+                                 test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+
+        if !soulbound_receipt {
+            return Ok(());
+        }
+        let (payer, destination_token, owner, receipt_mint, receipt_token, system_program, token_program, associated_token_program) =
+            match ctx.remaining_accounts {
+                [a, b, c, d, e, f, g, h, ..] => (a, b, c, d, e, f, g, h),
+                _ => return err!(VestingError::InvalidRemainingAccounts),
+            };
+        require!(expect_key("destination", &destination_token_address, &destination_token.key()), VestingError::InvalidDestination);
+        let destination_token = Account::<TokenAccount>::try_from(destination_token)?;
+        require!(expect_key("destination owner", &destination_token.owner, &owner.key()), VestingError::InvalidDestinationAuthority);
+        require!(system_program.key() == System::id(), VestingError::InvalidSoulboundReceipt);
+        require!(token_program.key() == token2022::ID, VestingError::InvalidSoulboundReceipt);
+        require!(associated_token_program.key() == AssociatedToken::id(), VestingError::InvalidSoulboundReceipt);
+
+        let vesting_key = ctx.accounts.vesting.key();
+        let (receipt_mint_address, receipt_bump) =
+            Pubkey::find_program_address(&[SOULBOUND_SEED.as_bytes(), vesting_key.as_ref()], ctx.program_id);
+        require!(receipt_mint.key() == receipt_mint_address, VestingError::InvalidSoulboundReceipt);
+        require!(receipt_token.key() == token2022::associated_token_address(owner.key, &receipt_mint_address),
+            VestingError::InvalidHolderToken);
+        verbose_log!("create.receipt", receipt_mint = receipt_mint_address, owner = owner.key);
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let vesting_seeds = &[seeds.as_ref(), &[bump]];
+        let mint_seeds = &[SOULBOUND_SEED.as_bytes(), vesting_key.as_ref(), &[receipt_bump]];
+        token2022::mint_soulbound_receipt(
+            &token2022::SoulboundReceiptAccounts {
+                payer: payer.clone(),
+                mint: receipt_mint.clone(),
+                receipt_token: receipt_token.clone(),
+                owner: owner.clone(),
+                authority: ctx.accounts.vesting.to_account_info(),
+                system_program: system_program.clone(),
+                token_program: token_program.clone(),
+                associated_token_program: associated_token_program.clone(),
+            },
+            &[&mint_seeds[..]],
+            &[&vesting_seeds[..]])?;
+
+        event_cpi::emit(ctx.remaining_accounts, &ReceiptMinted {
+            vesting: vesting_key,
+            receipt_mint: receipt_mint_address,
+            owner: owner.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates a new vesting schedule contract whose escrow is deposited into a yield vault until released
+    ///
+    /// # Arguments
+    /// * `yield_recipient` - The token account credited with the vault yield once the contract is fully released
+    pub fn create_with_yield(ctx: Context<CreateWithYield>,
+                             seeds: [u8; 31],
+                             mint_address: Pubkey,
+                             destination_token_address: Pubkey,
+                             schedules: Vec<Schedule>,
+                             min_claim_amount: u64,
+                             yield_recipient: Pubkey) -> Result<()> {
 
         let total_amount = total_amount(&schedules)?;
-        require!(ctx.accounts.source_token.amount > total_amount, VestingError::InsufficientFunds);
+        require!(expect_more_than("source token balance", total_amount, ctx.accounts.source_token.amount), VestingError::InsufficientFunds);
 
         let vesting = &mut ctx.accounts.vesting;
         vesting.destination_address = destination_token_address;
         vesting.mint_address = mint_address;
+        vesting.creator = ctx.accounts.source_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
         vesting.is_initialized = true;
+        vesting.min_claim_amount = min_claim_amount;
+        vesting.is_accepted = false;
+        vesting.yield_config = Some(YieldConfig {
+            vault_program: ctx.accounts.vault_program.key(),
+            vault: ctx.accounts.vault.key(),
+            share_token_address: ctx.accounts.share_token.key(),
+            yield_recipient,
+        });
         vesting.schedule = schedules;
+        refresh_next_action(vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
 
         let transfer_ctx = CpiContext::new(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.vesting_token.to_account_info(),
                 authority: ctx.accounts.source_authority.to_account_info(),
             });
-        transfer(transfer_ctx, total_amount)
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: mint_address,
+            creator: ctx.accounts.source_authority.key(),
+            destination: destination_token_address,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let vault_accounts = vault::VaultAccounts {
+            vault_program: ctx.accounts.vault_program.to_account_info(),
+            vault: ctx.accounts.vault.to_account_info(),
+            vault_reserve: ctx.accounts.vault_reserve.to_account_info(),
+            share_mint: ctx.accounts.share_mint.to_account_info(),
+            share_token: ctx.accounts.share_token.to_account_info(),
+            underlying_token: ctx.accounts.vesting_token.to_account_info(),
+            authority: ctx.accounts.vesting.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        vault::deposit(&vault_accounts, total_amount, signer)
+    }
+
+    /// Activates a pending vesting contract. Until the destination owner accepts it nothing can be claimed
+    /// and the creator can cancel the contract
+    pub fn accept(ctx: Context<Accept>, _seeds: [u8; 31]) -> Result<()> {
+        ctx.accounts.vesting.is_accepted = true;
+        refresh_next_action(&mut ctx.accounts.vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingAccepted {
+            vesting: ctx.accounts.vesting.key(),
+            destination_authority: ctx.accounts.destination_authority.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Activates a pending vesting contract paying a program derived address, signed by the creator instead of the
+    /// destination owner. Such an owner only signs through its program, which may not be able to call `accept`
+    pub fn accept_for_program_owner(ctx: Context<AcceptForProgramOwner>, _seeds: [u8; 31]) -> Result<()> {
+        ctx.accounts.vesting.is_accepted = true;
+        refresh_next_action(&mut ctx.accounts.vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingAccepted {
+            vesting: ctx.accounts.vesting.key(),
+            destination_authority: ctx.accounts.destination_token.owner,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Cancels a vesting contract the destination owner hasn't accepted yet, refunding the escrow to the creator
+    pub fn cancel(ctx: Context<Cancel>, seeds: [u8; 31]) -> Result<()> {
+        let refund_amount = total_amount(&ctx.accounts.vesting.schedule)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.refund_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, refund_amount, ctx.accounts.mint.decimals)?;
+
+        let before = ctx.accounts.vesting.schedule.clone();
+        ctx.accounts.vesting.schedule.iter_mut().for_each(|s| s.amount = 0);
+        refresh_next_action(&mut ctx.accounts.vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCancelled {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            refund: ctx.accounts.refund_token.key(),
+            amount: refund_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+            vesting: ctx.accounts.vesting.key(),
+            amendment: ScheduleAmendment::Cancel,
+            changes: schedule_changes(&before, &ctx.accounts.vesting.schedule),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting.destination_address, &ctx.accounts.vesting.key(),
+               InboxNotice::Cancelled, test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+        Ok(())
+    }
+
+    /// Pays a pending vesting contract to a wallet or a program derived address instead of a token account: the owner's
+    /// associated token account becomes the destination and `unlock_to_owner` recreates it when it has been closed
+    pub fn set_destination_owner(ctx: Context<SetDestinationOwner>, _seeds: [u8; 31], owner: Pubkey) -> Result<()> {
+        let vesting = &mut ctx.accounts.vesting;
+        uncount_voter_weight(ctx.program_id, vesting, ctx.remaining_accounts)?;
+        let old_destination = vesting.destination_address;
+        vesting.destination_address = get_associated_token_address(&owner, &vesting.mint_address);
+        vesting.destination_owner = Some(owner);
+        vesting.destination_change_count += 1;
+
+        event_cpi::emit(ctx.remaining_accounts, &DestinationChanged {
+            vesting: vesting.key(),
+            mint: vesting.mint_address,
+            old_destination,
+            new_destination: vesting.destination_address,
+            authority: ctx.accounts.creator.key(),
+            change_count: vesting.destination_change_count,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &old_destination, &vesting.key(),
+               InboxNotice::DestinationChanged, test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+        Ok(())
     }
 
+    /// Unlocks a vesting contract paying a wallet, creating its associated token account if needed at the expense of the payer.
+    /// A prior `sponsor_destination_rent` in the transaction leaves the payer nothing to pay
+    pub fn unlock_to_owner(ctx: Context<UnlockToOwner>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(ctx.accounts.vesting.unlock_policy == UnlockPolicy::Anyone
+                     || is_unlock_authority(&ctx.accounts.vesting, &ctx.accounts.owner.key(), &ctx.accounts.claimer.key(), now),
+                 VestingError::UnlockRestricted);
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.destination_token.key(), total_amount_to_transfer, now)?;
 
-    pub fn unlock(ctx: Context<Unlock>, seeds: [u8; 31]) -> Result<()> {
-        let now = anchor_lang::solana_program::clock::Clock::get()?.unix_timestamp;
-        let total_amount_to_transfer = total_amount_to_transfer(&ctx.accounts.vesting.schedule, now);
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
 
-        require!(total_amount_to_transfer > 0, VestingError::ReleaseTimeNotYetReached);
+    /// Unlocks the released amount of a vesting contract. Anyone can unlock on behalf of the beneficiary unless the creator
+    /// restricted unlocking, the tokens only ever go to the recorded destination.
+    /// Sets the transferred amount and the amount still locked as return data, two u64 LE.
+    /// Contracts with a claim hook notify it after the transfer, see `set_claim_hook`
+    ///
+    /// # Arguments
+    /// * `memo` - A memo recorded through the SPL Memo program, passed as the first remaining account
+    pub fn unlock(ctx: Context<Unlock>, seeds: [u8; 31], memo: Option<String>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+        verbose_log!("unlock.transfer", amount = total_amount_to_transfer, escrow_balance = ctx.accounts.vesting_token.amount,
+                     final_sweep = total_amount_to_transfer == ctx.accounts.vesting_token.amount);
 
-        let bump = *ctx.bumps.get("vesting").unwrap();
+        let bump = bump(&ctx.bumps, "vesting")?;
         let seeds = &[
             seeds.as_ref(),
             &[bump],
@@ -63,163 +435,8662 @@ pub mod token_vesting {
         // Unlocks a simple vesting contract (SVC)
         let transfer_ctx = CpiContext::new_with_signer(
             ctx.accounts.token_program.to_account_info(),
-            Transfer {
+            TransferChecked {
                 from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
                 to: ctx.accounts.destination_token.to_account_info(),
                 authority: ctx.accounts.vesting.to_account_info(),
             },
             signer
         );
-        transfer(transfer_ctx, total_amount_to_transfer)?;
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
 
         // Reset released amounts to 0. This makes the simple unlock safe with complex scheduling contracts
         reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+        verbose_log!("unlock.reset", locked = locked_amount(&ctx.accounts.vesting.schedule, now),
+                     claimed_tranches = ctx.accounts.vesting.schedule.iter().filter(|s| s.amount == 0).count());
 
+        let memo_hash = match memo {
+            Some(memo) => {
+                verbose_log!("unlock.memo", length = memo.len());
+                let memo_program = ctx.remaining_accounts.first().ok_or(VestingError::InvalidMemoProgram)?;
+                memo::log_memo(memo_program, &memo)?;
+                Some(anchor_lang::solana_program::hash::hash(memo.as_bytes()).to_bytes())
+            }
+            None => None,
+        };
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        enqueue_in_remaining(ctx.remaining_accounts, ctx.program_id, seeds[0], &ctx.accounts.vesting, now)?;
+        verbose_log!("unlock.claim_hook", registered = ctx.accounts.vesting.claim_hook.is_some());
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.destination_token.key(), total_amount_to_transfer, now)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash,
+            timestamp: now,
+        })?;
+
+        // Set last, a CPI clears the return data
+        let mut data = total_amount_to_transfer.to_le_bytes().to_vec();
+        data.extend_from_slice(&locked_amount(&ctx.accounts.vesting.schedule, now).to_le_bytes());
+        anchor_lang::solana_program::program::set_return_data(&data);
         Ok(())
     }
 
-    /// Change the destination account of a given simple vesting contract (SVC)
-    pub fn change_destination(ctx: Context<ChangeDestination>, seeds: [u8; 31]) -> Result<()> {
-        let destination = &mut ctx.accounts.vesting.destination_address;
-        *destination = ctx.accounts.new_destination_token.key();
+    /// Unlocks the released amount of a vesting contract like `unlock`, recording the claim in a receipt account
+    /// `["claim-receipt", vesting, claim_count (u64 LE)]` paid by the payer, as durable proof of the payment
+    pub fn unlock_with_receipt(ctx: Context<UnlockWithReceipt>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        let vesting = &mut ctx.accounts.vesting;
+        reset_released_amount(&mut vesting.schedule, now);
+        refresh_next_action(vesting, now);
+
+        let receipt = &mut ctx.accounts.claim_receipt;
+        receipt.vesting = vesting.key();
+        receipt.counter = vesting.claim_count;
+        receipt.destination = vesting.destination_address;
+        receipt.amount = total_amount_to_transfer;
+        receipt.timestamp = now;
+        vesting.claim_count += 1;
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.destination_token.key(), total_amount_to_transfer, now)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
         Ok(())
     }
-}
 
-#[derive(Accounts)]
-#[instruction(seeds: [u8; 31], number_of_schedules: u32)]
-pub struct Initialize<'info> {
-    #[account(init, payer = payer, space = calc_vesting_account_size(number_of_schedules), seeds = [seeds.as_ref()], bump)]
-    pub vesting: Account<'info, Vesting>,
-    #[account(mut)]
-    pub payer: Signer<'info>,
-    pub system_program: Program<'info, System>,
-}
+    /// Sets the amount `unlock` would transfer now as return data, a u64 LE that is 0 when nothing can be claimed.
+    /// Meant to be simulated by wallets and bots
+    pub fn get_claimable(ctx: Context<GetClaimable>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let claimable = claimable_amount(&ctx.accounts.vesting, now).unwrap_or(0);
+        anchor_lang::solana_program::program::set_return_data(&claimable.to_le_bytes());
+        Ok(())
+    }
 
-#[derive(Accounts)]
-#[instruction(seeds: [u8; 31], mint_address: Pubkey, destination_token_address: Pubkey, schedules: Vec<Schedule>)]
-pub struct Create<'info> {
-    #[account(mut, seeds = [seeds.as_ref()], bump,
-        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
-        constraint = vesting.schedule.len() == schedules.len() @ VestingError::InvalidScheduleLen
-    )]
-    pub vesting: Account<'info, Vesting>,
+    /// Sets the amount `unlock` would transfer at `at_timestamp` as return data, a u64 LE that is 0 when nothing
+    /// could be claimed then. Simulating it for a few timestamps gives the unlock curve of the contract
+    pub fn preview_claim(ctx: Context<GetClaimable>, _seeds: [u8; 31], at_timestamp: i64) -> Result<()> {
+        let claimable = claimable_amount(&ctx.accounts.vesting, at_timestamp).unwrap_or(0);
+        anchor_lang::solana_program::program::set_return_data(&claimable.to_le_bytes());
+        Ok(())
+    }
 
-    #[account(mut,
-        constraint = vesting_token.owner == vesting.key() @ VestingError::InvalidVestingTokenAuthority,
-        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
-        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
-    )]
-    pub vesting_token: Account<'info, TokenAccount>,
+    /// Sets the release time and amount of the next locked tranche as return data, two u64 LE that are 0 once
+    /// everything is released. Tranches sharing the release time are summed, tranches awaiting their release
+    /// condition are left out
+    pub fn next_unlock(ctx: Context<GetClaimable>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let (release_time, amount) = next_locked_tranche(&ctx.accounts.vesting.schedule, now).unwrap_or((0, 0));
 
-    #[account(mut)]
-    pub source_token: Account<'info, TokenAccount>,
+        let mut data = release_time.to_le_bytes().to_vec();
+        data.extend_from_slice(&amount.to_le_bytes());
+        anchor_lang::solana_program::program::set_return_data(&data);
+        Ok(())
+    }
 
-    pub source_authority: Signer<'info>,
-    pub token_program: Program<'info, Token>,
-}
+    /// Emits the complete schedule and accounting of a contract as a `Statement` event, so explorers and auditors can
+    /// materialize its state from the logs of one transaction. Anyone can call it
+    pub fn log_statement(ctx: Context<LogStatement>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let vesting = &ctx.accounts.vesting;
 
-#[derive(Accounts)]
-#[instruction(seeds: [u8; 31])]
-pub struct Unlock<'info> {
-    #[account(mut, seeds = [seeds.as_ref()], bump,
-        constraint = vesting.is_initialized @ VestingError::NotInitialized,
-    constraint = vesting.destination_address == destination_token.key() @ VestingError::InvalidDestination
-    )]
-    pub vesting: Account<'info, Vesting>,
+        event_cpi::emit(ctx.remaining_accounts, &Statement {
+            vesting: vesting.key(),
+            mint: vesting.mint_address,
+            creator: vesting.creator,
+            destination: vesting.destination_address,
+            revoker: vesting.revoker,
+            is_accepted: vesting.is_accepted,
+            unlock_policy: vesting.unlock_policy.clone(),
+            escrow_balance: ctx.accounts.vesting_token.amount,
+            total_amount: total_amount(&vesting.schedule)?,
+            released_amount: released_amount(&vesting.schedule, now),
+            locked_amount: locked_amount(&vesting.schedule, now),
+            claimable_amount: claimable_amount(vesting, now).unwrap_or(0),
+            min_claim_amount: vesting.min_claim_amount,
+            claim_count: vesting.claim_count,
+            destination_change_count: vesting.destination_change_count,
+            next_action: vesting.next_action.clone(),
+            schedule: vesting.schedule.clone(),
+            timestamp: now,
+        })?;
+        Ok(())
+    }
 
-    #[account(mut,
-        constraint = vesting_token.owner == vesting.key() @ VestingError::InvalidVestingTokenAuthority
-    )]
-    pub vesting_token: Account<'info, TokenAccount>,
+    /// Records the foreign-chain address `claim_and_bridge` sends the unlocked tokens to, none disables bridging
+    pub fn set_bridge_target(ctx: Context<SetBridgeTarget>, _seeds: [u8; 31], bridge_target: Option<BridgeTarget>) -> Result<()> {
+        ctx.accounts.vesting.bridge_target = bridge_target.clone();
 
-    #[account(mut)]
-    pub destination_token: Account<'info, TokenAccount>,
+        event_cpi::emit(ctx.remaining_accounts, &BridgeTargetChanged {
+            vesting: ctx.accounts.vesting.key(),
+            bridge_target,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
 
-    pub token_program: Program<'info, Token>,
-}
+    /// Registers the program notified through an `on_claim` CPI after each claim, with the accounts it is invoked with,
+    /// none removes the hook. The creator can only change it while the contract is pending, to a program of the claim
+    /// hook allowlist passed in the remaining accounts.
+    /// Claims then have to pass the claim hook allowlist, the hook program, the `claim_hook_authority` PDA and the
+    /// registered accounts as remaining accounts, and the instructions which can't run the hook are refused
+    pub fn set_claim_hook(ctx: Context<CreatorUpdate>, _seeds: [u8; 31], claim_hook: Option<ClaimHook>) -> Result<()> {
+        require!(!ctx.accounts.vesting.is_accepted, VestingError::AlreadyAccepted);
+        require!(!matches!(&claim_hook, Some(h) if h.program == crate::ID || h.accounts.len() > MAX_CLAIM_HOOK_ACCOUNTS),
+                 VestingError::InvalidClaimHook);
+        if let Some(hook) = &claim_hook {
+            require!(is_allowlisted_claim_hook(ctx.remaining_accounts, ctx.program_id, &hook.program)?, VestingError::ClaimHookNotAllowlisted);
+        }
+        ctx.accounts.vesting.claim_hook = claim_hook.clone();
 
-#[derive(Accounts)]
-#[instruction(seeds: [u8; 31])]
-pub struct ChangeDestination<'info> {
-    #[account(mut, seeds = [seeds.as_ref()], bump,
-        constraint = vesting.is_initialized @ VestingError::NotInitialized,
-        constraint = vesting.destination_address == current_destination_token.key() @ VestingError::InvalidDestination
-    )]
-    pub vesting: Account<'info, Vesting>,
+        event_cpi::emit(ctx.remaining_accounts, &ClaimHookChanged {
+            vesting: ctx.accounts.vesting.key(),
+            claim_hook,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
 
-    #[account(constraint = current_destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
-    pub current_destination_token: Account<'info, TokenAccount>,
-    pub destination_authority: Signer<'info>,
-    pub new_destination_token: Account<'info, TokenAccount>,
-}
+    /// Unlocks a vesting contract into the Wormhole token bridge, toward the recorded foreign-chain address.
+    /// The payer pays the bridge message fee and rent, the message account is a new keypair
+    ///
+    /// # Arguments
+    /// * `nonce` - The nonce of the Wormhole message
+    pub fn claim_and_bridge(ctx: Context<ClaimAndBridge>, seeds: [u8; 31], nonce: u32) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(is_unlock_authority(&ctx.accounts.vesting, &ctx.accounts.beneficiary.key(), &ctx.accounts.beneficiary.key(), now),
+                 VestingError::UnlockRestricted);
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+        let bridge_target = ctx.accounts.vesting.bridge_target.clone().ok_or(VestingError::NoBridgeTarget)?;
 
-#[account]
-pub struct Vesting {
-    pub destination_address: Pubkey,
-    pub mint_address: Pubkey,
-    pub is_initialized: bool,
-    pub schedule: Vec<Schedule>,
-}
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
 
-#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
-pub struct Schedule {
-    // Schedule release time in unix timestamp
-    pub release_time: u64,
-    pub amount: u64,
-}
+        let approve_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            Approve {
+                to: ctx.accounts.vesting_token.to_account_info(),
+                delegate: ctx.accounts.authority_signer.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        approve(approve_ctx, total_amount_to_transfer)?;
 
-#[error_code]
-pub enum VestingError {
-    #[msg("Cannot overwrite an existing vesting contract.")]
-    AlreadyInitialized,
-    #[msg("The vesting account isn't initialized.")]
-    NotInitialized,
-    #[msg("The vesting token account should be owned by the vesting account.")]
-    InvalidVestingTokenAuthority,
-    #[msg("The vesting token account should not have a delegate authority")]
-    InvalidVestingTokenDelegateAuthority,
-    #[msg("The vesting token account should not have a close authority")]
-    InvalidVestingTokenCloseAuthority,
-    #[msg("The source token account has insufficient funds.")]
-    InsufficientFunds,
-    #[msg("The schedule vector should have len defined during initialize.")]
-    InvalidScheduleLen,
-    #[msg("Total amount overflows u64")]
-    TotalAmountOverflow,
-    #[msg("Contract destination account does not matched provided account")]
-    InvalidDestination,
-    #[msg("Vesting contract has not yet reached release time")]
-    ReleaseTimeNotYetReached,
-    #[msg("The current destination token account isn't owned by the provided owner")]
-    InvalidDestinationAuthority,
-}
+        wormhole::transfer_native(
+            &wormhole::TransferNativeAccounts {
+                payer: ctx.accounts.payer.to_account_info(),
+                config: ctx.accounts.config.to_account_info(),
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                custody: ctx.accounts.custody.to_account_info(),
+                authority_signer: ctx.accounts.authority_signer.to_account_info(),
+                custody_signer: ctx.accounts.custody_signer.to_account_info(),
+                bridge: ctx.accounts.bridge.to_account_info(),
+                message: ctx.accounts.message.to_account_info(),
+                emitter: ctx.accounts.emitter.to_account_info(),
+                sequence: ctx.accounts.sequence.to_account_info(),
+                fee_collector: ctx.accounts.fee_collector.to_account_info(),
+                clock: ctx.accounts.clock.to_account_info(),
+                rent: ctx.accounts.rent.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+                core_bridge_program: ctx.accounts.core_bridge_program.to_account_info(),
+                token_program: ctx.accounts.token_program.to_account_info(),
+                token_bridge_program: ctx.accounts.token_bridge_program.to_account_info(),
+            },
+            nonce, total_amount_to_transfer, bridge_target.address, bridge_target.chain)?;
+        // The whole delegated amount is pulled by the bridge, which clears the delegate of the vesting token account
 
-fn calc_vesting_account_size(number_of_schedules: u32) -> usize {
-    8 // discriminator
-    + std::mem::size_of::<Pubkey>() // destination_address
-    + std::mem::size_of::<Pubkey>() // mint_address
-    + 1 // is_initialized
-    + 4 + (number_of_schedules as usize) * 2 * std::mem::size_of::<u64>() // schedule
-}
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
 
-fn total_amount(schedules: &Vec<Schedule>) -> Result<u64> {
-    schedules
-        .iter()
-        .try_fold(0u64, |sum, s| sum.checked_add(s.amount))
-        .ok_or_else(|| VestingError::TotalAmountOverflow.into())
-}
+        event_cpi::emit(ctx.remaining_accounts, &TokensBridged {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            amount: total_amount_to_transfer,
+            chain: bridge_target.chain,
+            address: bridge_target.address,
+            nonce,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
 
-fn total_amount_to_transfer(schedules: &Vec<Schedule>, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> u64 {
-    schedules
-        .iter()
-        .filter_map(|s| if timestamp as u64 >= s.release_time { Some(s.amount) } else { None })
-        .sum()
-}
+    /// Authorizes a delegate key to claim on behalf of the destination owner until `expires_at`, e.g. a hot wallet
+    /// or a session key. The tokens are still paid to the contract destination
+    pub fn set_claim_delegate(ctx: Context<SetClaimDelegate>, _seeds: [u8; 31], delegate: Pubkey, expires_at: i64) -> Result<()> {
+        ctx.accounts.vesting.claim_delegate = Some(ClaimDelegate { delegate, expires_at });
 
-fn reset_released_amount(schedules: &mut Vec<Schedule>, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) {
-    schedules
-        .iter_mut()
-        .filter_map(|s| if timestamp as u64 >= s.release_time {Some(s.amount.borrow_mut())} else {None} )
+        event_cpi::emit(ctx.remaining_accounts, &ClaimDelegateChanged {
+            vesting: ctx.accounts.vesting.key(),
+            claim_delegate: ctx.accounts.vesting.claim_delegate.clone(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Revokes the claim delegate of a vesting contract
+    pub fn clear_claim_delegate(ctx: Context<SetClaimDelegate>, _seeds: [u8; 31]) -> Result<()> {
+        ctx.accounts.vesting.claim_delegate = None;
+
+        event_cpi::emit(ctx.remaining_accounts, &ClaimDelegateChanged {
+            vesting: ctx.accounts.vesting.key(),
+            claim_delegate: None,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Unlocks a vesting contract signed by the destination owner or by its unexpired claim delegate, or by a key
+    /// of the allowlist when the contract restricts unlocking to one
+    pub fn authorized_unlock(ctx: Context<AuthorizedUnlock>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(is_unlock_authority(&ctx.accounts.vesting, &ctx.accounts.destination_token.owner, &ctx.accounts.authority.key(), now),
+            VestingError::InvalidClaimAuthority);
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.destination_token.key(), total_amount_to_transfer, now)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Unlocks a vesting contract with a permit signed off-chain by the destination owner, submitted by any relayer.
+    /// The transaction has to include an ed25519 program instruction verifying the owner signature over the message
+    /// `vesting address || nonce (u64 LE) || deadline (i64 LE)`. Program derived owners can't sign permits
+    ///
+    /// # Arguments
+    /// * `nonce` - The current permit nonce of the contract, incremented by every permit claim
+    /// * `deadline` - The unix timestamp after which the permit is no longer accepted
+    pub fn unlock_with_permit(ctx: Context<UnlockWithPermit>, seeds: [u8; 31], nonce: u64, deadline: i64) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(now <= deadline, VestingError::SignatureExpired);
+        require!(ctx.accounts.destination_token.owner.is_on_curve(), VestingError::ProgramOwnedDestination);
+        require!(expect_value("permit nonce", ctx.accounts.vesting.permit_nonce, nonce), VestingError::InvalidPermitNonce);
+
+        let mut message = ctx.accounts.vesting.key().to_bytes().to_vec();
+        message.extend_from_slice(&nonce.to_le_bytes());
+        message.extend_from_slice(&deadline.to_le_bytes());
+        require!(ed25519::verify_signed_message(&ctx.accounts.instructions, &ctx.accounts.destination_token.owner, &message)?,
+                 VestingError::MissingPermitSignature);
+        let owner = ctx.accounts.destination_token.owner;
+        require!(is_unlock_authority(&ctx.accounts.vesting, &owner, &owner, now), VestingError::UnlockRestricted);
+
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        let vesting = &mut ctx.accounts.vesting;
+        reset_released_amount(&mut vesting.schedule, now);
+        refresh_next_action(vesting, now);
+        vesting.permit_nonce = vesting.permit_nonce.checked_add(1).ok_or(VestingError::InvalidPermitNonce)?;
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.destination_token.key(), total_amount_to_transfer, now)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Permissionless unlock meant to be scheduled by on-chain automation. Unlike `unlock` it succeeds without
+    /// transferring anything when nothing can be claimed yet or the contract is paused, and after a successful unlock it tips the cranker
+    /// from the contract fee pool, if the contract has a funded one
+    pub fn crank_unlock(ctx: Context<CrankUnlock>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount_to_transfer = released_amount(&ctx.accounts.vesting.schedule, now);
+        if !ctx.accounts.vesting.is_accepted || ctx.accounts.vesting.is_paused || total_amount_to_transfer == 0
+            || !meets_min_claim_amount(&ctx.accounts.vesting, total_amount_to_transfer)? {
+            msg!("Nothing to unlock");
+            return Ok(());
+        }
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.destination_token.key(), total_amount_to_transfer, now)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+
+        let fee_pool = ctx.accounts.fee_pool.to_account_info();
+        if fee_pool.owner == ctx.program_id && !fee_pool.data_is_empty() {
+            let tip_lamports = Account::<FeePool>::try_from(&fee_pool)?.tip_lamports;
+            pay_from_fee_pool(&fee_pool, &ctx.accounts.cranker.to_account_info(), tip_lamports)?;
+        }
+
+        Ok(())
+    }
+
+    /// Unlocks many vesting contracts of a mint paying the same destination, e.g. a payroll run, emitting a single
+    /// `BatchUnlocked` event. The contracts are passed as (vesting, vesting token) pairs in the remaining accounts,
+    /// in the order of their seeds, followed by the rounds, rollups and partner stats of tagged contracts. Contracts
+    /// with nothing to claim yet are skipped
+    ///
+    /// # Arguments
+    /// * `seeds` - The seeds of the vesting accounts, signing their escrow transfers
+    pub fn batch_unlock<'info>(ctx: Context<'_, '_, '_, 'info, BatchUnlock<'info>>, seeds: Vec<[u8; 31]>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let accounts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        require!(accounts.len() >= 2 * seeds.len(), VestingError::InvalidRemainingAccounts);
+
+        let destination_token = ctx.accounts.destination_token.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let mut amount = 0u64;
+        let mut contract_count = 0u32;
+        for (seeds, pair) in seeds.iter().zip(accounts.chunks_exact(2)) {
+            let unlocked = unlock_remaining_contract(
+                ctx.program_id, seeds, &pair[0], &pair[1], &destination_token, &ctx.accounts.mint, &token_program, None, now)?;
+            if unlocked > 0 {
+                tally_claim(ctx.remaining_accounts, ctx.program_id, &*Account::<Vesting>::try_from(&pair[0])?, unlocked)?;
+                amount = amount.checked_add(unlocked).ok_or(VestingError::TotalAmountOverflow)?;
+                contract_count += 1;
+            }
+        }
+
+        event_cpi::emit(ctx.remaining_accounts, &BatchUnlocked {
+            mint: ctx.accounts.mint.key(),
+            destination: ctx.accounts.destination_token.key(),
+            contract_count,
+            amount,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(amount, ctx.accounts.mint.decimals),
+            timestamp: now,
+        })
+    }
+
+    /// Claims everything claimable across the vesting contracts of a destination owner, in any mint, for a one-click
+    /// "claim all". The contracts are passed as (vesting, vesting token, mint, destination token) tuples in the
+    /// remaining accounts, in the order of their seeds, followed by the rounds, rollups and partner stats of tagged
+    /// contracts. Each destination token account has to be owned by the owner. Contracts with nothing to claim yet are
+    /// skipped
+    ///
+    /// # Arguments
+    /// * `seeds` - The seeds of the vesting accounts, signing their escrow transfers
+    pub fn unlock_all_for_destination<'info>(ctx: Context<'_, '_, '_, 'info, UnlockAllForDestination<'info>>, seeds: Vec<[u8; 31]>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let accounts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        require!(accounts.len() >= 4 * seeds.len(), VestingError::InvalidRemainingAccounts);
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+        for (seeds, contract) in seeds.iter().zip(accounts.chunks_exact(4)) {
+            let (vesting, vesting_token, mint, destination_token) = (&contract[0], &contract[1], &contract[2], &contract[3]);
+            require!(expect_key("destination owner", &ctx.accounts.owner.key(), &Account::<TokenAccount>::try_from(destination_token)?.owner),
+                     VestingError::InvalidDestinationAuthority);
+            let mint = Account::<Mint>::try_from(mint)?;
+
+            let unlocked = unlock_remaining_contract(
+                ctx.program_id, seeds, vesting, vesting_token, destination_token, &mint, &token_program, Some(&ctx.accounts.owner.key()), now)?;
+            if unlocked > 0 {
+                tally_claim(ctx.remaining_accounts, ctx.program_id, &*Account::<Vesting>::try_from(vesting)?, unlocked)?;
+                event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+                    vesting: vesting.key(),
+                    mint: mint.key(),
+                    destination: destination_token.key(),
+                    amount: unlocked,
+                    decimals: mint.decimals,
+                    ui_amount: ui_amount(unlocked, mint.decimals),
+                    memo_hash: None,
+                    timestamp: now,
+                })?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Creates the fee pool of a vesting contract, paying cranks of the contract. The pool can be created by the
+    /// contract creator, e.g. pre-funded in the same transaction as `create`, or by the destination owner
+    ///
+    /// # Arguments
+    /// * `tip_lamports` - The lamports paid to the cranker of each successful `crank_unlock`
+    /// * `lamports` - The lamports initially deposited into the pool
+    pub fn create_fee_pool(ctx: Context<CreateFeePool>, _seeds: [u8; 31], tip_lamports: u64, lamports: u64) -> Result<()> {
+        let fee_pool = &mut ctx.accounts.fee_pool;
+        fee_pool.vesting = ctx.accounts.vesting.key();
+        fee_pool.authority = ctx.accounts.authority.key();
+        fee_pool.tip_lamports = tip_lamports;
+
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.authority.key(),
+            &ctx.accounts.fee_pool.key(),
+            lamports);
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.authority.to_account_info(),
+                ctx.accounts.fee_pool.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ])?;
+
+        event_cpi::emit(ctx.remaining_accounts, &FeePoolFunded {
+            vesting: ctx.accounts.vesting.key(),
+            fee_pool: ctx.accounts.fee_pool.key(),
+            funder: ctx.accounts.authority.key(),
+            lamports,
+            tip_lamports,
+        })?;
+        Ok(())
+    }
+
+    /// Deposits lamports into the fee pool of a vesting contract
+    pub fn fund_fee_pool(ctx: Context<FundFeePool>, _seeds: [u8; 31], lamports: u64) -> Result<()> {
+        let ix = anchor_lang::solana_program::system_instruction::transfer(
+            &ctx.accounts.funder.key(),
+            &ctx.accounts.fee_pool.key(),
+            lamports);
+        anchor_lang::solana_program::program::invoke(
+            &ix,
+            &[
+                ctx.accounts.funder.to_account_info(),
+                ctx.accounts.fee_pool.to_account_info(),
+                ctx.accounts.system_program.to_account_info(),
+            ])?;
+
+        event_cpi::emit(ctx.remaining_accounts, &FeePoolFunded {
+            vesting: ctx.accounts.vesting.key(),
+            fee_pool: ctx.accounts.fee_pool.key(),
+            funder: ctx.accounts.funder.key(),
+            lamports,
+            tip_lamports: ctx.accounts.fee_pool.tip_lamports,
+        })?;
+        Ok(())
+    }
+
+    /// Closes the fee pool of a fully released vesting contract, returning the remaining lamports to the pool authority
+    pub fn close_fee_pool(ctx: Context<CloseFeePool>, _seeds: [u8; 31]) -> Result<()> {
+        event_cpi::emit(ctx.remaining_accounts, &FeePoolClosed {
+            vesting: ctx.accounts.vesting.key(),
+            fee_pool: ctx.accounts.fee_pool.key(),
+            authority: ctx.accounts.authority.key(),
+            lamports: ctx.accounts.fee_pool.to_account_info().lamports(),
+        })?;
+        Ok(())
+    }
+
+    /// Unlocks a vesting contract and deposits the released amount straight into a staking program on behalf
+    /// of the destination owner. The staking program must expose the interface described in [`vault`]
+    pub fn claim_and_stake(ctx: Context<ClaimAndStake>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(is_unlock_authority(&ctx.accounts.vesting, &ctx.accounts.destination_authority.key(), &ctx.accounts.destination_authority.key(), now),
+                 VestingError::UnlockRestricted);
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let stake_accounts = vault::VaultAccounts {
+            vault_program: ctx.accounts.stake_program.to_account_info(),
+            vault: ctx.accounts.stake_pool.to_account_info(),
+            vault_reserve: ctx.accounts.stake_reserve.to_account_info(),
+            share_mint: ctx.accounts.pool_mint.to_account_info(),
+            share_token: ctx.accounts.pool_token.to_account_info(),
+            underlying_token: ctx.accounts.vesting_token.to_account_info(),
+            authority: ctx.accounts.vesting.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        vault::deposit(&stake_accounts, total_amount_to_transfer, signer)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensStaked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            stake_pool: ctx.accounts.stake_pool.key(),
+            pool_token: ctx.accounts.pool_token.key(),
+            amount: total_amount_to_transfer,
+            timestamp: now,
+        })?;
+
+        Ok(())
+    }
+
+    /// Unlocks a vesting contract whose escrow is held in a yield vault, withdrawing just the released amount.
+    /// The final claim also redeems the remaining shares, i.e. the accrued yield, to the yield recipient
+    pub fn unlock_from_yield(ctx: Context<UnlockFromYield>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let mut vault_accounts = vault::VaultAccounts {
+            vault_program: ctx.accounts.vault_program.to_account_info(),
+            vault: ctx.accounts.vault.to_account_info(),
+            vault_reserve: ctx.accounts.vault_reserve.to_account_info(),
+            share_mint: ctx.accounts.share_mint.to_account_info(),
+            share_token: ctx.accounts.share_token.to_account_info(),
+            underlying_token: ctx.accounts.destination_token.to_account_info(),
+            authority: ctx.accounts.vesting.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+        };
+        vault::withdraw(&vault_accounts, total_amount_to_transfer, signer)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+
+        if total_amount(&ctx.accounts.vesting.schedule)? == 0 {
+            ctx.accounts.share_token.reload()?;
+            let shares = ctx.accounts.share_token.amount;
+            if shares > 0 {
+                vault_accounts.underlying_token = ctx.accounts.yield_recipient.to_account_info();
+                vault::redeem(&vault_accounts, shares, signer)?;
+
+                event_cpi::emit(ctx.remaining_accounts, &YieldRedeemed {
+                    vesting: ctx.accounts.vesting.key(),
+                    yield_recipient: ctx.accounts.yield_recipient.key(),
+                    shares,
+                    timestamp: now,
+                })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Backs a vesting contract with collateral from a guarantor, covering top-ups promised by the granter
+    ///
+    /// # Arguments
+    /// * `promised_amount` - The amount the granter promises to top up the contract with
+    /// * `deadline` - The unix timestamp by which the promised amount must be topped up
+    /// * `collateral_amount` - The amount of collateral deposited by the guarantor
+    pub fn create_guarantee(ctx: Context<CreateGuarantee>,
+                            _seeds: [u8; 31],
+                            promised_amount: u64,
+                            deadline: i64,
+                            collateral_amount: u64) -> Result<()> {
+        let guarantee = &mut ctx.accounts.guarantee;
+        guarantee.vesting = ctx.accounts.vesting.key();
+        guarantee.guarantor = ctx.accounts.guarantor.key();
+        guarantee.granter = ctx.accounts.granter.key();
+        guarantee.collateral_token_address = ctx.accounts.collateral_token.key();
+        guarantee.promised_amount = promised_amount;
+        guarantee.topped_up_amount = 0;
+        guarantee.deadline = deadline;
+        guarantee.is_settled = false;
+
+        ctx.accounts.vesting.guarantor = Some(ctx.accounts.guarantor.key());
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.guarantor_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.collateral_token.to_account_info(),
+                authority: ctx.accounts.guarantor.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, collateral_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &GuaranteeCreated {
+            vesting: ctx.accounts.vesting.key(),
+            guarantee: ctx.accounts.guarantee.key(),
+            guarantor: ctx.accounts.guarantor.key(),
+            granter: ctx.accounts.granter.key(),
+            promised_amount,
+            collateral_amount,
+            deadline,
+        })?;
+        Ok(())
+    }
+
+    /// Tops up a tranche of a guaranteed vesting contract, counting towards the granter's promised amount
+    ///
+    /// # Arguments
+    /// * `schedule_index` - The index of the tranche to increase
+    /// * `amount` - The amount added to the tranche
+    pub fn top_up(ctx: Context<TopUp>, _seeds: [u8; 31], schedule_index: u32, amount: u64) -> Result<()> {
+        let before = ctx.accounts.vesting.schedule.clone();
+        let schedule = ctx.accounts.vesting.schedule
+            .get_mut(schedule_index as usize)
+            .ok_or(VestingError::InvalidScheduleIndex)?;
+        schedule.amount = schedule.amount.checked_add(amount).ok_or(VestingError::TotalAmountOverflow)?;
+        total_amount(&ctx.accounts.vesting.schedule)?;
+        refresh_next_action(&mut ctx.accounts.vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        let guarantee = &mut ctx.accounts.guarantee;
+        guarantee.topped_up_amount = guarantee.topped_up_amount.checked_add(amount).ok_or(VestingError::TotalAmountOverflow)?;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.granter.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &ToppedUp {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            schedule_index,
+            amount,
+            topped_up_amount: ctx.accounts.guarantee.topped_up_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+            vesting: ctx.accounts.vesting.key(),
+            amendment: ScheduleAmendment::TopUp,
+            changes: schedule_changes(&before, &ctx.accounts.vesting.schedule),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting.destination_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ToppedUp, test_clock::unix_timestamp(ctx.remaining_accounts)?)?;
+        Ok(())
+    }
+
+    /// Settles a guarantee. If the promised top-ups were made the collateral returns to the guarantor,
+    /// otherwise once the deadline has passed the shortfall is paid from the collateral to the contract destination.
+    /// The guarantor no longer has to co-sign revocations of a settled contract
+    pub fn settle_guarantee(ctx: Context<SettleGuarantee>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let guarantee = &ctx.accounts.guarantee;
+        let shortfall = guarantee.promised_amount.saturating_sub(guarantee.topped_up_amount);
+        require!(shortfall == 0 || now > guarantee.deadline, VestingError::GuaranteeNotDue);
+
+        let collateral = ctx.accounts.collateral_token.amount;
+        let to_destination = shortfall.min(collateral);
+        let to_guarantor = collateral - to_destination;
+
+        let vesting_key = ctx.accounts.vesting.key();
+        let bump = bump(&ctx.bumps, "guarantee")?;
+        let seeds = &[
+            GUARANTEE_SEED.as_bytes(),
+            vesting_key.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        if to_destination > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.collateral_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.destination_token.to_account_info(),
+                    authority: ctx.accounts.guarantee.to_account_info(),
+                },
+                signer
+            );
+            transfer_checked(transfer_ctx, to_destination, ctx.accounts.mint.decimals)?;
+        }
+        if to_guarantor > 0 {
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.collateral_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.guarantor_token.to_account_info(),
+                    authority: ctx.accounts.guarantee.to_account_info(),
+                },
+                signer
+            );
+            transfer_checked(transfer_ctx, to_guarantor, ctx.accounts.mint.decimals)?;
+        }
+
+        ctx.accounts.guarantee.is_settled = true;
+        ctx.accounts.vesting.guarantor = None;
+
+        event_cpi::emit(ctx.remaining_accounts, &GuaranteeSettled {
+            vesting: vesting_key,
+            guarantee: ctx.accounts.guarantee.key(),
+            shortfall_paid: to_destination,
+            collateral_returned: to_guarantor,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Registers a realm and governing token mint so the realm can use this program as its voter weight addin
+    pub fn create_registrar(ctx: Context<CreateRegistrar>) -> Result<()> {
+        let registrar = &mut ctx.accounts.registrar;
+        registrar.governance_program_id = ctx.accounts.governance_program.key();
+        registrar.realm = ctx.accounts.realm.key();
+        registrar.governing_token_mint = ctx.accounts.governing_token_mint.key();
+
+        event_cpi::emit(ctx.remaining_accounts, &RegistrarCreated {
+            registrar: registrar.key(),
+            realm: registrar.realm,
+            governing_token_mint: registrar.governing_token_mint,
+        })?;
+        Ok(())
+    }
+
+    /// Creates an empty voter weight record for a governing token owner
+    pub fn create_voter_weight_record(ctx: Context<CreateVoterWeightRecord>) -> Result<()> {
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.realm = ctx.accounts.registrar.realm;
+        record.governing_token_mint = ctx.accounts.registrar.governing_token_mint;
+        record.governing_token_owner = ctx.accounts.governing_token_owner.key();
+        record.voter_weight = 0;
+        record.voter_weight_expiry = None;
+        record.weight_action = None;
+        record.weight_action_target = None;
+
+        event_cpi::emit(ctx.remaining_accounts, &VoterWeightUpdated {
+            voter_weight_record: record.key(),
+            realm: record.realm,
+            governing_token_mint: record.governing_token_mint,
+            governing_token_owner: record.governing_token_owner,
+            voter_weight: 0,
+            slot: anchor_lang::solana_program::clock::Clock::get()?.slot,
+        })?;
+        Ok(())
+    }
+
+    /// Sets the voter weight of a governing token owner to the unvested balance of the contracts they vote for, valid for
+    /// the current slot. The contracts are passed as writable (vesting, destination token) pairs in the remaining accounts.
+    /// The vote delegate of a contract votes for it instead of its destination owner.
+    ///
+    /// Each contract records the voter weight record counting it and its weight, so a contract is counted for one voter
+    /// at a time: changing its destination or its vote delegate takes its weight off the record
+    pub fn update_voter_weight_record(ctx: Context<UpdateVoterWeightRecord>) -> Result<()> {
+        let clock = anchor_lang::solana_program::clock::Clock::get()?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let voter_weight = count_voter_weight(
+            ctx.program_id,
+            event_cpi::instruction_accounts(ctx.remaining_accounts),
+            &ctx.accounts.voter_weight_record.key(),
+            &ctx.accounts.voter_weight_record.governing_token_owner,
+            &ctx.accounts.registrar.governing_token_mint,
+            now)?;
+
+        let record = &mut ctx.accounts.voter_weight_record;
+        record.voter_weight = voter_weight;
+        record.voter_weight_expiry = Some(clock.slot);
+
+        event_cpi::emit(ctx.remaining_accounts, &VoterWeightUpdated {
+            voter_weight_record: record.key(),
+            realm: record.realm,
+            governing_token_mint: record.governing_token_mint,
+            governing_token_owner: record.governing_token_owner,
+            voter_weight,
+            slot: clock.slot,
+        })?;
+        Ok(())
+    }
+
+    /// Creates an empty max voter weight record for the realm and governing token mint of a registrar
+    pub fn create_max_voter_weight_record(ctx: Context<CreateMaxVoterWeightRecord>) -> Result<()> {
+        let record = &mut ctx.accounts.max_voter_weight_record;
+        record.realm = ctx.accounts.registrar.realm;
+        record.governing_token_mint = ctx.accounts.registrar.governing_token_mint;
+        record.max_voter_weight = 0;
+        record.max_voter_weight_expiry = None;
+
+        event_cpi::emit(ctx.remaining_accounts, &MaxVoterWeightUpdated {
+            max_voter_weight_record: record.key(),
+            realm: record.realm,
+            governing_token_mint: record.governing_token_mint,
+            max_voter_weight: 0,
+            slot: anchor_lang::solana_program::clock::Clock::get()?.slot,
+        })?;
+        Ok(())
+    }
+
+    /// Sets the max voter weight to the total unvested balance of the governing token mint, valid for the current slot.
+    /// Every vesting contract of the mint has to be passed in the remaining accounts for quorums to be correct, so only
+    /// the realm authority can update it
+    pub fn update_max_voter_weight_record(ctx: Context<UpdateMaxVoterWeightRecord>) -> Result<()> {
+        let clock = anchor_lang::solana_program::clock::Clock::get()?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let max_voter_weight = mint_locked_amount(
+            event_cpi::instruction_accounts(ctx.remaining_accounts),
+            &ctx.accounts.registrar.governing_token_mint,
+            now)?;
+
+        let record = &mut ctx.accounts.max_voter_weight_record;
+        record.max_voter_weight = max_voter_weight;
+        record.max_voter_weight_expiry = Some(clock.slot);
+
+        event_cpi::emit(ctx.remaining_accounts, &MaxVoterWeightUpdated {
+            max_voter_weight_record: record.key(),
+            realm: record.realm,
+            governing_token_mint: record.governing_token_mint,
+            max_voter_weight,
+            slot: clock.slot,
+        })?;
+        Ok(())
+    }
+
+    /// Refreshes the attestation of the locked and claimable balances of `owner` in `mint`, for other programs to read.
+    /// The contracts are passed as (vesting, destination token) pairs in the remaining accounts. Anyone can attest,
+    /// so the attested amounts are a lower bound: readers should check `contract_count` and `refreshed_slot`.
+    /// Each attester has its own attestation account, so only the original attester can refresh it
+    pub fn refresh_attestation(ctx: Context<RefreshAttestation>, owner: Pubkey, mint: Pubkey) -> Result<()> {
+        let clock = anchor_lang::solana_program::clock::Clock::get()?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let (locked_amount, claimable_amount, contract_count) =
+            owner_balances(event_cpi::instruction_accounts(ctx.remaining_accounts), &owner, &mint, now)?;
+
+        let attestation = &mut ctx.accounts.attestation;
+        attestation.owner = owner;
+        attestation.mint = mint;
+        attestation.locked_amount = locked_amount;
+        attestation.claimable_amount = claimable_amount;
+        attestation.contract_count = contract_count;
+        attestation.refreshed_at = now;
+        attestation.refreshed_slot = clock.slot;
+        attestation.attester = ctx.accounts.attester.key();
+
+        event_cpi::emit(ctx.remaining_accounts, &AttestationRefreshed {
+            attestation: attestation.key(),
+            owner,
+            mint,
+            locked_amount,
+            claimable_amount,
+            contract_count,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Freezes the amount locked in a vesting contract into a snapshot account for `slot`. The snapshot can't be taken
+    /// before `slot` and is taken once, at the first slot it lands in, which is recorded as well
+    ///
+    /// # Arguments
+    /// * `slot` - The slot the snapshot is meant for, part of the snapshot account address
+    pub fn snapshot(ctx: Context<Snapshot>, _seeds: [u8; 31], slot: u64) -> Result<()> {
+        let clock = anchor_lang::solana_program::clock::Clock::get()?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(clock.slot >= slot, VestingError::SnapshotSlotNotReached);
+
+        let snapshot = &mut ctx.accounts.snapshot;
+        snapshot.vesting = ctx.accounts.vesting.key();
+        snapshot.slot = slot;
+        snapshot.taken_at_slot = clock.slot;
+        snapshot.locked_amount = locked_amount(&ctx.accounts.vesting.schedule, now);
+
+        event_cpi::emit(ctx.remaining_accounts, &SnapshotTaken {
+            vesting: snapshot.vesting,
+            snapshot: snapshot.key(),
+            slot,
+            taken_at_slot: clock.slot,
+            locked_amount: snapshot.locked_amount,
+        })?;
+        Ok(())
+    }
+
+    /// Delegates the governance power of the tokens locked in a vesting contract without moving them.
+    /// The delegation is recorded on the contract and attested in a vote delegation account other programs can read.
+    /// The voter weight record counting the contract, if any, has to be in the remaining accounts
+    pub fn set_vote_delegate(ctx: Context<SetVoteDelegate>, _seeds: [u8; 31], delegate: Pubkey) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        uncount_voter_weight(ctx.program_id, &mut ctx.accounts.vesting, ctx.remaining_accounts)?;
+        ctx.accounts.vesting.vote_delegate = Some(delegate);
+
+        let delegation = &mut ctx.accounts.vote_delegation;
+        delegation.vesting = ctx.accounts.vesting.key();
+        delegation.mint = ctx.accounts.vesting.mint_address;
+        delegation.delegator = ctx.accounts.destination_authority.key();
+        delegation.delegate = delegate;
+        delegation.delegated_at = now;
+
+        event_cpi::emit(ctx.remaining_accounts, &VoteDelegateChanged {
+            vesting: delegation.vesting,
+            mint: delegation.mint,
+            vote_delegate: Some(delegate),
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Clears the vote delegate of a vesting contract and closes its vote delegation account.
+    /// The voter weight record counting the contract, if any, has to be in the remaining accounts
+    pub fn clear_vote_delegate(ctx: Context<ClearVoteDelegate>, _seeds: [u8; 31]) -> Result<()> {
+        uncount_voter_weight(ctx.program_id, &mut ctx.accounts.vesting, ctx.remaining_accounts)?;
+        ctx.accounts.vesting.vote_delegate = None;
+
+        event_cpi::emit(ctx.remaining_accounts, &VoteDelegateChanged {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            vote_delegate: None,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Revokes the unvested part of a vesting contract, returning it to a token account chosen by the revoker.
+    /// Tranches whose release time has passed stay claimable by the destination.
+    ///
+    /// The revoker only has to sign, so it can be a program derived address such as an SPL Governance
+    /// governance account signing through CPI when a proposal is executed.
+    /// A guarantor recorded on the contract must co-sign and is passed in the remaining accounts
+    pub fn revoke(ctx: Context<Revoke>, seeds: [u8; 31]) -> Result<()> {
+        if let Some(guarantor) = ctx.accounts.vesting.guarantor {
+            require!(ctx.remaining_accounts.iter().any(|a| a.is_signer && *a.key == guarantor),
+                     VestingError::MissingGuarantorSignature);
+        }
+
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let revoked_amount = locked_amount(&ctx.accounts.vesting.schedule, now);
+        require!(revoked_amount > 0, VestingError::NothingToRevoke);
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.refund_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, revoked_amount, ctx.accounts.mint.decimals)?;
+
+        let before = ctx.accounts.vesting.schedule.clone();
+        reset_locked_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        tally_round(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, 0, revoked_amount)?;
+        tally_rollup(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, 0, revoked_amount)?;
+        tally_partner_stats(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, 0, revoked_amount)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingRevoked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            revoker: ctx.accounts.revoker.key(),
+            refund: ctx.accounts.refund_token.key(),
+            amount: revoked_amount,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+            vesting: ctx.accounts.vesting.key(),
+            amendment: ScheduleAmendment::Revoke,
+            changes: schedule_changes(&before, &ctx.accounts.vesting.schedule),
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting.destination_address, &ctx.accounts.vesting.key(),
+               InboxNotice::Revoked, now)?;
+
+        Ok(())
+    }
+
+    /// Checks the release condition of a tranche and clears it once met, after which the tranche releases
+    /// at its release time. Anyone can trigger a condition
+    ///
+    /// # Arguments
+    /// * `schedule_index` - The index of the gated tranche
+    pub fn trigger_release_condition(ctx: Context<TriggerReleaseCondition>, _seeds: [u8; 31], schedule_index: u32) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let schedule = ctx.accounts.vesting.schedule
+            .get_mut(schedule_index as usize)
+            .ok_or(VestingError::InvalidScheduleIndex)?;
+
+        match schedule.condition.as_ref().ok_or(VestingError::NoReleaseCondition)? {
+            ReleaseCondition::Price { price_feed, target_price, target_expo, is_above, max_staleness } => {
+                let price = load_condition_price(ctx.remaining_accounts, price_feed, *max_staleness, now)?;
+                let ordering = pyth::cmp_prices(price.price, price.expo, *target_price, *target_expo)
+                    .ok_or(VestingError::PriceConditionNotMet)?;
+                require!(meets_price_target(ordering, *is_above), VestingError::PriceConditionNotMet);
+            }
+            ReleaseCondition::GovernanceProposal { governance_program, proposal } => {
+                let proposal_account = ctx.remaining_accounts.first().ok_or(VestingError::InvalidProposal)?;
+                require!(proposal_account.key == proposal, VestingError::InvalidProposal);
+                require!(governance::is_proposal_succeeded(proposal_account, governance_program)?,
+                         VestingError::ProposalNotSucceeded);
+            }
+            ReleaseCondition::Twap { .. } => return err!(VestingError::TwapRequiresObservations),
+            ReleaseCondition::SwitchboardFunction { .. } => return err!(VestingError::FunctionRequiresEnclaveSigner),
+            ReleaseCondition::OracleSignature { .. } => return err!(VestingError::SignatureRequiresEd25519Instruction),
+        }
+
+        schedule.condition = None;
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        event_cpi::emit(ctx.remaining_accounts, &ReleaseConditionMet {
+            vesting: ctx.accounts.vesting.key(),
+            schedule_index,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Records a price observation for a TWAP release condition. Once the observations span the condition window
+    /// the time weighted average price is checked: the condition is cleared if it is met, otherwise a new window starts.
+    /// Anyone can record observations. Observations are at most a `MIN_TWAP_OBSERVATIONS`th of the window apart,
+    /// so a window averages at least that many of them
+    ///
+    /// # Arguments
+    /// * `schedule_index` - The index of the gated tranche
+    pub fn record_observation(ctx: Context<TriggerReleaseCondition>, _seeds: [u8; 31], schedule_index: u32) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let vesting_key = ctx.accounts.vesting.key();
+        let schedule = ctx.accounts.vesting.schedule
+            .get_mut(schedule_index as usize)
+            .ok_or(VestingError::InvalidScheduleIndex)?;
+
+        let is_met = match schedule.condition.as_mut().ok_or(VestingError::NoReleaseCondition)? {
+            ReleaseCondition::Twap {
+                price_feed, target_price, target_expo, is_above, max_staleness, window,
+                window_start, last_observation_time, last_price, cumulative_price
+            } => {
+                let price = load_condition_price(ctx.remaining_accounts, price_feed, *max_staleness, now)?;
+                let price = pyth::scale_price(price.price, price.expo, *target_expo).ok_or(VestingError::PriceOverflow)?;
+
+                // Observations too far apart can't vouch for the price in between, the window restarts
+                let max_gap = (*max_staleness).min(*window / MIN_TWAP_OBSERVATIONS).max(1);
+                if *last_observation_time == 0 || now.saturating_sub(*last_observation_time) as u64 > max_gap {
+                    *window_start = now;
+                    *cumulative_price = 0;
+                } else {
+                    let elapsed = (now - *last_observation_time) as i128;
+                    *cumulative_price = last_price.checked_mul(elapsed)
+                        .and_then(|p| cumulative_price.checked_add(p))
+                        .ok_or(VestingError::PriceOverflow)?;
+                }
+                *last_observation_time = now;
+                *last_price = price;
+                event_cpi::emit(ctx.remaining_accounts, &PriceObserved {
+                    vesting: vesting_key,
+                    schedule_index,
+                    price,
+                    timestamp: now,
+                })?;
+
+                let elapsed = now.saturating_sub(*window_start);
+                if elapsed > 0 && elapsed as u64 >= *window {
+                    let twap = *cumulative_price / elapsed as i128;
+                    let is_met = meets_price_target(twap.cmp(&(*target_price as i128)), *is_above);
+                    if !is_met {
+                        *window_start = now;
+                        *cumulative_price = 0;
+                    }
+                    is_met
+                } else {
+                    false
+                }
+            }
+            _ => return err!(VestingError::NotTwapCondition),
+        };
+
+        if is_met {
+            schedule.condition = None;
+            refresh_next_action(&mut ctx.accounts.vesting, now);
+            event_cpi::emit(ctx.remaining_accounts, &ReleaseConditionMet {
+                vesting: vesting_key,
+                schedule_index,
+                timestamp: now,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Clears a Switchboard Functions release condition. The instruction has to be signed by the enclave signer
+    /// of the condition function, which only a verified and unexpired run of the function holds. The function
+    /// attests the milestone by submitting this instruction
+    ///
+    /// # Arguments
+    /// * `schedule_index` - The index of the gated tranche
+    pub fn fulfill_function_condition(ctx: Context<FulfillFunctionCondition>, _seeds: [u8; 31], schedule_index: u32) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let schedule = ctx.accounts.vesting.schedule
+            .get_mut(schedule_index as usize)
+            .ok_or(VestingError::InvalidScheduleIndex)?;
+
+        match schedule.condition.as_ref().ok_or(VestingError::NoReleaseCondition)? {
+            ReleaseCondition::SwitchboardFunction { function } => {
+                require!(*function == ctx.accounts.function.key(), VestingError::InvalidSwitchboardFunction);
+                require!(switchboard::validate_enclave_signer(&ctx.accounts.function, &ctx.accounts.enclave_signer, now)?,
+                         VestingError::InvalidEnclaveSigner);
+            }
+            _ => return err!(VestingError::NotFunctionCondition),
+        }
+
+        schedule.condition = None;
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        event_cpi::emit(ctx.remaining_accounts, &ReleaseConditionMet {
+            vesting: ctx.accounts.vesting.key(),
+            schedule_index,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Clears an oracle signature release condition. The transaction has to include an ed25519 program instruction
+    /// verifying the oracle signature over the message `vesting address || schedule_index (u32 LE) || deadline (i64 LE)`
+    ///
+    /// # Arguments
+    /// * `schedule_index` - The index of the gated tranche
+    /// * `deadline` - The unix timestamp after which the signature is no longer accepted
+    pub fn fulfill_signature_condition(ctx: Context<FulfillSignatureCondition>, _seeds: [u8; 31], schedule_index: u32, deadline: i64) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(now <= deadline, VestingError::SignatureExpired);
+
+        let vesting_key = ctx.accounts.vesting.key();
+        let schedule = ctx.accounts.vesting.schedule
+            .get_mut(schedule_index as usize)
+            .ok_or(VestingError::InvalidScheduleIndex)?;
+
+        match schedule.condition.as_ref().ok_or(VestingError::NoReleaseCondition)? {
+            ReleaseCondition::OracleSignature { oracle } => {
+                let mut message = vesting_key.to_bytes().to_vec();
+                message.extend_from_slice(&schedule_index.to_le_bytes());
+                message.extend_from_slice(&deadline.to_le_bytes());
+                require!(ed25519::verify_signed_message(&ctx.accounts.instructions, oracle, &message)?,
+                         VestingError::MissingOracleSignature);
+            }
+            _ => return err!(VestingError::NotSignatureCondition),
+        }
+
+        schedule.condition = None;
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        event_cpi::emit(ctx.remaining_accounts, &ReleaseConditionMet {
+            vesting: ctx.accounts.vesting.key(),
+            schedule_index,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Mints the receipt NFT of a vesting contract to the destination owner. From then on the holder of the receipt
+    /// is the beneficiary: `unlock_to_holder` pays the holder's associated token account and the destination can no
+    /// longer be changed. The receipt mint has a supply of one and no mint authority
+    pub fn create_receipt(ctx: Context<CreateReceipt>, seeds: [u8; 31]) -> Result<()> {
+        ctx.accounts.vesting.receipt_mint = Some(ctx.accounts.receipt_mint.key());
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let mint_to_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            MintTo {
+                mint: ctx.accounts.receipt_mint.to_account_info(),
+                to: ctx.accounts.receipt_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        mint_to(mint_to_ctx, 1)?;
+
+        let set_authority_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            SetAuthority {
+                current_authority: ctx.accounts.vesting.to_account_info(),
+                account_or_mint: ctx.accounts.receipt_mint.to_account_info(),
+            },
+            signer
+        );
+        set_authority(set_authority_ctx, spl_token::instruction::AuthorityType::MintTokens, None)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &ReceiptMinted {
+            vesting: ctx.accounts.vesting.key(),
+            receipt_mint: ctx.accounts.receipt_mint.key(),
+            owner: ctx.accounts.beneficiary.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Mints a compressed NFT receipt of a vesting contract to the destination owner through Bubblegum, for cohorts
+    /// too large for a mint per contract. The receipt is a wallet-visible artifact with the vesting account as its
+    /// verified creator, it doesn't change who the beneficiary is
+    ///
+    /// # Arguments
+    /// * `name`, `symbol`, `uri` - The metadata of the receipt
+    pub fn mint_compressed_receipt(ctx: Context<MintCompressedReceipt>, seeds: [u8; 31], name: String, symbol: String, uri: String) -> Result<()> {
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        bubblegum::mint_v1(
+            &bubblegum::MintAccounts {
+                bubblegum_program: ctx.accounts.bubblegum_program.to_account_info(),
+                tree_authority: ctx.accounts.tree_authority.to_account_info(),
+                leaf_owner: ctx.accounts.beneficiary.to_account_info(),
+                merkle_tree: ctx.accounts.merkle_tree.to_account_info(),
+                payer: ctx.accounts.payer.to_account_info(),
+                tree_delegate: ctx.accounts.creator.to_account_info(),
+                creator: ctx.accounts.vesting.to_account_info(),
+                log_wrapper: ctx.accounts.log_wrapper.to_account_info(),
+                compression_program: ctx.accounts.compression_program.to_account_info(),
+                system_program: ctx.accounts.system_program.to_account_info(),
+            },
+            name, symbol, uri, signer)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &CompressedReceiptMinted {
+            vesting: ctx.accounts.vesting.key(),
+            merkle_tree: ctx.accounts.merkle_tree.key(),
+            owner: ctx.accounts.beneficiary.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Unlocks a vesting contract with a receipt, paying the associated token account of the receipt holder
+    pub fn unlock_to_holder(ctx: Context<UnlockToHolder>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let total_amount_to_transfer =
+            final_sweep_amount(&ctx.accounts.vesting.schedule, total_amount_to_transfer, ctx.accounts.vesting_token.amount)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vesting_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.holder_token.to_account_info(),
+                authority: ctx.accounts.vesting.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+        run_claim_hook(ctx.remaining_accounts, &ctx.accounts.vesting, &ctx.accounts.holder_token.key(), total_amount_to_transfer, now)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.holder_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+
+        Ok(())
+    }
+
+    /// Lists the remaining position of a vesting contract for sale. The seller is the destination owner
+    ///
+    /// # Arguments
+    /// * `price` - The price of the position in the payment mint of the seller payment token account
+    pub fn list_position(ctx: Context<ListPosition>, _seeds: [u8; 31], price: u64) -> Result<()> {
+        let listing = &mut ctx.accounts.listing;
+        listing.vesting = ctx.accounts.vesting.key();
+        listing.seller = ctx.accounts.seller.key();
+        listing.seller_payment_token_address = ctx.accounts.seller_payment_token.key();
+        listing.payment_mint = ctx.accounts.seller_payment_token.mint;
+        listing.price = price;
+        listing.is_approved = false;
+
+        event_cpi::emit(ctx.remaining_accounts, &PositionListed {
+            vesting: listing.vesting,
+            listing: listing.key(),
+            seller: listing.seller,
+            payment_mint: listing.payment_mint,
+            price,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Cancels the sale of a vesting position
+    pub fn cancel_listing(ctx: Context<CancelListing>, _seeds: [u8; 31]) -> Result<()> {
+        event_cpi::emit(ctx.remaining_accounts, &ListingCancelled {
+            vesting: ctx.accounts.vesting.key(),
+            listing: ctx.accounts.listing.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Requires or stops requiring the creator's approval of vesting position sales
+    pub fn set_transfer_approval_required(ctx: Context<CreatorUpdate>, _seeds: [u8; 31], required: bool) -> Result<()> {
+        ctx.accounts.vesting.transfer_approval_required = required;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingSettingsChanged {
+            vesting: ctx.accounts.vesting.key(),
+            transfer_approval_required: ctx.accounts.vesting.transfer_approval_required,
+            unlock_policy: ctx.accounts.vesting.unlock_policy.clone(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Changes who may unlock the contract, see `UnlockPolicy`. The creator can only change it while the contract is pending
+    pub fn set_unlock_policy(ctx: Context<CreatorUpdate>, _seeds: [u8; 31], unlock_policy: UnlockPolicy) -> Result<()> {
+        require!(!ctx.accounts.vesting.is_accepted, VestingError::AlreadyAccepted);
+        require!(is_valid_unlock_policy(&unlock_policy), VestingError::InvalidUnlockPolicy);
+        ctx.accounts.vesting.unlock_policy = unlock_policy;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingSettingsChanged {
+            vesting: ctx.accounts.vesting.key(),
+            transfer_approval_required: ctx.accounts.vesting.transfer_approval_required,
+            unlock_policy: ctx.accounts.vesting.unlock_policy.clone(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Approves the sale of a vesting position by the contract creator
+    pub fn approve_listing(ctx: Context<ApproveListing>, _seeds: [u8; 31]) -> Result<()> {
+        ctx.accounts.listing.is_approved = true;
+
+        event_cpi::emit(ctx.remaining_accounts, &ListingApproved {
+            vesting: ctx.accounts.vesting.key(),
+            listing: ctx.accounts.listing.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Buys a listed vesting position: the price is paid to the seller and the contract destination
+    /// is changed to the buyer token account in the same instruction. The vote delegate, claim delegate, bridge target
+    /// and claim hook of the seller are cleared and the voter weight record counting the contract, if any, has to be
+    /// in the remaining accounts
+    ///
+    /// # Arguments
+    /// * `expected_price` - The listing price the buyer agreed to, guarding against a changed listing
+    pub fn accept_listing(ctx: Context<AcceptListing>, _seeds: [u8; 31], expected_price: u64) -> Result<()> {
+        let listing = &ctx.accounts.listing;
+        require!(expect_value("listing price", listing.price, expected_price), VestingError::ListingPriceChanged);
+        require!(listing.is_approved || !ctx.accounts.vesting.transfer_approval_required, VestingError::ListingNotApproved);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.buyer_payment_token.to_account_info(),
+                mint: ctx.accounts.payment_mint.to_account_info(),
+                to: ctx.accounts.seller_payment_token.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, listing.price, ctx.accounts.payment_mint.decimals)?;
+
+        uncount_voter_weight(ctx.program_id, &mut ctx.accounts.vesting, ctx.remaining_accounts)?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let vesting = &mut ctx.accounts.vesting;
+        let old_destination = vesting.destination_address;
+        vesting.destination_address = ctx.accounts.buyer_destination_token.key();
+        vesting.destination_owner = None;
+        vesting.destination_change_count += 1;
+        let delegate_cleared = vesting.vote_delegate.take().is_some();
+        let claim_delegate_cleared = vesting.claim_delegate.take().is_some();
+        let bridge_target_cleared = vesting.bridge_target.take().is_some();
+        let claim_hook_cleared = vesting.claim_hook.take().is_some();
+
+        event_cpi::emit(ctx.remaining_accounts, &PositionSold {
+            vesting: vesting.key(),
+            listing: ctx.accounts.listing.key(),
+            seller: ctx.accounts.seller.key(),
+            buyer: ctx.accounts.buyer.key(),
+            payment_mint: ctx.accounts.listing.payment_mint,
+            price: ctx.accounts.listing.price,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &DestinationChanged {
+            vesting: vesting.key(),
+            mint: vesting.mint_address,
+            old_destination,
+            new_destination: vesting.destination_address,
+            authority: ctx.accounts.seller.key(),
+            change_count: vesting.destination_change_count,
+            timestamp: now,
+        })?;
+        if delegate_cleared {
+            event_cpi::emit(ctx.remaining_accounts, &VoteDelegateChanged {
+                vesting: vesting.key(),
+                mint: vesting.mint_address,
+                vote_delegate: None,
+                timestamp: now,
+            })?;
+        }
+        if claim_delegate_cleared {
+            event_cpi::emit(ctx.remaining_accounts, &ClaimDelegateChanged { vesting: vesting.key(), claim_delegate: None, timestamp: now })?;
+        }
+        if bridge_target_cleared {
+            event_cpi::emit(ctx.remaining_accounts, &BridgeTargetChanged { vesting: vesting.key(), bridge_target: None, timestamp: now })?;
+        }
+        if claim_hook_cleared {
+            event_cpi::emit(ctx.remaining_accounts, &ClaimHookChanged { vesting: vesting.key(), claim_hook: None, timestamp: now })?;
+        }
+        notify(ctx.remaining_accounts, ctx.program_id, &old_destination, &vesting.key(), InboxNotice::DestinationChanged, now)?;
+        Ok(())
+    }
+
+    /// Creates the inbox of a destination token account. Instructions of the creator, revoker or buyer affecting
+    /// a contract paying that account append a notice to it when the inbox is passed in their remaining accounts,
+    /// so wallets can show pending actions without an indexer. The inbox keeps the latest notices
+    pub fn create_inbox(ctx: Context<CreateInbox>) -> Result<()> {
+        let inbox = &mut ctx.accounts.inbox;
+        inbox.destination = ctx.accounts.destination_token.key();
+        inbox.next_sequence = 0;
+        inbox.records = Vec::new();
+        Ok(())
+    }
+
+    /// Removes the notices up to `sequence` from the inbox of a destination token account, signed by its owner
+    pub fn acknowledge_inbox(ctx: Context<AcknowledgeInbox>, sequence: u64) -> Result<()> {
+        ctx.accounts.inbox.records.retain(|r| r.sequence > sequence);
+        Ok(())
+    }
+
+    /// Change the destination account of a given simple vesting contract (SVC)
+    ///
+    /// The destination authority is the owner of the current destination token account. It either signs,
+    /// which includes program derived addresses signing through CPI, or is an SPL Token multisig
+    /// whose signers are passed in the remaining accounts. The vote delegate of the previous owner is cleared and the
+    /// voter weight record counting the contract, if any, has to be in the remaining accounts
+    #[allow(unused_variables)]
+    pub fn change_destination(ctx: Context<ChangeDestination>, seeds: [u8; 31]) -> Result<()> {
+        verify_owner_authority(&ctx.accounts.destination_authority, ctx.remaining_accounts)?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+
+        uncount_voter_weight(ctx.program_id, &mut ctx.accounts.vesting, ctx.remaining_accounts)?;
+        let vesting = &mut ctx.accounts.vesting;
+        let old_destination = vesting.destination_address;
+        vesting.destination_address = ctx.accounts.new_destination_token.key();
+        vesting.destination_owner = None;
+        vesting.destination_change_count += 1;
+        let delegate_cleared = vesting.vote_delegate.take().is_some();
+
+        event_cpi::emit(ctx.remaining_accounts, &DestinationChanged {
+            vesting: vesting.key(),
+            mint: vesting.mint_address,
+            old_destination,
+            new_destination: vesting.destination_address,
+            authority: ctx.accounts.destination_authority.key(),
+            change_count: vesting.destination_change_count,
+            timestamp: now,
+        })?;
+        if delegate_cleared {
+            event_cpi::emit(ctx.remaining_accounts, &VoteDelegateChanged {
+                vesting: vesting.key(),
+                mint: vesting.mint_address,
+                vote_delegate: None,
+                timestamp: now,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Points many contracts to new destinations at once, e.g. when a custodian rotates its omnibus wallets. The
+    /// contracts are passed as (vesting, current destination token, new destination token) triples in the remaining
+    /// accounts, in the order of their seeds, followed by the voter weight records counting them and the multisig signers
+    /// if any. Every current destination is owned by `destination_authority`, checked like `change_destination` does for
+    /// each contract
+    pub fn batch_change_destination<'info>(ctx: Context<'_, '_, '_, 'info, BatchChangeDestination<'info>>, seeds: Vec<[u8; 31]>) -> Result<()> {
+        verify_owner_authority(&ctx.accounts.destination_authority, ctx.remaining_accounts)?;
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let accounts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        require!(accounts.len() >= 3 * seeds.len(), VestingError::InvalidRemainingAccounts);
+
+        for (seeds, triple) in seeds.iter().zip(accounts.chunks_exact(3)) {
+            let (changed, delegate_cleared) = change_remaining_destination(
+                ctx.program_id, seeds, &triple[0], &triple[1], &triple[2], &ctx.accounts.destination_authority.key(), ctx.remaining_accounts, now)?;
+            event_cpi::emit(ctx.remaining_accounts, &changed)?;
+            if let Some(delegate_cleared) = delegate_cleared {
+                event_cpi::emit(ctx.remaining_accounts, &delegate_cleared)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Imports a Bonfida vesting contract, signed by the owner of its destination token account. The contract keeps
+    /// its schedule and destination here, accepted, with the destination owner as creator.
+    ///
+    /// Only Bonfida can move its escrow, so the Bonfida contract is pointed to the new escrow and its released tranches
+    /// unlocked there. The tranches still locked reach the new escrow as Bonfida's `unlock` is sent once they release,
+    /// which `unlock` here expects: send it first, see `bonfida::unlock_instruction`
+    ///
+    /// # Arguments
+    /// * `bonfida_seeds` - The seeds of the Bonfida vesting account, its bump last
+    pub fn migrate_from_bonfida(ctx: Context<MigrateFromBonfida>, _seeds: [u8; 31], bonfida_seeds: [u8; 32]) -> Result<()> {
+        let bonfida_vesting = bonfida::BonfidaVesting::unpack(&ctx.accounts.bonfida_vesting.data.borrow())
+            .filter(|v| v.is_initialized)
+            .ok_or(VestingError::InvalidBonfidaVesting)?;
+        require!(bonfida::vesting_address(&bonfida_seeds) == Some(ctx.accounts.bonfida_vesting.key()), VestingError::InvalidBonfidaVesting);
+        require!(expect_key("destination", &bonfida_vesting.destination_address, &ctx.accounts.destination_token.key()), VestingError::InvalidDestination);
+        require!(expect_key("mint", &bonfida_vesting.mint_address, &ctx.accounts.vesting_token.mint), VestingError::InvalidMint);
+        require!(ctx.accounts.vesting.schedule.len() == bonfida_vesting.schedules.len(), VestingError::InvalidScheduleLen);
+
+        let bonfida_accounts = bonfida::BonfidaAccounts {
+            bonfida_program: ctx.accounts.bonfida_program.to_account_info(),
+            vesting: ctx.accounts.bonfida_vesting.to_account_info(),
+            vesting_token: ctx.accounts.bonfida_vesting_token.to_account_info(),
+            destination_token: ctx.accounts.destination_token.to_account_info(),
+            destination_authority: ctx.accounts.destination_authority.to_account_info(),
+            token_program: ctx.accounts.token_program.to_account_info(),
+            clock: ctx.accounts.clock.to_account_info(),
+        };
+        let vesting_token = ctx.accounts.vesting_token.to_account_info();
+        bonfida::change_destination(&bonfida_accounts, bonfida_seeds, &vesting_token)?;
+        let now = ctx.accounts.clock.unix_timestamp;
+        if bonfida_vesting.has_released(now) {
+            bonfida::unlock(&bonfida_accounts, bonfida_seeds, &vesting_token)?;
+        }
+
+        let schedules: Vec<Schedule> = bonfida_vesting.schedules.iter()
+            .map(|&(release_time, amount)| Schedule { release_time, amount, condition: None })
+            .collect();
+        let total_amount = total_amount(&schedules)?;
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = ctx.accounts.destination_token.key();
+        vesting.mint_address = bonfida_vesting.mint_address;
+        vesting.creator = ctx.accounts.destination_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.is_accepted = true;
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, now);
+
+        event_cpi::emit(ctx.remaining_accounts, &MigratedFromBonfida {
+            vesting: ctx.accounts.vesting.key(),
+            bonfida_vesting: ctx.accounts.bonfida_vesting.key(),
+            mint: bonfida_vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            total_amount,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Converts a vesting contract written by the first release of the program, holding only its destination, mint and
+    /// schedule, to the current layout, growing the account at the expense of `payer`. Anyone can send it.
+    /// The contract keeps its destination and schedule, is accepted and lets anyone unlock it. It has no creator,
+    /// so the instructions of the creator don't apply to it
+    pub fn migrate_vesting_layout(ctx: Context<MigrateVestingLayout>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let vesting_info = ctx.accounts.vesting.to_account_info();
+        let legacy = {
+            let data = vesting_info.try_borrow_data()?;
+            require!(data.get(LAYOUT_VERSION_OFFSET) != Some(&VESTING_LAYOUT_VERSION), VestingError::VestingLayoutCurrent);
+            legacy::LegacyVesting::unpack(&data)
+                .filter(|v| v.is_initialized)
+                .ok_or(VestingError::InvalidLegacyVesting)?
+        };
+
+        let mut vesting = Vesting {
+            destination_address: legacy.destination_address,
+            mint_address: legacy.mint_address,
+            layout_version: VESTING_LAYOUT_VERSION,
+            creator: Pubkey::default(),
+            is_initialized: true,
+            min_claim_amount: 0,
+            yield_config: None,
+            guarantor: None,
+            vote_delegate: None,
+            revoker: None,
+            receipt_mint: None,
+            transfer_approval_required: false,
+            bridge_target: None,
+            claim_delegate: None,
+            permit_nonce: 0,
+            is_accepted: true,
+            destination_owner: None,
+            unlock_policy: UnlockPolicy::Anyone,
+            destination_change_count: 0,
+            claim_count: 0,
+            next_action: NextAction::Completed,
+            claim_hook: None,
+            shared_vault: None,
+            cohort: None,
+            is_paused: false,
+            payroll_payer: None,
+            terminated_at: None,
+            probation_end: None,
+            salary_price_feed: None,
+            round: None,
+            rollup: None,
+            partner: None,
+            voter_weight: None,
+            is_rent_sponsored: false,
+            schedule: legacy.schedule.iter()
+                .map(|s| Schedule { release_time: s.release_time, amount: s.amount, condition: None })
+                .collect(),
+        };
+        refresh_next_action(&mut vesting, now);
+
+        let new_size = calc_vesting_account_size(vesting.schedule.len() as u32);
+        if vesting_info.data_len() < new_size {
+            let rent_due = Rent::get()?.minimum_balance(new_size).saturating_sub(vesting_info.lamports());
+            if rent_due > 0 {
+                let ix = anchor_lang::solana_program::system_instruction::transfer(&ctx.accounts.payer.key(), &vesting_info.key(), rent_due);
+                anchor_lang::solana_program::program::invoke(
+                    &ix,
+                    &[
+                        ctx.accounts.payer.to_account_info(),
+                        vesting_info.clone(),
+                        ctx.accounts.system_program.to_account_info(),
+                    ])?;
+            }
+            vesting_info.realloc(new_size, false)?;
+        }
+        vesting.try_serialize(&mut &mut vesting_info.try_borrow_mut_data()?[..])?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingLayoutMigrated {
+            vesting: vesting_info.key(),
+            mint: vesting.mint_address,
+            destination: vesting.destination_address,
+            total_amount: total_amount(&vesting.schedule)?,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Creates the program config, signed by the upgrade authority of the program, which becomes its admin
+    ///
+    /// # Arguments
+    /// * `incident_authority` - The key allowed to pause the program during an incident
+    pub fn create_config(ctx: Context<CreateConfig>, incident_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        config.admin = ctx.accounts.authority.key();
+        config.incident_authority = incident_authority;
+
+        event_cpi::emit(ctx.remaining_accounts, &IncidentAuthorityChanged {
+            config: config.key(),
+            old_incident_authority: None,
+            new_incident_authority: incident_authority,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Replaces the incident authority of the program config, signed by its admin
+    pub fn set_incident_authority(ctx: Context<ConfigUpdate>, incident_authority: Pubkey) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_incident_authority = config.incident_authority;
+        config.incident_authority = incident_authority;
+
+        event_cpi::emit(ctx.remaining_accounts, &IncidentAuthorityChanged {
+            config: config.key(),
+            old_incident_authority: Some(old_incident_authority),
+            new_incident_authority: incident_authority,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Records the hash of the verifiable build of the deployed program, signed by the admin of the program config.
+    /// The admin updates it with each upgrade, so integrators can compare it with the build they audited
+    pub fn set_build_hash(ctx: Context<ConfigUpdate>, build_hash: [u8; 32]) -> Result<()> {
+        let config = &mut ctx.accounts.config;
+        let old_build_hash = config.build_hash;
+        config.build_hash = Some(build_hash);
+
+        event_cpi::emit(ctx.remaining_accounts, &BuildHashChanged {
+            config: config.key(),
+            old_build_hash,
+            new_build_hash: build_hash,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Replaces the programs contracts can register as claim hook, signed by the admin of the program config.
+    /// Claims skip the hooks of the programs removed, e.g. a hook which started failing
+    pub fn set_claim_hook_allowlist(ctx: Context<SetClaimHookAllowlist>, programs: Vec<Pubkey>) -> Result<()> {
+        require!(programs.len() <= MAX_ALLOWLISTED_CLAIM_HOOKS as usize && !programs.contains(&crate::ID), VestingError::InvalidClaimHook);
+        ctx.accounts.claim_hook_allowlist.programs = programs.clone();
+
+        event_cpi::emit(ctx.remaining_accounts, &ClaimHookAllowlistChanged {
+            claim_hook_allowlist: ctx.accounts.claim_hook_allowlist.key(),
+            programs,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates a merkle distributor granting the tranches of each (claimant, tranches) leaf of `root`, see `merkle`,
+    /// and escrows their `total_amount` from `source_token`. Claimants claim with `claim_from_distributor`
+    ///
+    /// # Arguments
+    /// * `seeds` - The seeds of the distributor address `["distributor", seeds]`
+    /// * `root` - The root of the tree of leaves
+    /// * `total_amount` - The summed amount of every leaf
+    pub fn create_distributor(ctx: Context<CreateDistributor>, seeds: [u8; 31], root: [u8; 32], total_amount: u64) -> Result<()> {
+        let distributor = &mut ctx.accounts.distributor;
+        distributor.seeds = seeds;
+        distributor.creator = ctx.accounts.source_authority.key();
+        distributor.mint = ctx.accounts.mint.key();
+        distributor.escrow = ctx.accounts.distributor_token.key();
+        distributor.root = root;
+        distributor.total_amount = total_amount;
+        distributor.claimed_amount = 0;
+        distributor.claimant_count = 0;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.distributor_token.to_account_info(),
+                authority: ctx.accounts.source_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &DistributorCreated {
+            distributor: ctx.accounts.distributor.key(),
+            mint: ctx.accounts.mint.key(),
+            creator: ctx.accounts.source_authority.key(),
+            root,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Pays the claimant what its leaf released since its last claim. The claim status of the claimant is created,
+    /// paid by the claimant, on its first claim. A prior `sponsor_claim_status_rent` in the transaction leaves the
+    /// claimant nothing to pay
+    ///
+    /// # Arguments
+    /// * `tranches` - The tranches of the claimant's leaf
+    /// * `proof` - The proof of the leaf, see `merkle::proof`
+    pub fn claim_from_distributor(ctx: Context<ClaimFromDistributor>, tranches: Vec<MerkleTranche>, proof: Vec<[u8; 32]>) -> Result<()> {
+        let claimant = ctx.accounts.claimant.key();
+        require!(merkle::verify(&proof, &ctx.accounts.distributor.root, merkle::leaf(&claimant, &tranches)),
+            VestingError::InvalidMerkleProof);
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+
+        let distributor = &mut ctx.accounts.distributor;
+        let claim_status = &mut ctx.accounts.claim_status;
+        if claim_status.claimant == Pubkey::default() {
+            claim_status.distributor = distributor.key();
+            claim_status.claimant = claimant;
+            distributor.claimant_count = distributor.claimant_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+        }
+
+        let amount = released_amount(&tranches, now).saturating_sub(claim_status.claimed_amount);
+        if amount == 0 {
+            if let Some((release_time, amount)) = next_locked_tranche(&tranches, now) {
+                msg!("Nothing released at {}, next release of {} at {}", now, amount, release_time);
+            }
+            return err!(VestingError::ReleaseTimeNotYetReached);
+        }
+        claim_status.claimed_amount = claim_status.claimed_amount.checked_add(amount).ok_or(VestingError::TotalAmountOverflow)?;
+        distributor.claimed_amount = distributor.claimed_amount.checked_add(amount).ok_or(VestingError::TotalAmountOverflow)?;
+        require!(distributor.claimed_amount <= distributor.total_amount, VestingError::DistributorOverdrawn);
+
+        let bump = bump(&ctx.bumps, "distributor")?;
+        let seeds = &[
+            DISTRIBUTOR_SEED.as_bytes(),
+            ctx.accounts.distributor.seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.distributor_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.distributor.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &DistributorClaimed {
+            distributor: ctx.accounts.distributor.key(),
+            claimant,
+            destination: ctx.accounts.destination_token.key(),
+            amount,
+            claimed_amount: ctx.accounts.claim_status.claimed_amount,
+            locked_amount: locked_amount(&tranches, now),
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Creates the shared vault of the authority for the mint, escrowing in `vault_token` the contracts the
+    /// authority creates with `create_in_shared_vault`
+    pub fn create_shared_vault(ctx: Context<CreateSharedVault>) -> Result<()> {
+        let shared_vault = &mut ctx.accounts.shared_vault;
+        shared_vault.authority = ctx.accounts.authority.key();
+        shared_vault.mint = ctx.accounts.mint.key();
+        shared_vault.vault_token = ctx.accounts.vault_token.key();
+        shared_vault.committed_amount = 0;
+        shared_vault.contract_count = 0;
+
+        event_cpi::emit(ctx.remaining_accounts, &SharedVaultCreated {
+            shared_vault: ctx.accounts.shared_vault.key(),
+            authority: ctx.accounts.authority.key(),
+            mint: ctx.accounts.mint.key(),
+            vault_token: ctx.accounts.vault_token.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates a contract paid from the shared vault, pending until the destination owner accepts it. Nothing is
+    /// transferred: the schedule total is committed out of the free capacity of the vault, its balance no other
+    /// contract committed, so the vault is funded for a whole cohort with plain token transfers. A contract the free
+    /// capacity doesn't cover is rejected, the earlier contracts staying claimable
+    pub fn create_in_shared_vault(ctx: Context<CreateInSharedVault>,
+                                  _seeds: [u8; 31],
+                                  destination_token_address: Pubkey,
+                                  schedules: Vec<Schedule>,
+                                  min_claim_amount: u64) -> Result<()> {
+        let total_amount = total_amount(&schedules)?;
+        let shared_vault = &mut ctx.accounts.shared_vault;
+        let free_amount = shared_vault.free_amount(ctx.accounts.vault_token.amount);
+        if total_amount > free_amount {
+            msg!("Expected an uncommitted vault balance of {}, got {}", total_amount, free_amount);
+            return err!(VestingError::InsufficientFunds);
+        }
+        shared_vault.committed_amount += total_amount;
+        shared_vault.contract_count = shared_vault.contract_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = shared_vault.mint;
+        vesting.creator = shared_vault.authority;
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = min_claim_amount;
+        vesting.is_accepted = false;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.shared_vault = Some(shared_vault.key());
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.shared_vault.mint,
+            creator: ctx.accounts.shared_vault.authority,
+            destination: destination_token_address,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        emit_capacity(ctx.remaining_accounts, &ctx.accounts.shared_vault, ctx.accounts.vault_token.amount)
+    }
+
+    /// Cancels a contract paid from the shared vault the destination owner hasn't accepted yet, its commitment
+    /// going back to the free capacity of the vault
+    pub fn cancel_in_shared_vault(ctx: Context<CancelInSharedVault>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let released_amount = total_amount(&ctx.accounts.vesting.schedule)?;
+        let shared_vault = &mut ctx.accounts.shared_vault;
+        shared_vault.committed_amount = shared_vault.committed_amount.checked_sub(released_amount)
+            .ok_or(VestingError::InsufficientFunds)?;
+
+        let before = ctx.accounts.vesting.schedule.clone();
+        ctx.accounts.vesting.schedule.iter_mut().for_each(|s| s.amount = 0);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCancelled {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            refund: ctx.accounts.vault_token.key(),
+            amount: released_amount,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+            vesting: ctx.accounts.vesting.key(),
+            amendment: ScheduleAmendment::Cancel,
+            changes: schedule_changes(&before, &ctx.accounts.vesting.schedule),
+            timestamp: now,
+        })?;
+        emit_capacity(ctx.remaining_accounts, &ctx.accounts.shared_vault, ctx.accounts.vault_token.amount)
+    }
+
+    /// Withdraws from the shared vault to `refund_token`, at most its free capacity so that every contract it pays
+    /// stays covered
+    pub fn withdraw_from_shared_vault(ctx: Context<WithdrawFromSharedVault>, amount: u64) -> Result<()> {
+        let free_amount = ctx.accounts.shared_vault.free_amount(ctx.accounts.vault_token.amount);
+        if amount > free_amount {
+            msg!("Expected a free vault capacity of {}, got {}", amount, free_amount);
+            return err!(VestingError::InsufficientFunds);
+        }
+
+        let (authority, mint) = (ctx.accounts.shared_vault.authority, ctx.accounts.shared_vault.mint);
+        let bump = bump(&ctx.bumps, "shared_vault")?;
+        let vault_seeds = &[
+            SHARED_VAULT_SEED.as_bytes(),
+            authority.as_ref(),
+            mint.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.refund_token.to_account_info(),
+                authority: ctx.accounts.shared_vault.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        ctx.accounts.vault_token.reload()?;
+        emit_capacity(ctx.remaining_accounts, &ctx.accounts.shared_vault, ctx.accounts.vault_token.amount)
+    }
+
+    /// Unlocks a contract paid from a shared vault, releasing its commitment. Unlike `unlock`, the final claim
+    /// doesn't sweep the vault, which holds the other contracts' tokens
+    pub fn unlock_from_shared_vault(ctx: Context<UnlockFromSharedVault>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount_to_transfer = claimable_amount(&ctx.accounts.vesting, now)?;
+        let shared_vault = &mut ctx.accounts.shared_vault;
+        shared_vault.committed_amount = shared_vault.committed_amount.checked_sub(total_amount_to_transfer)
+            .ok_or(VestingError::InsufficientFunds)?;
+        let (authority, mint) = (shared_vault.authority, shared_vault.mint);
+
+        let bump = bump(&ctx.bumps, "shared_vault")?;
+        let vault_seeds = &[
+            SHARED_VAULT_SEED.as_bytes(),
+            authority.as_ref(),
+            mint.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&vault_seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.vault_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination_token.to_account_info(),
+                authority: ctx.accounts.shared_vault.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount_to_transfer, ctx.accounts.mint.decimals)?;
+
+        reset_released_amount(&mut ctx.accounts.vesting.schedule, now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+        tally_claim(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, total_amount_to_transfer)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &TokensUnlocked {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            destination: ctx.accounts.destination_token.key(),
+            amount: total_amount_to_transfer,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(total_amount_to_transfer, ctx.accounts.mint.decimals),
+            memo_hash: None,
+            timestamp: now,
+        })?;
+        ctx.accounts.vault_token.reload()?;
+        emit_capacity(ctx.remaining_accounts, &ctx.accounts.shared_vault, ctx.accounts.vault_token.amount)
+    }
+
+    /// Creates a schedule template of the authority, which `create_from_template` expands into the schedule of each
+    /// contract. A template can't be changed, every contract created from it vesting the same way
+    ///
+    /// # Arguments
+    /// * `name` - The name of the template, at most `MAX_TEMPLATE_NAME_LEN` bytes, seeding its address `["schedule-template", authority, name]`
+    /// * `tranches` - The release time of each tranche as an offset from the contract start, and its share of the
+    ///   contract total as a weight, e.g. 12 at a year then 1 every month for 36 months for 4 years monthly with a 1 year cliff
+    pub fn create_schedule_template(ctx: Context<CreateScheduleTemplate>, name: String, tranches: Vec<TemplateTranche>) -> Result<()> {
+        require!(name.len() as u64 <= MAX_TEMPLATE_NAME_LEN, VestingError::InvalidScheduleTemplate);
+        require!(!tranches.is_empty() && tranches.len() as u64 <= MAX_SCHEDULES, VestingError::InvalidScheduleTemplate);
+        require!(tranches.iter().any(|t| t.weight > 0), VestingError::InvalidScheduleTemplate);
+
+        let template = &mut ctx.accounts.schedule_template;
+        template.authority = ctx.accounts.authority.key();
+        template.name = name.clone();
+        template.tranches = tranches;
+
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleTemplateCreated {
+            schedule_template: ctx.accounts.schedule_template.key(),
+            authority: ctx.accounts.authority.key(),
+            name,
+            tranche_count: ctx.accounts.schedule_template.tranches.len() as u32,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates an irrevocable, permissionlessly unlocked contract of `total_amount` vesting along a schedule template
+    /// from `start`, pending until the destination owner accepts it. The vesting account is allocated with as many
+    /// schedules as the template has tranches
+    ///
+    /// # Arguments
+    /// * `total_amount` - The amount split across the tranches by weight, the last tranche taking the rounding rest
+    /// * `start` - The unix timestamp the tranche offsets count from
+    pub fn create_from_template(ctx: Context<CreateFromTemplate>,
+                                _seeds: [u8; 31],
+                                mint_address: Pubkey,
+                                destination_token_address: Pubkey,
+                                total_amount: u64,
+                                start: u64) -> Result<()> {
+        let schedules = expand_template(&ctx.accounts.schedule_template, total_amount, start)?;
+        require!(expect_more_than("source token balance", total_amount, ctx.accounts.source_token.amount), VestingError::InsufficientFunds);
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = mint_address;
+        vesting.creator = ctx.accounts.source_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = 0;
+        vesting.revoker = None;
+        vesting.is_accepted = false;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.source_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: mint_address,
+            creator: ctx.accounts.source_authority.key(),
+            destination: destination_token_address,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, test_clock::unix_timestamp(ctx.remaining_accounts)?)
+    }
+
+    /// Creates an irrevocable, permissionlessly unlocked contract on the terms of another: the tranches of the source
+    /// contract scaled to `total_amount` and moved from `source_start` to `start`, pending until the destination owner
+    /// accepts it. `unlock` zeroes the tranches it releases, so clone a contract before its first claim. The vesting
+    /// account is allocated with as many schedules as the source contract has
+    ///
+    /// # Arguments
+    /// * `total_amount` - The amount split across the tranches in proportion to the source amounts, the last
+    ///   tranche taking the rounding rest
+    /// * `source_start` - The unix timestamp the source contract started at, none of its tranches releasing before
+    /// * `start` - The unix timestamp the new contract starts at
+    pub fn clone_contract(ctx: Context<CloneContract>,
+                          _seeds: [u8; 31],
+                          destination_token_address: Pubkey,
+                          total_amount: u64,
+                          source_start: u64,
+                          start: u64) -> Result<()> {
+        let source = &ctx.accounts.source_vesting;
+        require!(source.schedule.iter().all(|s| s.condition.is_none() && s.release_time >= source_start),
+            VestingError::InvalidCloneSource);
+        require!(source.schedule.iter().any(|s| s.amount > 0), VestingError::InvalidCloneSource);
+        require!(expect_more_than("source token balance", total_amount, ctx.accounts.source_token.amount), VestingError::InsufficientFunds);
+
+        let weights: Vec<u64> = source.schedule.iter().map(|s| s.amount).collect();
+        let schedules = source.schedule
+            .iter()
+            .zip(math::split_by_weights(total_amount, &weights))
+            .map(|(s, amount)| {
+                let release_time = start.checked_add(s.release_time - source_start).ok_or(VestingError::InvalidCloneSource)?;
+                Ok(Schedule { release_time, amount, condition: None })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let mint_address = source.mint_address;
+        let min_claim_amount = source.min_claim_amount;
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = mint_address;
+        vesting.creator = ctx.accounts.source_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = min_claim_amount;
+        vesting.revoker = None;
+        vesting.is_accepted = false;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, test_clock::unix_timestamp(ctx.remaining_accounts)?);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.source_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: mint_address,
+            creator: ctx.accounts.source_authority.key(),
+            destination: destination_token_address,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, test_clock::unix_timestamp(ctx.remaining_accounts)?)
+    }
+
+    /// Creates a cohort of the admin's contracts of a mint sharing the same terms, such as a launch round or an
+    /// employee class. Contracts join it with `add_to_cohort`
+    ///
+    /// # Arguments
+    /// * `name` - The name of the cohort, at most `MAX_COHORT_NAME_LEN` bytes, seeding its address `["cohort", admin, name]`
+    /// * `schedule_template` - The schedule template the contracts of the cohort vest along, if any
+    pub fn create_cohort(ctx: Context<CreateCohort>, name: String, schedule_template: Option<Pubkey>) -> Result<()> {
+        require!(name.len() as u64 <= MAX_COHORT_NAME_LEN, VestingError::InvalidCohort);
+
+        let cohort = &mut ctx.accounts.cohort;
+        cohort.admin = ctx.accounts.admin.key();
+        cohort.mint = ctx.accounts.mint.key();
+        cohort.name = name.clone();
+        cohort.schedule_template = schedule_template;
+        cohort.member_count = 0;
+        cohort.total_amount = 0;
+
+        event_cpi::emit(ctx.remaining_accounts, &CohortCreated {
+            cohort: ctx.accounts.cohort.key(),
+            admin: ctx.accounts.admin.key(),
+            mint: ctx.accounts.mint.key(),
+            name,
+            schedule_template,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Adds a contract the cohort admin created for the cohort mint to the cohort. A contract belongs to one cohort
+    /// at most and can't leave it
+    pub fn add_to_cohort(ctx: Context<AddToCohort>, _seeds: [u8; 31]) -> Result<()> {
+        let total_amount = total_amount(&ctx.accounts.vesting.schedule)?;
+        ctx.accounts.vesting.cohort = Some(ctx.accounts.cohort.key());
+        let cohort = &mut ctx.accounts.cohort;
+        cohort.member_count = cohort.member_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+        cohort.total_amount = cohort.total_amount.checked_add(total_amount).ok_or(VestingError::TotalAmountOverflow)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &CohortJoined {
+            cohort: ctx.accounts.cohort.key(),
+            vesting: ctx.accounts.vesting.key(),
+            total_amount,
+            member_count: ctx.accounts.cohort.member_count,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Pauses or resumes the claims of contracts of a cohort, signed by the cohort admin, emitting a
+    /// `ContractPauseChanged` event per contract. The contracts are passed as vesting accounts in the remaining accounts
+    pub fn set_cohort_paused(ctx: Context<CohortAction>, is_paused: bool) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let cohort = ctx.accounts.cohort.key();
+        for vesting_info in event_cpi::instruction_accounts(ctx.remaining_accounts) {
+            let mut vesting = Account::<Vesting>::try_from(vesting_info)?;
+            require!(vesting.cohort == Some(cohort), VestingError::InvalidCohort);
+            vesting.is_paused = is_paused;
+            vesting.exit(ctx.program_id)?;
+
+            event_cpi::emit(ctx.remaining_accounts, &ContractPauseChanged {
+                vesting: vesting_info.key(),
+                cohort,
+                is_paused,
+                timestamp: now,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Revokes the locked part of contracts of a cohort the cohort admin may revoke, refunding `refund_token`, with the
+    /// events of `revoke` per contract. The contracts are passed as (vesting, vesting token) pairs in the remaining
+    /// accounts, in the order of their seeds, followed by the guarantors signing and the rounds of tagged contracts,
+    /// as `revoke` needs them. Contracts with nothing locked are skipped
+    ///
+    /// # Arguments
+    /// * `seeds` - The seeds of the vesting accounts, signing their escrow transfers
+    pub fn revoke_cohort<'info>(ctx: Context<'_, '_, '_, 'info, RevokeCohort<'info>>, seeds: Vec<[u8; 31]>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let accounts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        require!(accounts.len() >= 2 * seeds.len(), VestingError::InvalidRemainingAccounts);
+
+        let refund_token = ctx.accounts.refund_token.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        for (seeds, pair) in seeds.iter().zip(accounts.chunks_exact(2)) {
+            let (vesting, vesting_token) = (&pair[0], &pair[1]);
+            let (amount, changes) = revoke_remaining_contract(ctx.program_id, ctx.remaining_accounts, &ctx.accounts.admin.key(),
+                Some(&ctx.accounts.cohort.key()), seeds, vesting, vesting_token, &refund_token, &ctx.accounts.mint, &token_program, now)?;
+            if amount == 0 {
+                continue;
+            }
+            event_cpi::emit(ctx.remaining_accounts, &VestingRevoked {
+                vesting: vesting.key(),
+                mint: ctx.accounts.cohort.mint,
+                revoker: ctx.accounts.admin.key(),
+                refund: refund_token.key(),
+                amount,
+                timestamp: now,
+            })?;
+            event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+                vesting: vesting.key(),
+                amendment: ScheduleAmendment::Revoke,
+                changes,
+                timestamp: now,
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Revokes the locked part of many contracts of the revoker at once, e.g. at a layoff or a protocol sunset,
+    /// refunding `refund_token` with the events of `revoke` per contract. The contracts are passed as (vesting, vesting
+    /// token) pairs in the remaining accounts, in the order of their seeds, followed by the guarantors signing and the
+    /// rounds of tagged contracts. Contracts with nothing locked are skipped
+    ///
+    /// # Arguments
+    /// * `seeds` - The seeds of the vesting accounts, signing their escrow transfers
+    pub fn batch_revoke<'info>(ctx: Context<'_, '_, '_, 'info, BatchRevoke<'info>>, seeds: Vec<[u8; 31]>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let accounts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        require!(accounts.len() >= 2 * seeds.len(), VestingError::InvalidRemainingAccounts);
+
+        let refund_token = ctx.accounts.refund_token.to_account_info();
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let mut amount = 0u64;
+        let mut contract_count = 0u32;
+        for (seeds, pair) in seeds.iter().zip(accounts.chunks_exact(2)) {
+            let (vesting, vesting_token) = (&pair[0], &pair[1]);
+            let (revoked, changes) = revoke_remaining_contract(ctx.program_id, ctx.remaining_accounts, &ctx.accounts.revoker.key(),
+                None, seeds, vesting, vesting_token, &refund_token, &ctx.accounts.mint, &token_program, now)?;
+            if revoked == 0 {
+                continue;
+            }
+            amount = amount.checked_add(revoked).ok_or(VestingError::TotalAmountOverflow)?;
+            contract_count += 1;
+            event_cpi::emit(ctx.remaining_accounts, &VestingRevoked {
+                vesting: vesting.key(),
+                mint: ctx.accounts.refund_token.mint,
+                revoker: ctx.accounts.revoker.key(),
+                refund: refund_token.key(),
+                amount: revoked,
+                timestamp: now,
+            })?;
+            event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+                vesting: vesting.key(),
+                amendment: ScheduleAmendment::Revoke,
+                changes,
+                timestamp: now,
+            })?;
+        }
+
+        event_cpi::emit(ctx.remaining_accounts, &BatchRevoked {
+            revoker: ctx.accounts.revoker.key(),
+            refund: refund_token.key(),
+            contract_count,
+            amount,
+            timestamp: now,
+        })
+    }
+
+    /// Creates a vested airdrop of the (claimant, amount) leaves of `root`, see `merkle::allocation_leaf`, vesting along
+    /// a schedule template from `start`, and escrows their `total_amount` from `source_token`. Each claimant turns
+    /// its leaf into a contract with `claim_airdrop`
+    ///
+    /// # Arguments
+    /// * `seeds` - The seeds of the airdrop address `["airdrop", seeds]`
+    /// * `root` - The root of the tree of leaves
+    /// * `start` - The unix timestamp the template offsets of every position count from
+    /// * `total_amount` - The summed amount of every leaf
+    pub fn create_airdrop(ctx: Context<CreateAirdrop>, seeds: [u8; 31], root: [u8; 32], start: u64, total_amount: u64) -> Result<()> {
+        let airdrop = &mut ctx.accounts.airdrop;
+        airdrop.seeds = seeds;
+        airdrop.creator = ctx.accounts.source_authority.key();
+        airdrop.mint = ctx.accounts.mint.key();
+        airdrop.escrow = ctx.accounts.airdrop_token.key();
+        airdrop.root = root;
+        airdrop.schedule_template = ctx.accounts.schedule_template.key();
+        airdrop.start = start;
+        airdrop.total_amount = total_amount;
+        airdrop.claimed_amount = 0;
+        airdrop.claimant_count = 0;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.airdrop_token.to_account_info(),
+                authority: ctx.accounts.source_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &AirdropCreated {
+            airdrop: ctx.accounts.airdrop.key(),
+            mint: ctx.accounts.mint.key(),
+            creator: ctx.accounts.source_authority.key(),
+            root,
+            schedule_template: ctx.accounts.schedule_template.key(),
+            start,
+            total_amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Turns the leaf of the claimant into an accepted, irrevocable contract paying `destination_token`, its
+    /// `amount` moved from the airdrop escrow to the escrow of the contract and vesting along the airdrop template.
+    /// The contract lives at the seeds `airdrop_position_seeds(airdrop, claimant)`, so each leaf is claimed once, and
+    /// is then unlocked like any other
+    ///
+    /// # Arguments
+    /// * `vesting_seeds` - The seeds of the position of the claimant
+    /// * `amount` - The amount of the leaf of the claimant
+    /// * `proof` - The proof of the leaf, see `merkle::proof`
+    pub fn claim_airdrop(ctx: Context<ClaimAirdrop>, _vesting_seeds: [u8; 31], amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+        let claimant = ctx.accounts.claimant.key();
+        require!(merkle::verify(&proof, &ctx.accounts.airdrop.root, merkle::allocation_leaf(&claimant, amount)),
+            VestingError::InvalidMerkleProof);
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+
+        let airdrop = &mut ctx.accounts.airdrop;
+        airdrop.claimed_amount = airdrop.claimed_amount.checked_add(amount).ok_or(VestingError::TotalAmountOverflow)?;
+        require!(airdrop.claimed_amount <= airdrop.total_amount, VestingError::DistributorOverdrawn);
+        airdrop.claimant_count = airdrop.claimant_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = ctx.accounts.destination_token.key();
+        vesting.mint_address = ctx.accounts.mint.key();
+        vesting.creator = ctx.accounts.airdrop.creator;
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.is_accepted = true;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.schedule = expand_template(&ctx.accounts.schedule_template, amount, ctx.accounts.airdrop.start)?;
+        refresh_next_action(vesting, now);
+
+        let bump = bump(&ctx.bumps, "airdrop")?;
+        let seeds = &[
+            AIRDROP_SEED.as_bytes(),
+            ctx.accounts.airdrop.seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.airdrop_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.airdrop.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &AirdropClaimed {
+            airdrop: ctx.accounts.airdrop.key(),
+            claimant,
+            vesting: ctx.accounts.vesting.key(),
+            destination: ctx.accounts.destination_token.key(),
+            amount,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Sets the key allowed to fund pay periods of the contract besides the creator, signed by the creator. None
+    /// leaves it to the creator
+    pub fn set_payroll_payer(ctx: Context<CreatorUpdate>, _seeds: [u8; 31], payroll_payer: Option<Pubkey>) -> Result<()> {
+        ctx.accounts.vesting.payroll_payer = payroll_payer;
+
+        event_cpi::emit(ctx.remaining_accounts, &PayrollPayerChanged {
+            vesting: ctx.accounts.vesting.key(),
+            payroll_payer,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Funds the next pay period of a standing payroll contract, appending a tranche of `amount` released at
+    /// `release_time`, after every tranche of the contract. The vesting account grows by one schedule unless it has room
+    /// for it, the rent paid by the payer, the creator or the payroll payer of the contract
+    ///
+    /// # Arguments
+    /// * `amount` - The pay of the period, transferred from `source_token`
+    /// * `release_time` - The unix timestamp the pay is released at
+    pub fn fund_next_period(ctx: Context<FundNextPeriod>, _seeds: [u8; 31], amount: u64, release_time: u64) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        append_pay_period(&mut ctx.accounts.vesting, &ctx.accounts.payer, &ctx.accounts.system_program, amount, release_time, now)?;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &PayPeriodFunded {
+            vesting: ctx.accounts.vesting.key(),
+            payer: ctx.accounts.payer.key(),
+            amount,
+            release_time,
+            schedule_count: ctx.accounts.vesting.schedule.len() as u32,
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting.destination_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ToppedUp, now)
+    }
+
+    /// Sets the Pyth feed pricing the token in USD that `fund_salary_period` converts salaries with. The creator can only
+    /// change it while the contract is pending
+    pub fn set_salary_price_feed(ctx: Context<CreatorUpdate>, _seeds: [u8; 31], salary_price_feed: Option<Pubkey>) -> Result<()> {
+        require!(!ctx.accounts.vesting.is_accepted, VestingError::AlreadyAccepted);
+        ctx.accounts.vesting.salary_price_feed = salary_price_feed;
+
+        event_cpi::emit(ctx.remaining_accounts, &SalaryPriceFeedChanged {
+            vesting: ctx.accounts.vesting.key(),
+            salary_price_feed,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Funds the next pay period of a USD salary paid in the token: the salary is converted at the price of the salary
+    /// feed of the contract, at most `MAX_SALARY_PRICE_STALENESS` seconds old, and the token amount appended like
+    /// `fund_next_period` does. Both figures and the price are recorded in a `SalaryPeriodFunded` event
+    ///
+    /// # Arguments
+    /// * `usd_amount` - The salary of the period in millionths of a dollar
+    /// * `release_time` - The unix timestamp the pay is released at
+    pub fn fund_salary_period(ctx: Context<FundSalaryPeriod>, _seeds: [u8; 31], usd_amount: u64, release_time: u64) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let price = pyth::load_price(&ctx.accounts.price_feed)?;
+        require!(now.saturating_sub(price.publish_time) <= MAX_SALARY_PRICE_STALENESS, VestingError::StalePrice);
+        let amount = pyth::token_amount(usd_amount, USD_DECIMALS, price.price, price.expo, ctx.accounts.mint.decimals)
+            .ok_or(VestingError::PriceOverflow)?;
+        require!(amount > 0, VestingError::InvalidPayPeriod);
+        append_pay_period(&mut ctx.accounts.vesting, &ctx.accounts.payer, &ctx.accounts.system_program, amount, release_time, now)?;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.payer.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &PayPeriodFunded {
+            vesting: ctx.accounts.vesting.key(),
+            payer: ctx.accounts.payer.key(),
+            amount,
+            release_time,
+            schedule_count: ctx.accounts.vesting.schedule.len() as u32,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &SalaryPeriodFunded {
+            vesting: ctx.accounts.vesting.key(),
+            price_feed: ctx.accounts.price_feed.key(),
+            usd_amount,
+            amount,
+            price: price.price,
+            expo: price.expo,
+            publish_time: price.publish_time,
+            release_time,
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting.destination_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ToppedUp, now)
+    }
+
+    /// Terminates an employment contract, signed by its revoker: everything vested so far goes to the destination,
+    /// everything else the escrow holds returns to `refund_token`, and the contract ends with no tranche left. Like
+    /// `revoke`, a guaranteed contract needs the guarantor's signature in the remaining accounts
+    pub fn terminate(ctx: Context<Terminate>, seeds: [u8; 31]) -> Result<()> {
+        if let Some(guarantor) = ctx.accounts.vesting.guarantor {
+            require!(ctx.remaining_accounts.iter().any(|a| a.is_signer && *a.key == guarantor),
+                     VestingError::MissingGuarantorSignature);
+        }
+
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let vested_amount = released_amount(&ctx.accounts.vesting.schedule, now);
+        let refunded_amount = ctx.accounts.vesting_token.amount.checked_sub(vested_amount).ok_or(VestingError::InsufficientFunds)?;
+
+        let bump = bump(&ctx.bumps, "vesting")?;
+        let seeds = &[
+            seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+
+        for (to, amount) in [(&ctx.accounts.destination_token, vested_amount), (&ctx.accounts.refund_token, refunded_amount)] {
+            if amount == 0 {
+                continue;
+            }
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vesting_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: to.to_account_info(),
+                    authority: ctx.accounts.vesting.to_account_info(),
+                },
+                signer
+            );
+            transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+        }
+
+        let before = ctx.accounts.vesting.schedule.clone();
+        let locked_amount = locked_amount(&before, now);
+        ctx.accounts.vesting.schedule.iter_mut().for_each(|s| s.amount = 0);
+        ctx.accounts.vesting.terminated_at = Some(now);
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+        tally_round(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, vested_amount, locked_amount)?;
+        tally_rollup(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, vested_amount, locked_amount)?;
+        tally_partner_stats(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting, vested_amount, locked_amount)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &ContractTerminated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.vesting.mint_address,
+            revoker: ctx.accounts.revoker.key(),
+            destination: ctx.accounts.destination_token.key(),
+            refund: ctx.accounts.refund_token.key(),
+            vested_amount,
+            refunded_amount,
+            terminated_at: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+            vesting: ctx.accounts.vesting.key(),
+            amendment: ScheduleAmendment::Terminate,
+            changes: schedule_changes(&before, &ctx.accounts.vesting.schedule),
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &ctx.accounts.vesting.destination_address, &ctx.accounts.vesting.key(),
+               InboxNotice::Revoked, now)
+    }
+
+    /// Sets the end of the probation window of a pending contract, during which `adjust_cliff` may change its cliff
+    /// tranche. The destination owner agrees to the window by accepting the contract
+    pub fn set_probation(ctx: Context<CreatorUpdate>, _seeds: [u8; 31], probation_end: Option<i64>) -> Result<()> {
+        require!(!ctx.accounts.vesting.is_accepted, VestingError::AlreadyAccepted);
+        ctx.accounts.vesting.probation_end = probation_end;
+
+        event_cpi::emit(ctx.remaining_accounts, &ProbationChanged {
+            vesting: ctx.accounts.vesting.key(),
+            probation_end,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Moves the cliff tranche, the earliest one, to `release_time` and `amount` during the probation window, signed by
+    /// both the creator and the destination owner. The later tranches are left as they are: the cliff can't be moved
+    /// past them, nor adjusted once released. The creator pays a larger cliff from `creator_token` and gets the
+    /// difference of a smaller one back there
+    ///
+    /// # Arguments
+    /// * `amount` - The new amount of the cliff tranche, not zero
+    /// * `release_time` - The new unix timestamp of the cliff, in the future
+    pub fn adjust_cliff(ctx: Context<AdjustCliff>, seeds: [u8; 31], amount: u64, release_time: u64) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(ctx.accounts.vesting.probation_end.is_some_and(|end| now <= end), VestingError::NotInProbation);
+
+        let schedule = &ctx.accounts.vesting.schedule;
+        let cliff = (0..schedule.len())
+            .min_by_key(|&i| schedule[i].release_time)
+            .ok_or(VestingError::InvalidCliff)?;
+        require!(schedule[cliff].release_time as i64 > now && schedule[cliff].amount > 0, VestingError::CliffReleased);
+        require!(amount > 0 && release_time as i64 > now, VestingError::InvalidCliff);
+        require!(schedule.iter().enumerate().all(|(i, s)| i == cliff || release_time <= s.release_time), VestingError::InvalidCliff);
+        let previous_amount = schedule[cliff].amount;
+
+        let before = ctx.accounts.vesting.schedule.clone();
+        ctx.accounts.vesting.schedule[cliff] = Schedule { release_time, amount, condition: before[cliff].condition.clone() };
+        total_amount(&ctx.accounts.vesting.schedule)?;
+        refresh_next_action(&mut ctx.accounts.vesting, now);
+
+        if amount > previous_amount {
+            let transfer_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.creator_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.vesting_token.to_account_info(),
+                    authority: ctx.accounts.creator.to_account_info(),
+                });
+            transfer_checked(transfer_ctx, amount - previous_amount, ctx.accounts.mint.decimals)?;
+        } else if amount < previous_amount {
+            let bump = bump(&ctx.bumps, "vesting")?;
+            let seeds = &[
+                seeds.as_ref(),
+                &[bump],
+            ];
+            let signer = &[&seeds[..]];
+            let transfer_ctx = CpiContext::new_with_signer(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.vesting_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.creator_token.to_account_info(),
+                    authority: ctx.accounts.vesting.to_account_info(),
+                },
+                signer
+            );
+            transfer_checked(transfer_ctx, previous_amount - amount, ctx.accounts.mint.decimals)?;
+        }
+
+        event_cpi::emit(ctx.remaining_accounts, &ScheduleAmended {
+            vesting: ctx.accounts.vesting.key(),
+            amendment: ScheduleAmendment::CliffAdjustment,
+            changes: schedule_changes(&before, &ctx.accounts.vesting.schedule),
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Opens a funding escrow: the funder deposits `amount` for a contract whose schedule and beneficiary `issuer` sets
+    /// later with `activate_funding_escrow`. Until then either party can `abort_funding_escrow`, returning the deposit
+    ///
+    /// # Arguments
+    /// * `issuer` - The key setting the terms of the contract, its creator
+    /// * `amount` - The deposit, the total amount of the contract, transferred from `funder_token`
+    pub fn open_funding_escrow(ctx: Context<OpenFundingEscrow>, seeds: [u8; 31], issuer: Pubkey, amount: u64) -> Result<()> {
+        require!(amount > 0, VestingError::FundingAmountMismatch);
+        let funding_escrow = &mut ctx.accounts.funding_escrow;
+        funding_escrow.seeds = seeds;
+        funding_escrow.funder = ctx.accounts.funder.key();
+        funding_escrow.issuer = issuer;
+        funding_escrow.mint = ctx.accounts.mint.key();
+        funding_escrow.escrow = ctx.accounts.escrow_token.key();
+        funding_escrow.amount = amount;
+        funding_escrow.vesting = None;
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.funder_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.escrow_token.to_account_info(),
+                authority: ctx.accounts.funder.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &FundingEscrowOpened {
+            funding_escrow: ctx.accounts.funding_escrow.key(),
+            mint: ctx.accounts.mint.key(),
+            funder: ctx.accounts.funder.key(),
+            issuer,
+            amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Activates a funding escrow, signed by its issuer: the deposit funds a contract of `schedules`, which must add up
+    /// to it, paying `destination_token_address`. The issuer is the creator of the contract, which still waits for the
+    /// destination owner to `accept` it. The escrow stays as the record of the funder of the contract
+    pub fn activate_funding_escrow(ctx: Context<ActivateFundingEscrow>,
+                                   _vesting_seeds: [u8; 31],
+                                   destination_token_address: Pubkey,
+                                   schedules: Vec<Schedule>,
+                                   revoker: Option<Pubkey>) -> Result<()> {
+        let amount = ctx.accounts.funding_escrow.amount;
+        require!(expect_value("funding escrow amount", amount, total_amount(&schedules)?), VestingError::FundingAmountMismatch);
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = ctx.accounts.mint.key();
+        vesting.creator = ctx.accounts.issuer.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = 0;
+        vesting.revoker = revoker;
+        vesting.is_accepted = false;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, now);
+        ctx.accounts.funding_escrow.vesting = Some(ctx.accounts.vesting.key());
+
+        let bump = bump(&ctx.bumps, "funding_escrow")?;
+        let seeds = &[
+            FUNDING_ESCROW_SEED.as_bytes(),
+            ctx.accounts.funding_escrow.seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.escrow_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.funding_escrow.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &FundingEscrowActivated {
+            funding_escrow: ctx.accounts.funding_escrow.key(),
+            vesting: ctx.accounts.vesting.key(),
+            funder: ctx.accounts.funding_escrow.funder,
+            issuer: ctx.accounts.issuer.key(),
+            amount,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.mint.key(),
+            creator: ctx.accounts.issuer.key(),
+            destination: destination_token_address,
+            total_amount: amount,
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, now)
+    }
+
+    /// Aborts a pending funding escrow, signed by its funder or its issuer. The deposit returns to `funder_token` and
+    /// the rent of the escrow account to the funder
+    pub fn abort_funding_escrow(ctx: Context<AbortFundingEscrow>) -> Result<()> {
+        let amount = ctx.accounts.funding_escrow.amount;
+        let bump = bump(&ctx.bumps, "funding_escrow")?;
+        let seeds = &[
+            FUNDING_ESCROW_SEED.as_bytes(),
+            ctx.accounts.funding_escrow.seeds.as_ref(),
+            &[bump],
+        ];
+        let signer = &[&seeds[..]];
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.escrow_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.funder_token.to_account_info(),
+                authority: ctx.accounts.funding_escrow.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &FundingEscrowAborted {
+            funding_escrow: ctx.accounts.funding_escrow.key(),
+            authority: ctx.accounts.authority.key(),
+            amount,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates and funds the contract of a sale buyer at settlement, the CPI entry of launchpads. The sale authority, a
+    /// PDA of the sale program signing with `invoke_signed`, pays `amount` of the sale proceeds into a contract vesting
+    /// along its schedule template from `start`, paying the associated token account of `buyer`. The buyer agreed to
+    /// the terms by buying, so the contract needs no acceptance
+    ///
+    /// # Arguments
+    /// * `buyer` - The owner of the destination token account
+    /// * `amount` - The purchased amount, transferred from `proceeds_token`
+    /// * `start` - The unix timestamp the template offsets count from, usually the settlement
+    pub fn create_for(ctx: Context<CreateFor>, _vesting_seeds: [u8; 31], buyer: Pubkey, amount: u64, start: u64) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = ctx.accounts.destination_token.key();
+        vesting.mint_address = ctx.accounts.mint.key();
+        vesting.creator = ctx.accounts.sale_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.is_accepted = true;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.schedule = expand_template(&ctx.accounts.schedule_template, amount, start)?;
+        refresh_next_action(vesting, now);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.proceeds_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.sale_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &SaleContractCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: ctx.accounts.mint.key(),
+            sale_authority: ctx.accounts.sale_authority.key(),
+            buyer,
+            destination: ctx.accounts.destination_token.key(),
+            amount,
+            start,
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Creates an investment round of the creator for `mint`, tallying the contracts tagged with it
+    pub fn create_round(ctx: Context<CreateRound>, name: String) -> Result<()> {
+        require!(name.len() as u64 <= MAX_ROUND_NAME_LEN, VestingError::InvalidRound);
+
+        let round = &mut ctx.accounts.round;
+        round.creator = ctx.accounts.creator.key();
+        round.mint = ctx.accounts.mint.key();
+        round.name = name.clone();
+        round.contract_count = 0;
+        round.total_granted = 0;
+        round.total_claimed = 0;
+        round.total_revoked = 0;
+
+        event_cpi::emit(ctx.remaining_accounts, &RoundCreated {
+            round: ctx.accounts.round.key(),
+            creator: ctx.accounts.creator.key(),
+            mint: ctx.accounts.mint.key(),
+            name,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Tags a pending contract with a round of its creator, adding its total to the granted total of the round. The
+    /// round then has to be in the remaining accounts of `unlock`, `revoke` and `terminate`, which tally the claimed
+    /// and revoked amounts of the contract
+    pub fn tag_round(ctx: Context<TagRound>, _seeds: [u8; 31]) -> Result<()> {
+        let total_amount = total_amount(&ctx.accounts.vesting.schedule)?;
+        ctx.accounts.vesting.round = Some(ctx.accounts.round.key());
+        let round = &mut ctx.accounts.round;
+        round.contract_count = round.contract_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+        round.total_granted = round.total_granted.checked_add(total_amount).ok_or(VestingError::TotalAmountOverflow)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &RoundTagged {
+            round: ctx.accounts.round.key(),
+            vesting: ctx.accounts.vesting.key(),
+            total_amount,
+            contract_count: ctx.accounts.round.contract_count,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates a grants budget of the org for `mint`, escrowing in `budget_token` the tokens its admins may lock into
+    /// contracts with `create_from_budget`, up to `allocation_limit`. The org funds `budget_token` with plain transfers
+    ///
+    /// # Arguments
+    /// * `name` - The name of the budget, at most `MAX_BUDGET_NAME_LEN` bytes, seeding its address `["budget", org, name]`
+    /// * `allocation_limit` - The most the admins may allocate to contracts in total
+    /// * `admins` - The keys allowed to create contracts drawing on the budget, at most `MAX_BUDGET_ADMINS`
+    pub fn create_budget(ctx: Context<CreateBudget>, name: String, allocation_limit: u64, admins: Vec<Pubkey>) -> Result<()> {
+        require!(name.len() as u64 <= MAX_BUDGET_NAME_LEN, VestingError::InvalidBudget);
+        require!(admins.len() as u64 <= MAX_BUDGET_ADMINS, VestingError::InvalidBudget);
+
+        let budget = &mut ctx.accounts.budget;
+        budget.org = ctx.accounts.org.key();
+        budget.mint = ctx.accounts.mint.key();
+        budget.name = name.clone();
+        budget.budget_token = ctx.accounts.budget_token.key();
+        budget.admins = admins;
+        budget.allocation_limit = allocation_limit;
+        budget.allocated_amount = 0;
+
+        event_cpi::emit(ctx.remaining_accounts, &BudgetCreated {
+            budget: ctx.accounts.budget.key(),
+            org: ctx.accounts.org.key(),
+            mint: ctx.accounts.mint.key(),
+            name,
+            allocation_limit,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Changes the allocation limit and the admins of a budget of the org. A limit under the allocated amount stops
+    /// further allocations without touching the contracts created
+    pub fn set_budget(ctx: Context<SetBudget>, allocation_limit: u64, admins: Vec<Pubkey>) -> Result<()> {
+        require!(admins.len() as u64 <= MAX_BUDGET_ADMINS, VestingError::InvalidBudget);
+
+        let budget = &mut ctx.accounts.budget;
+        budget.allocation_limit = allocation_limit;
+        budget.admins = admins.clone();
+
+        event_cpi::emit(ctx.remaining_accounts, &BudgetChanged {
+            budget: ctx.accounts.budget.key(),
+            allocation_limit,
+            admins,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates a contract funded by a budget, pending until the destination owner accepts it. The contract total is
+    /// drawn from the budget token account and allocated out of the budget, a contract over the remaining allocation
+    /// being rejected. The org is the creator of the contract, and its revoker when `revocable`
+    pub fn create_from_budget(ctx: Context<CreateFromBudget>,
+                              _seeds: [u8; 31],
+                              destination_token_address: Pubkey,
+                              schedules: Vec<Schedule>,
+                              min_claim_amount: u64,
+                              revocable: bool) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount = total_amount(&schedules)?;
+        let budget = &mut ctx.accounts.budget;
+        let remaining_allocation = budget.remaining_allocation();
+        if total_amount > remaining_allocation {
+            msg!("Expected a remaining allocation of {}, got {}", total_amount, remaining_allocation);
+            return err!(VestingError::BudgetExceeded);
+        }
+        budget.allocated_amount += total_amount;
+        let (org, mint, name) = (budget.org, budget.mint, budget.name.clone());
+
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = mint;
+        vesting.creator = org;
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = min_claim_amount;
+        vesting.revoker = if revocable { Some(org) } else { None };
+        vesting.is_accepted = false;
+        vesting.unlock_policy = UnlockPolicy::Anyone;
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, now);
+
+        let bump = bump(&ctx.bumps, "budget")?;
+        let budget_seeds = &[
+            BUDGET_SEED.as_bytes(),
+            org.as_ref(),
+            name.as_bytes(),
+            &[bump],
+        ];
+        let signer = &[&budget_seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.budget_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.budget.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint,
+            creator: org,
+            destination: destination_token_address,
+            total_amount,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &BudgetDrawn {
+            budget: ctx.accounts.budget.key(),
+            vesting: ctx.accounts.vesting.key(),
+            admin: ctx.accounts.admin.key(),
+            amount: total_amount,
+            remaining_allocation: ctx.accounts.budget.remaining_allocation(),
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, now)
+    }
+
+    /// Withdraws `amount` from the budget token account of a budget of the org to `refund_token`, leaving the
+    /// allocations unchanged
+    pub fn withdraw_from_budget(ctx: Context<WithdrawFromBudget>, amount: u64) -> Result<()> {
+        let (org, name) = (ctx.accounts.budget.org, ctx.accounts.budget.name.clone());
+        let bump = bump(&ctx.bumps, "budget")?;
+        let budget_seeds = &[
+            BUDGET_SEED.as_bytes(),
+            org.as_ref(),
+            name.as_bytes(),
+            &[bump],
+        ];
+        let signer = &[&budget_seeds[..]];
+
+        let transfer_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.budget_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.refund_token.to_account_info(),
+                authority: ctx.accounts.budget.to_account_info(),
+            },
+            signer
+        );
+        transfer_checked(transfer_ctx, amount, ctx.accounts.mint.decimals)
+    }
+
+    /// Creates the rollup of the contracts of the org for `beneficiary` in `mint`, summing their amounts so that the
+    /// compensation statement of the beneficiary is a single account read
+    pub fn create_rollup(ctx: Context<CreateRollup>, beneficiary: Pubkey) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let rollup = &mut ctx.accounts.rollup;
+        rollup.org = ctx.accounts.org.key();
+        rollup.beneficiary = beneficiary;
+        rollup.mint = ctx.accounts.mint.key();
+        rollup.contract_count = 0;
+        rollup.locked_amount = 0;
+        rollup.vested_unclaimed_amount = 0;
+        rollup.claimed_amount = 0;
+        rollup.revoked_amount = 0;
+        rollup.updated_at = now;
+
+        event_cpi::emit(ctx.remaining_accounts, &RollupCreated {
+            rollup: ctx.accounts.rollup.key(),
+            org: ctx.accounts.org.key(),
+            beneficiary,
+            mint: ctx.accounts.mint.key(),
+            timestamp: now,
+        })?;
+        Ok(())
+    }
+
+    /// Adds a pending contract of the org paying the beneficiary to their rollup. The rollup then has to be in the
+    /// remaining accounts of `unlock`, `revoke` and `terminate`, which move the claimed and revoked amounts out of the
+    /// locked and vested ones
+    pub fn add_to_rollup(ctx: Context<AddToRollup>, _seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount = total_amount(&ctx.accounts.vesting.schedule)?;
+        let locked_amount = locked_amount(&ctx.accounts.vesting.schedule, now);
+        ctx.accounts.vesting.rollup = Some(ctx.accounts.rollup.key());
+        let rollup = &mut ctx.accounts.rollup;
+        rollup.contract_count = rollup.contract_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+        rollup.locked_amount = rollup.locked_amount.checked_add(locked_amount).ok_or(VestingError::TotalAmountOverflow)?;
+        rollup.vested_unclaimed_amount = rollup.vested_unclaimed_amount.checked_add(total_amount - locked_amount)
+            .ok_or(VestingError::TotalAmountOverflow)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &rollup_updated(&ctx.accounts.rollup, now))?;
+        Ok(())
+    }
+
+    /// Brings the locked and vested amounts of a rollup to the current time, as tranches releasing move their amounts
+    /// from one to the other. Every contract of the rollup has to be in the remaining accounts. Anyone can refresh a
+    /// rollup
+    pub fn refresh_rollup(ctx: Context<RefreshRollup>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let rollup_key = ctx.accounts.rollup.key();
+        let contracts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        require!(contracts.len() as u64 == ctx.accounts.rollup.contract_count, VestingError::InvalidRemainingAccounts);
+
+        let (mut locked, mut vested_unclaimed) = (0u64, 0u64);
+        for (i, vesting_info) in contracts.iter().enumerate() {
+            require!(contracts[..i].iter().all(|c| c.key() != vesting_info.key()), VestingError::InvalidRemainingAccounts);
+            let vesting = Account::<Vesting>::try_from(vesting_info)?;
+            require!(vesting.rollup == Some(rollup_key), VestingError::InvalidRollup);
+            let contract_locked = locked_amount(&vesting.schedule, now);
+            locked = locked.checked_add(contract_locked).ok_or(VestingError::TotalAmountOverflow)?;
+            vested_unclaimed = vested_unclaimed.checked_add(total_amount(&vesting.schedule)? - contract_locked)
+                .ok_or(VestingError::TotalAmountOverflow)?;
+        }
+
+        let rollup = &mut ctx.accounts.rollup;
+        rollup.locked_amount = locked;
+        rollup.vested_unclaimed_amount = vested_unclaimed;
+        rollup.updated_at = now;
+
+        event_cpi::emit(ctx.remaining_accounts, &rollup_updated(&ctx.accounts.rollup, now))?;
+        Ok(())
+    }
+
+    /// Creates the unlock queue of a mint, listing contracts by their next release time so that automation watches
+    /// one account. `create` and `unlock` queue their contract when the queue is in their remaining accounts,
+    /// `register_unlock` queues any contract
+    pub fn create_unlock_queue(ctx: Context<CreateUnlockQueue>) -> Result<()> {
+        let queue = &mut ctx.accounts.unlock_queue;
+        queue.mint = ctx.accounts.mint.key();
+        queue.entries = vec![];
+
+        event_cpi::emit(ctx.remaining_accounts, &UnlockQueueCreated {
+            unlock_queue: ctx.accounts.unlock_queue.key(),
+            mint: ctx.accounts.mint.key(),
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Queues a contract in the unlock queue of its mint at its next release time, or drops it from the queue once
+    /// nothing is left to release. Anyone can register a contract
+    pub fn register_unlock(ctx: Context<RegisterUnlock>, seeds: [u8; 31]) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        require!(is_crankable(&ctx.accounts.vesting), VestingError::UnlockRestricted);
+        enqueue(&mut ctx.accounts.unlock_queue, seeds, next_queue_time(&ctx.accounts.vesting, now))
+    }
+
+    /// Unlocks up to `n` due contracts at the head of an unlock queue, queueing each again at its next release
+    /// time. The due contracts are passed as (vesting, vesting token, destination token) triples in the remaining
+    /// accounts, in the queue order, followed by the rounds, rollups and partner stats of tagged contracts. Contracts
+    /// no longer unlockable by anyone, or not accepted when due, leave the queue
+    pub fn process_queue<'info>(ctx: Context<'_, '_, '_, 'info, ProcessQueue<'info>>, n: u8) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let accounts = event_cpi::instruction_accounts(ctx.remaining_accounts);
+        let queue = &mut ctx.accounts.unlock_queue;
+        let due_count = queue.entries.iter().take(n as usize).take_while(|e| e.release_time <= now).count();
+        require!(accounts.len() >= 3 * due_count, VestingError::InvalidRemainingAccounts);
+        let due: Vec<QueueEntry> = queue.entries.drain(..due_count).collect();
+
+        let token_program = ctx.accounts.token_program.to_account_info();
+        let mut amount = 0u64;
+        let mut contract_count = 0u32;
+        for (entry, triple) in due.iter().zip(accounts.chunks_exact(3)) {
+            let (vesting_info, vesting_token, destination_token) = (&triple[0], &triple[1], &triple[2]);
+            let vesting_address = Pubkey::find_program_address(&[entry.seeds.as_ref()], ctx.program_id).0;
+            require!(expect_key("vesting", &vesting_address, &vesting_info.key()), VestingError::InvalidVestingSeeds);
+            if !is_crankable(&*Account::<Vesting>::try_from(vesting_info)?) {
+                continue;
+            }
+
+            let unlocked = unlock_remaining_contract(ctx.program_id, &entry.seeds, vesting_info, vesting_token, destination_token,
+                                                     &ctx.accounts.mint, &token_program, None, now)?;
+            let vesting = Account::<Vesting>::try_from(vesting_info)?;
+            if unlocked > 0 {
+                tally_claim(ctx.remaining_accounts, ctx.program_id, &vesting, unlocked)?;
+                amount = amount.checked_add(unlocked).ok_or(VestingError::TotalAmountOverflow)?;
+                contract_count += 1;
+            }
+            let release_time = next_queue_time(&vesting, now).filter(|&release_time| release_time > now);
+            enqueue(&mut ctx.accounts.unlock_queue, entry.seeds, release_time)?;
+        }
+
+        event_cpi::emit(ctx.remaining_accounts, &QueueProcessed {
+            unlock_queue: ctx.accounts.unlock_queue.key(),
+            processed_count: due.len() as u32,
+            contract_count,
+            amount,
+            decimals: ctx.accounts.mint.decimals,
+            ui_amount: ui_amount(amount, ctx.accounts.mint.decimals),
+            timestamp: now,
+        })
+    }
+
+    /// Registers the config of a partner front-end, under which its users create contracts with
+    /// `create_with_partner`, each paying the partner fee on top of the contract total
+    ///
+    /// # Arguments
+    /// * `fee_recipient` - The owner of the token accounts the fees are paid to
+    /// * `fee_bps` - The fee, in basis points of the contract total, at most `MAX_PARTNER_FEE_BPS`
+    /// * `branding` - A key front-ends resolve the partner branding from, e.g. a metadata account
+    /// * `default_min_claim_amount`, `default_unlock_policy`, `default_revocable` - The terms of the contracts created
+    ///   under the config, revocable contracts being revoked by their creator
+    pub fn create_partner_config(ctx: Context<CreatePartnerConfig>,
+                                 fee_recipient: Pubkey,
+                                 fee_bps: u16,
+                                 branding: Pubkey,
+                                 default_min_claim_amount: u64,
+                                 default_unlock_policy: UnlockPolicy,
+                                 default_revocable: bool) -> Result<()> {
+        ctx.accounts.partner_config.authority = ctx.accounts.authority.key();
+        set_partner_terms(&mut ctx.accounts.partner_config, fee_recipient, fee_bps, branding, default_min_claim_amount,
+                          default_unlock_policy, default_revocable)?;
+        event_cpi::emit(ctx.remaining_accounts, &partner_config_changed(&ctx.accounts.partner_config,
+                                                                        test_clock::unix_timestamp(ctx.remaining_accounts)?))
+    }
+
+    /// Replaces the fee and the default terms of a partner config, signed by the partner. Contracts created before
+    /// keep their terms
+    pub fn set_partner_config(ctx: Context<SetPartnerConfig>,
+                              fee_recipient: Pubkey,
+                              fee_bps: u16,
+                              branding: Pubkey,
+                              default_min_claim_amount: u64,
+                              default_unlock_policy: UnlockPolicy,
+                              default_revocable: bool) -> Result<()> {
+        set_partner_terms(&mut ctx.accounts.partner_config, fee_recipient, fee_bps, branding, default_min_claim_amount,
+                          default_unlock_policy, default_revocable)?;
+        event_cpi::emit(ctx.remaining_accounts, &partner_config_changed(&ctx.accounts.partner_config,
+                                                                        test_clock::unix_timestamp(ctx.remaining_accounts)?))
+    }
+
+    /// Creates a contract on the default terms of a partner config, pending until the destination owner accepts it.
+    /// The source pays the contract total to the escrow and the partner fee to `fee_token`, a token account of the
+    /// partner fee recipient. The contract counts in the partner stats of its mint, created by the payer if needed
+    pub fn create_with_partner(ctx: Context<CreateWithPartner>,
+                               _seeds: [u8; 31],
+                               destination_token_address: Pubkey,
+                               schedules: Vec<Schedule>) -> Result<()> {
+        let now = test_clock::unix_timestamp(ctx.remaining_accounts)?;
+        let total_amount = total_amount(&schedules)?;
+        let partner = &ctx.accounts.partner_config;
+        let fee = (total_amount as u128 * partner.fee_bps as u128 / 10_000) as u64;
+        let charged_amount = total_amount.checked_add(fee).ok_or(VestingError::TotalAmountOverflow)?;
+        require!(expect_more_than("source token balance", charged_amount, ctx.accounts.source_token.amount), VestingError::InsufficientFunds);
+
+        let mint_address = ctx.accounts.source_token.mint;
+        let vesting = &mut ctx.accounts.vesting;
+        vesting.destination_address = destination_token_address;
+        vesting.mint_address = mint_address;
+        vesting.creator = ctx.accounts.source_authority.key();
+        vesting.layout_version = VESTING_LAYOUT_VERSION;
+        vesting.is_initialized = true;
+        vesting.min_claim_amount = partner.default_min_claim_amount;
+        vesting.revoker = if partner.default_revocable { Some(ctx.accounts.source_authority.key()) } else { None };
+        vesting.is_accepted = false;
+        vesting.unlock_policy = partner.default_unlock_policy.clone();
+        vesting.partner = Some(partner.key());
+        vesting.schedule = schedules;
+        refresh_next_action(vesting, now);
+
+        let transfer_ctx = CpiContext::new(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source_token.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.vesting_token.to_account_info(),
+                authority: ctx.accounts.source_authority.to_account_info(),
+            });
+        transfer_checked(transfer_ctx, total_amount, ctx.accounts.mint.decimals)?;
+        if fee > 0 {
+            let fee_ctx = CpiContext::new(
+                ctx.accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: ctx.accounts.source_token.to_account_info(),
+                    mint: ctx.accounts.mint.to_account_info(),
+                    to: ctx.accounts.fee_token.to_account_info(),
+                    authority: ctx.accounts.source_authority.to_account_info(),
+                });
+            transfer_checked(fee_ctx, fee, ctx.accounts.mint.decimals)?;
+        }
+
+        let stats = &mut ctx.accounts.partner_stats;
+        stats.partner_config = ctx.accounts.partner_config.key();
+        stats.mint = mint_address;
+        stats.contract_count = stats.contract_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+        stats.total_value_locked = stats.total_value_locked.checked_add(total_amount).ok_or(VestingError::TotalAmountOverflow)?;
+        stats.fee_amount = stats.fee_amount.checked_add(fee).ok_or(VestingError::TotalAmountOverflow)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &VestingCreated {
+            vesting: ctx.accounts.vesting.key(),
+            mint: mint_address,
+            creator: ctx.accounts.source_authority.key(),
+            destination: destination_token_address,
+            total_amount,
+            timestamp: now,
+        })?;
+        event_cpi::emit(ctx.remaining_accounts, &PartnerContractCreated {
+            partner_config: ctx.accounts.partner_config.key(),
+            vesting: ctx.accounts.vesting.key(),
+            fee_token: ctx.accounts.fee_token.key(),
+            fee,
+            timestamp: now,
+        })?;
+        notify(ctx.remaining_accounts, ctx.program_id, &destination_token_address, &ctx.accounts.vesting.key(),
+               InboxNotice::ContractCreated, now)
+    }
+
+    /// Creates the rent sponsorship pool of the creator, out of which the rent of the accounts created when claiming
+    /// the contracts and distributors of the creator is paid, so beneficiaries without SOL can still claim
+    ///
+    /// # Arguments
+    /// * `lamports` - The lamports initially deposited into the pool
+    pub fn create_rent_sponsorship_pool(ctx: Context<CreateRentSponsorshipPool>, lamports: u64) -> Result<()> {
+        let pool = &mut ctx.accounts.pool;
+        pool.creator = ctx.accounts.creator.key();
+        deposit_to_rent_sponsorship_pool(&ctx.accounts.creator, &ctx.accounts.pool, &ctx.accounts.system_program, lamports)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &RentSponsorshipPoolFunded {
+            pool: ctx.accounts.pool.key(),
+            funder: ctx.accounts.creator.key(),
+            lamports,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Deposits lamports into the rent sponsorship pool of a creator
+    pub fn fund_rent_sponsorship_pool(ctx: Context<FundRentSponsorshipPool>, lamports: u64) -> Result<()> {
+        deposit_to_rent_sponsorship_pool(&ctx.accounts.funder, &ctx.accounts.pool, &ctx.accounts.system_program, lamports)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &RentSponsorshipPoolFunded {
+            pool: ctx.accounts.pool.key(),
+            funder: ctx.accounts.funder.key(),
+            lamports,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Returns lamports of the rent sponsorship pool to its creator, the pool keeping its rent exempt minimum
+    pub fn withdraw_from_rent_sponsorship_pool(ctx: Context<WithdrawFromRentSponsorshipPool>, lamports: u64) -> Result<()> {
+        let pool = ctx.accounts.pool.to_account_info();
+        require!(sponsorable_lamports(&pool)? >= lamports, VestingError::InsufficientFunds);
+        **pool.try_borrow_mut_lamports()? -= lamports;
+        **ctx.accounts.creator.to_account_info().try_borrow_mut_lamports()? += lamports;
+
+        event_cpi::emit(ctx.remaining_accounts, &RentSponsorshipPoolWithdrawn {
+            pool: ctx.accounts.pool.key(),
+            creator: ctx.accounts.creator.key(),
+            lamports,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Funds, out of the rent sponsorship pool of the contract creator, the rent of the associated token account of the
+    /// destination owner, which a following `unlock_to_owner` then creates without charging its payer.
+    /// Anyone can send it, nothing is drawn when the account already holds its rent. The pool funds one destination
+    /// account per contract, so closing and sponsoring it again doesn't drain the pool
+    pub fn sponsor_destination_rent(ctx: Context<SponsorDestinationRent>, _seeds: [u8; 31]) -> Result<()> {
+        let lamports = draw_sponsored_rent(&mut ctx.accounts.pool, &ctx.accounts.destination_token, TokenAccount::LEN)?;
+        if lamports > 0 {
+            ctx.accounts.vesting.is_rent_sponsored = true;
+        }
+
+        event_cpi::emit(ctx.remaining_accounts, &RentSponsored {
+            pool: ctx.accounts.pool.key(),
+            account: ctx.accounts.destination_token.key(),
+            lamports,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Funds, out of the rent sponsorship pool of the distributor creator, the rent of the claim status of the
+    /// claimant, which a following first `claim_from_distributor` then creates without charging the claimant.
+    /// Anyone can send it, nothing is drawn when the account already holds its rent
+    pub fn sponsor_claim_status_rent(ctx: Context<SponsorClaimStatusRent>) -> Result<()> {
+        let lamports = draw_sponsored_rent(&mut ctx.accounts.pool, &ctx.accounts.claim_status, CLAIM_STATUS_ACCOUNT_SIZE)?;
+
+        event_cpi::emit(ctx.remaining_accounts, &RentSponsored {
+            pool: ctx.accounts.pool.key(),
+            account: ctx.accounts.claim_status.key(),
+            lamports,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Creates the grant index of the creator for `beneficiary`, the owner of the destinations of the contracts it lists.
+    /// The contracts the creator then creates at the seeds `grant_seeds(creator, beneficiary, grant_id)`, with the index
+    /// in the remaining accounts of `create`, are listed by grant id
+    pub fn create_grant_index(ctx: Context<CreateGrantIndex>, beneficiary: Pubkey) -> Result<()> {
+        let grant_index = &mut ctx.accounts.grant_index;
+        grant_index.creator = ctx.accounts.creator.key();
+        grant_index.beneficiary = beneficiary;
+        grant_index.next_grant_id = 0;
+        grant_index.grant_ids = Vec::new();
+
+        event_cpi::emit(ctx.remaining_accounts, &GrantIndexCreated {
+            grant_index: ctx.accounts.grant_index.key(),
+            creator: ctx.accounts.creator.key(),
+            beneficiary,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Removes a fully released or revoked contract from the grant index of its creator. Its grant id isn't reused
+    pub fn close_indexed_grant(ctx: Context<CloseIndexedGrant>, _seeds: [u8; 31], grant_id: u64) -> Result<()> {
+        let grant_index = &mut ctx.accounts.grant_index;
+        let position = grant_index.grant_ids.iter().position(|&id| id == grant_id).ok_or(VestingError::InvalidGrantIndex)?;
+        grant_index.grant_ids.remove(position);
+
+        event_cpi::emit(ctx.remaining_accounts, &GrantIndexChanged {
+            grant_index: ctx.accounts.grant_index.key(),
+            vesting: ctx.accounts.vesting.key(),
+            grant_id,
+            indexed: false,
+            timestamp: test_clock::unix_timestamp(ctx.remaining_accounts)?,
+        })?;
+        Ok(())
+    }
+
+    /// Handles the instructions no handler matches: `set_test_clock`, see `test_clock`, with the `event-cpi`
+    /// feature, the self-CPI carrying an event, and with the `bonfida-compat` feature, Bonfida's `init`, `create` and `unlock`
+    pub fn fallback(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> Result<()> {
+        if let Some(result) = test_clock::handle_instruction(program_id, accounts, data) {
+            return result;
+        }
+        if cfg!(feature = "bonfida-compat") {
+            if let Some(translated) = bonfida::translate_instruction(accounts, data) {
+                let (data, accounts) = translated?;
+                return crate::dispatch(program_id, &accounts, &data);
+            }
+        }
+        event_cpi::handle_event_instruction(accounts, data)
+    }
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], number_of_schedules: u32)]
+pub struct Initialize<'info> {
+    #[account(init, payer = payer, space = calc_vesting_account_size(number_of_schedules), seeds = [seeds.as_ref()], bump)]
+    pub vesting: Account<'info, Vesting>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], mint_address: Pubkey, destination_token_address: Pubkey, schedules: Vec<Schedule>, min_claim_amount: u64, revoker: Option<Pubkey>)]
+pub struct Create<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == schedules.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub source_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], mint_address: Pubkey, destination_token_address: Pubkey, schedules: Vec<Schedule>, min_claim_amount: u64, yield_recipient: Pubkey)]
+pub struct CreateWithYield<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == schedules.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub source_authority: Signer<'info>,
+
+    /// CHECK: the vault program is chosen by the creator and recorded on the contract
+    #[account(executable)]
+    pub vault_program: UncheckedAccount<'info>,
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub vault_reserve: UncheckedAccount<'info>,
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub share_mint: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = share_token.owner == vesting.key() @ VestingError::InvalidVestingTokenAuthority,
+        constraint = share_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = share_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub share_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct Accept<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub destination_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct AcceptForProgramOwner<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = !destination_token.owner.is_on_curve() @ VestingError::DestinationOwnerOnCurve)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct Cancel<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.guarantor.is_none() @ VestingError::GuaranteeAlreadyExists,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = refund_token.mint == vesting.mint_address @ VestingError::InvalidMint)]
+    pub refund_token: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct SetDestinationOwner<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct UnlockToOwner<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.destination_owner == Some(owner.key()) @ VestingError::InvalidDestinationOwner,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    /// CHECK: the wallet or program derived address recorded as the destination owner
+    pub owner: UncheckedAccount<'info>,
+    #[account(init_if_needed, payer = payer, associated_token::mint = mint, associated_token::authority = owner)]
+    pub destination_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    // The destination owner or its claim delegate when unlocking is restricted, anyone otherwise
+    pub claimer: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct Unlock<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.unlock_policy == UnlockPolicy::Anyone @ VestingError::UnlockRestricted,
+    constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct UnlockWithReceipt<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.unlock_policy == UnlockPolicy::Anyone @ VestingError::UnlockRestricted,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    #[account(init, payer = payer, space = CLAIM_RECEIPT_ACCOUNT_SIZE,
+        seeds = [CLAIM_RECEIPT_SEED.as_bytes(), vesting.key().as_ref(), vesting.claim_count.to_le_bytes().as_ref()], bump)]
+    pub claim_receipt: Account<'info, ClaimReceipt>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct GetClaimable<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct LogStatement<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority)]
+    pub vesting_token: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct SetBridgeTarget<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = destination_token.owner == beneficiary.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub beneficiary: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct ClaimAndBridge<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.claim_hook.is_none() @ VestingError::ClaimHookEnabled,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.bridge_target.is_some() @ VestingError::NoBridgeTarget
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    #[account(constraint = destination_token.owner == beneficiary.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub beneficiary: Signer<'info>,
+    // Pays the bridge message fee and rent
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    #[account(mut, constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    /// CHECK: validated by the token bridge program
+    pub config: UncheckedAccount<'info>,
+    /// CHECK: validated by the token bridge program
+    #[account(mut)]
+    pub custody: UncheckedAccount<'info>,
+    /// CHECK: the token bridge PDA approved as delegate of the vesting token account
+    #[account(address = wormhole::authority_signer() @ VestingError::InvalidBridgeAccounts)]
+    pub authority_signer: UncheckedAccount<'info>,
+    /// CHECK: validated by the token bridge program
+    pub custody_signer: UncheckedAccount<'info>,
+    /// CHECK: validated by the core bridge program
+    #[account(mut)]
+    pub bridge: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub message: Signer<'info>,
+    /// CHECK: validated by the token bridge program
+    pub emitter: UncheckedAccount<'info>,
+    /// CHECK: validated by the core bridge program
+    #[account(mut)]
+    pub sequence: UncheckedAccount<'info>,
+    /// CHECK: validated by the core bridge program
+    #[account(mut)]
+    pub fee_collector: UncheckedAccount<'info>,
+
+    pub clock: Sysvar<'info, Clock>,
+    pub rent: Sysvar<'info, Rent>,
+    pub system_program: Program<'info, System>,
+    pub token_program: Program<'info, Token>,
+    /// CHECK: the Wormhole core bridge program
+    #[account(address = wormhole::core_bridge_program::ID)]
+    pub core_bridge_program: UncheckedAccount<'info>,
+    /// CHECK: the Wormhole token bridge program
+    #[account(address = wormhole::token_bridge_program::ID)]
+    pub token_bridge_program: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct SetClaimDelegate<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub destination_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct AuthorizedUnlock<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    // The destination owner or its claim delegate
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct UnlockWithPermit<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    /// CHECK: the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CrankUnlock<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.unlock_policy == UnlockPolicy::Anyone @ VestingError::UnlockRestricted,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    /// CHECK: the contract fee pool address, which may not have been created
+    #[account(mut, seeds = [FEE_POOL_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub fee_pool: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub cranker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct BatchUnlock<'info> {
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct UnlockAllForDestination<'info> {
+    // The owner of the destination token accounts
+    pub owner: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreateFeePool<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init, payer = payer, space = FEE_POOL_ACCOUNT_SIZE,
+        seeds = [FEE_POOL_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub fee_pool: Account<'info, FeePool>,
+
+    pub destination_token: Account<'info, TokenAccount>,
+    #[account(mut,
+        constraint = authority.key() == vesting.creator || authority.key() == destination_token.owner @ VestingError::InvalidFeePoolAuthority
+    )]
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CloseFeePool<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = total_amount(&vesting.schedule)? == 0 @ VestingError::ContractNotFullyReleased
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, close = authority, seeds = [FEE_POOL_SEED.as_bytes(), vesting.key().as_ref()], bump,
+        constraint = fee_pool.authority == authority.key() @ VestingError::InvalidFeePoolAuthority
+    )]
+    pub fee_pool: Account<'info, FeePool>,
+
+    /// CHECK: the fee pool authority receiving the remaining lamports
+    #[account(mut)]
+    pub authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct FundFeePool<'info> {
+    #[account(seeds = [seeds.as_ref()], bump)]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [FEE_POOL_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub fee_pool: Account<'info, FeePool>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct ClaimAndStake<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.claim_hook.is_none() @ VestingError::ClaimHookEnabled,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+    pub destination_authority: Signer<'info>,
+
+    /// CHECK: chosen by the destination owner, who signs for the deposit
+    #[account(executable)]
+    pub stake_program: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake program
+    #[account(mut)]
+    pub stake_pool: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake program
+    #[account(mut)]
+    pub stake_reserve: UncheckedAccount<'info>,
+    /// CHECK: validated by the stake program
+    #[account(mut)]
+    pub pool_mint: UncheckedAccount<'info>,
+    #[account(mut,
+        constraint = pool_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority
+    )]
+    pub pool_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct UnlockFromYield<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.claim_hook.is_none() @ VestingError::ClaimHookEnabled,
+        constraint = vesting.unlock_policy == UnlockPolicy::Anyone @ VestingError::UnlockRestricted,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.as_ref().map(|c| c.vault_program) == Some(vault_program.key()) @ VestingError::InvalidYieldVault,
+        constraint = vesting.yield_config.as_ref().map(|c| c.vault) == Some(vault.key()) @ VestingError::InvalidYieldVault,
+        constraint = vesting.yield_config.as_ref().map(|c| c.share_token_address) == Some(share_token.key()) @ VestingError::InvalidYieldVault,
+        constraint = vesting.yield_config.as_ref().map(|c| c.yield_recipient) == Some(yield_recipient.key()) @ VestingError::InvalidYieldRecipient
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub yield_recipient: Account<'info, TokenAccount>,
+
+    /// CHECK: matched against the vault program recorded on the contract
+    pub vault_program: UncheckedAccount<'info>,
+    /// CHECK: matched against the vault recorded on the contract
+    #[account(mut)]
+    pub vault: UncheckedAccount<'info>,
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub vault_reserve: UncheckedAccount<'info>,
+    /// CHECK: validated by the vault program
+    #[account(mut)]
+    pub share_mint: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub share_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreateGuarantee<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.guarantor.is_none() @ VestingError::GuaranteeAlreadyExists,
+        constraint = granter.key() == vesting.creator || vesting.revoker == Some(granter.key()) @ VestingError::InvalidGuaranteeGranter
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init, payer = payer, space = GUARANTEE_ACCOUNT_SIZE, seeds = [GUARANTEE_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub guarantee: Account<'info, Guarantee>,
+
+    #[account(mut,
+        constraint = collateral_token.owner == guarantee.key() @ VestingError::InvalidCollateralTokenAuthority,
+        constraint = collateral_token.mint == vesting.mint_address @ VestingError::InvalidMint,
+        constraint = collateral_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = collateral_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub collateral_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub guarantor_token: Account<'info, TokenAccount>,
+
+    pub guarantor: Signer<'info>,
+    pub granter: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct TopUp<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [GUARANTEE_SEED.as_bytes(), vesting.key().as_ref()], bump,
+        constraint = !guarantee.is_settled @ VestingError::GuaranteeSettled,
+        constraint = guarantee.granter == granter.key() @ VestingError::InvalidGranter
+    )]
+    pub guarantee: Account<'info, Guarantee>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.mint == vesting.mint_address @ VestingError::InvalidMint
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub granter: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct SettleGuarantee<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [GUARANTEE_SEED.as_bytes(), vesting.key().as_ref()], bump,
+        constraint = !guarantee.is_settled @ VestingError::GuaranteeSettled,
+        constraint = guarantee.collateral_token_address == collateral_token.key() @ VestingError::InvalidCollateralTokenAuthority
+    )]
+    pub guarantee: Account<'info, Guarantee>,
+
+    #[account(mut)]
+    pub collateral_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = guarantor_token.owner == guarantee.guarantor @ VestingError::InvalidGuarantorToken)]
+    pub guarantor_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRegistrar<'info> {
+    #[account(init, payer = payer, space = REGISTRAR_ACCOUNT_SIZE,
+        seeds = [realm.key().as_ref(), REGISTRAR_SEED.as_bytes(), governing_token_mint.key().as_ref()], bump)]
+    pub registrar: Account<'info, Registrar>,
+
+    /// CHECK: only its ownership by the governance program is relevant
+    #[account(constraint = *realm.owner == governance_program.key() @ VestingError::InvalidRealm)]
+    pub realm: UncheckedAccount<'info>,
+    /// CHECK: the governance program owning the realm
+    #[account(executable)]
+    pub governance_program: UncheckedAccount<'info>,
+    pub governing_token_mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateVoterWeightRecord<'info> {
+    pub registrar: Account<'info, Registrar>,
+
+    #[account(init, payer = payer, space = VOTER_WEIGHT_RECORD_ACCOUNT_SIZE,
+        seeds = [registrar.key().as_ref(), VOTER_WEIGHT_RECORD_SEED.as_bytes(), governing_token_owner.key().as_ref()], bump)]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+
+    /// CHECK: the wallet owning the vesting destination token accounts
+    pub governing_token_owner: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateVoterWeightRecord<'info> {
+    pub registrar: Account<'info, Registrar>,
+
+    #[account(mut,
+        seeds = [registrar.key().as_ref(), VOTER_WEIGHT_RECORD_SEED.as_bytes(), voter_weight_record.governing_token_owner.as_ref()], bump)]
+    pub voter_weight_record: Account<'info, VoterWeightRecord>,
+}
+
+#[derive(Accounts)]
+pub struct CreateMaxVoterWeightRecord<'info> {
+    pub registrar: Account<'info, Registrar>,
+
+    #[account(init, payer = payer, space = MAX_VOTER_WEIGHT_RECORD_ACCOUNT_SIZE,
+        seeds = [registrar.key().as_ref(), MAX_VOTER_WEIGHT_RECORD_SEED.as_bytes()], bump)]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct UpdateMaxVoterWeightRecord<'info> {
+    pub registrar: Account<'info, Registrar>,
+
+    #[account(mut, seeds = [registrar.key().as_ref(), MAX_VOTER_WEIGHT_RECORD_SEED.as_bytes()], bump)]
+    pub max_voter_weight_record: Account<'info, MaxVoterWeightRecord>,
+
+    /// CHECK: the realm of the registrar, its authority read in the constraint below
+    #[account(constraint = realm.key() == registrar.realm @ VestingError::InvalidRealm)]
+    pub realm: UncheckedAccount<'info>,
+    #[account(constraint = governance::realm_authority(&realm, &registrar.governance_program_id)? == Some(realm_authority.key())
+        @ VestingError::InvalidRealmAuthority)]
+    pub realm_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(owner: Pubkey, mint: Pubkey)]
+pub struct RefreshAttestation<'info> {
+    #[account(init_if_needed, payer = payer, space = ATTESTATION_ACCOUNT_SIZE,
+        seeds = [ATTESTATION_SEED.as_bytes(), owner.as_ref(), mint.as_ref(), attester.key().as_ref()], bump)]
+    pub attestation: Account<'info, Attestation>,
+
+    pub attester: Signer<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], slot: u64)]
+pub struct Snapshot<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init, payer = payer, space = SNAPSHOT_ACCOUNT_SIZE,
+        seeds = [SNAPSHOT_SEED.as_bytes(), vesting.key().as_ref(), slot.to_le_bytes().as_ref()], bump)]
+    pub snapshot: Account<'info, LockedSnapshot>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct SetVoteDelegate<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init_if_needed, payer = payer, space = VOTE_DELEGATION_ACCOUNT_SIZE,
+        seeds = [VOTE_DELEGATION_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub vote_delegation: Account<'info, VoteDelegation>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+    pub destination_authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct ClearVoteDelegate<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, close = destination_authority,
+        seeds = [VOTE_DELEGATION_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub vote_delegation: Account<'info, VoteDelegation>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+    #[account(mut)]
+    pub destination_authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct Revoke<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.revoker == Some(revoker.key()) @ VestingError::InvalidRevoker
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = refund_token.mint == vesting.mint_address @ VestingError::InvalidMint)]
+    pub refund_token: Account<'info, TokenAccount>,
+
+    pub revoker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct TriggerReleaseCondition<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct FulfillFunctionCondition<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    /// CHECK: matched against the release condition and parsed in the instruction
+    pub function: UncheckedAccount<'info>,
+    pub enclave_signer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct FulfillSignatureCondition<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    /// CHECK: the instructions sysvar
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreateReceipt<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptAlreadyExists,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init, payer = payer, seeds = [RECEIPT_SEED.as_bytes(), vesting.key().as_ref()], bump,
+        mint::decimals = 0, mint::authority = vesting)]
+    pub receipt_mint: Account<'info, Mint>,
+
+    #[account(init, payer = payer, associated_token::mint = receipt_mint, associated_token::authority = beneficiary)]
+    pub receipt_token: Account<'info, TokenAccount>,
+
+    pub destination_token: Account<'info, TokenAccount>,
+    /// CHECK: the owner of the destination token account receiving the receipt
+    #[account(address = destination_token.owner)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    pub creator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct MintCompressedReceipt<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    pub destination_token: Account<'info, TokenAccount>,
+    /// CHECK: the owner of the destination token account receiving the receipt
+    #[account(address = destination_token.owner)]
+    pub beneficiary: UncheckedAccount<'info>,
+
+    /// CHECK: the tree config, validated by the bubblegum program
+    #[account(mut)]
+    pub tree_authority: UncheckedAccount<'info>,
+    /// CHECK: the merkle tree, validated by the bubblegum program
+    #[account(mut)]
+    pub merkle_tree: UncheckedAccount<'info>,
+
+    // Signs as the tree delegate
+    pub creator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    /// CHECK: the bubblegum program
+    #[account(address = bubblegum::ID)]
+    pub bubblegum_program: UncheckedAccount<'info>,
+    /// CHECK: the SPL noop program
+    #[account(address = bubblegum::noop_program::ID)]
+    pub log_wrapper: UncheckedAccount<'info>,
+    /// CHECK: the SPL account compression program
+    #[account(address = bubblegum::compression_program::ID)]
+    pub compression_program: UncheckedAccount<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct UnlockToHolder<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.unlock_policy == UnlockPolicy::Anyone @ VestingError::UnlockRestricted,
+        constraint = vesting.receipt_mint == Some(receipt_token.mint) @ VestingError::InvalidReceipt
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(constraint = receipt_token.amount == 1 @ VestingError::InvalidReceipt)]
+    pub receipt_token: Account<'info, TokenAccount>,
+
+    #[account(mut,
+        address = get_associated_token_address(&receipt_token.owner, &vesting.mint_address) @ VestingError::InvalidHolderToken
+    )]
+    pub holder_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct ListPosition<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init, payer = payer, space = LISTING_ACCOUNT_SIZE,
+        seeds = [LISTING_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub listing: Account<'info, Listing>,
+
+    #[account(constraint = destination_token.owner == seller.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+    #[account(constraint = seller_payment_token.owner == seller.key() @ VestingError::InvalidPaymentToken)]
+    pub seller_payment_token: Account<'info, TokenAccount>,
+
+    pub seller: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CancelListing<'info> {
+    #[account(seeds = [seeds.as_ref()], bump)]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, close = seller, seeds = [LISTING_SEED.as_bytes(), vesting.key().as_ref()], bump,
+        constraint = listing.seller == seller.key() @ VestingError::InvalidSeller
+    )]
+    pub listing: Account<'info, Listing>,
+
+    #[account(mut)]
+    pub seller: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreatorUpdate<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct ApproveListing<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [LISTING_SEED.as_bytes(), vesting.key().as_ref()], bump)]
+    pub listing: Account<'info, Listing>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct AcceptListing<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.destination_address == current_destination_token.key() @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, close = seller, seeds = [LISTING_SEED.as_bytes(), vesting.key().as_ref()], bump,
+        constraint = listing.seller == seller.key() @ VestingError::InvalidSeller,
+        constraint = listing.seller_payment_token_address == seller_payment_token.key() @ VestingError::InvalidPaymentToken
+    )]
+    pub listing: Account<'info, Listing>,
+
+    // The seller must still own the destination for the listing to be valid
+    #[account(constraint = current_destination_token.owner == listing.seller @ VestingError::InvalidSeller)]
+    pub current_destination_token: Account<'info, TokenAccount>,
+
+    /// CHECK: the seller recorded on the listing, receiving the listing rent
+    #[account(mut)]
+    pub seller: UncheckedAccount<'info>,
+    #[account(mut)]
+    pub seller_payment_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub buyer_payment_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("payment mint", &listing.payment_mint, &payment_mint.key()) @ VestingError::InvalidPaymentToken)]
+    pub payment_mint: Account<'info, Mint>,
+    #[account(
+        constraint = buyer_destination_token.owner == buyer.key() @ VestingError::InvalidDestinationAuthority,
+        constraint = buyer_destination_token.mint == vesting.mint_address @ VestingError::InvalidMint
+    )]
+    pub buyer_destination_token: Account<'info, TokenAccount>,
+
+    pub buyer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreateInbox<'info> {
+    pub destination_token: Account<'info, TokenAccount>,
+
+    #[account(init, payer = payer, space = INBOX_ACCOUNT_SIZE,
+        seeds = [INBOX_SEED.as_bytes(), destination_token.key().as_ref()], bump)]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct AcknowledgeInbox<'info> {
+    #[account(mut, seeds = [INBOX_SEED.as_bytes(), destination_token.key().as_ref()], bump)]
+    pub inbox: Account<'info, Inbox>,
+
+    #[account(constraint = expect_key("destination owner", &destination_token.owner, &owner.key()) @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub owner: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct ChangeDestination<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.destination_address == current_destination_token.key() @ VestingError::InvalidDestination,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = current_destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub current_destination_token: Account<'info, TokenAccount>,
+    /// CHECK: verified to be a signer or a satisfied SPL Token multisig in the instruction
+    pub destination_authority: UncheckedAccount<'info>,
+    pub new_destination_token: Account<'info, TokenAccount>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct MigrateVestingLayout<'info> {
+    /// CHECK: a vesting account in the layout of the first release, which doesn't deserialize as a `Vesting`
+    #[account(mut, seeds = [seeds.as_ref()], bump, owner = crate::ID)]
+    pub vesting: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct MigrateFromBonfida<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    /// CHECK: a Bonfida vesting account, unpacked and matched with its seeds in the instruction
+    #[account(mut, owner = bonfida::ID @ VestingError::InvalidBonfidaVesting)]
+    pub bonfida_vesting: UncheckedAccount<'info>,
+    #[account(mut, constraint = bonfida_vesting_token.owner == bonfida_vesting.key() @ VestingError::InvalidBonfidaVesting)]
+    pub bonfida_vesting_token: Account<'info, TokenAccount>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+    pub destination_authority: Signer<'info>,
+
+    /// CHECK: Bonfida's token vesting program
+    #[account(address = bonfida::ID @ VestingError::InvalidBonfidaVesting)]
+    pub bonfida_program: UncheckedAccount<'info>,
+    pub token_program: Program<'info, Token>,
+    pub clock: Sysvar<'info, Clock>,
+}
+
+#[derive(Accounts)]
+pub struct CreateConfig<'info> {
+    #[account(init, payer = authority, space = PROGRAM_CONFIG_ACCOUNT_SIZE, seeds = [CONFIG_SEED.as_bytes()], bump)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(mut)]
+    pub authority: Signer<'info>,
+    #[account(constraint = program.programdata_address() == Some(program_data.key()) @ VestingError::InvalidProgramData)]
+    pub program: Program<'info, crate::program::TokenVesting>,
+    #[account(constraint = program_data.upgrade_authority_address == Some(authority.key()) @ VestingError::InvalidProgramData)]
+    pub program_data: Account<'info, ProgramData>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ConfigUpdate<'info> {
+    #[account(mut, seeds = [CONFIG_SEED.as_bytes()], bump, has_one = admin @ VestingError::InvalidConfigAdmin)]
+    pub config: Account<'info, ProgramConfig>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SetClaimHookAllowlist<'info> {
+    #[account(seeds = [CONFIG_SEED.as_bytes()], bump, has_one = admin @ VestingError::InvalidConfigAdmin)]
+    pub config: Account<'info, ProgramConfig>,
+
+    #[account(init_if_needed, payer = admin, space = CLAIM_HOOK_ALLOWLIST_ACCOUNT_SIZE,
+        seeds = [CLAIM_HOOK_ALLOWLIST_SEED.as_bytes()], bump)]
+    pub claim_hook_allowlist: Account<'info, ClaimHookAllowlist>,
+
+    #[account(mut)]
+    pub admin: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreateDistributor<'info> {
+    #[account(init, payer = payer, space = MERKLE_DISTRIBUTOR_ACCOUNT_SIZE, seeds = [DISTRIBUTOR_SEED.as_bytes(), seeds.as_ref()], bump)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    #[account(mut,
+        constraint = expect_key("distributor token owner", &distributor.key(), &distributor_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = distributor_token.mint == mint.key() @ VestingError::InvalidMint,
+        constraint = distributor_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = distributor_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub distributor_token: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub source_authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ClaimFromDistributor<'info> {
+    #[account(mut, seeds = [DISTRIBUTOR_SEED.as_bytes(), distributor.seeds.as_ref()], bump,
+        has_one = mint @ VestingError::InvalidMint
+    )]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    #[account(mut, constraint = expect_key("distributor token", &distributor.escrow, &distributor_token.key()) @ VestingError::InvalidVestingTokenAuthority)]
+    pub distributor_token: Account<'info, TokenAccount>,
+
+    #[account(init_if_needed, payer = claimant, space = CLAIM_STATUS_ACCOUNT_SIZE,
+        seeds = [CLAIM_STATUS_SEED.as_bytes(), distributor.key().as_ref(), claimant.key().as_ref()], bump)]
+    pub claim_status: Account<'info, ClaimStatus>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(mut,
+        constraint = destination_token.owner == claimant.key() @ VestingError::InvalidDestinationAuthority,
+        constraint = destination_token.mint == mint.key() @ VestingError::InvalidMint
+    )]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateSharedVault<'info> {
+    #[account(init, payer = payer, space = SHARED_VAULT_ACCOUNT_SIZE,
+        seeds = [SHARED_VAULT_SEED.as_bytes(), authority.key().as_ref(), mint.key().as_ref()], bump)]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(
+        constraint = expect_key("vault token owner", &shared_vault.key(), &vault_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vault_token.mint == mint.key() @ VestingError::InvalidMint,
+        constraint = vault_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vault_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vault_token: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], destination_token_address: Pubkey, schedules: Vec<Schedule>)]
+pub struct CreateInSharedVault<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == schedules.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        has_one = authority @ VestingError::InvalidSharedVault,
+        has_one = vault_token @ VestingError::InvalidSharedVault
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    pub vault_token: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct UnlockFromSharedVault<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.unlock_policy == UnlockPolicy::Anyone @ VestingError::UnlockRestricted,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.shared_vault == Some(shared_vault.key()) @ VestingError::InvalidSharedVault
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [SHARED_VAULT_SEED.as_bytes(), shared_vault.authority.as_ref(), shared_vault.mint.as_ref()], bump,
+        has_one = vault_token @ VestingError::InvalidSharedVault,
+        has_one = mint @ VestingError::InvalidMint
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CancelInSharedVault<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = vesting.shared_vault == Some(shared_vault.key()) @ VestingError::InvalidSharedVault
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        has_one = authority @ VestingError::InvalidSharedVault,
+        has_one = vault_token @ VestingError::InvalidSharedVault
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    pub vault_token: Account<'info, TokenAccount>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromSharedVault<'info> {
+    #[account(seeds = [SHARED_VAULT_SEED.as_bytes(), shared_vault.authority.as_ref(), shared_vault.mint.as_ref()], bump,
+        has_one = authority @ VestingError::InvalidSharedVault,
+        has_one = vault_token @ VestingError::InvalidSharedVault,
+        has_one = mint @ VestingError::InvalidMint
+    )]
+    pub shared_vault: Account<'info, SharedVault>,
+
+    #[account(mut)]
+    pub vault_token: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub refund_token: Account<'info, TokenAccount>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String, tranches: Vec<TemplateTranche>)]
+pub struct CreateScheduleTemplate<'info> {
+    #[account(init, payer = payer, space = calc_schedule_template_size(tranches.len()),
+        seeds = [SCHEDULE_TEMPLATE_SEED.as_bytes(), authority.key().as_ref(), name.as_bytes()], bump)]
+    pub schedule_template: Account<'info, ScheduleTemplate>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreateFromTemplate<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == schedule_template.tranches.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting_token.mint, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub source_authority: Signer<'info>,
+    pub schedule_template: Account<'info, ScheduleTemplate>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CloneContract<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == source_vesting.schedule.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(constraint = source_vesting.is_initialized @ VestingError::NotInitialized)]
+    pub source_vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &source_vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = source_token.mint == source_vesting.mint_address @ VestingError::InvalidMint)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub source_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateCohort<'info> {
+    #[account(init, payer = payer, space = COHORT_ACCOUNT_SIZE,
+        seeds = [COHORT_SEED.as_bytes(), admin.key().as_ref(), name.as_bytes()], bump)]
+    pub cohort: Account<'info, Cohort>,
+    pub mint: Account<'info, Mint>,
+    pub admin: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct AddToCohort<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.cohort.is_none() @ VestingError::InvalidCohort,
+        constraint = vesting.creator == cohort.admin @ VestingError::InvalidCohort,
+        constraint = vesting.mint_address == cohort.mint @ VestingError::InvalidCohort
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, has_one = admin @ VestingError::InvalidCohort)]
+    pub cohort: Account<'info, Cohort>,
+
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct CohortAction<'info> {
+    #[account(has_one = admin @ VestingError::InvalidCohort)]
+    pub cohort: Account<'info, Cohort>,
+    pub admin: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RevokeCohort<'info> {
+    #[account(has_one = admin @ VestingError::InvalidCohort)]
+    pub cohort: Account<'info, Cohort>,
+    pub admin: Signer<'info>,
+
+    #[account(mut, constraint = refund_token.mint == cohort.mint @ VestingError::InvalidMint)]
+    pub refund_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &cohort.mint, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct CreateAirdrop<'info> {
+    #[account(init, payer = payer, space = VESTED_AIRDROP_ACCOUNT_SIZE, seeds = [AIRDROP_SEED.as_bytes(), seeds.as_ref()], bump)]
+    pub airdrop: Account<'info, VestedAirdrop>,
+
+    #[account(mut,
+        constraint = expect_key("airdrop token owner", &airdrop.key(), &airdrop_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = airdrop_token.mint == mint.key() @ VestingError::InvalidMint,
+        constraint = airdrop_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = airdrop_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub airdrop_token: Account<'info, TokenAccount>,
+
+    pub schedule_template: Account<'info, ScheduleTemplate>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub source_authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vesting_seeds: [u8; 31])]
+pub struct ClaimAirdrop<'info> {
+    #[account(mut, seeds = [AIRDROP_SEED.as_bytes(), airdrop.seeds.as_ref()], bump,
+        has_one = mint @ VestingError::InvalidMint,
+        has_one = schedule_template @ VestingError::InvalidScheduleTemplate
+    )]
+    pub airdrop: Account<'info, VestedAirdrop>,
+
+    #[account(mut, constraint = expect_key("airdrop token", &airdrop.escrow, &airdrop_token.key()) @ VestingError::InvalidVestingTokenAuthority)]
+    pub airdrop_token: Account<'info, TokenAccount>,
+
+    pub schedule_template: Account<'info, ScheduleTemplate>,
+
+    #[account(init_if_needed, payer = claimant, space = calc_vesting_account_size(schedule_template.tranches.len() as u32),
+        seeds = [vesting_seeds.as_ref()], bump,
+        constraint = vesting_seeds == airdrop_position_seeds(&airdrop.key(), &claimant.key()) @ VestingError::InvalidVestingSeeds,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init_if_needed, payer = claimant, associated_token::mint = mint, associated_token::authority = vesting)]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub claimant: Signer<'info>,
+
+    #[account(
+        constraint = destination_token.owner == claimant.key() @ VestingError::InvalidDestinationAuthority,
+        constraint = destination_token.mint == mint.key() @ VestingError::InvalidMint
+    )]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct FundNextPeriod<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract,
+        constraint = vesting.terminated_at.is_none() @ VestingError::AlreadyTerminated,
+        constraint = vesting.creator == payer.key() || vesting.payroll_payer == Some(payer.key()) @ VestingError::InvalidPayrollPayer
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.mint == vesting.mint_address @ VestingError::InvalidMint
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct FundSalaryPeriod<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract,
+        constraint = vesting.terminated_at.is_none() @ VestingError::AlreadyTerminated,
+        constraint = vesting.creator == payer.key() || vesting.payroll_payer == Some(payer.key()) @ VestingError::InvalidPayrollPayer,
+        constraint = vesting.salary_price_feed == Some(price_feed.key()) @ VestingError::InvalidPriceFeed
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.mint == vesting.mint_address @ VestingError::InvalidMint
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    /// CHECK: a Pyth price account, the salary price feed of the contract, read by `pyth::load_price`
+    pub price_feed: UncheckedAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct Terminate<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.is_accepted @ VestingError::NotAccepted,
+        constraint = vesting.terminated_at.is_none() @ VestingError::AlreadyTerminated,
+        constraint = vesting.revoker == Some(revoker.key()) @ VestingError::InvalidRevoker,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.receipt_mint.is_none() @ VestingError::ReceiptHolderIsBeneficiary,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    #[account(mut, constraint = refund_token.mint == vesting.mint_address @ VestingError::InvalidMint)]
+    pub refund_token: Account<'info, TokenAccount>,
+
+    pub revoker: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct AdjustCliff<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = vesting.is_accepted @ VestingError::NotAccepted,
+        constraint = vesting.terminated_at.is_none() @ VestingError::AlreadyTerminated,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination,
+        constraint = vesting.yield_config.is_none() @ VestingError::YieldVaultEnabled,
+        constraint = vesting.shared_vault.is_none() @ VestingError::SharedVaultContract
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.mint == vesting.mint_address @ VestingError::InvalidMint
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &vesting.mint_address, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = creator_token.mint == vesting.mint_address @ VestingError::InvalidMint)]
+    pub creator_token: Account<'info, TokenAccount>,
+
+    #[account(constraint = destination_token.owner == destination_authority.key() @ VestingError::InvalidDestinationAuthority)]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub creator: Signer<'info>,
+    pub destination_authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct OpenFundingEscrow<'info> {
+    #[account(init, payer = funder, space = FUNDING_ESCROW_ACCOUNT_SIZE, seeds = [FUNDING_ESCROW_SEED.as_bytes(), seeds.as_ref()], bump)]
+    pub funding_escrow: Account<'info, FundingEscrow>,
+
+    #[account(mut,
+        constraint = expect_key("funding escrow token owner", &funding_escrow.key(), &escrow_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = escrow_token.mint == mint.key() @ VestingError::InvalidMint,
+        constraint = escrow_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = escrow_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub escrow_token: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub funder_token: Account<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(vesting_seeds: [u8; 31])]
+pub struct ActivateFundingEscrow<'info> {
+    #[account(mut, seeds = [FUNDING_ESCROW_SEED.as_bytes(), funding_escrow.seeds.as_ref()], bump,
+        has_one = mint @ VestingError::InvalidMint,
+        has_one = issuer @ VestingError::InvalidIssuer,
+        constraint = funding_escrow.vesting.is_none() @ VestingError::FundingEscrowActivated
+    )]
+    pub funding_escrow: Account<'info, FundingEscrow>,
+
+    #[account(mut, constraint = expect_key("funding escrow token", &funding_escrow.escrow, &escrow_token.key()) @ VestingError::InvalidVestingTokenAuthority)]
+    pub escrow_token: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [vesting_seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    pub issuer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct AbortFundingEscrow<'info> {
+    #[account(mut, close = funder, seeds = [FUNDING_ESCROW_SEED.as_bytes(), funding_escrow.seeds.as_ref()], bump,
+        has_one = mint @ VestingError::InvalidMint,
+        has_one = funder @ VestingError::InvalidFundingParty,
+        constraint = funding_escrow.vesting.is_none() @ VestingError::FundingEscrowActivated,
+        constraint = authority.key() == funding_escrow.funder || authority.key() == funding_escrow.issuer @ VestingError::InvalidFundingParty
+    )]
+    pub funding_escrow: Account<'info, FundingEscrow>,
+
+    #[account(mut, constraint = expect_key("funding escrow token", &funding_escrow.escrow, &escrow_token.key()) @ VestingError::InvalidVestingTokenAuthority)]
+    pub escrow_token: Account<'info, TokenAccount>,
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, constraint = funder_token.owner == funding_escrow.funder @ VestingError::InvalidFundingParty)]
+    pub funder_token: Account<'info, TokenAccount>,
+
+    /// CHECK: the funder of the escrow, receiving its rent
+    #[account(mut)]
+    pub funder: UncheckedAccount<'info>,
+
+    pub authority: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(vesting_seeds: [u8; 31], buyer: Pubkey)]
+pub struct CreateFor<'info> {
+    pub sale_authority: Signer<'info>,
+
+    #[account(constraint = expect_key("schedule template authority", &schedule_template.authority, &sale_authority.key())
+        @ VestingError::InvalidScheduleTemplate)]
+    pub schedule_template: Account<'info, ScheduleTemplate>,
+
+    #[account(mut, constraint = proceeds_token.mint == mint.key() @ VestingError::InvalidMint)]
+    pub proceeds_token: Account<'info, TokenAccount>,
+
+    #[account(init_if_needed, payer = payer, space = calc_vesting_account_size(schedule_template.tranches.len() as u32),
+        seeds = [vesting_seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(init_if_needed, payer = payer, associated_token::mint = mint, associated_token::authority = vesting)]
+    pub vesting_token: Account<'info, TokenAccount>,
+
+    #[account(
+        constraint = expect_key("destination token", &get_associated_token_address(&buyer, &mint.key()), &destination_token.key())
+            @ VestingError::InvalidDestination
+    )]
+    pub destination_token: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+    pub rent: Sysvar<'info, Rent>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateRound<'info> {
+    #[account(init, payer = payer, space = ROUND_ACCOUNT_SIZE,
+        seeds = [ROUND_SEED.as_bytes(), creator.key().as_ref(), name.as_bytes()], bump)]
+    pub round: Account<'info, Round>,
+    pub mint: Account<'info, Mint>,
+    pub creator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct TagRound<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = vesting.round.is_none() @ VestingError::InvalidRound,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidCreator,
+        constraint = vesting.mint_address == round.mint @ VestingError::InvalidRound
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, has_one = creator @ VestingError::InvalidRound)]
+    pub round: Account<'info, Round>,
+
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BatchChangeDestination<'info> {
+    /// CHECK: verified to be a signer or a satisfied SPL Token multisig in the instruction
+    pub destination_authority: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct BatchRevoke<'info> {
+    pub revoker: Signer<'info>,
+
+    #[account(mut)]
+    pub refund_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &refund_token.mint, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(name: String)]
+pub struct CreateBudget<'info> {
+    #[account(init, payer = payer, space = BUDGET_ACCOUNT_SIZE,
+        seeds = [BUDGET_SEED.as_bytes(), org.key().as_ref(), name.as_bytes()], bump)]
+    pub budget: Account<'info, Budget>,
+
+    #[account(
+        constraint = expect_key("budget token owner", &budget.key(), &budget_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = budget_token.mint == mint.key() @ VestingError::InvalidMint,
+        constraint = budget_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = budget_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub budget_token: Account<'info, TokenAccount>,
+
+    pub mint: Account<'info, Mint>,
+    pub org: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetBudget<'info> {
+    #[account(mut, has_one = org @ VestingError::InvalidBudget)]
+    pub budget: Account<'info, Budget>,
+    pub org: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], destination_token_address: Pubkey, schedules: Vec<Schedule>)]
+pub struct CreateFromBudget<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == schedules.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.mint == budget.mint @ VestingError::InvalidMint,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &budget.mint, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut, seeds = [BUDGET_SEED.as_bytes(), budget.org.as_ref(), budget.name.as_bytes()], bump,
+        has_one = budget_token @ VestingError::InvalidBudget,
+        constraint = budget.admins.contains(&admin.key()) @ VestingError::InvalidBudgetAdmin
+    )]
+    pub budget: Account<'info, Budget>,
+
+    #[account(mut)]
+    pub budget_token: Account<'info, TokenAccount>,
+
+    pub admin: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromBudget<'info> {
+    #[account(seeds = [BUDGET_SEED.as_bytes(), budget.org.as_ref(), budget.name.as_bytes()], bump,
+        has_one = org @ VestingError::InvalidBudget,
+        has_one = budget_token @ VestingError::InvalidBudget
+    )]
+    pub budget: Account<'info, Budget>,
+
+    #[account(mut)]
+    pub budget_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &budget.mint, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub refund_token: Account<'info, TokenAccount>,
+
+    pub org: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct CreateRollup<'info> {
+    #[account(init, payer = payer, space = ROLLUP_ACCOUNT_SIZE,
+        seeds = [ROLLUP_SEED.as_bytes(), org.key().as_ref(), beneficiary.as_ref()], bump)]
+    pub rollup: Account<'info, Rollup>,
+    pub mint: Account<'info, Mint>,
+    pub org: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct AddToRollup<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_accepted @ VestingError::AlreadyAccepted,
+        constraint = vesting.rollup.is_none() @ VestingError::InvalidRollup,
+        constraint = vesting.creator == org.key() @ VestingError::InvalidCreator,
+        constraint = vesting.mint_address == rollup.mint @ VestingError::InvalidRollup,
+        constraint = expect_key("destination", &vesting.destination_address, &destination_token.key()) @ VestingError::InvalidDestination
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        has_one = org @ VestingError::InvalidRollup,
+        constraint = destination_token.owner == rollup.beneficiary @ VestingError::InvalidRollup
+    )]
+    pub rollup: Account<'info, Rollup>,
+
+    pub destination_token: Account<'info, TokenAccount>,
+    pub org: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct RefreshRollup<'info> {
+    #[account(mut)]
+    pub rollup: Account<'info, Rollup>,
+}
+
+#[derive(Accounts)]
+pub struct CreateUnlockQueue<'info> {
+    #[account(init, payer = payer, space = UNLOCK_QUEUE_ACCOUNT_SIZE,
+        seeds = [UNLOCK_QUEUE_SEED.as_bytes(), mint.key().as_ref()], bump)]
+    pub unlock_queue: Account<'info, UnlockQueue>,
+    pub mint: Account<'info, Mint>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct RegisterUnlock<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [UNLOCK_QUEUE_SEED.as_bytes(), vesting.mint_address.as_ref()], bump)]
+    pub unlock_queue: Account<'info, UnlockQueue>,
+}
+
+#[derive(Accounts)]
+pub struct ProcessQueue<'info> {
+    #[account(mut, has_one = mint @ VestingError::InvalidMint)]
+    pub unlock_queue: Account<'info, UnlockQueue>,
+    pub mint: Account<'info, Mint>,
+    pub token_program: Program<'info, Token>,
+}
+
+#[derive(Accounts)]
+pub struct CreatePartnerConfig<'info> {
+    #[account(init, payer = payer, space = PARTNER_CONFIG_ACCOUNT_SIZE,
+        seeds = [PARTNER_SEED.as_bytes(), authority.key().as_ref()], bump)]
+    pub partner_config: Account<'info, PartnerConfig>,
+    pub authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPartnerConfig<'info> {
+    #[account(mut, has_one = authority @ VestingError::InvalidPartnerConfig)]
+    pub partner_config: Account<'info, PartnerConfig>,
+    pub authority: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], destination_token_address: Pubkey, schedules: Vec<Schedule>)]
+pub struct CreateWithPartner<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = !vesting.is_initialized @ VestingError::AlreadyInitialized,
+        constraint = vesting.schedule.len() == schedules.len() @ VestingError::InvalidScheduleLen
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut,
+        constraint = expect_key("vesting token owner", &vesting.key(), &vesting_token.owner) @ VestingError::InvalidVestingTokenAuthority,
+        constraint = vesting_token.mint == source_token.mint @ VestingError::InvalidMint,
+        constraint = vesting_token.delegate.is_none() @ VestingError::InvalidVestingTokenDelegateAuthority,
+        constraint = vesting_token.close_authority.is_none() @ VestingError::InvalidVestingTokenCloseAuthority
+    )]
+    pub vesting_token: Account<'info, TokenAccount>,
+    #[account(constraint = expect_key("mint", &source_token.mint, &mint.key()) @ VestingError::InvalidMint)]
+    pub mint: Account<'info, Mint>,
+
+    #[account(mut)]
+    pub source_token: Account<'info, TokenAccount>,
+
+    pub partner_config: Account<'info, PartnerConfig>,
+
+    #[account(mut,
+        constraint = fee_token.owner == partner_config.fee_recipient @ VestingError::InvalidPartnerConfig,
+        constraint = fee_token.mint == source_token.mint @ VestingError::InvalidMint
+    )]
+    pub fee_token: Account<'info, TokenAccount>,
+
+    #[account(init_if_needed, payer = payer, space = PARTNER_STATS_ACCOUNT_SIZE,
+        seeds = [PARTNER_STATS_SEED.as_bytes(), partner_config.key().as_ref(), source_token.mint.as_ref()], bump)]
+    pub partner_stats: Account<'info, PartnerStats>,
+
+    pub source_authority: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub token_program: Program<'info, Token>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct CreateRentSponsorshipPool<'info> {
+    #[account(init, payer = creator, space = RENT_SPONSORSHIP_POOL_ACCOUNT_SIZE,
+        seeds = [RENT_SPONSORSHIP_SEED.as_bytes(), creator.key().as_ref()], bump)]
+    pub pool: Account<'info, RentSponsorshipPool>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct FundRentSponsorshipPool<'info> {
+    #[account(mut, seeds = [RENT_SPONSORSHIP_SEED.as_bytes(), pool.creator.as_ref()], bump)]
+    pub pool: Account<'info, RentSponsorshipPool>,
+
+    #[account(mut)]
+    pub funder: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawFromRentSponsorshipPool<'info> {
+    #[account(mut, seeds = [RENT_SPONSORSHIP_SEED.as_bytes(), creator.key().as_ref()], bump)]
+    pub pool: Account<'info, RentSponsorshipPool>,
+
+    #[account(mut)]
+    pub creator: Signer<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31])]
+pub struct SponsorDestinationRent<'info> {
+    #[account(mut, seeds = [seeds.as_ref()], bump,
+        constraint = vesting.is_initialized @ VestingError::NotInitialized,
+        constraint = !vesting.is_rent_sponsored @ VestingError::RentAlreadySponsored,
+        constraint = vesting.destination_owner == Some(owner.key()) @ VestingError::InvalidDestinationOwner
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [RENT_SPONSORSHIP_SEED.as_bytes(), vesting.creator.as_ref()], bump)]
+    pub pool: Account<'info, RentSponsorshipPool>,
+
+    /// CHECK: the wallet recorded as the destination owner
+    pub owner: UncheckedAccount<'info>,
+    /// CHECK: the associated token account of the owner, funded before it exists
+    #[account(mut, address = get_associated_token_address(&owner.key(), &vesting.mint_address) @ VestingError::InvalidDestination)]
+    pub destination_token: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+pub struct SponsorClaimStatusRent<'info> {
+    #[account(seeds = [DISTRIBUTOR_SEED.as_bytes(), distributor.seeds.as_ref()], bump)]
+    pub distributor: Account<'info, MerkleDistributor>,
+
+    #[account(mut, seeds = [RENT_SPONSORSHIP_SEED.as_bytes(), distributor.creator.as_ref()], bump)]
+    pub pool: Account<'info, RentSponsorshipPool>,
+
+    /// CHECK: the claimant whose claim status is funded
+    pub claimant: UncheckedAccount<'info>,
+    /// CHECK: the claim status of the claimant, funded before it exists
+    #[account(mut, seeds = [CLAIM_STATUS_SEED.as_bytes(), distributor.key().as_ref(), claimant.key().as_ref()], bump)]
+    pub claim_status: UncheckedAccount<'info>,
+}
+
+#[derive(Accounts)]
+#[instruction(beneficiary: Pubkey)]
+pub struct CreateGrantIndex<'info> {
+    #[account(init, payer = payer, space = GRANT_INDEX_ACCOUNT_SIZE,
+        seeds = [GRANT_INDEX_SEED.as_bytes(), creator.key().as_ref(), beneficiary.as_ref()], bump)]
+    pub grant_index: Account<'info, GrantIndex>,
+    pub creator: Signer<'info>,
+    #[account(mut)]
+    pub payer: Signer<'info>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(seeds: [u8; 31], grant_id: u64)]
+pub struct CloseIndexedGrant<'info> {
+    #[account(seeds = [seeds.as_ref()], bump,
+        constraint = vesting.creator == creator.key() @ VestingError::InvalidGrantIndex,
+        constraint = total_amount(&vesting.schedule)? == 0 @ VestingError::ContractNotFullyReleased
+    )]
+    pub vesting: Account<'info, Vesting>,
+
+    #[account(mut, seeds = [GRANT_INDEX_SEED.as_bytes(), creator.key().as_ref(), grant_index.beneficiary.as_ref()], bump,
+        constraint = seeds == grant_seeds(&creator.key(), &grant_index.beneficiary, grant_id) @ VestingError::InvalidVestingSeeds
+    )]
+    pub grant_index: Account<'info, GrantIndex>,
+
+    pub creator: Signer<'info>,
+}
+
+#[account]
+pub struct ProgramConfig {
+    /// The upgrade authority of the program when the config was created
+    pub admin: Pubkey,
+    pub incident_authority: Pubkey,
+    /// The hash of the verifiable build of the deployed program, none until recorded
+    pub build_hash: Option<[u8; 32]>,
+}
+
+/// The programs contracts can register as claim hook, kept by the program config admin
+#[account]
+pub struct ClaimHookAllowlist {
+    pub programs: Vec<Pubkey>,
+}
+
+#[account]
+pub struct Vesting {
+    pub destination_address: Pubkey,
+    pub mint_address: Pubkey,
+    // VESTING_LAYOUT_VERSION once created. The first release stored `is_initialized`, 0 or 1, at this offset
+    pub layout_version: u8,
+    pub creator: Pubkey,
+    pub is_initialized: bool,
+    pub min_claim_amount: u64,
+    // The voter weight record counting the contract, if any
+    pub voter_weight: Option<CountedVoterWeight>,
+    pub yield_config: Option<YieldConfig>,
+    pub guarantor: Option<Pubkey>,
+    pub vote_delegate: Option<Pubkey>,
+    pub revoker: Option<Pubkey>,
+    // Mint of the receipt NFT whose holder is the beneficiary, if any
+    pub receipt_mint: Option<Pubkey>,
+    // Whether sales of the position have to be approved by the creator
+    pub transfer_approval_required: bool,
+    pub bridge_target: Option<BridgeTarget>,
+    pub claim_delegate: Option<ClaimDelegate>,
+    // The nonce the next permit has to be signed with
+    pub permit_nonce: u64,
+    // A contract is pending until the destination owner accepts it
+    pub is_accepted: bool,
+    // When set, the destination is the associated token account of this wallet
+    pub destination_owner: Option<Pubkey>,
+    // Who may unlock the contract
+    pub unlock_policy: UnlockPolicy,
+    // The number of times the destination changed
+    pub destination_change_count: u64,
+    // The number of claims recorded with a receipt, the counter of the next receipt
+    pub claim_count: u64,
+    // What the contract waits for, refreshed by every instruction changing its state
+    pub next_action: NextAction,
+    pub claim_hook: Option<ClaimHook>,
+    // The shared vault paying the contract instead of an escrow of its own, if any
+    pub shared_vault: Option<Pubkey>,
+    // The cohort the contract belongs to, if any
+    pub cohort: Option<Pubkey>,
+    // Whether the cohort admin paused the claims of the contract
+    pub is_paused: bool,
+    // The key allowed to fund pay periods besides the creator, if any
+    pub payroll_payer: Option<Pubkey>,
+    // The unix timestamp the revoker terminated the contract at, after which nothing is left to vest
+    pub terminated_at: Option<i64>,
+    // The end of the probation window during which the creator and the destination owner may adjust the cliff tranche
+    pub probation_end: Option<i64>,
+    // The Pyth feed converting the USD salaries of `fund_salary_period`, if any
+    pub salary_price_feed: Option<Pubkey>,
+    // The investment round the contract is tagged with, if any
+    pub round: Option<Pubkey>,
+    // The rollup of the contracts of the creator for the beneficiary, if any
+    pub rollup: Option<Pubkey>,
+    // The config of the partner front-end the contract was created through, if any
+    pub partner: Option<Pubkey>,
+    // Whether the creator's rent sponsorship pool already funded the destination account
+    pub is_rent_sponsored: bool,
+    pub schedule: Vec<Schedule>,
+}
+
+#[account]
+pub struct Guarantee {
+    pub vesting: Pubkey,
+    pub guarantor: Pubkey,
+    pub granter: Pubkey,
+    // Token account owned by the guarantee account holding the collateral
+    pub collateral_token_address: Pubkey,
+    pub promised_amount: u64,
+    pub topped_up_amount: u64,
+    // Unix timestamp by which the promised amount must be topped up
+    pub deadline: i64,
+    pub is_settled: bool,
+}
+
+/// Attests the vote delegation of a vesting contract. It is only meaningful while `delegator`
+/// still owns the contract destination token account and the contract still records `delegate`:
+/// changing the destination clears the delegation
+#[account]
+pub struct VoteDelegation {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub delegator: Pubkey,
+    pub delegate: Pubkey,
+    pub delegated_at: i64,
+}
+
+/// Holds the lamports tipping cranks of a vesting contract, anything above the rent exempt minimum can be paid out
+#[account]
+pub struct FeePool {
+    pub vesting: Pubkey,
+    // The creator or destination owner who created the pool, refunded when it is closed
+    pub authority: Pubkey,
+    pub tip_lamports: u64,
+}
+
+/// A vesting position offered for sale by its destination owner
+#[account]
+pub struct Listing {
+    pub vesting: Pubkey,
+    pub seller: Pubkey,
+    pub seller_payment_token_address: Pubkey,
+    pub payment_mint: Pubkey,
+    pub price: u64,
+    pub is_approved: bool,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct Schedule {
+    // Schedule release time in unix timestamp
+    pub release_time: u64,
+    pub amount: u64,
+    // Condition the tranche is gated on in addition to the release time, cleared once met
+    pub condition: Option<ReleaseCondition>,
+}
+
+impl math::Tranche for Schedule {
+    fn release_time(&self) -> u64 {
+        self.release_time
+    }
+
+    fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    fn is_gated(&self) -> bool {
+        self.condition.is_some()
+    }
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ReleaseCondition {
+    /// Met when the Pyth feed reports a price at or above (or at or below) `target_price * 10^target_expo`,
+    /// published at most `max_staleness` seconds ago. The feed is passed in the remaining accounts
+    Price {
+        price_feed: Pubkey,
+        target_price: i64,
+        target_expo: i32,
+        is_above: bool,
+        max_staleness: u64,
+    },
+    /// Met when the time weighted average of the prices sampled by `record_observation` over at least `window`
+    /// seconds is at or above (or at or below) `target_price * 10^target_expo`. Observations more than
+    /// `max_staleness` seconds or a `MIN_TWAP_OBSERVATIONS`th of the window apart restart the window
+    Twap {
+        price_feed: Pubkey,
+        target_price: i64,
+        target_expo: i32,
+        is_above: bool,
+        max_staleness: u64,
+        window: u64,
+        // Observation state, zero when created
+        window_start: i64,
+        last_observation_time: i64,
+        last_price: i128,
+        cumulative_price: i128,
+    },
+    /// Met when a verified run of the Switchboard function attests the milestone through `fulfill_function_condition`
+    SwitchboardFunction {
+        function: Pubkey,
+    },
+    /// Met when the off-chain oracle signs the tranche, checked through `fulfill_signature_condition`
+    OracleSignature {
+        oracle: Pubkey,
+    },
+    /// Met when the SPL Governance proposal has succeeded, e.g. approving a milestone review.
+    /// The proposal is passed in the remaining accounts
+    GovernanceProposal {
+        governance_program: Pubkey,
+        proposal: Pubkey,
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct YieldConfig {
+    pub vault_program: Pubkey,
+    pub vault: Pubkey,
+    // Share token account owned by the vesting account
+    pub share_token_address: Pubkey,
+    pub yield_recipient: Pubkey,
+}
+
+/// The voter weight record counting a vesting contract and the weight it was counted with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy)]
+pub struct CountedVoterWeight {
+    pub voter_weight_record: Pubkey,
+    pub weight: u64,
+}
+
+/// Locked and claimable balances of an owner in a mint across the contracts passed at the last refresh
+#[account]
+pub struct Attestation {
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub locked_amount: u64,
+    pub claimable_amount: u64,
+    pub contract_count: u32,
+    pub refreshed_at: i64,
+    pub refreshed_slot: u64,
+    pub attester: Pubkey,
+}
+
+/// The amount locked in a vesting contract as of a slot
+#[account]
+pub struct LockedSnapshot {
+    pub vesting: Pubkey,
+    pub slot: u64,
+    pub taken_at_slot: u64,
+    pub locked_amount: u64,
+}
+
+/// The contracts of a mint awaiting their next unlock, earliest release time first
+#[account]
+pub struct UnlockQueue {
+    pub mint: Pubkey,
+    pub entries: Vec<QueueEntry>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct QueueEntry {
+    pub release_time: i64,
+    // The seeds of the vesting account, signing its escrow transfers
+    pub seeds: [u8; 31],
+}
+
+/// The latest notices about the contracts paying a destination token account, oldest first
+#[account]
+pub struct Inbox {
+    pub destination: Pubkey,
+    // The sequence number of the next notice
+    pub next_sequence: u64,
+    pub records: Vec<InboxRecord>,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct InboxRecord {
+    pub sequence: u64,
+    pub notice: InboxNotice,
+    pub vesting: Pubkey,
+    pub timestamp: i64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum InboxNotice {
+    // A contract is pending the acceptance of the destination owner
+    ContractCreated,
+    ToppedUp,
+    Revoked,
+    Cancelled,
+    // The contract no longer pays this destination
+    DestinationChanged,
+}
+
+/// The record of a claim paid by `unlock_with_receipt`
+#[account]
+pub struct ClaimReceipt {
+    pub vesting: Pubkey,
+    pub counter: u64,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+/// A distributor of the tranches of many claimants, committed to by the root of a merkle tree, see `merkle`
+#[account]
+pub struct MerkleDistributor {
+    pub seeds: [u8; 31],
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    // Token account owned by the distributor holding the tokens of every leaf
+    pub escrow: Pubkey,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    // The number of claimants who claimed, each with a claim status
+    pub claimant_count: u64,
+}
+
+/// The amount a claimant claimed from a distributor, created on its first claim
+#[account]
+pub struct ClaimStatus {
+    pub distributor: Pubkey,
+    pub claimant: Pubkey,
+    pub claimed_amount: u64,
+}
+
+/// A tranche of a distributor leaf
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct MerkleTranche {
+    pub release_time: u64,
+    pub amount: u64,
+}
+
+impl math::Tranche for MerkleTranche {
+    fn release_time(&self) -> u64 {
+        self.release_time
+    }
+
+    fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    fn is_gated(&self) -> bool {
+        false
+    }
+}
+
+/// A token account escrowing many contracts of the authority, each paying its schedule out of it
+#[account]
+pub struct SharedVault {
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    // Token account owned by the shared vault
+    pub vault_token: Pubkey,
+    // The amount the schedules of the contracts still hold, which the vault balance covers
+    pub committed_amount: u64,
+    pub contract_count: u64,
+}
+
+impl SharedVault {
+    /// Returns the part of `vault_balance` no contract committed, which new contracts and withdrawals may take
+    pub fn free_amount(&self, vault_balance: u64) -> u64 {
+        vault_balance.saturating_sub(self.committed_amount)
+    }
+}
+
+/// A schedule of tranches relative to a start and weighted out of a total, shared by the contracts created from it
+#[account]
+pub struct ScheduleTemplate {
+    pub authority: Pubkey,
+    pub name: String,
+    pub tranches: Vec<TemplateTranche>,
+}
+
+/// A tranche of a schedule template
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct TemplateTranche {
+    // Seconds from the contract start to the release
+    pub offset: u64,
+    // Share of the contract total, out of the summed weights of the template
+    pub weight: u64,
+}
+
+/// Contracts of an admin for a mint grouped under shared terms, such as a launch round or an employee class
+#[account]
+pub struct Cohort {
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    // The schedule template the contracts of the cohort vest along, if any
+    pub schedule_template: Option<Pubkey>,
+    pub member_count: u64,
+    // The summed schedule totals of the contracts when they joined
+    pub total_amount: u64,
+}
+
+/// The investment round of contracts of a creator for a mint, whose tallies report the unlock overhang of the round:
+/// what was granted and neither claimed nor revoked yet. `unlock`, `revoke` and `terminate` keep them current
+#[account]
+pub struct Round {
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub contract_count: u64,
+    // The summed schedule totals of the contracts when they were tagged
+    pub total_granted: u64,
+    pub total_claimed: u64,
+    pub total_revoked: u64,
+}
+
+/// The sums of the contracts of an org paying a beneficiary, their compensation statement. `unlock`, `revoke` and
+/// `terminate` keep the claimed and revoked amounts current, the locked and vested ones being as of `updated_at`
+/// until `refresh_rollup`
+#[account]
+pub struct Rollup {
+    pub org: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub contract_count: u64,
+    pub locked_amount: u64,
+    // Released and not claimed yet
+    pub vested_unclaimed_amount: u64,
+    pub claimed_amount: u64,
+    pub revoked_amount: u64,
+    pub updated_at: i64,
+}
+
+/// The config of a partner front-end sharing the program: the fee it takes on the contracts created under it, where
+/// the fees go, and the terms of those contracts
+#[account]
+pub struct PartnerConfig {
+    pub authority: Pubkey,
+    // The owner of the token accounts the fees are paid to
+    pub fee_recipient: Pubkey,
+    pub fee_bps: u16,
+    pub branding: Pubkey,
+    pub default_min_claim_amount: u64,
+    pub default_unlock_policy: UnlockPolicy,
+    pub default_revocable: bool,
+}
+
+/// The usage of a partner config in a mint, metering the partner and feeding its dashboard. `unlock`, `revoke` and
+/// `terminate` keep the claims and the value locked current
+#[account]
+pub struct PartnerStats {
+    pub partner_config: Pubkey,
+    pub mint: Pubkey,
+    pub contract_count: u64,
+    // The amount the escrows of the contracts still hold
+    pub total_value_locked: u64,
+    pub claim_count: u64,
+    pub claimed_amount: u64,
+    pub revoked_amount: u64,
+    pub fee_amount: u64,
+}
+
+/// Holds the lamports of a creator paying the rent of the accounts claiming its contracts and distributors creates,
+/// anything above the rent exempt minimum can be drawn
+#[account]
+pub struct RentSponsorshipPool {
+    pub creator: Pubkey,
+    pub sponsored_count: u64,
+    pub sponsored_lamports: u64,
+}
+
+/// The contracts a creator issued to a beneficiary, listed by grant id. The contract of a grant id lives at the seeds
+/// `grant_seeds(creator, beneficiary, grant_id)`, so the index alone resolves every grant to the beneficiary
+#[account]
+pub struct GrantIndex {
+    pub creator: Pubkey,
+    pub beneficiary: Pubkey,
+    // The grant id of the next contract, ids of closed grants aren't reused
+    pub next_grant_id: u64,
+    pub grant_ids: Vec<u64>,
+}
+
+/// A grants budget of an org for a mint, out of which its admins create contracts up to an allocation limit
+#[account]
+pub struct Budget {
+    pub org: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    // Token account owned by the budget, funding its contracts
+    pub budget_token: Pubkey,
+    pub admins: Vec<Pubkey>,
+    pub allocation_limit: u64,
+    // The summed totals of the contracts created from the budget
+    pub allocated_amount: u64,
+}
+
+impl Budget {
+    /// Returns the amount the admins may still allocate
+    pub fn remaining_allocation(&self) -> u64 {
+        self.allocation_limit.saturating_sub(self.allocated_amount)
+    }
+}
+
+/// An airdrop of the amounts of many claimants, committed to by the root of a merkle tree, each claim creating a
+/// contract vesting along the same schedule template
+#[account]
+pub struct VestedAirdrop {
+    pub seeds: [u8; 31],
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    // Token account owned by the airdrop, holding the unclaimed amounts
+    pub escrow: Pubkey,
+    pub root: [u8; 32],
+    pub schedule_template: Pubkey,
+    // The unix timestamp the template offsets count from
+    pub start: u64,
+    pub total_amount: u64,
+    pub claimed_amount: u64,
+    pub claimant_count: u64,
+}
+
+/// A deposit of a funder waiting for its issuer to set the terms of the contract it funds, see `open_funding_escrow`
+#[account]
+pub struct FundingEscrow {
+    pub seeds: [u8; 31],
+    pub funder: Pubkey,
+    pub issuer: Pubkey,
+    pub mint: Pubkey,
+    // Token account owned by the funding escrow, holding the deposit until activation
+    pub escrow: Pubkey,
+    pub amount: u64,
+    // The contract the deposit funded, none while pending
+    pub vesting: Option<Pubkey>,
+}
+
+/// A key allowed to claim on behalf of the destination owner
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimDelegate {
+    pub delegate: Pubkey,
+    pub expires_at: i64,
+}
+
+#[event]
+pub struct VestingInitialized {
+    pub vesting: Pubkey,
+    pub number_of_schedules: u32,
+}
+
+#[event]
+pub struct VestingCreated {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub destination: Pubkey,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct MigratedFromBonfida {
+    pub vesting: Pubkey,
+    pub bonfida_vesting: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingLayoutMigrated {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct IncidentAuthorityChanged {
+    pub config: Pubkey,
+    pub old_incident_authority: Option<Pubkey>,
+    pub new_incident_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BuildHashChanged {
+    pub config: Pubkey,
+    pub old_build_hash: Option<[u8; 32]>,
+    pub new_build_hash: [u8; 32],
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingAccepted {
+    pub vesting: Pubkey,
+    pub destination_authority: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingCancelled {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub refund: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingRevoked {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub revoker: Pubkey,
+    pub refund: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensUnlocked {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub decimals: u8,
+    // The amount in whole tokens, e.g. "1.5" for 1500000000 base units of a 9 decimals mint
+    pub ui_amount: String,
+    // SHA-256 of the memo attached to the transfer
+    pub memo_hash: Option<[u8; 32]>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchUnlocked {
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    // The number of contracts something was unlocked from
+    pub contract_count: u32,
+    pub amount: u64,
+    pub decimals: u8,
+    pub ui_amount: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensStaked {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub stake_pool: Pubkey,
+    pub pool_token: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokensBridged {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub amount: u64,
+    pub chain: u16,
+    pub address: [u8; 32],
+    pub nonce: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct YieldRedeemed {
+    pub vesting: Pubkey,
+    pub yield_recipient: Pubkey,
+    pub shares: u64,
+    pub timestamp: i64,
+}
+
+// Every tranche whose release time, amount or condition changed, so the schedule history can be rebuilt from the logs
+#[event]
+pub struct ScheduleAmended {
+    pub vesting: Pubkey,
+    pub amendment: ScheduleAmendment,
+    pub changes: Vec<ScheduleChange>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DestinationChanged {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub old_destination: Pubkey,
+    pub new_destination: Pubkey,
+    // The key that authorized the change: the destination authority, the creator or the seller of the position
+    pub authority: Pubkey,
+    // The destination change count of the contract after this change
+    pub change_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BridgeTargetChanged {
+    pub vesting: Pubkey,
+    pub bridge_target: Option<BridgeTarget>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimHookChanged {
+    pub vesting: Pubkey,
+    pub claim_hook: Option<ClaimHook>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimHookAllowlistChanged {
+    pub claim_hook_allowlist: Pubkey,
+    pub programs: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimHookSkipped {
+    pub vesting: Pubkey,
+    pub program: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ClaimDelegateChanged {
+    pub vesting: Pubkey,
+    pub claim_delegate: Option<ClaimDelegate>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VoteDelegateChanged {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub vote_delegate: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestingSettingsChanged {
+    pub vesting: Pubkey,
+    pub transfer_approval_required: bool,
+    pub unlock_policy: UnlockPolicy,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FeePoolFunded {
+    pub vesting: Pubkey,
+    pub fee_pool: Pubkey,
+    pub funder: Pubkey,
+    pub lamports: u64,
+    pub tip_lamports: u64,
+}
+
+#[event]
+pub struct FeePoolClosed {
+    pub vesting: Pubkey,
+    pub fee_pool: Pubkey,
+    pub authority: Pubkey,
+    pub lamports: u64,
+}
+
+#[event]
+pub struct GuaranteeCreated {
+    pub vesting: Pubkey,
+    pub guarantee: Pubkey,
+    pub guarantor: Pubkey,
+    pub granter: Pubkey,
+    pub promised_amount: u64,
+    pub collateral_amount: u64,
+    pub deadline: i64,
+}
+
+#[event]
+pub struct ToppedUp {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub schedule_index: u32,
+    pub amount: u64,
+    pub topped_up_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GuaranteeSettled {
+    pub vesting: Pubkey,
+    pub guarantee: Pubkey,
+    pub shortfall_paid: u64,
+    pub collateral_returned: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RegistrarCreated {
+    pub registrar: Pubkey,
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+}
+
+#[event]
+pub struct VoterWeightUpdated {
+    pub voter_weight_record: Pubkey,
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub governing_token_owner: Pubkey,
+    pub voter_weight: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct MaxVoterWeightUpdated {
+    pub max_voter_weight_record: Pubkey,
+    pub realm: Pubkey,
+    pub governing_token_mint: Pubkey,
+    pub max_voter_weight: u64,
+    pub slot: u64,
+}
+
+#[event]
+pub struct AttestationRefreshed {
+    pub attestation: Pubkey,
+    pub owner: Pubkey,
+    pub mint: Pubkey,
+    pub locked_amount: u64,
+    pub claimable_amount: u64,
+    pub contract_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SnapshotTaken {
+    pub vesting: Pubkey,
+    pub snapshot: Pubkey,
+    pub slot: u64,
+    pub taken_at_slot: u64,
+    pub locked_amount: u64,
+}
+
+/// The state of a contract at `timestamp`. Claimed tranches are zeroed, so `total_amount` is what the escrow still owes
+#[event]
+pub struct Statement {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub destination: Pubkey,
+    pub revoker: Option<Pubkey>,
+    pub is_accepted: bool,
+    pub unlock_policy: UnlockPolicy,
+    pub escrow_balance: u64,
+    pub total_amount: u64,
+    pub released_amount: u64,
+    pub locked_amount: u64,
+    pub claimable_amount: u64,
+    pub min_claim_amount: u64,
+    pub claim_count: u64,
+    pub destination_change_count: u64,
+    pub next_action: NextAction,
+    pub schedule: Vec<Schedule>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PriceObserved {
+    pub vesting: Pubkey,
+    pub schedule_index: u32,
+    // Scaled to the exponent of the condition target price
+    pub price: i128,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReleaseConditionMet {
+    pub vesting: Pubkey,
+    pub schedule_index: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ReceiptMinted {
+    pub vesting: Pubkey,
+    pub receipt_mint: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CompressedReceiptMinted {
+    pub vesting: Pubkey,
+    pub merkle_tree: Pubkey,
+    pub owner: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionListed {
+    pub vesting: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub payment_mint: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingCancelled {
+    pub vesting: Pubkey,
+    pub listing: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ListingApproved {
+    pub vesting: Pubkey,
+    pub listing: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PositionSold {
+    pub vesting: Pubkey,
+    pub listing: Pubkey,
+    pub seller: Pubkey,
+    pub buyer: Pubkey,
+    pub payment_mint: Pubkey,
+    pub price: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributorCreated {
+    pub distributor: Pubkey,
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub root: [u8; 32],
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct DistributorClaimed {
+    pub distributor: Pubkey,
+    pub claimant: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    // The amount the claimant claimed so far, this claim included
+    pub claimed_amount: u64,
+    // The amount of the leaf still locked
+    pub locked_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharedVaultCreated {
+    pub shared_vault: Pubkey,
+    pub authority: Pubkey,
+    pub mint: Pubkey,
+    pub vault_token: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SharedVaultCapacityChanged {
+    pub shared_vault: Pubkey,
+    pub committed_amount: u64,
+    pub free_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ScheduleTemplateCreated {
+    pub schedule_template: Pubkey,
+    pub authority: Pubkey,
+    pub name: String,
+    pub tranche_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CohortCreated {
+    pub cohort: Pubkey,
+    pub admin: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub schedule_template: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct CohortJoined {
+    pub cohort: Pubkey,
+    pub vesting: Pubkey,
+    pub total_amount: u64,
+    pub member_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContractPauseChanged {
+    pub vesting: Pubkey,
+    pub cohort: Pubkey,
+    pub is_paused: bool,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AirdropCreated {
+    pub airdrop: Pubkey,
+    pub mint: Pubkey,
+    pub creator: Pubkey,
+    pub root: [u8; 32],
+    pub schedule_template: Pubkey,
+    pub start: u64,
+    pub total_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct AirdropClaimed {
+    pub airdrop: Pubkey,
+    pub claimant: Pubkey,
+    // The contract created for the claimant
+    pub vesting: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayrollPayerChanged {
+    pub vesting: Pubkey,
+    pub payroll_payer: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PayPeriodFunded {
+    pub vesting: Pubkey,
+    pub payer: Pubkey,
+    pub amount: u64,
+    pub release_time: u64,
+    // The number of tranches of the contract, this one included
+    pub schedule_count: u32,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct ContractTerminated {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub revoker: Pubkey,
+    pub destination: Pubkey,
+    pub refund: Pubkey,
+    // The amount vested by the termination, paid to the destination
+    pub vested_amount: u64,
+    // The rest of the escrow, returned to the refund account
+    pub refunded_amount: u64,
+    pub terminated_at: i64,
+}
+
+#[event]
+pub struct ProbationChanged {
+    pub vesting: Pubkey,
+    pub probation_end: Option<i64>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SalaryPriceFeedChanged {
+    pub vesting: Pubkey,
+    pub salary_price_feed: Option<Pubkey>,
+    pub timestamp: i64,
+}
+
+// The conversion of a USD salary funded by `fund_salary_period`, both figures kept for accounting
+#[event]
+pub struct SalaryPeriodFunded {
+    pub vesting: Pubkey,
+    pub price_feed: Pubkey,
+    // The salary in millionths of a dollar
+    pub usd_amount: u64,
+    // The token amount the salary converted to, appended to the schedule
+    pub amount: u64,
+    // The price of the token, `price * 10^expo` dollars
+    pub price: i64,
+    pub expo: i32,
+    pub publish_time: i64,
+    pub release_time: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingEscrowOpened {
+    pub funding_escrow: Pubkey,
+    pub mint: Pubkey,
+    pub funder: Pubkey,
+    pub issuer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingEscrowActivated {
+    pub funding_escrow: Pubkey,
+    // The contract the deposit funded
+    pub vesting: Pubkey,
+    pub funder: Pubkey,
+    pub issuer: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundingEscrowAborted {
+    pub funding_escrow: Pubkey,
+    // The funder or the issuer
+    pub authority: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+// A contract created by a launchpad at the settlement of a sale with `create_for`
+#[event]
+pub struct SaleContractCreated {
+    pub vesting: Pubkey,
+    pub mint: Pubkey,
+    pub sale_authority: Pubkey,
+    pub buyer: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+    pub start: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundCreated {
+    pub round: Pubkey,
+    pub creator: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RoundTagged {
+    pub round: Pubkey,
+    pub vesting: Pubkey,
+    pub total_amount: u64,
+    pub contract_count: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BatchRevoked {
+    pub revoker: Pubkey,
+    pub refund: Pubkey,
+    // The number of contracts something was revoked from
+    pub contract_count: u32,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BudgetCreated {
+    pub budget: Pubkey,
+    pub org: Pubkey,
+    pub mint: Pubkey,
+    pub name: String,
+    pub allocation_limit: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BudgetChanged {
+    pub budget: Pubkey,
+    pub allocation_limit: u64,
+    pub admins: Vec<Pubkey>,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct BudgetDrawn {
+    pub budget: Pubkey,
+    pub vesting: Pubkey,
+    pub admin: Pubkey,
+    pub amount: u64,
+    pub remaining_allocation: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RollupCreated {
+    pub rollup: Pubkey,
+    pub org: Pubkey,
+    pub beneficiary: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RollupUpdated {
+    pub rollup: Pubkey,
+    pub contract_count: u64,
+    pub locked_amount: u64,
+    pub vested_unclaimed_amount: u64,
+    pub claimed_amount: u64,
+    pub revoked_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct UnlockQueueCreated {
+    pub unlock_queue: Pubkey,
+    pub mint: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct QueueProcessed {
+    pub unlock_queue: Pubkey,
+    // The number of due entries popped
+    pub processed_count: u32,
+    // The number of contracts something was unlocked from
+    pub contract_count: u32,
+    pub amount: u64,
+    pub decimals: u8,
+    pub ui_amount: String,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PartnerConfigChanged {
+    pub partner_config: Pubkey,
+    pub authority: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub fee_bps: u16,
+    pub branding: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct PartnerContractCreated {
+    pub partner_config: Pubkey,
+    pub vesting: Pubkey,
+    pub fee_token: Pubkey,
+    pub fee: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RentSponsorshipPoolFunded {
+    pub pool: Pubkey,
+    pub funder: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RentSponsorshipPoolWithdrawn {
+    pub pool: Pubkey,
+    pub creator: Pubkey,
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RentSponsored {
+    pub pool: Pubkey,
+    pub account: Pubkey,
+    // 0 when the account already held its rent
+    pub lamports: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GrantIndexCreated {
+    pub grant_index: Pubkey,
+    pub creator: Pubkey,
+    pub beneficiary: Pubkey,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct GrantIndexChanged {
+    pub grant_index: Pubkey,
+    pub vesting: Pubkey,
+    pub grant_id: u64,
+    // Whether the grant was added to the index or removed from it
+    pub indexed: bool,
+    pub timestamp: i64,
+}
+
+/// Who may unlock a contract. Restricted contracts can't be unlocked by the permissionless instructions
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum UnlockPolicy {
+    Anyone,
+    // The destination owner and its unexpired claim delegate
+    DestinationOwner,
+    // Only these keys, at most MAX_UNLOCK_ALLOWLIST_LEN
+    Allowlist(Vec<Pubkey>),
+}
+
+/// The state of a contract for clients not reimplementing the schedule logic. A contract waiting until a release
+/// time becomes claimable once that time has passed
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, PartialEq)]
+pub enum NextAction {
+    AcceptPending,
+    Claimable,
+    WaitUntil(i64),
+    // A tranche past its release time waits for its release condition
+    ConditionPending,
+    Completed,
+}
+
+/// The instruction amending a schedule
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub enum ScheduleAmendment {
+    TopUp,
+    Revoke,
+    Cancel,
+    Terminate,
+    CliffAdjustment,
+}
+
+/// A tranche of a schedule before and after an amendment
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ScheduleChange {
+    pub index: u32,
+    pub old_release_time: u64,
+    pub new_release_time: u64,
+    pub old_amount: u64,
+    pub new_amount: u64,
+    pub old_has_condition: bool,
+    pub new_has_condition: bool,
+}
+
+/// The program notified after each claim of a contract and the accounts it is invoked with
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct ClaimHook {
+    pub program: Pubkey,
+    // At most MAX_CLAIM_HOOK_ACCOUNTS, writable when passed writable to the claim
+    pub accounts: Vec<Pubkey>,
+}
+
+/// The foreign-chain recipient of bridged unlocks
+#[derive(AnchorSerialize, AnchorDeserialize, Clone)]
+pub struct BridgeTarget {
+    // Wormhole chain id
+    pub chain: u16,
+    // The recipient address, left-padded to 32 bytes
+    pub address: [u8; 32],
+}
+
+#[error_code]
+pub enum VestingError {
+    #[msg("Cannot overwrite an existing vesting contract.")]
+    AlreadyInitialized,
+    #[msg("The vesting account isn't initialized.")]
+    NotInitialized,
+    #[msg("The vesting token account should be owned by the vesting account.")]
+    InvalidVestingTokenAuthority,
+    #[msg("The vesting token account should not have a delegate authority")]
+    InvalidVestingTokenDelegateAuthority,
+    #[msg("The vesting token account should not have a close authority")]
+    InvalidVestingTokenCloseAuthority,
+    #[msg("The source token account has insufficient funds.")]
+    InsufficientFunds,
+    #[msg("The schedule vector should have len defined during initialize.")]
+    InvalidScheduleLen,
+    #[msg("Total amount overflows u64")]
+    TotalAmountOverflow,
+    #[msg("Contract destination account does not matched provided account")]
+    InvalidDestination,
+    #[msg("Vesting contract has not yet reached release time")]
+    ReleaseTimeNotYetReached,
+    #[msg("The current destination token account isn't owned by the provided owner")]
+    InvalidDestinationAuthority,
+    #[msg("The claimable amount is below the contract minimum claim amount")]
+    ClaimBelowMinimum,
+    #[msg("The contract escrow is held in a yield vault, use unlock_from_yield")]
+    YieldVaultEnabled,
+    #[msg("The vault accounts do not match the contract yield configuration")]
+    InvalidYieldVault,
+    #[msg("The yield recipient does not match the contract yield configuration")]
+    InvalidYieldRecipient,
+    #[msg("The vesting contract already has a guarantee")]
+    GuaranteeAlreadyExists,
+    #[msg("The collateral token account should be owned by the guarantee account")]
+    InvalidCollateralTokenAuthority,
+    #[msg("The token account mint does not match the contract mint")]
+    InvalidMint,
+    #[msg("The guarantee has already been settled")]
+    GuaranteeSettled,
+    #[msg("Only the creator or the revoker of the contract can be the granter of its guarantee")]
+    InvalidGuaranteeGranter,
+    #[msg("Only the granter recorded on the guarantee can top up the contract")]
+    InvalidGranter,
+    #[msg("The schedule index is out of range")]
+    InvalidScheduleIndex,
+    #[msg("The promised amount has not been topped up and the deadline has not passed yet")]
+    GuaranteeNotDue,
+    #[msg("The guarantor token account isn't owned by the guarantor")]
+    InvalidGuarantorToken,
+    #[msg("The realm isn't owned by the governance program")]
+    InvalidRealm,
+    #[msg("The remaining accounts should be (vesting, destination token) pairs")]
+    InvalidRemainingAccounts,
+    #[msg("The same vesting account was provided more than once")]
+    DuplicateVestingAccount,
+    #[msg("The contract is counted in the voter weight record of another voter")]
+    VoterWeightCountedElsewhere,
+    #[msg("The voter weight record counting the contract must be in the remaining accounts")]
+    MissingVoterWeightRecord,
+    #[msg("Only the realm authority can update the max voter weight record")]
+    InvalidRealmAuthority,
+    #[msg("The contract isn't revocable by the provided authority")]
+    InvalidRevoker,
+    #[msg("The contract guarantor must co-sign the revocation")]
+    MissingGuarantorSignature,
+    #[msg("The contract has no unvested amount left to revoke")]
+    NothingToRevoke,
+    #[msg("The token account owner did not sign")]
+    MissingOwnerSignature,
+    #[msg("Not enough signers of the multisig token account owner signed")]
+    MissingMultisigSignatures,
+    #[msg("Only the contract creator or destination owner can manage the fee pool")]
+    InvalidFeePoolAuthority,
+    #[msg("The vesting contract still has unreleased tranches")]
+    ContractNotFullyReleased,
+    #[msg("The tranche has no pending release condition")]
+    NoReleaseCondition,
+    #[msg("The price feed does not match the release condition or isn't a Pyth price account")]
+    InvalidPriceFeed,
+    #[msg("The price feed isn't currently trading")]
+    PriceUnavailable,
+    #[msg("The price feed hasn't been updated within the allowed staleness")]
+    StalePrice,
+    #[msg("The price condition isn't met")]
+    PriceConditionNotMet,
+    #[msg("TWAP release conditions are met through record_observation")]
+    TwapRequiresObservations,
+    #[msg("The tranche release condition isn't a TWAP condition")]
+    NotTwapCondition,
+    #[msg("Price arithmetic overflowed")]
+    PriceOverflow,
+    #[msg("Switchboard function release conditions are met through fulfill_function_condition")]
+    FunctionRequiresEnclaveSigner,
+    #[msg("The tranche release condition isn't a Switchboard function condition")]
+    NotFunctionCondition,
+    #[msg("The account doesn't match the release condition or isn't a Switchboard function")]
+    InvalidSwitchboardFunction,
+    #[msg("The signer isn't the enclave signer of a verified run of the function")]
+    InvalidEnclaveSigner,
+    #[msg("Oracle signature release conditions are met through fulfill_signature_condition")]
+    SignatureRequiresEd25519Instruction,
+    #[msg("The tranche release condition isn't an oracle signature condition")]
+    NotSignatureCondition,
+    #[msg("The oracle signature deadline has passed")]
+    SignatureExpired,
+    #[msg("The transaction doesn't verify the oracle signature of the tranche")]
+    MissingOracleSignature,
+    #[msg("The account doesn't match the release condition or isn't a governance proposal")]
+    InvalidProposal,
+    #[msg("The governance proposal hasn't succeeded")]
+    ProposalNotSucceeded,
+    #[msg("Only the contract creator can perform this action")]
+    InvalidCreator,
+    #[msg("The vesting contract already has a receipt")]
+    ReceiptAlreadyExists,
+    #[msg("The receipt holder is the beneficiary of the contract, use unlock_to_holder")]
+    ReceiptHolderIsBeneficiary,
+    #[msg("The token account doesn't hold the contract receipt")]
+    InvalidReceipt,
+    #[msg("The token account isn't the receipt holder's associated token account")]
+    InvalidHolderToken,
+    #[msg("The payment token account doesn't match the listing")]
+    InvalidPaymentToken,
+    #[msg("The seller doesn't match the listing or no longer owns the destination")]
+    InvalidSeller,
+    #[msg("The listing price differs from the expected price")]
+    ListingPriceChanged,
+    #[msg("The sale has to be approved by the contract creator")]
+    ListingNotApproved,
+    #[msg("The soulbound receipt accounts don't match the contract")]
+    InvalidSoulboundReceipt,
+    #[msg("No foreign-chain address is recorded for bridging")]
+    NoBridgeTarget,
+    #[msg("The Wormhole accounts don't match the bridge")]
+    InvalidBridgeAccounts,
+    #[msg("The snapshot slot hasn't been reached yet")]
+    SnapshotSlotNotReached,
+    #[msg("The signer is neither the destination owner, an unexpired claim delegate nor on the unlock allowlist")]
+    InvalidClaimAuthority,
+    #[msg("The permit nonce isn't the current nonce of the contract")]
+    InvalidPermitNonce,
+    #[msg("No ed25519 instruction verifies the permit signature of the destination owner")]
+    MissingPermitSignature,
+    #[msg("The vesting contract has already been accepted")]
+    AlreadyAccepted,
+    #[msg("The vesting contract hasn't been accepted by the destination owner")]
+    NotAccepted,
+    #[msg("The wallet isn't the recorded destination owner")]
+    InvalidDestinationOwner,
+    #[msg("The unlock policy of the contract doesn't allow this unlock")]
+    UnlockRestricted,
+    #[msg("A memo requires the SPL Memo program as the first remaining account")]
+    InvalidMemoProgram,
+    #[msg("Events can only be emitted by the event authority")]
+    InvalidEventAuthority,
+    #[msg("The vesting account isn't derived from the given seeds")]
+    InvalidVestingSeeds,
+    #[msg("An unlock allowlist holds between 1 and MAX_UNLOCK_ALLOWLIST_LEN keys")]
+    InvalidUnlockPolicy,
+    #[msg("A claim hook can't target this program and holds at most MAX_CLAIM_HOOK_ACCOUNTS accounts")]
+    InvalidClaimHook,
+    #[msg("The claim hook program, its authority and registered accounts must be passed as remaining accounts")]
+    InvalidClaimHookAccounts,
+    #[msg("The contract has a claim hook this instruction can't run")]
+    ClaimHookEnabled,
+    #[msg("The destination owner is a wallet, which has to accept the contract itself")]
+    DestinationOwnerOnCurve,
+    #[msg("A program derived destination owner can't sign permits, its program has to call authorized_unlock")]
+    ProgramOwnedDestination,
+    #[msg("The account isn't an initialized Bonfida vesting contract of the given seeds")]
+    InvalidBonfidaVesting,
+    #[msg("The program data isn't this program's, or the signer isn't its upgrade authority")]
+    InvalidProgramData,
+    #[msg("Only the admin of the program config can update it")]
+    InvalidConfigAdmin,
+    #[msg("The program is built without the test-clock feature")]
+    TestClockDisabled,
+    #[msg("The proof doesn't link the claimant's leaf to the distributor root")]
+    InvalidMerkleProof,
+    #[msg("The claim exceeds the total amount of the distributor")]
+    DistributorOverdrawn,
+    #[msg("The shared vault isn't the contract's, or its vault token account or authority doesn't match")]
+    InvalidSharedVault,
+    #[msg("The contract is paid from a shared vault, unlock it with unlock_from_shared_vault")]
+    SharedVaultContract,
+    #[msg("A schedule template needs a name of at most 32 bytes and 1 to MAX_SCHEDULES tranches of positive total weight, releasing within the timestamp range")]
+    InvalidScheduleTemplate,
+    #[msg("The source contract has conditional tranches, releases before the start given for it or holds nothing")]
+    InvalidCloneSource,
+    #[msg("The cohort name is longer than 32 bytes, or the contract isn't the cohort admin's for the cohort mint or already belongs to a cohort")]
+    InvalidCohort,
+    #[msg("The cohort admin paused the claims of the contract")]
+    ContractPaused,
+    #[msg("Only the creator or the payroll payer of the contract can fund its pay periods")]
+    InvalidPayrollPayer,
+    #[msg("A pay period has to release after every tranche of the contract")]
+    InvalidPayPeriod,
+    #[msg("The contract was terminated")]
+    AlreadyTerminated,
+    #[msg("The contract isn't in its probation window")]
+    NotInProbation,
+    #[msg("The cliff must stay the earliest future tranche and not be empty")]
+    InvalidCliff,
+    #[msg("The cliff tranche was already released")]
+    CliffReleased,
+    #[msg("Only the issuer of the funding escrow can activate it")]
+    InvalidIssuer,
+    #[msg("Only the funder or the issuer of the funding escrow can abort it, refunding the funder")]
+    InvalidFundingParty,
+    #[msg("The funding escrow already funded a contract")]
+    FundingEscrowActivated,
+    #[msg("The schedules must add up to the funding escrow deposit, which can't be zero")]
+    FundingAmountMismatch,
+    #[msg("A round needs a name of at most 32 bytes and tags pending untagged contracts of its creator and mint")]
+    InvalidRound,
+    #[msg("The round of the contract must be in the remaining accounts")]
+    MissingRoundAccount,
+    #[msg("The budget name is longer than 32 bytes, it has more than 8 admins, or it isn't the org's")]
+    InvalidBudget,
+    #[msg("The signer isn't an admin of the budget")]
+    InvalidBudgetAdmin,
+    #[msg("The contract total exceeds the remaining allocation of the budget")]
+    BudgetExceeded,
+    #[msg("The contract or the beneficiary isn't the rollup's, or the contract already belongs to a rollup")]
+    InvalidRollup,
+    #[msg("The rollup of the contract must be in the remaining accounts")]
+    MissingRollupAccount,
+    #[msg("The unlock queue is full")]
+    UnlockQueueFull,
+    #[msg("The partner fee exceeds MAX_PARTNER_FEE_BPS, or the signer or the fee token account isn't the partner's")]
+    InvalidPartnerConfig,
+    #[msg("The partner stats of the contract must be in the remaining accounts")]
+    MissingPartnerStatsAccount,
+    #[msg("The rent sponsorship pool can't pay the rent of the account")]
+    RentSponsorshipPoolEmpty,
+    #[msg("The sponsored account already exists")]
+    SponsoredAccountExists,
+    #[msg("The rent sponsorship pool already funded the destination account of the contract")]
+    RentAlreadySponsored,
+    #[msg("The grant isn't in the grant index, or the signer isn't the creator of the index")]
+    InvalidGrantIndex,
+    #[msg("The grant index lists MAX_INDEXED_GRANTS grants already")]
+    GrantIndexFull,
+    #[msg("The claim hook program isn't in the claim hook allowlist")]
+    ClaimHookNotAllowlisted,
+    #[msg("The vesting account already has the current layout")]
+    VestingLayoutCurrent,
+    #[msg("The account isn't an initialized vesting account of the first release")]
+    InvalidLegacyVesting,
+}
+
+const fn calc_vesting_account_size(number_of_schedules: u32) -> usize {
+    8 // discriminator
+    + std::mem::size_of::<Pubkey>() // destination_address
+    + std::mem::size_of::<Pubkey>() // mint_address
+    + 1 // layout_version
+    + std::mem::size_of::<Pubkey>() // creator
+    + 1 // is_initialized
+    + std::mem::size_of::<u64>() // min_claim_amount
+    + 1 + std::mem::size_of::<Pubkey>() + std::mem::size_of::<u64>() // voter_weight
+    + 1 + 4 * std::mem::size_of::<Pubkey>() // yield_config
+    + 1 + std::mem::size_of::<Pubkey>() // guarantor
+    + 1 + std::mem::size_of::<Pubkey>() // vote_delegate
+    + 1 + std::mem::size_of::<Pubkey>() // revoker
+    + 1 + std::mem::size_of::<Pubkey>() // receipt_mint
+    + 1 // transfer_approval_required
+    + 1 + 2 + 32 // bridge_target
+    + 1 + std::mem::size_of::<Pubkey>() + std::mem::size_of::<i64>() // claim_delegate
+    + std::mem::size_of::<u64>() // permit_nonce
+    + 1 // is_accepted
+    + 1 + std::mem::size_of::<Pubkey>() // destination_owner
+    + 1 + 4 + MAX_UNLOCK_ALLOWLIST_LEN * std::mem::size_of::<Pubkey>() // unlock_policy
+    + std::mem::size_of::<u64>() // destination_change_count
+    + std::mem::size_of::<u64>() // claim_count
+    + 1 + std::mem::size_of::<i64>() // next_action
+    + 1 + std::mem::size_of::<Pubkey>() + 4 + MAX_CLAIM_HOOK_ACCOUNTS * std::mem::size_of::<Pubkey>() // claim_hook
+    + 1 + std::mem::size_of::<Pubkey>() // shared_vault
+    + 1 + std::mem::size_of::<Pubkey>() // cohort
+    + 1 // is_paused
+    + 1 + std::mem::size_of::<Pubkey>() // payroll_payer
+    + 1 + std::mem::size_of::<i64>() // terminated_at
+    + 1 + std::mem::size_of::<i64>() // probation_end
+    + 1 + std::mem::size_of::<Pubkey>() // salary_price_feed
+    + 1 + std::mem::size_of::<Pubkey>() // round
+    + 1 + std::mem::size_of::<Pubkey>() // rollup
+    + 1 + std::mem::size_of::<Pubkey>() // partner
+    + 1 // is_rent_sponsored
+    + 4 + (number_of_schedules as usize) * SCHEDULE_SIZE // schedule
+}
+
+/// Size of a vesting account without schedules, discriminator included. A contract of `n` schedules takes
+/// `VESTING_ACCOUNT_BASE_SIZE + n * VESTING_SCHEDULE_SIZE` bytes
+#[constant]
+pub const VESTING_ACCOUNT_BASE_SIZE: u64 = 1241;
+
+#[constant]
+pub const VESTING_SCHEDULE_SIZE: u64 = 127;
+
+/// The layout of the vesting accounts the program writes. Accounts of an older layout are converted by
+/// `migrate_vesting_layout`, and a change of the layout bumps the version
+#[constant]
+pub const VESTING_LAYOUT_VERSION: u8 = 2;
+
+/// Offset of `layout_version` in a vesting account, where the first release stored `is_initialized`
+const LAYOUT_VERSION_OFFSET: usize = 8 + 2 * std::mem::size_of::<Pubkey>();
+
+/// The most schedules of a contract, as `init` creates its account through a CPI, which grows an account by at most
+/// `MAX_PERMITTED_DATA_INCREASE`
+#[constant]
+pub const MAX_SCHEDULES: u64 = 70;
+
+// The IDL carries the values as written, keep them in line with the layout
+const _: () = assert!(calc_vesting_account_size(0) as u64 == VESTING_ACCOUNT_BASE_SIZE);
+const _: () = assert!(SCHEDULE_SIZE as u64 == VESTING_SCHEDULE_SIZE);
+const _: () = assert!(calc_vesting_account_size(MAX_SCHEDULES as u32) <= MAX_PERMITTED_DATA_INCREASE
+    && calc_vesting_account_size(MAX_SCHEDULES as u32 + 1) > MAX_PERMITTED_DATA_INCREASE);
+
+// Seeds naming the kind of each program derived account, a vesting account is derived from its 31 bytes seeds alone
+#[constant]
+pub const CONFIG_SEED: &str = "config";
+
+#[constant]
+pub const SOULBOUND_SEED: &str = "soulbound";
+
+#[constant]
+pub const RECEIPT_SEED: &str = "receipt";
+
+#[constant]
+pub const CLAIM_RECEIPT_SEED: &str = "claim-receipt";
+
+#[constant]
+pub const GUARANTEE_SEED: &str = "guarantee";
+
+#[constant]
+pub const FEE_POOL_SEED: &str = "fee-pool";
+
+#[constant]
+pub const VOTE_DELEGATION_SEED: &str = "vote-delegation";
+
+#[constant]
+pub const ATTESTATION_SEED: &str = "attestation";
+
+#[constant]
+pub const SNAPSHOT_SEED: &str = "snapshot";
+
+#[constant]
+pub const LISTING_SEED: &str = "listing";
+
+#[constant]
+pub const INBOX_SEED: &str = "inbox";
+
+#[constant]
+pub const REGISTRAR_SEED: &str = "registrar";
+
+#[constant]
+pub const VOTER_WEIGHT_RECORD_SEED: &str = "voter-weight-record";
+
+#[constant]
+pub const MAX_VOTER_WEIGHT_RECORD_SEED: &str = "max-voter-weight-record";
+
+#[constant]
+pub const DISTRIBUTOR_SEED: &str = "distributor";
+
+#[constant]
+pub const CLAIM_STATUS_SEED: &str = "claim-status";
+
+#[constant]
+pub const SHARED_VAULT_SEED: &str = "shared-vault";
+
+#[constant]
+pub const SCHEDULE_TEMPLATE_SEED: &str = "schedule-template";
+
+/// The longest schedule template name, in bytes
+#[constant]
+pub const MAX_TEMPLATE_NAME_LEN: u64 = 32;
+
+#[constant]
+pub const COHORT_SEED: &str = "cohort";
+
+/// The longest cohort name, in bytes
+#[constant]
+pub const MAX_COHORT_NAME_LEN: u64 = 32;
+
+#[constant]
+pub const AIRDROP_SEED: &str = "airdrop";
+
+#[constant]
+pub const AIRDROP_POSITION_SEED: &str = "airdrop-position";
+
+#[constant]
+pub const FUNDING_ESCROW_SEED: &str = "funding-escrow";
+
+#[constant]
+pub const ROUND_SEED: &str = "round";
+
+/// The longest round name, in bytes
+#[constant]
+pub const MAX_ROUND_NAME_LEN: u64 = 32;
+
+#[constant]
+pub const BUDGET_SEED: &str = "budget";
+
+/// The longest budget name, in bytes
+#[constant]
+pub const MAX_BUDGET_NAME_LEN: u64 = 32;
+
+/// The most admins a budget has
+#[constant]
+pub const MAX_BUDGET_ADMINS: u64 = 8;
+
+#[constant]
+pub const ROLLUP_SEED: &str = "rollup";
+
+#[constant]
+pub const UNLOCK_QUEUE_SEED: &str = "unlock-queue";
+
+/// The most contracts an unlock queue holds
+#[constant]
+pub const UNLOCK_QUEUE_CAPACITY: u64 = 64;
+
+#[constant]
+pub const PARTNER_SEED: &str = "partner";
+
+#[constant]
+pub const PARTNER_STATS_SEED: &str = "partner-stats";
+
+#[constant]
+pub const RENT_SPONSORSHIP_SEED: &str = "rent-sponsorship";
+
+#[constant]
+pub const GRANT_INDEX_SEED: &str = "grant-index";
+
+/// The most grants a grant index lists at once
+#[constant]
+pub const MAX_INDEXED_GRANTS: u64 = 32;
+
+#[constant]
+pub const CLAIM_HOOK_ALLOWLIST_SEED: &str = "claim-hook-allowlist";
+
+/// The most programs the claim hook allowlist holds
+#[constant]
+pub const MAX_ALLOWLISTED_CLAIM_HOOKS: u64 = 16;
+
+/// The highest partner fee, in basis points of the contract total
+#[constant]
+pub const MAX_PARTNER_FEE_BPS: u16 = 1_000;
+
+/// The oldest price, in seconds, `fund_salary_period` converts a salary at
+pub const MAX_SALARY_PRICE_STALENESS: i64 = 60;
+
+/// The decimals of the USD amounts of `fund_salary_period`, those of the common stablecoins
+pub const USD_DECIMALS: u8 = 6;
+
+const MAX_UNLOCK_ALLOWLIST_LEN: usize = 4;
+
+const MAX_CLAIM_HOOK_ACCOUNTS: usize = 8;
+
+const SCHEDULE_SIZE: usize =
+    2 * std::mem::size_of::<u64>() // release_time, amount
+    + 1 + RELEASE_CONDITION_SIZE; // condition
+
+// Size of the largest ReleaseCondition variant, Twap
+const RELEASE_CONDITION_SIZE: usize =
+    1 // variant
+    + std::mem::size_of::<Pubkey>() // price_feed
+    + std::mem::size_of::<i64>() // target_price
+    + std::mem::size_of::<i32>() // target_expo
+    + 1 // is_above
+    + std::mem::size_of::<u64>() // max_staleness
+    + std::mem::size_of::<u64>() // window
+    + 2 * std::mem::size_of::<i64>() // window_start, last_observation_time
+    + 2 * std::mem::size_of::<i128>(); // last_price, cumulative_price
+
+/// The fewest observations a TWAP release condition averages over its window
+#[constant]
+pub const MIN_TWAP_OBSERVATIONS: u64 = 10;
+
+const GUARANTEE_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 4 * std::mem::size_of::<Pubkey>() // vesting, guarantor, granter, collateral_token_address
+    + 3 * std::mem::size_of::<u64>() // promised_amount, topped_up_amount, deadline
+    + 1; // is_settled
+
+const VOTE_DELEGATION_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 4 * std::mem::size_of::<Pubkey>() // vesting, mint, delegator, delegate
+    + std::mem::size_of::<i64>(); // delegated_at
+
+/// Verifies that a token account owner authorizes the instruction, either by signing or, for
+/// an SPL Token multisig owner, by enough of its signers signing among `signers`
+fn verify_owner_authority(owner: &AccountInfo, signers: &[AccountInfo]) -> Result<()> {
+    if *owner.owner == spl_token::ID && owner.data_len() == spl_token::state::Multisig::LEN {
+        let multisig = spl_token::state::Multisig::unpack(&owner.data.borrow())?;
+        let approvals = multisig.signers[..multisig.n as usize]
+            .iter()
+            .filter(|key| signers.iter().any(|s| s.is_signer && s.key == *key))
+            .count();
+        require!(approvals >= multisig.m as usize, VestingError::MissingMultisigSignatures);
+    } else {
+        require!(owner.is_signer, VestingError::MissingOwnerSignature);
+    }
+    Ok(())
+}
+
+const FEE_POOL_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // vesting, authority
+    + std::mem::size_of::<u64>(); // tip_lamports
+
+/// Pays `lamports` from a fee pool to `recipient`, unless that would take the pool below its rent exempt minimum
+fn pay_from_fee_pool(fee_pool: &AccountInfo, recipient: &AccountInfo, lamports: u64) -> Result<()> {
+    let rent_exempt_lamports = Rent::get()?.minimum_balance(fee_pool.data_len());
+    if fee_pool.lamports().saturating_sub(rent_exempt_lamports) < lamports {
+        msg!("The fee pool is empty");
+        return Ok(());
+    }
+
+    **fee_pool.try_borrow_mut_lamports()? -= lamports;
+    **recipient.try_borrow_mut_lamports()? += lamports;
+    Ok(())
+}
+
+const ATTESTATION_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // owner, mint
+    + 2 * std::mem::size_of::<u64>() // locked_amount, claimable_amount
+    + std::mem::size_of::<u32>() // contract_count
+    + std::mem::size_of::<i64>() // refreshed_at
+    + std::mem::size_of::<u64>() // refreshed_slot
+    + std::mem::size_of::<Pubkey>(); // attester
+
+const SNAPSHOT_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + std::mem::size_of::<Pubkey>() // vesting
+    + 3 * std::mem::size_of::<u64>(); // slot, taken_at_slot, locked_amount
+
+const UNLOCK_QUEUE_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + std::mem::size_of::<Pubkey>() // mint
+    + 4 + UNLOCK_QUEUE_CAPACITY as usize * (std::mem::size_of::<i64>() + 31); // entries
+
+const INBOX_CAPACITY: usize = 16;
+
+const INBOX_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + std::mem::size_of::<Pubkey>() // destination
+    + std::mem::size_of::<u64>() // next_sequence
+    + 4 + INBOX_CAPACITY * INBOX_RECORD_SIZE; // records
+
+const INBOX_RECORD_SIZE: usize =
+    std::mem::size_of::<u64>() // sequence
+    + 1 // notice
+    + std::mem::size_of::<Pubkey>() // vesting
+    + std::mem::size_of::<i64>(); // timestamp
+
+const CLAIM_RECEIPT_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // vesting, destination
+    + 2 * std::mem::size_of::<u64>() // counter, amount
+    + std::mem::size_of::<i64>(); // timestamp
+
+const PROGRAM_CONFIG_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // admin, incident_authority
+    + 1 + 32; // build_hash
+
+const MERKLE_DISTRIBUTOR_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 31 // seeds
+    + 3 * std::mem::size_of::<Pubkey>() // creator, mint, escrow
+    + 32 // root
+    + 3 * std::mem::size_of::<u64>(); // total_amount, claimed_amount, claimant_count
+
+const CLAIM_STATUS_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // distributor, claimant
+    + std::mem::size_of::<u64>(); // claimed_amount
+
+const SHARED_VAULT_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 3 * std::mem::size_of::<Pubkey>() // authority, mint, vault_token
+    + 2 * std::mem::size_of::<u64>(); // committed_amount, contract_count
+
+const fn calc_schedule_template_size(number_of_tranches: usize) -> usize {
+    8 // discriminator
+    + std::mem::size_of::<Pubkey>() // authority
+    + 4 + MAX_TEMPLATE_NAME_LEN as usize // name
+    + 4 + number_of_tranches * 2 * std::mem::size_of::<u64>() // tranches
+}
+
+const COHORT_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // admin, mint
+    + 4 + MAX_COHORT_NAME_LEN as usize // name
+    + 1 + std::mem::size_of::<Pubkey>() // schedule_template
+    + 2 * std::mem::size_of::<u64>(); // member_count, total_amount
+
+const VESTED_AIRDROP_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 31 // seeds
+    + 3 * std::mem::size_of::<Pubkey>() // creator, mint, escrow
+    + 32 // root
+    + std::mem::size_of::<Pubkey>() // schedule_template
+    + 4 * std::mem::size_of::<u64>(); // start, total_amount, claimed_amount, claimant_count
+
+const FUNDING_ESCROW_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 31 // seeds
+    + 4 * std::mem::size_of::<Pubkey>() // funder, issuer, mint, escrow
+    + std::mem::size_of::<u64>() // amount
+    + 1 + std::mem::size_of::<Pubkey>(); // vesting
+
+const ROUND_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // creator, mint
+    + 4 + MAX_ROUND_NAME_LEN as usize // name
+    + 4 * std::mem::size_of::<u64>(); // contract_count, total_granted, total_claimed, total_revoked
+
+const BUDGET_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 3 * std::mem::size_of::<Pubkey>() // org, mint, budget_token
+    + 4 + MAX_BUDGET_NAME_LEN as usize // name
+    + 4 + MAX_BUDGET_ADMINS as usize * std::mem::size_of::<Pubkey>() // admins
+    + 2 * std::mem::size_of::<u64>(); // allocation_limit, allocated_amount
+
+const ROLLUP_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 3 * std::mem::size_of::<Pubkey>() // org, beneficiary, mint
+    + 5 * std::mem::size_of::<u64>() // contract_count, locked_amount, vested_unclaimed_amount, claimed_amount, revoked_amount
+    + std::mem::size_of::<i64>(); // updated_at
+
+const PARTNER_CONFIG_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 3 * std::mem::size_of::<Pubkey>() // authority, fee_recipient, branding
+    + std::mem::size_of::<u16>() // fee_bps
+    + std::mem::size_of::<u64>() // default_min_claim_amount
+    + 1 + 4 + MAX_UNLOCK_ALLOWLIST_LEN * std::mem::size_of::<Pubkey>() // default_unlock_policy
+    + 1; // default_revocable
+
+const PARTNER_STATS_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // partner_config, mint
+    + 6 * std::mem::size_of::<u64>(); // contract_count, total_value_locked, claim_count, claimed_amount, revoked_amount, fee_amount
+
+const RENT_SPONSORSHIP_POOL_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + std::mem::size_of::<Pubkey>() // creator
+    + 2 * std::mem::size_of::<u64>(); // sponsored_count, sponsored_lamports
+
+const GRANT_INDEX_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 2 * std::mem::size_of::<Pubkey>() // creator, beneficiary
+    + std::mem::size_of::<u64>() // next_grant_id
+    + 4 + MAX_INDEXED_GRANTS as usize * std::mem::size_of::<u64>(); // grant_ids
+
+const CLAIM_HOOK_ALLOWLIST_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 4 + MAX_ALLOWLISTED_CLAIM_HOOKS as usize * std::mem::size_of::<Pubkey>(); // programs
+
+const LISTING_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + 4 * std::mem::size_of::<Pubkey>() // vesting, seller, seller_payment_token_address, payment_mint
+    + std::mem::size_of::<u64>() // price
+    + 1; // is_approved
+
+fn total_amount(schedules: &[Schedule]) -> Result<u64> {
+    math::total_amount(schedules).ok_or_else(|| VestingError::TotalAmountOverflow.into())
+}
+
+/// Returns the seeds of the contract `claim_airdrop` creates for `claimant` in `airdrop`, the first 31 bytes of
+/// the hash of `["airdrop-position", airdrop, claimant]`
+fn airdrop_position_seeds(airdrop: &Pubkey, claimant: &Pubkey) -> [u8; 31] {
+    let hash = anchor_lang::solana_program::hash::hashv(&[AIRDROP_POSITION_SEED.as_bytes(), airdrop.as_ref(), claimant.as_ref()]);
+    let mut seeds = [0; 31];
+    seeds.copy_from_slice(&hash.as_ref()[..31]);
+    seeds
+}
+
+/// Returns the seeds of the contract of `grant_id` in the grant index of `creator` for `beneficiary`, the first 31
+/// bytes of the hash of `["grant-index", creator, beneficiary, grant_id (u64 LE)]`
+fn grant_seeds(creator: &Pubkey, beneficiary: &Pubkey, grant_id: u64) -> [u8; 31] {
+    let hash = anchor_lang::solana_program::hash::hashv(
+        &[GRANT_INDEX_SEED.as_bytes(), creator.as_ref(), beneficiary.as_ref(), &grant_id.to_le_bytes()]);
+    let mut seeds = [0; 31];
+    seeds.copy_from_slice(&hash.as_ref()[..31]);
+    seeds
+}
+
+/// Expands `template` into the schedules of a contract of `total_amount` starting at `start`
+fn expand_template(template: &ScheduleTemplate, total_amount: u64, start: u64) -> Result<Vec<Schedule>> {
+    let weights: Vec<u64> = template.tranches.iter().map(|t| t.weight).collect();
+    template.tranches
+        .iter()
+        .zip(math::split_by_weights(total_amount, &weights))
+        .map(|(tranche, amount)| {
+            let release_time = start.checked_add(tranche.offset).ok_or(VestingError::InvalidScheduleTemplate)?;
+            Ok(Schedule { release_time, amount, condition: None })
+        })
+        .collect()
+}
+
+/// Appends a pay period tranche of `amount` released at `release_time` after every tranche of `vesting`, growing the
+/// account by one schedule unless it has room for it, the rent paid by `payer`
+fn append_pay_period<'info>(vesting: &mut Account<'info, Vesting>, payer: &Signer<'info>, system_program: &Program<'info, System>,
+                            amount: u64, release_time: u64, now: i64) -> Result<()> {
+    require!(vesting.schedule.iter().all(|s| s.release_time < release_time), VestingError::InvalidPayPeriod);
+
+    let vesting_info = vesting.to_account_info();
+    let new_size = calc_vesting_account_size(vesting.schedule.len() as u32 + 1);
+    if vesting_info.data_len() < new_size {
+        let rent_due = Rent::get()?.minimum_balance(new_size).saturating_sub(vesting_info.lamports());
+        if rent_due > 0 {
+            let ix = anchor_lang::solana_program::system_instruction::transfer(&payer.key(), &vesting_info.key(), rent_due);
+            anchor_lang::solana_program::program::invoke(
+                &ix,
+                &[
+                    payer.to_account_info(),
+                    vesting_info.clone(),
+                    system_program.to_account_info(),
+                ])?;
+        }
+        vesting_info.realloc(new_size, false)?;
+    }
+
+    vesting.schedule.push(Schedule { release_time, amount, condition: None });
+    total_amount(&vesting.schedule)?;
+    refresh_next_action(vesting, now);
+    Ok(())
+}
+
+/// Emits the committed and free capacity of `shared_vault` holding `vault_balance`
+fn emit_capacity(remaining_accounts: &[AccountInfo], shared_vault: &Account<SharedVault>, vault_balance: u64) -> Result<()> {
+    event_cpi::emit(remaining_accounts, &SharedVaultCapacityChanged {
+        shared_vault: shared_vault.key(),
+        committed_amount: shared_vault.committed_amount,
+        free_amount: shared_vault.free_amount(vault_balance),
+        timestamp: test_clock::unix_timestamp(remaining_accounts)?,
+    })
+}
+
+/// Returns the amount `unlock` may release at `timestamp`, enforcing the contract claim rules
+fn claimable_amount(vesting: &Vesting, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> Result<u64> {
+    let total_amount_to_transfer = released_amount(&vesting.schedule, timestamp);
+    verbose_log!("unlock.released", now = timestamp, amount = total_amount_to_transfer, is_accepted = vesting.is_accepted,
+                 min_claim_amount = vesting.min_claim_amount);
+
+    require!(vesting.is_accepted, VestingError::NotAccepted);
+    require!(!vesting.is_paused, VestingError::ContractPaused);
+    if total_amount_to_transfer == 0 {
+        if let Some((release_time, amount)) = next_locked_tranche(&vesting.schedule, timestamp) {
+            msg!("Nothing released at {}, next release of {} at {}", timestamp, amount, release_time);
+        }
+        return err!(VestingError::ReleaseTimeNotYetReached);
+    }
+    if !meets_min_claim_amount(vesting, total_amount_to_transfer)? {
+        msg!("Released {}, the minimum claim is {}", total_amount_to_transfer, vesting.min_claim_amount);
+        return err!(VestingError::ClaimBelowMinimum);
+    }
+
+    Ok(total_amount_to_transfer)
+}
+
+/// Returns the amount a claim of `amount` released tokens transfers out of an escrow holding `escrow_balance`.
+///
+/// Rounding policy: tranches hold whole base units, so whatever splits a total into tranches floors them and the
+/// remainder, like any token sent to the escrow directly, is escrow balance above the schedule. Claims during the
+/// stream transfer exactly the released amount and the final claim, releasing everything left in the schedule,
+/// sweeps the whole escrow so no base unit is stranded
+fn final_sweep_amount(schedules: &[Schedule], amount: u64, escrow_balance: u64) -> Result<u64> {
+    if amount == total_amount(schedules)? {
+        Ok(amount.max(escrow_balance))
+    } else {
+        Ok(amount)
+    }
+}
+
+/// Dust claims are refused unless they release everything that is left in the contract
+fn meets_min_claim_amount(vesting: &Vesting, amount: u64) -> Result<bool> {
+    Ok(amount >= vesting.min_claim_amount || amount == total_amount(&vesting.schedule)?)
+}
+
+/// Loads the price of the release condition feed, passed as the first remaining account
+fn load_condition_price(accounts: &[AccountInfo], price_feed: &Pubkey, max_staleness: u64, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> Result<pyth::Price> {
+    let price_account = accounts.first().ok_or(VestingError::InvalidPriceFeed)?;
+    require!(expect_key("price feed", price_feed, price_account.key), VestingError::InvalidPriceFeed);
+
+    let price = pyth::load_price(price_account)?;
+    require!(timestamp.saturating_sub(price.publish_time).max(0) as u64 <= max_staleness, VestingError::StalePrice);
+    Ok(price)
+}
+
+fn meets_price_target(price_to_target: std::cmp::Ordering, is_above: bool) -> bool {
+    if is_above {
+        price_to_target != std::cmp::Ordering::Less
+    } else {
+        price_to_target != std::cmp::Ordering::Greater
+    }
+}
+
+/// Counts the (vesting, destination token) account pairs `voter` votes for in `voter_weight_record`, each with the amount
+/// it still locks at `timestamp`, and returns their sum. A contract counted in another record is rejected
+fn count_voter_weight(
+    program_id: &Pubkey,
+    accounts: &[AccountInfo],
+    voter_weight_record: &Pubkey,
+    voter: &Pubkey,
+    mint: &Pubkey,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<u64> {
+    let mut pairs = accounts.chunks_exact(2);
+    require!(pairs.remainder().is_empty(), VestingError::InvalidRemainingAccounts);
+
+    let mut counted: Vec<Pubkey> = Vec::with_capacity(pairs.len());
+    pairs
+        .try_fold(0u64, |sum, pair| {
+            let mut vesting = Account::<Vesting>::try_from(&pair[0])?;
+            let destination_token = Account::<TokenAccount>::try_from(&pair[1])?;
+
+            require!(!counted.contains(&vesting.key()), VestingError::DuplicateVestingAccount);
+            require!(vesting.is_initialized, VestingError::NotInitialized);
+            require!(expect_key("mint", mint, &vesting.mint_address), VestingError::InvalidMint);
+            require!(expect_key("destination", &vesting.destination_address, &destination_token.key()), VestingError::InvalidDestination);
+            require!(expect_key("voter", voter, &vesting.vote_delegate.unwrap_or(destination_token.owner)), VestingError::InvalidDestinationAuthority);
+            require!(vesting.voter_weight.as_ref().is_none_or(|c| c.voter_weight_record == *voter_weight_record),
+                     VestingError::VoterWeightCountedElsewhere);
+            counted.push(vesting.key());
+
+            let weight = locked_amount(&vesting.schedule, timestamp);
+            vesting.voter_weight = Some(CountedVoterWeight { voter_weight_record: *voter_weight_record, weight });
+            vesting.exit(program_id)?;
+            sum.checked_add(weight).ok_or_else(|| VestingError::TotalAmountOverflow.into())
+        })
+}
+
+/// Takes a vesting contract off the voter weight record counting it, if any, as its voter changes.
+/// The record has to be in the remaining accounts
+fn uncount_voter_weight(program_id: &Pubkey, vesting: &mut Vesting, remaining_accounts: &[AccountInfo]) -> Result<()> {
+    let counted = match vesting.voter_weight.take() {
+        Some(counted) => counted,
+        None => return Ok(()),
+    };
+    let record_info = remaining_accounts.iter().find(|a| a.key() == counted.voter_weight_record)
+        .ok_or(VestingError::MissingVoterWeightRecord)?;
+
+    let mut record = Account::<VoterWeightRecord>::try_from(record_info)?;
+    record.voter_weight = record.voter_weight.saturating_sub(counted.weight);
+    record.exit(program_id)
+}
+
+/// Sums the amount still locked at `timestamp` across the vesting accounts of `mint`
+fn mint_locked_amount(accounts: &[AccountInfo], mint: &Pubkey, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> Result<u64> {
+    let mut counted: Vec<Pubkey> = Vec::with_capacity(accounts.len());
+    accounts
+        .iter()
+        .try_fold(0u64, |sum, account| {
+            let vesting = Account::<Vesting>::try_from(account)?;
+
+            require!(!counted.contains(&vesting.key()), VestingError::DuplicateVestingAccount);
+            require!(vesting.is_initialized, VestingError::NotInitialized);
+            require!(expect_key("mint", mint, &vesting.mint_address), VestingError::InvalidMint);
+            counted.push(vesting.key());
+
+            sum.checked_add(locked_amount(&vesting.schedule, timestamp))
+                .ok_or_else(|| VestingError::TotalAmountOverflow.into())
+        })
+}
+
+/// Sums the locked and claimable amounts at `timestamp` across (vesting, destination token) account pairs paying to `owner`,
+/// along with the number of contracts
+fn owner_balances(accounts: &[AccountInfo], owner: &Pubkey, mint: &Pubkey, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> Result<(u64, u64, u32)> {
+    let mut pairs = accounts.chunks_exact(2);
+    require!(pairs.remainder().is_empty(), VestingError::InvalidRemainingAccounts);
+
+    let mut counted: Vec<Pubkey> = Vec::with_capacity(pairs.len());
+    pairs
+        .try_fold((0u64, 0u64, 0u32), |(locked, claimable, count), pair| {
+            let vesting = Account::<Vesting>::try_from(&pair[0])?;
+            let destination_token = Account::<TokenAccount>::try_from(&pair[1])?;
+
+            require!(!counted.contains(&vesting.key()), VestingError::DuplicateVestingAccount);
+            require!(vesting.is_initialized, VestingError::NotInitialized);
+            require!(expect_key("mint", mint, &vesting.mint_address), VestingError::InvalidMint);
+            require!(expect_key("destination", &vesting.destination_address, &destination_token.key()), VestingError::InvalidDestination);
+            require!(expect_key("destination owner", owner, &destination_token.owner), VestingError::InvalidDestinationAuthority);
+            counted.push(vesting.key());
+
+            let locked = locked.checked_add(locked_amount(&vesting.schedule, timestamp))
+                .ok_or(VestingError::TotalAmountOverflow)?;
+            let claimable = claimable.checked_add(released_amount(&vesting.schedule, timestamp))
+                .ok_or(VestingError::TotalAmountOverflow)?;
+            Ok((locked, claimable, count + 1))
+        })
+}
+
+/// Unlocks a vesting contract passed in the remaining accounts to `destination_token`, checking it as the `Unlock`
+/// accounts do. Restricted contracts are only unlocked when their destination owner signed.
+/// Returns the amount transferred, 0 when nothing can be claimed yet
+fn unlock_remaining_contract<'info>(
+    program_id: &Pubkey,
+    seeds: &[u8; 31],
+    vesting_info: &AccountInfo<'info>,
+    vesting_token: &AccountInfo<'info>,
+    destination_token: &AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    token_program: &AccountInfo<'info>,
+    signing_owner: Option<&Pubkey>,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<u64> {
+    let (vesting_address, bump) = Pubkey::find_program_address(&[seeds.as_ref()], program_id);
+    require!(expect_key("vesting", &vesting_address, &vesting_info.key()), VestingError::InvalidVestingSeeds);
+
+    let mut vesting = Account::<Vesting>::try_from(vesting_info)?;
+    require!(vesting.is_initialized, VestingError::NotInitialized);
+    require!(vesting.unlock_policy == UnlockPolicy::Anyone
+                 || matches!(signing_owner, Some(owner) if is_unlock_authority(&vesting, owner, owner, timestamp)),
+             VestingError::UnlockRestricted);
+    require!(expect_key("destination", &vesting.destination_address, &destination_token.key()), VestingError::InvalidDestination);
+    require!(expect_key("mint", &vesting.mint_address, &mint.key()), VestingError::InvalidMint);
+    require!(vesting.yield_config.is_none(), VestingError::YieldVaultEnabled);
+    require!(vesting.receipt_mint.is_none(), VestingError::ReceiptHolderIsBeneficiary);
+    require!(vesting.claim_hook.is_none(), VestingError::ClaimHookEnabled);
+    require!(vesting.shared_vault.is_none(), VestingError::SharedVaultContract);
+    require!(!vesting.is_paused, VestingError::ContractPaused);
+    let escrow = Account::<TokenAccount>::try_from(vesting_token)?;
+    require!(expect_key("vesting token owner", &vesting_address, &escrow.owner), VestingError::InvalidVestingTokenAuthority);
+
+    let amount = released_amount(&vesting.schedule, timestamp);
+    if !vesting.is_accepted || amount == 0 || !meets_min_claim_amount(&vesting, amount)? {
+        return Ok(0);
+    }
+    let amount = final_sweep_amount(&vesting.schedule, amount, escrow.amount)?;
+
+    let seeds = &[
+        seeds.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        TransferChecked {
+            from: vesting_token.clone(),
+            mint: mint.to_account_info(),
+            to: destination_token.clone(),
+            authority: vesting_info.clone(),
+        },
+        signer
+    );
+    transfer_checked(transfer_ctx, amount, mint.decimals)?;
+
+    reset_released_amount(&mut vesting.schedule, timestamp);
+    refresh_next_action(&mut vesting, timestamp);
+    vesting.exit(program_id)?;
+    Ok(amount)
+}
+
+/// Revokes the locked part of the contract of `seeds` into `refund_token` on behalf of `revoker`, the checks of
+/// `Revoke` made by hand: a guarantor has to sign among `remaining_accounts`, where the round of a tagged contract is
+/// tallied. Contracts of a cohort batch must also be of `cohort`. Returns the revoked amount, 0 when nothing is
+/// locked, and the schedule changes
+fn revoke_remaining_contract<'info>(
+    program_id: &Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    revoker: &Pubkey,
+    cohort: Option<&Pubkey>,
+    seeds: &[u8; 31],
+    vesting_info: &AccountInfo<'info>,
+    vesting_token: &AccountInfo<'info>,
+    refund_token: &AccountInfo<'info>,
+    mint: &Account<'info, Mint>,
+    token_program: &AccountInfo<'info>,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<(u64, Vec<ScheduleChange>)> {
+    let (vesting_address, bump) = Pubkey::find_program_address(&[seeds.as_ref()], program_id);
+    require!(expect_key("vesting", &vesting_address, &vesting_info.key()), VestingError::InvalidVestingSeeds);
+
+    let mut vesting = Account::<Vesting>::try_from(vesting_info)?;
+    require!(vesting.is_initialized, VestingError::NotInitialized);
+    if let Some(cohort) = cohort {
+        require!(vesting.cohort == Some(*cohort), VestingError::InvalidCohort);
+    }
+    require!(vesting.revoker == Some(*revoker), VestingError::InvalidRevoker);
+    if let Some(guarantor) = vesting.guarantor {
+        require!(remaining_accounts.iter().any(|a| a.is_signer && *a.key == guarantor), VestingError::MissingGuarantorSignature);
+    }
+    require!(vesting.shared_vault.is_none(), VestingError::SharedVaultContract);
+    require!(expect_key("vesting token owner", &vesting_address, &Account::<TokenAccount>::try_from(vesting_token)?.owner),
+             VestingError::InvalidVestingTokenAuthority);
+
+    let amount = locked_amount(&vesting.schedule, timestamp);
+    if amount == 0 {
+        return Ok((0, vec![]));
+    }
+
+    let seeds = &[
+        seeds.as_ref(),
+        &[bump],
+    ];
+    let signer = &[&seeds[..]];
+
+    let transfer_ctx = CpiContext::new_with_signer(
+        token_program.clone(),
+        TransferChecked {
+            from: vesting_token.clone(),
+            mint: mint.to_account_info(),
+            to: refund_token.clone(),
+            authority: vesting_info.clone(),
+        },
+        signer
+    );
+    transfer_checked(transfer_ctx, amount, mint.decimals)?;
+
+    let before = vesting.schedule.clone();
+    reset_locked_amount(&mut vesting.schedule, timestamp);
+    refresh_next_action(&mut vesting, timestamp);
+    tally_round(remaining_accounts, program_id, &vesting, 0, amount)?;
+    tally_rollup(remaining_accounts, program_id, &vesting, 0, amount)?;
+    tally_partner_stats(remaining_accounts, program_id, &vesting, 0, amount)?;
+    let changes = schedule_changes(&before, &vesting.schedule);
+    vesting.exit(program_id)?;
+    Ok((amount, changes))
+}
+
+/// Points the contract of `seeds` at `vesting_info` from `current_destination_token`, owned by
+/// `destination_authority`, to `new_destination_token`, the checks of `ChangeDestination` made by hand. Returns the
+/// event recording the change and, if the vote delegate was cleared, the one recording that
+fn change_remaining_destination<'info>(
+    program_id: &Pubkey,
+    seeds: &[u8; 31],
+    vesting_info: &AccountInfo<'info>,
+    current_destination_token: &AccountInfo<'info>,
+    new_destination_token: &AccountInfo<'info>,
+    destination_authority: &Pubkey,
+    remaining_accounts: &[AccountInfo<'info>],
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<(DestinationChanged, Option<VoteDelegateChanged>)> {
+    let vesting_address = Pubkey::find_program_address(&[seeds.as_ref()], program_id).0;
+    require!(expect_key("vesting", &vesting_address, &vesting_info.key()), VestingError::InvalidVestingSeeds);
+
+    let mut vesting = Account::<Vesting>::try_from(vesting_info)?;
+    require!(vesting.is_initialized, VestingError::NotInitialized);
+    require!(expect_key("destination", &vesting.destination_address, &current_destination_token.key()), VestingError::InvalidDestination);
+    require!(vesting.receipt_mint.is_none(), VestingError::ReceiptHolderIsBeneficiary);
+    let current_destination = Account::<TokenAccount>::try_from(current_destination_token)?;
+    require!(current_destination.owner == *destination_authority, VestingError::InvalidDestinationAuthority);
+    Account::<TokenAccount>::try_from(new_destination_token)?;
+
+    uncount_voter_weight(program_id, &mut vesting, remaining_accounts)?;
+    let old_destination = vesting.destination_address;
+    vesting.destination_address = new_destination_token.key();
+    vesting.destination_owner = None;
+    vesting.destination_change_count += 1;
+    let delegate_cleared = vesting.vote_delegate.take().map(|_| VoteDelegateChanged {
+        vesting: vesting_address,
+        mint: vesting.mint_address,
+        vote_delegate: None,
+        timestamp,
+    });
+    vesting.exit(program_id)?;
+
+    Ok((DestinationChanged {
+        vesting: vesting_address,
+        mint: vesting.mint_address,
+        old_destination,
+        new_destination: vesting.destination_address,
+        authority: *destination_authority,
+        change_count: vesting.destination_change_count,
+        timestamp,
+    }, delegate_cleared))
+}
+
+/// Returns whether `hook_program` is in the claim hook allowlist, which has to be in the remaining accounts
+fn is_allowlisted_claim_hook(remaining_accounts: &[AccountInfo], program_id: &Pubkey, hook_program: &Pubkey) -> Result<bool> {
+    let allowlist_address = Pubkey::find_program_address(&[CLAIM_HOOK_ALLOWLIST_SEED.as_bytes()], program_id).0;
+    let allowlist_info = remaining_accounts.iter().find(|a| a.key() == allowlist_address)
+        .ok_or(VestingError::InvalidClaimHookAccounts)?;
+    Ok(Account::<ClaimHookAllowlist>::try_from(allowlist_info)?.programs.contains(hook_program))
+}
+
+/// Notifies the claim hook of the contract, if any, of a claim paid to `destination`. The claim hook allowlist, the
+/// hook program, its authority and the registered accounts are looked up by address in the remaining accounts.
+/// A hook whose program left the allowlist is skipped
+fn run_claim_hook<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    vesting: &Account<Vesting>,
+    destination: &Pubkey,
+    amount: u64,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<()> {
+    let hook = match &vesting.claim_hook {
+        Some(hook) => hook,
+        None => return Ok(()),
+    };
+    if !is_allowlisted_claim_hook(remaining_accounts, &crate::ID, &hook.program)? {
+        return event_cpi::emit(remaining_accounts, &ClaimHookSkipped {
+            vesting: vesting.key(),
+            program: hook.program,
+            timestamp,
+        });
+    }
+    let find = |key: &Pubkey| remaining_accounts.iter().find(|a| a.key == key).cloned().ok_or(VestingError::InvalidClaimHookAccounts);
+    let program = find(&hook.program)?;
+    let authority = find(&claim_hook::claim_hook_authority().0)?;
+    let accounts = hook.accounts.iter().map(find).collect::<std::result::Result<Vec<_>, _>>()?;
+
+    // The hook reads the contract as left by the claim
+    vesting.exit(&crate::ID)?;
+    claim_hook::on_claim(&program, &authority, &accounts, &claim_hook::OnClaim {
+        vesting: vesting.key(),
+        mint: vesting.mint_address,
+        destination: *destination,
+        amount,
+        timestamp,
+    })
+}
+
+/// Appends a notice to the inbox of `destination` when it is passed in the remaining accounts,
+/// dropping the oldest notice of a full inbox
+fn notify<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    program_id: &Pubkey,
+    destination: &Pubkey,
+    vesting: &Pubkey,
+    notice: InboxNotice,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<()> {
+    let inbox_address = Pubkey::find_program_address(&[INBOX_SEED.as_bytes(), destination.as_ref()], program_id).0;
+    let inbox_info = match remaining_accounts.iter().find(|a| a.key() == inbox_address) {
+        Some(inbox_info) => inbox_info,
+        None => return Ok(()),
+    };
+
+    let mut inbox = Account::<Inbox>::try_from(inbox_info)?;
+    if inbox.records.len() >= INBOX_CAPACITY {
+        inbox.records.remove(0);
+    }
+    let sequence = inbox.next_sequence;
+    inbox.records.push(InboxRecord { sequence, notice, vesting: *vesting, timestamp });
+    inbox.next_sequence += 1;
+    inbox.exit(program_id)
+}
+
+/// Sets the fee and the default terms of a partner config
+fn set_partner_terms(
+    partner_config: &mut PartnerConfig,
+    fee_recipient: Pubkey,
+    fee_bps: u16,
+    branding: Pubkey,
+    default_min_claim_amount: u64,
+    default_unlock_policy: UnlockPolicy,
+    default_revocable: bool,
+) -> Result<()> {
+    require!(fee_bps <= MAX_PARTNER_FEE_BPS, VestingError::InvalidPartnerConfig);
+    require!(is_valid_unlock_policy(&default_unlock_policy), VestingError::InvalidUnlockPolicy);
+    partner_config.fee_recipient = fee_recipient;
+    partner_config.fee_bps = fee_bps;
+    partner_config.branding = branding;
+    partner_config.default_min_claim_amount = default_min_claim_amount;
+    partner_config.default_unlock_policy = default_unlock_policy;
+    partner_config.default_revocable = default_revocable;
+    Ok(())
+}
+
+fn partner_config_changed(partner_config: &Account<PartnerConfig>, timestamp: i64) -> PartnerConfigChanged {
+    PartnerConfigChanged {
+        partner_config: partner_config.key(),
+        authority: partner_config.authority,
+        fee_recipient: partner_config.fee_recipient,
+        fee_bps: partner_config.fee_bps,
+        branding: partner_config.branding,
+        timestamp,
+    }
+}
+
+/// Counts `claimed` and `revoked` in the partner stats of `vesting`, which have to be in `remaining_accounts`.
+/// Contracts created without a partner have nothing to count
+fn tally_partner_stats<'info>(remaining_accounts: &[AccountInfo<'info>], program_id: &Pubkey, vesting: &Vesting, claimed: u64, revoked: u64) -> Result<()> {
+    let partner_config = match vesting.partner {
+        Some(partner_config) => partner_config,
+        None => return Ok(()),
+    };
+    let stats_address = Pubkey::find_program_address(
+        &[PARTNER_STATS_SEED.as_bytes(), partner_config.as_ref(), vesting.mint_address.as_ref()], program_id).0;
+    let stats_info = remaining_accounts.iter().find(|a| a.key() == stats_address).ok_or(VestingError::MissingPartnerStatsAccount)?;
+
+    let mut stats = Account::<PartnerStats>::try_from(stats_info)?;
+    if claimed > 0 {
+        stats.claim_count += 1;
+    }
+    stats.claimed_amount = stats.claimed_amount.checked_add(claimed).ok_or(VestingError::TotalAmountOverflow)?;
+    stats.revoked_amount = stats.revoked_amount.checked_add(revoked).ok_or(VestingError::TotalAmountOverflow)?;
+    stats.total_value_locked = stats.total_value_locked.saturating_sub(claimed + revoked);
+    stats.exit(program_id)
+}
+
+fn deposit_to_rent_sponsorship_pool<'info>(funder: &Signer<'info>, pool: &Account<'info, RentSponsorshipPool>,
+                                          system_program: &Program<'info, System>, lamports: u64) -> Result<()> {
+    let ix = anchor_lang::solana_program::system_instruction::transfer(&funder.key(), &pool.key(), lamports);
+    anchor_lang::solana_program::program::invoke(
+        &ix,
+        &[
+            funder.to_account_info(),
+            pool.to_account_info(),
+            system_program.to_account_info(),
+        ]).map_err(Into::into)
+}
+
+/// Returns the lamports of the rent sponsorship pool above its rent exempt minimum
+fn sponsorable_lamports(pool: &AccountInfo) -> Result<u64> {
+    Ok(pool.lamports().saturating_sub(Rent::get()?.minimum_balance(pool.data_len())))
+}
+
+/// Moves from `pool` to the not yet created `account` the lamports it lacks to be rent exempt with `space` bytes, so
+/// creating it charges its payer nothing. Returns the lamports moved
+fn draw_sponsored_rent(pool: &mut Account<RentSponsorshipPool>, account: &AccountInfo, space: usize) -> Result<u64> {
+    require!(account.owner == &System::id() && account.data_is_empty(), VestingError::SponsoredAccountExists);
+    let lamports = Rent::get()?.minimum_balance(space).saturating_sub(account.lamports());
+    if lamports == 0 {
+        return Ok(0);
+    }
+
+    let pool_info = pool.to_account_info();
+    require!(sponsorable_lamports(&pool_info)? >= lamports, VestingError::RentSponsorshipPoolEmpty);
+    **pool_info.try_borrow_mut_lamports()? -= lamports;
+    **account.try_borrow_mut_lamports()? += lamports;
+
+    pool.sponsored_count = pool.sponsored_count.checked_add(1).ok_or(VestingError::TotalAmountOverflow)?;
+    pool.sponsored_lamports = pool.sponsored_lamports.checked_add(lamports).ok_or(VestingError::TotalAmountOverflow)?;
+    Ok(lamports)
+}
+
+/// Lists the contract of `seeds` in the grant index of its creator found among `remaining_accounts`, when `seeds` are
+/// the next grant seeds of the index
+fn index_grant_in_remaining<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    program_id: &Pubkey,
+    seeds: &[u8; 31],
+    vesting: &Account<'info, Vesting>,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<()> {
+    let is_grant_index = |a: &&AccountInfo<'info>| a.owner == program_id
+        && a.try_borrow_data().is_ok_and(|data| data.starts_with(&<GrantIndex as anchor_lang::Discriminator>::discriminator()));
+    let index_info = match remaining_accounts.iter().find(is_grant_index) {
+        Some(index_info) => index_info,
+        None => return Ok(()),
+    };
+
+    let mut grant_index = Account::<GrantIndex>::try_from(index_info)?;
+    let grant_id = grant_index.next_grant_id;
+    require!(grant_index.creator == vesting.creator, VestingError::InvalidGrantIndex);
+    require!(*seeds == grant_seeds(&grant_index.creator, &grant_index.beneficiary, grant_id), VestingError::InvalidVestingSeeds);
+    require!((grant_index.grant_ids.len() as u64) < MAX_INDEXED_GRANTS, VestingError::GrantIndexFull);
+    grant_index.grant_ids.push(grant_id);
+    grant_index.next_grant_id += 1;
+    grant_index.exit(program_id)?;
+
+    event_cpi::emit(remaining_accounts, &GrantIndexChanged {
+        grant_index: index_info.key(),
+        vesting: vesting.key(),
+        grant_id,
+        indexed: true,
+        timestamp,
+    })
+}
+
+/// Returns whether `process_queue` can unlock `vesting`, which anyone may unlock without extra accounts
+fn is_crankable(vesting: &Vesting) -> bool {
+    vesting.unlock_policy == UnlockPolicy::Anyone
+        && vesting.yield_config.is_none()
+        && vesting.receipt_mint.is_none()
+        && vesting.claim_hook.is_none()
+        && vesting.shared_vault.is_none()
+        && !vesting.is_paused
+}
+
+/// Returns the time `vesting` next has something to unlock: now when a released amount awaits, the next release time
+/// otherwise, none once nothing is left to release
+fn next_queue_time(vesting: &Vesting, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> Option<i64> {
+    if released_amount(&vesting.schedule, timestamp) > 0 {
+        Some(timestamp)
+    } else {
+        next_locked_tranche(&vesting.schedule, timestamp).map(|(release_time, _)| release_time as i64)
+    }
+}
+
+/// Moves the contract of `seeds` to `release_time` in `queue`, keeping it sorted, or drops it without release time
+fn enqueue(queue: &mut UnlockQueue, seeds: [u8; 31], release_time: Option<i64>) -> Result<()> {
+    queue.entries.retain(|e| e.seeds != seeds);
+    if let Some(release_time) = release_time {
+        require!((queue.entries.len() as u64) < UNLOCK_QUEUE_CAPACITY, VestingError::UnlockQueueFull);
+        let index = queue.entries.partition_point(|e| e.release_time <= release_time);
+        queue.entries.insert(index, QueueEntry { release_time, seeds });
+    }
+    Ok(())
+}
+
+/// Moves the contract of `seeds` to its next release time in the unlock queue of its mint when the queue is passed in
+/// the remaining accounts. Contracts `process_queue` can't unlock aren't queued
+fn enqueue_in_remaining<'info>(
+    remaining_accounts: &[AccountInfo<'info>],
+    program_id: &Pubkey,
+    seeds: &[u8],
+    vesting: &Vesting,
+    timestamp: anchor_lang::solana_program::clock::UnixTimestamp,
+) -> Result<()> {
+    let queue_address = Pubkey::find_program_address(&[UNLOCK_QUEUE_SEED.as_bytes(), vesting.mint_address.as_ref()], program_id).0;
+    let queue_info = match remaining_accounts.iter().find(|a| a.key() == queue_address) {
+        Some(queue_info) if is_crankable(vesting) => queue_info,
+        _ => return Ok(()),
+    };
+
+    let seeds = seeds.try_into().map_err(|_| VestingError::InvalidVestingSeeds)?;
+    let mut queue = Account::<UnlockQueue>::try_from(queue_info)?;
+    enqueue(&mut queue, seeds, next_queue_time(vesting, timestamp))?;
+    queue.exit(program_id)
+}
+
+/// Counts a claim of `claimed` in the round, the rollup and the partner stats of `vesting`, each of which it has to
+/// find in `remaining_accounts`. Every instruction moving released tokens out of a contract goes through it
+fn tally_claim<'info>(remaining_accounts: &[AccountInfo<'info>], program_id: &Pubkey, vesting: &Vesting, claimed: u64) -> Result<()> {
+    tally_round(remaining_accounts, program_id, vesting, claimed, 0)?;
+    tally_rollup(remaining_accounts, program_id, vesting, claimed, 0)?;
+    tally_partner_stats(remaining_accounts, program_id, vesting, claimed, 0)
+}
+
+/// Adds `claimed` and `revoked` to the tallies of the round of `vesting`, which has to be in `remaining_accounts`.
+/// Contracts without a round have nothing to tally
+fn tally_round<'info>(remaining_accounts: &[AccountInfo<'info>], program_id: &Pubkey, vesting: &Vesting, claimed: u64, revoked: u64) -> Result<()> {
+    let round_address = match vesting.round {
+        Some(round_address) => round_address,
+        None => return Ok(()),
+    };
+    let round_info = remaining_accounts.iter().find(|a| a.key() == round_address).ok_or(VestingError::MissingRoundAccount)?;
+
+    let mut round = Account::<Round>::try_from(round_info)?;
+    round.total_claimed = round.total_claimed.checked_add(claimed).ok_or(VestingError::TotalAmountOverflow)?;
+    round.total_revoked = round.total_revoked.checked_add(revoked).ok_or(VestingError::TotalAmountOverflow)?;
+    round.exit(program_id)
+}
+
+/// Moves `claimed` and `revoked` out of the amounts of the rollup of `vesting`, which has to be in
+/// `remaining_accounts`: claims come out of the vested amount first, revocations out of the locked amount first, as
+/// the rollup may be stale. Contracts without a rollup have nothing to tally
+fn tally_rollup<'info>(remaining_accounts: &[AccountInfo<'info>], program_id: &Pubkey, vesting: &Vesting, claimed: u64, revoked: u64) -> Result<()> {
+    let rollup_address = match vesting.rollup {
+        Some(rollup_address) => rollup_address,
+        None => return Ok(()),
+    };
+    let rollup_info = remaining_accounts.iter().find(|a| a.key() == rollup_address).ok_or(VestingError::MissingRollupAccount)?;
+
+    let mut rollup = Account::<Rollup>::try_from(rollup_info)?;
+    let from_vested = claimed.min(rollup.vested_unclaimed_amount);
+    rollup.vested_unclaimed_amount -= from_vested;
+    rollup.locked_amount = rollup.locked_amount.saturating_sub(claimed - from_vested);
+    let from_locked = revoked.min(rollup.locked_amount);
+    rollup.locked_amount -= from_locked;
+    rollup.vested_unclaimed_amount = rollup.vested_unclaimed_amount.saturating_sub(revoked - from_locked);
+    rollup.claimed_amount = rollup.claimed_amount.checked_add(claimed).ok_or(VestingError::TotalAmountOverflow)?;
+    rollup.revoked_amount = rollup.revoked_amount.checked_add(revoked).ok_or(VestingError::TotalAmountOverflow)?;
+    rollup.exit(program_id)
+}
+
+fn rollup_updated(rollup: &Account<Rollup>, timestamp: i64) -> RollupUpdated {
+    RollupUpdated {
+        rollup: rollup.key(),
+        contract_count: rollup.contract_count,
+        locked_amount: rollup.locked_amount,
+        vested_unclaimed_amount: rollup.vested_unclaimed_amount,
+        claimed_amount: rollup.claimed_amount,
+        revoked_amount: rollup.revoked_amount,
+        timestamp,
+    }
+}
+
+fn refresh_next_action(vesting: &mut Vesting, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) {
+    vesting.next_action = if vesting.schedule.iter().all(|s| s.amount == 0) {
+        NextAction::Completed
+    } else if !vesting.is_accepted {
+        NextAction::AcceptPending
+    } else if released_amount(&vesting.schedule, timestamp) > 0 {
+        NextAction::Claimable
+    } else {
+        match next_locked_tranche(&vesting.schedule, timestamp) {
+            Some((release_time, _)) if release_time as i64 > timestamp => NextAction::WaitUntil(release_time as i64),
+            _ => NextAction::ConditionPending,
+        }
+    };
+}
+
+/// Lists the tranches that differ between two versions of a schedule
+fn schedule_changes(before: &[Schedule], after: &[Schedule]) -> Vec<ScheduleChange> {
+    before
+        .iter()
+        .zip(after)
+        .enumerate()
+        .filter(|(_, (old, new))| {
+            old.release_time != new.release_time || old.amount != new.amount || old.condition.is_some() != new.condition.is_some()
+        })
+        .map(|(index, (old, new))| ScheduleChange {
+            index: index as u32,
+            old_release_time: old.release_time,
+            new_release_time: new.release_time,
+            old_amount: old.amount,
+            new_amount: new.amount,
+            old_has_condition: old.condition.is_some(),
+            new_has_condition: new.condition.is_some(),
+        })
+        .collect()
+}
+
+/// Returns the bump Anchor found for the PDA `account` of the accounts struct, failing instead of panicking when missing
+fn bump(bumps: &BTreeMap<String, u8>, account: &str) -> Result<u8> {
+    bumps.get(account).copied().ok_or_else(|| anchor_lang::error::ErrorCode::ConstraintSeeds.into())
+}
+
+/// Returns whether `actual` is the `expected` key, logging both otherwise so failed checks can be debugged from the logs
+fn expect_key(name: &str, expected: &Pubkey, actual: &Pubkey) -> bool {
+    let is_expected = expected == actual;
+    if !is_expected {
+        msg!("Expected {} {}, got {}", name, expected, actual);
+    }
+    is_expected
+}
+
+/// Returns whether `actual` is the `expected` value, logging both otherwise
+fn expect_value(name: &str, expected: u64, actual: u64) -> bool {
+    let is_expected = expected == actual;
+    if !is_expected {
+        msg!("Expected {} {}, got {}", name, expected, actual);
+    }
+    is_expected
+}
+
+/// Returns whether `actual` exceeds `needed`, logging both otherwise
+fn expect_more_than(name: &str, needed: u64, actual: u64) -> bool {
+    let is_enough = actual > needed;
+    if !is_enough {
+        msg!("Expected {} above {}, got {}", name, needed, actual);
+    }
+    is_enough
+}
+
+/// Returns whether `authority` may claim for the destination owner: it is the owner or an unexpired claim delegate
+fn is_claim_authority(vesting: &Vesting, destination_owner: &Pubkey, authority: &Pubkey, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> bool {
+    destination_owner == authority
+        || matches!(&vesting.claim_delegate, Some(d) if d.delegate == *authority && d.expires_at > timestamp)
+}
+
+/// Returns whether the unlock policy lets `authority` unlock for the destination owner: it is on the allowlist of an
+/// allowlist policy, otherwise the owner or an unexpired claim delegate
+fn is_unlock_authority(vesting: &Vesting, destination_owner: &Pubkey, authority: &Pubkey, timestamp: anchor_lang::solana_program::clock::UnixTimestamp) -> bool {
+    match &vesting.unlock_policy {
+        UnlockPolicy::Allowlist(keys) => keys.contains(authority),
+        _ => is_claim_authority(vesting, destination_owner, authority, timestamp),
+    }
+}
+
+fn is_valid_unlock_policy(unlock_policy: &UnlockPolicy) -> bool {
+    match unlock_policy {
+        UnlockPolicy::Allowlist(keys) => !keys.is_empty() && keys.len() <= MAX_UNLOCK_ALLOWLIST_LEN,
+        _ => true,
+    }
+}
+
+/// Formats a base unit amount in whole tokens, without trailing zeros
+fn ui_amount(amount: u64, decimals: u8) -> String {
+    let decimals = decimals as usize;
+    let digits = format!("{:0>width$}", amount, width = decimals + 1);
+    let (whole, fraction) = digits.split_at(digits.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
+fn reset_released_amount(schedules: &mut [Schedule], timestamp: anchor_lang::solana_program::clock::UnixTimestamp) {
+    schedules
+        .iter_mut()
+        .filter_map(|s| if is_released(s, timestamp) {Some(s.amount.borrow_mut())} else {None} )
+        .for_each(|amount|*amount = 0);
+}
+
+fn reset_locked_amount(schedules: &mut [Schedule], timestamp: anchor_lang::solana_program::clock::UnixTimestamp) {
+    schedules
+        .iter_mut()
+        .filter_map(|s| if !is_released(s, timestamp) {Some(s.amount.borrow_mut())} else {None} )
         .for_each(|amount|*amount = 0);
-}
\ No newline at end of file
+}
+
+/// Kani proofs of the claims of the program, run with `cargo kani -p token-vesting`
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    fn any_schedules() -> Vec<Schedule> {
+        (0..3).map(|_| Schedule { release_time: kani::any(), amount: kani::any(), condition: None }).collect()
+    }
+
+    /// Repeated unlocks at the same timestamp transfer nothing, the first one having zeroed the released tranches
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn repeated_unlocks_transfer_nothing() {
+        let mut schedules = any_schedules();
+        kani::assume(total_amount(&schedules).is_ok());
+        let timestamp: i64 = kani::any();
+        reset_released_amount(&mut schedules, timestamp);
+        assert_eq!(released_amount(&schedules, timestamp), 0);
+    }
+
+    /// A funded escrow covers every claim, the final sweep included
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn claims_stay_within_the_escrow() {
+        let schedules = any_schedules();
+        let total = total_amount(&schedules);
+        kani::assume(total.is_ok());
+        let escrow_balance: u64 = kani::any();
+        kani::assume(escrow_balance >= *total.as_ref().unwrap());
+        let released = released_amount(&schedules, kani::any());
+        assert!(final_sweep_amount(&schedules, released, escrow_balance).unwrap() <= escrow_balance);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tranche(release_time: u64, amount: u64) -> Schedule {
+        Schedule { release_time, amount, condition: None }
+    }
+
+    #[test]
+    fn stream_claims_transfer_the_released_amount() {
+        // 1_000 split in thirds, floored: 333 each and 1 base unit of dust in the escrow
+        let schedules = vec![tranche(10, 333), tranche(20, 333), tranche(30, 333)];
+        assert_eq!(final_sweep_amount(&schedules, 333, 1_000).unwrap(), 333);
+        assert_eq!(final_sweep_amount(&schedules, 666, 1_000).unwrap(), 666);
+    }
+
+    #[test]
+    fn final_claim_sweeps_the_escrow() {
+        let schedules = vec![tranche(10, 0), tranche(20, 0), tranche(30, 333)];
+        assert_eq!(final_sweep_amount(&schedules, 333, 334).unwrap(), 334);
+
+        let schedules = vec![tranche(10, 333), tranche(20, 333), tranche(30, 333)];
+        assert_eq!(final_sweep_amount(&schedules, 999, 1_000).unwrap(), 1_000);
+    }
+
+    #[test]
+    fn final_claim_without_dust_transfers_the_released_amount() {
+        let schedules = vec![tranche(10, 0), tranche(20, 500)];
+        assert_eq!(final_sweep_amount(&schedules, 500, 500).unwrap(), 500);
+        // An underfunded escrow still fails in the token program rather than transferring less
+        assert_eq!(final_sweep_amount(&schedules, 500, 400).unwrap(), 500);
+    }
+
+    proptest::proptest! {
+        #[test]
+        fn claims_drain_exactly_the_escrow(
+            tranches in proptest::collection::vec((0..1_000u64, 0..u64::MAX / 64), 1..16),
+            dust in 0..1_000u64,
+            mut claim_times in proptest::collection::vec(0..1_100i64, 0..8),
+        ) {
+            let mut schedules: Vec<Schedule> = tranches.iter().map(|&(release_time, amount)| tranche(release_time, amount)).collect();
+            let total = total_amount(&schedules).unwrap();
+            let mut escrow = total + dust;
+            claim_times.sort_unstable();
+            claim_times.push(1_000);
+            for timestamp in claim_times {
+                let released = released_amount(&schedules, timestamp);
+                let transferred = final_sweep_amount(&schedules, released, escrow).unwrap();
+                proptest::prop_assert!(transferred <= escrow);
+                escrow -= transferred;
+                reset_released_amount(&mut schedules, timestamp);
+                // A second claim at the same timestamp releases nothing
+                proptest::prop_assert_eq!(released_amount(&schedules, timestamp), 0);
+                proptest::prop_assert!(escrow >= total_amount(&schedules).unwrap());
+            }
+            proptest::prop_assert_eq!(escrow, 0);
+        }
+
+        #[test]
+        fn revocation_keeps_the_released_amount(
+            tranches in proptest::collection::vec((0..1_000u64, 0..u64::MAX / 64), 0..16),
+            timestamp in 0..1_100i64,
+        ) {
+            let mut schedules: Vec<Schedule> = tranches.iter().map(|&(release_time, amount)| tranche(release_time, amount)).collect();
+            let released = released_amount(&schedules, timestamp);
+            reset_locked_amount(&mut schedules, timestamp);
+            proptest::prop_assert_eq!(total_amount(&schedules).unwrap(), released);
+        }
+    }
+}
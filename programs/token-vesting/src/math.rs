@@ -0,0 +1,283 @@
+//! Schedule math shared by the program and off-chain clients.
+//!
+//! The module only uses `core`, so clients, bots and tests can build it under `no_std` and get results
+//! bit-identical to the program. It works on any tranche type through `Tranche`, which `Schedule` implements.
+
+/// A release time and amount, possibly gated on a release condition
+pub trait Tranche {
+    fn release_time(&self) -> u64;
+    fn amount(&self) -> u64;
+    /// Whether a release condition still gates the tranche
+    fn is_gated(&self) -> bool;
+}
+
+impl Tranche for (u64, u64) {
+    fn release_time(&self) -> u64 {
+        self.0
+    }
+
+    fn amount(&self) -> u64 {
+        self.1
+    }
+
+    fn is_gated(&self) -> bool {
+        false
+    }
+}
+
+/// A tranche is released once its release time has passed and its release condition, if any, has been met
+pub fn is_released<T: Tranche>(tranche: &T, timestamp: i64) -> bool {
+    timestamp as u64 >= tranche.release_time() && !tranche.is_gated()
+}
+
+/// Returns the amount the tranches still hold, none on overflow
+pub fn total_amount<T: Tranche>(tranches: &[T]) -> Option<u64> {
+    tranches.iter().try_fold(0u64, |sum, t| sum.checked_add(t.amount()))
+}
+
+/// Returns the amount released at `timestamp` and not claimed yet
+pub fn released_amount<T: Tranche>(tranches: &[T], timestamp: i64) -> u64 {
+    tranches.iter().filter(|t| is_released(*t, timestamp)).map(|t| t.amount()).sum()
+}
+
+/// Returns the amount still locked at `timestamp`
+pub fn locked_amount<T: Tranche>(tranches: &[T], timestamp: i64) -> u64 {
+    tranches.iter().filter(|t| !is_released(*t, timestamp)).map(|t| t.amount()).sum()
+}
+
+/// Returns the amount a claim at `timestamp` releases: 0 when the released amount is below `min_claim_amount`,
+/// unless it releases everything left
+pub fn claimable_amount<T: Tranche>(tranches: &[T], min_claim_amount: u64, timestamp: i64) -> u64 {
+    let released = released_amount(tranches, timestamp);
+    if released >= min_claim_amount || Some(released) == total_amount(tranches) {
+        released
+    } else {
+        0
+    }
+}
+
+/// Returns the earliest release time of the tranches still locked at `timestamp` and their summed amount. Tranches
+/// gated on an unmet release condition are skipped: they release when the condition is met, not at their release time
+pub fn next_locked_tranche<T: Tranche>(tranches: &[T], timestamp: i64) -> Option<(u64, u64)> {
+    let locked = tranches.iter().filter(|t| t.amount() > 0 && !t.is_gated() && !is_released(*t, timestamp));
+    let release_time = locked.clone().map(|t| t.release_time()).min()?;
+    let amount = locked.filter(|t| t.release_time() == release_time).map(|t| t.amount()).sum();
+    Some((release_time, amount))
+}
+
+/// Evaluates a linear vesting curve: the amount of `total` released between `start` and `end` at `timestamp`,
+/// floored. Everything is released from `end` on
+pub fn linear_released(total: u64, start: u64, end: u64, timestamp: u64) -> u64 {
+    if timestamp >= end {
+        total
+    } else if timestamp <= start {
+        0
+    } else {
+        (total as u128 * (timestamp - start) as u128 / (end - start) as u128) as u64
+    }
+}
+
+/// Generates `count` tranches of `total` released every `interval` seconds after `start`, as (release time, amount).
+/// The amounts follow the linear curve sampled at each release time, floored, so they sum to `total`
+pub fn linear_tranches(total: u64, start: u64, interval: u64, count: u64) -> impl Iterator<Item = (u64, u64)> {
+    let released_after = move |i: u64| (total as u128 * i as u128 / count as u128) as u64;
+    (1..=count).map(move |i| (start.saturating_add(interval.saturating_mul(i)), released_after(i) - released_after(i - 1)))
+}
+
+/// Splits `total` in proportion to `weights`, flooring every part but the last, which takes the rest so the parts sum
+/// to `total`. Yields nothing when the weights sum to 0
+pub fn split_by_weights(total: u64, weights: &[u64]) -> impl Iterator<Item = u64> + '_ {
+    let total_weight: u128 = weights.iter().map(|&w| w as u128).sum();
+    let mut allotted = 0;
+    weights.iter().enumerate().filter(move |_| total_weight > 0).map(move |(i, &weight)| {
+        let part = if i + 1 == weights.len() {
+            total - allotted
+        } else {
+            (total as u128 * weight as u128 / total_weight) as u64
+        };
+        allotted += part;
+        part
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+    use std::convert::TryFrom;
+
+    /// A tranche whose release condition isn't met yet
+    struct Gated(u64, u64);
+
+    impl Tranche for Gated {
+        fn release_time(&self) -> u64 {
+            self.0
+        }
+
+        fn amount(&self) -> u64 {
+            self.1
+        }
+
+        fn is_gated(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn next_locked_tranche_skips_gated_tranches() {
+        assert_eq!(next_locked_tranche(&[Gated(100, 5), Gated(2_000, 7)], 1_000), None);
+        assert_eq!(locked_amount(&[Gated(100, 5)], 1_000), 5);
+    }
+
+    /// Tranches released within the first 1_000 seconds, amounts bounded so their sum fits a u64
+    fn tranches() -> impl Strategy<Value = Vec<(u64, u64)>> {
+        prop::collection::vec((0..1_000u64, 0..u64::MAX / 64), 0..32)
+    }
+
+    proptest! {
+        #[test]
+        fn total_amount_is_the_exact_sum(tranches in prop::collection::vec((any::<u64>(), any::<u64>()), 0..8)) {
+            let sum: u128 = tranches.iter().map(|t| t.1 as u128).sum();
+            prop_assert_eq!(total_amount(&tranches), u64::try_from(sum).ok());
+        }
+
+        #[test]
+        fn released_and_locked_split_the_total(tranches in tranches(), timestamp in 0..1_100i64) {
+            let total = total_amount(&tranches).unwrap();
+            prop_assert_eq!(released_amount(&tranches, timestamp) + locked_amount(&tranches, timestamp), total);
+        }
+
+        #[test]
+        fn released_amount_never_decreases(tranches in tranches(), earlier in 0..1_100i64, later in 0..1_100i64) {
+            let (earlier, later) = (earlier.min(later), earlier.max(later));
+            prop_assert!(released_amount(&tranches, earlier) <= released_amount(&tranches, later));
+            prop_assert!(locked_amount(&tranches, earlier) >= locked_amount(&tranches, later));
+        }
+
+        #[test]
+        fn claims_release_everything_or_nothing(tranches in tranches(), min_claim_amount in any::<u64>(), timestamp in 0..1_100i64) {
+            let released = released_amount(&tranches, timestamp);
+            let claimable = claimable_amount(&tranches, min_claim_amount, timestamp);
+            prop_assert!(claimable == released || claimable == 0);
+            prop_assert!(claimable == 0 || claimable >= min_claim_amount || Some(claimable) == total_amount(&tranches));
+        }
+
+        #[test]
+        fn claims_after_the_last_release_take_everything(tranches in tranches(), min_claim_amount in any::<u64>()) {
+            prop_assert_eq!(Some(claimable_amount(&tranches, min_claim_amount, 1_000)), total_amount(&tranches));
+        }
+
+        #[test]
+        fn next_locked_tranche_is_the_earliest_locked(tranches in tranches(), timestamp in 0..1_100i64) {
+            match next_locked_tranche(&tranches, timestamp) {
+                Some((release_time, amount)) => {
+                    prop_assert!(release_time as i64 > timestamp);
+                    prop_assert!(amount > 0);
+                    // Nothing else is released before it
+                    let just_before = release_time as i64 - 1;
+                    prop_assert_eq!(released_amount(&tranches, just_before), released_amount(&tranches, timestamp));
+                    prop_assert_eq!(released_amount(&tranches, release_time as i64), released_amount(&tranches, timestamp) + amount);
+                }
+                None => prop_assert_eq!(locked_amount(&tranches, timestamp), 0),
+            }
+        }
+
+        #[test]
+        fn linear_curve_is_bounded_and_non_decreasing(total in any::<u64>(), start in 0..1_000u64, duration in 1..1_000u64,
+                                                      earlier in 0..2_100u64, later in 0..2_100u64) {
+            let end = start + duration;
+            let (earlier, later) = (earlier.min(later), earlier.max(later));
+            prop_assert!(linear_released(total, start, end, earlier) <= linear_released(total, start, end, later));
+            prop_assert!(linear_released(total, start, end, later) <= total);
+            prop_assert_eq!(linear_released(total, start, end, start), 0);
+            prop_assert_eq!(linear_released(total, start, end, end), total);
+        }
+
+        #[test]
+        fn linear_tranches_split_the_total(total in any::<u64>(), start in 0..1_000u64, interval in 1..1_000u64, count in 1..64u64) {
+            let tranches: Vec<(u64, u64)> = linear_tranches(total, start, interval, count).collect();
+            prop_assert_eq!(tranches.len() as u64, count);
+            prop_assert_eq!(total_amount(&tranches), Some(total));
+            prop_assert!(tranches.windows(2).all(|w| w[0].0 < w[1].0));
+        }
+
+        #[test]
+        fn linear_tranches_follow_the_curve(total in any::<u64>(), start in 0..1_000u64, interval in 1..1_000u64, count in 1..64u64) {
+            let end = start + interval * count;
+            let tranches: Vec<(u64, u64)> = linear_tranches(total, start, interval, count).collect();
+            for &(release_time, _) in &tranches {
+                prop_assert_eq!(released_amount(&tranches, release_time as i64), linear_released(total, start, end, release_time));
+            }
+        }
+
+        #[test]
+        fn weighted_parts_split_the_total(total in any::<u64>(), weights in prop::collection::vec(0..u64::MAX / 64, 1..64)) {
+            let parts: Vec<u64> = split_by_weights(total, &weights).collect();
+            let total_weight: u128 = weights.iter().map(|&w| w as u128).sum();
+            if total_weight == 0 {
+                prop_assert!(parts.is_empty());
+            } else {
+                prop_assert_eq!(parts.iter().map(|&p| p as u128).sum::<u128>(), total as u128);
+                for (&part, &weight) in parts.iter().zip(&weights).take(weights.len() - 1) {
+                    prop_assert!(part as u128 * total_weight <= total as u128 * weight as u128);
+                }
+            }
+        }
+    }
+}
+
+/// Kani proofs of the claim math over every tranche of a small schedule, run with `cargo kani -p token-vesting`
+#[cfg(kani)]
+mod proofs {
+    use super::*;
+
+    const TRANCHES: usize = 3;
+
+    fn any_tranches() -> [(u64, u64); TRANCHES] {
+        [(kani::any(), kani::any()), (kani::any(), kani::any()), (kani::any(), kani::any())]
+    }
+
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn total_amount_never_overflows() {
+        let tranches = any_tranches();
+        if let Some(total) = total_amount(&tranches) {
+            assert!(tranches.iter().all(|t| t.1 <= total));
+        }
+    }
+
+    /// The program refuses schedules whose total overflows, below that nothing overflows and a claim never takes
+    /// more than the schedule holds
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn claims_never_exceed_the_total() {
+        let tranches = any_tranches();
+        let total = total_amount(&tranches);
+        kani::assume(total.is_some());
+        let timestamp: i64 = kani::any();
+        let released = released_amount(&tranches, timestamp);
+        assert_eq!(Some(released + locked_amount(&tranches, timestamp)), total);
+        assert!(claimable_amount(&tranches, kani::any(), timestamp) <= released);
+    }
+
+    #[kani::proof]
+    #[kani::unwind(4)]
+    fn claimed_tranches_release_nothing() {
+        let mut tranches = any_tranches();
+        kani::assume(total_amount(&tranches).is_some());
+        let timestamp: i64 = kani::any();
+        for tranche in tranches.iter_mut() {
+            if is_released(tranche, timestamp) {
+                tranche.1 = 0;
+            }
+        }
+        assert_eq!(released_amount(&tranches, timestamp), 0);
+    }
+
+    #[kani::proof]
+    fn linear_curve_never_exceeds_the_total() {
+        let (total, start, end, timestamp): (u64, u64, u64, u64) = (kani::any(), kani::any(), kani::any(), kani::any());
+        kani::assume(start < end);
+        assert!(linear_released(total, start, end, timestamp) <= total);
+    }
+}
@@ -0,0 +1,18 @@
+//! CPI helper attaching an SPL Memo to the transaction of a transfer.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::program::invoke;
+
+anchor_lang::declare_id!("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr");
+
+/// Records `memo` in the transaction through the SPL Memo program, passed as `memo_program`
+pub fn log_memo(memo_program: &AccountInfo, memo: &str) -> Result<()> {
+    require!(*memo_program.key == ID, crate::VestingError::InvalidMemoProgram);
+    let ix = Instruction {
+        program_id: ID,
+        accounts: vec![],
+        data: memo.as_bytes().to_vec(),
+    };
+    invoke(&ix, std::slice::from_ref(memo_program)).map_err(Into::into)
+}
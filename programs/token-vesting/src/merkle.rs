@@ -0,0 +1,101 @@
+//! Merkle tree of the (claimant, tranches) leaves of a distributor, whose root alone is stored on chain.
+//!
+//! Leaves and inner nodes are SHA-256 hashes prefixed with a different byte, so a node can't pass for a leaf. A node
+//! hashes its two children in ascending order, proofs then being the bare sibling hashes from the leaf up. A node
+//! without sibling moves up a level unchanged and adds nothing to the proofs of its leaves.
+//!
+//! The leaves of a vested airdrop allot a bare amount, which the schedule template of the airdrop vests.
+//!
+//! `root` and `proof` build the tree off chain from the leaves in the order the distributor creator chose.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hashv;
+
+use crate::MerkleTranche;
+
+const LEAF_PREFIX: u8 = 0;
+const NODE_PREFIX: u8 = 1;
+
+/// Returns the leaf granting `tranches` to `claimant`
+pub fn leaf(claimant: &Pubkey, tranches: &[MerkleTranche]) -> [u8; 32] {
+    let mut data = Vec::with_capacity(16 * tranches.len());
+    for tranche in tranches {
+        data.extend_from_slice(&tranche.release_time.to_le_bytes());
+        data.extend_from_slice(&tranche.amount.to_le_bytes());
+    }
+    hashv(&[&[LEAF_PREFIX], claimant.as_ref(), &data]).to_bytes()
+}
+
+/// Returns the leaf allotting `amount` to `claimant`, the leaves of a vested airdrop
+pub fn allocation_leaf(claimant: &Pubkey, amount: u64) -> [u8; 32] {
+    hashv(&[&[LEAF_PREFIX], claimant.as_ref(), &amount.to_le_bytes()]).to_bytes()
+}
+
+fn node(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let (left, right) = if a <= b { (a, b) } else { (b, a) };
+    hashv(&[&[NODE_PREFIX], left, right]).to_bytes()
+}
+
+/// Returns whether `proof` links `leaf` to `root`
+pub fn verify(proof: &[[u8; 32]], root: &[u8; 32], leaf: [u8; 32]) -> bool {
+    proof.iter().fold(leaf, |hash, sibling| node(&hash, sibling)) == *root
+}
+
+fn parent_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [a, b] => node(a, b),
+            [a] => *a,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Returns the root of the tree of `leaves`, the zero hash without leaves
+pub fn root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = parent_level(&level);
+    }
+    level.first().copied().unwrap_or_default()
+}
+
+/// Returns the proof of the leaf at `index` in the tree of `leaves`
+pub fn proof(leaves: &[[u8; 32]], mut index: usize) -> Vec<[u8; 32]> {
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        if let Some(sibling) = level.get(index ^ 1) {
+            proof.push(*sibling);
+        }
+        level = parent_level(&level);
+        index /= 2;
+    }
+    proof
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tranches(amount: u64) -> Vec<MerkleTranche> {
+        vec![MerkleTranche { release_time: 100, amount }, MerkleTranche { release_time: 200, amount: 2 * amount }]
+    }
+
+    #[test]
+    fn every_leaf_is_proven() {
+        for count in 1..10u64 {
+            let claimants: Vec<Pubkey> = (0..count).map(|_| Pubkey::new_unique()).collect();
+            let leaves: Vec<[u8; 32]> = claimants.iter().zip(1..).map(|(c, amount)| leaf(c, &tranches(amount))).collect();
+            let root = root(&leaves);
+            for (index, claimant) in claimants.iter().enumerate() {
+                let proof = proof(&leaves, index);
+                assert!(verify(&proof, &root, leaf(claimant, &tranches(index as u64 + 1))));
+                // Another amount or another claimant isn't in the tree
+                assert!(!verify(&proof, &root, leaf(claimant, &tranches(index as u64 + 2))));
+                assert!(!verify(&proof, &root, leaf(&Pubkey::new_unique(), &tranches(index as u64 + 1))));
+            }
+        }
+    }
+}
@@ -0,0 +1,837 @@
+//! `solana-program-test` fixtures for integration tests against vesting timelines, enabled by the `test-fixture` feature.
+//!
+//! `VestingFixture` runs the program and SPL Token natively in a `ProgramTest` bank, creates mints, associated
+//! token accounts and grants, and travels the cluster clock so cliffs and tranches can be crossed without waiting.
+//! Token accounts are written straight into the bank, so no BPF program has to run.
+//!
+//! Natively run programs can't create accounts through CPI in `solana-program-test` 1.10: the bank doesn't resize
+//! the account data of the caller. The fixture therefore writes the accounts an Anchor `init` allocates ahead of
+//! the instruction, zeroed and rent exempt but still owned by the system program, and its syscall stubs skip the
+//! allocation of such an account. The instruction then runs as it does on chain, its constraints included, except
+//! that the payer isn't charged the rent.
+//! Natively run CPIs also read the instruction context of the caller after the callee freed it, so test binaries
+//! install `DeferredFreeAllocator` as their global allocator.
+//!
+//! The feature implies `cpi`, whose instruction builders the fixture uses.
+//!
+//! ```ignore
+//! #[global_allocator]
+//! static ALLOCATOR: DeferredFreeAllocator = DeferredFreeAllocator;
+//!
+//! let mut fixture = VestingFixture::start().await;
+//! let mint = fixture.create_mint(6).await;
+//! // ...
+//! fixture.warp_to_timestamp(cliff).await;
+//! ```
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::Instruction;
+use anchor_lang::solana_program::entrypoint::ProgramResult;
+use anchor_lang::solana_program::program_pack::Pack;
+use anchor_lang::solana_program::program_stubs::{self, SyscallStubs};
+use anchor_lang::solana_program::program_utils::limited_deserialize;
+use anchor_lang::solana_program::system_instruction::{self, SystemInstruction};
+use anchor_lang::solana_program::system_program;
+use anchor_lang::{AccountDeserialize, AccountSerialize, Discriminator, InstructionData};
+use anchor_spl::associated_token::get_associated_token_address;
+use solana_program_test::{processor, BanksClientError, ProgramTest, ProgramTestBanksClientExt, ProgramTestContext};
+use solana_sdk::account::Account;
+use solana_sdk::commitment_config::CommitmentLevel;
+use solana_sdk::signature::{Keypair, Signer};
+use solana_sdk::transaction::{Transaction, TransactionError};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::{Mutex, Once, OnceLock};
+
+use crate::integration::{self, find_vesting_address};
+use crate::{Schedule, Vesting};
+
+// The frees `DeferredFreeAllocator` holds back, far more than a CPI makes
+const DEFERRED_FREES: usize = 1 << 12;
+
+struct DeferredFrees {
+    next: usize,
+    // Address, size and alignment of the held back allocations, a zero address for an empty slot
+    frees: [(usize, usize, usize); DEFERRED_FREES],
+}
+
+static DEFERRED: Mutex<DeferredFrees> = Mutex::new(DeferredFrees { next: 0, frees: [(0, 0, 0); DEFERRED_FREES] });
+
+/// A global allocator freeing memory only after `DEFERRED_FREES` later frees. `builtin_process_instruction` of
+/// `solana-program-test` 1.10 keeps a reference to the instruction context of the caller across a CPI, whose trace
+/// vector the CPI reallocates. Holding the old allocation back leaves that reference reading the unchanged context,
+/// while long running harnesses like the fuzzer don't grow without bound
+pub struct DeferredFreeAllocator;
+
+unsafe impl GlobalAlloc for DeferredFreeAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        let (address, size, align) = {
+            let mut deferred = DEFERRED.lock().unwrap_or_else(|e| e.into_inner());
+            let slot = deferred.next % DEFERRED_FREES;
+            deferred.next = deferred.next.wrapping_add(1);
+            std::mem::replace(&mut deferred.frees[slot], (ptr as usize, layout.size(), layout.align()))
+        };
+        if address != 0 {
+            System.dealloc(address as *mut u8, Layout::from_size_align_unchecked(size, align));
+        }
+    }
+}
+
+// The syscall stubs of `solana-program-test`, which `PreparedInitStubs` forwards to
+static PROGRAM_TEST_STUBS: OnceLock<Box<dyn SyscallStubs>> = OnceLock::new();
+
+/// Syscall stubs skipping the system `Allocate` of accounts the fixture prepared for an `init`, see `prepare_init`.
+/// Every other syscall goes to the stubs of `solana-program-test`
+struct PreparedInitStubs;
+
+impl PreparedInitStubs {
+    /// Installs the stubs around those of `solana-program-test`, which the first bank started installs
+    fn install() {
+        static INSTALL: Once = Once::new();
+        INSTALL.call_once(|| {
+            let inner = program_stubs::set_syscall_stubs(Box::new(PreparedInitStubs));
+            let _ = PROGRAM_TEST_STUBS.set(inner);
+        });
+    }
+
+    fn inner(&self) -> &'static dyn SyscallStubs {
+        // Another test may call in between installing the stubs and storing the ones they wrap
+        loop {
+            if let Some(inner) = PROGRAM_TEST_STUBS.get() {
+                return inner.as_ref();
+            }
+            std::thread::yield_now();
+        }
+    }
+}
+
+/// Whether `instruction` allocates a system account already holding the zeroed space it asks for
+fn is_prepared_allocation(instruction: &Instruction, account_infos: &[AccountInfo]) -> bool {
+    if instruction.program_id != system_program::ID {
+        return false;
+    }
+    let space = match limited_deserialize(&instruction.data, instruction.data.len() as u64) {
+        Ok(SystemInstruction::Allocate { space }) => space,
+        _ => return false,
+    };
+    instruction.accounts.first()
+        .and_then(|meta| account_infos.iter().find(|info| *info.key == meta.pubkey))
+        .is_some_and(|info| system_program::check_id(info.owner) && space > 0 && info.data_len() as u64 == space
+            && info.try_borrow_data().is_ok_and(|data| data.iter().all(|byte| *byte == 0)))
+}
+
+impl SyscallStubs for PreparedInitStubs {
+    fn sol_log(&self, message: &str) {
+        self.inner().sol_log(message)
+    }
+    fn sol_log_compute_units(&self) {
+        self.inner().sol_log_compute_units()
+    }
+    fn sol_invoke_signed(&self, instruction: &Instruction, account_infos: &[AccountInfo], signers_seeds: &[&[&[u8]]]) -> ProgramResult {
+        if is_prepared_allocation(instruction, account_infos) {
+            return Ok(());
+        }
+        self.inner().sol_invoke_signed(instruction, account_infos, signers_seeds)
+    }
+    fn sol_get_clock_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner().sol_get_clock_sysvar(var_addr)
+    }
+    fn sol_get_epoch_schedule_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner().sol_get_epoch_schedule_sysvar(var_addr)
+    }
+    #[allow(deprecated)]
+    fn sol_get_fees_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner().sol_get_fees_sysvar(var_addr)
+    }
+    fn sol_get_rent_sysvar(&self, var_addr: *mut u8) -> u64 {
+        self.inner().sol_get_rent_sysvar(var_addr)
+    }
+    unsafe fn sol_memcpy(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.inner().sol_memcpy(dst, src, n)
+    }
+    unsafe fn sol_memmove(&self, dst: *mut u8, src: *const u8, n: usize) {
+        self.inner().sol_memmove(dst, src, n)
+    }
+    unsafe fn sol_memcmp(&self, s1: *const u8, s2: *const u8, n: usize, result: *mut i32) {
+        self.inner().sol_memcmp(s1, s2, n, result)
+    }
+    unsafe fn sol_memset(&self, s: *mut u8, c: u8, n: usize) {
+        self.inner().sol_memset(s, c, n)
+    }
+    fn sol_get_return_data(&self) -> Option<(Pubkey, Vec<u8>)> {
+        self.inner().sol_get_return_data()
+    }
+    fn sol_set_return_data(&self, data: &[u8]) {
+        self.inner().sol_set_return_data(data)
+    }
+    fn sol_log_data(&self, fields: &[&[u8]]) {
+        self.inner().sol_log_data(fields)
+    }
+    fn sol_get_processed_sibling_instruction(&self, index: usize) -> Option<Instruction> {
+        self.inner().sol_get_processed_sibling_instruction(index)
+    }
+    fn sol_get_stack_height(&self) -> u64 {
+        self.inner().sol_get_stack_height()
+    }
+}
+
+/// Returns a `ProgramTest` running the program, SPL Token and SPL Associated Token natively, to add accounts or
+/// programs to before starting a fixture. With `BPF_OUT_DIR` set, the BPF build of the program found there runs
+/// instead, next to the SPL builds embedded in `solana-program-test`
+pub fn program_test() -> ProgramTest {
+    let mut program_test = ProgramTest::new("token_vesting", crate::ID, processor!(crate::entry));
+    if std::env::var("BPF_OUT_DIR").is_err() {
+        program_test.add_program("spl_token", spl_token::ID, processor!(spl_token::processor::Processor::process));
+        program_test.add_program("spl_associated_token_account", spl_associated_token_account::ID,
+                                 processor!(spl_associated_token_account::processor::process_instruction));
+    }
+    program_test
+}
+
+/// A started test bank with the program deployed. The payer of the context creates and signs for the mints
+pub struct VestingFixture {
+    pub context: ProgramTestContext,
+}
+
+impl VestingFixture {
+    /// Starts a bank with the programs of `program_test()`
+    pub async fn start() -> Self {
+        Self::start_with(program_test()).await
+    }
+
+    /// Starts a bank from `program_test`
+    pub async fn start_with(program_test: ProgramTest) -> Self {
+        let context = program_test.start_with_context().await;
+        PreparedInitStubs::install();
+        Self { context }
+    }
+
+    pub fn payer(&self) -> Pubkey {
+        self.context.payer.pubkey()
+    }
+
+    /// Sends `instructions` in one transaction paid by the context payer and signed by `signers` too.
+    /// Every transaction gets a fresh blockhash, so identical transactions can be sent in a row
+    pub async fn process(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> std::result::Result<(), BanksClientError> {
+        let transaction = self.transaction(instructions, signers).await?;
+        self.context.banks_client.process_transaction(transaction).await
+    }
+
+    /// Returns the compute units `instructions` consume, simulated without landing them. Only BPF programs are metered,
+    /// natively run programs consume none.
+    ///
+    /// The bank only reports the units of transactions failing their simulation, so the instructions are followed by
+    /// a transfer the payer can't fund
+    pub async fn simulate_compute_units(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> std::result::Result<u64, BanksClientError> {
+        let payer = self.payer();
+        let mut instructions = instructions.to_vec();
+        instructions.push(system_instruction::transfer(&payer, &payer, u64::MAX));
+        let transaction = self.transaction(&instructions, signers).await?;
+        let outcome = self.context.banks_client
+            .process_transaction_with_preflight_and_commitment_and_context(
+                tarpc::context::current(), transaction, CommitmentLevel::default())
+            .await?;
+        match (outcome.result, outcome.simulation_details) {
+            (Some(Err(TransactionError::InstructionError(index, _))), Some(details)) if index as usize == instructions.len() - 1 =>
+                Ok(details.units_consumed),
+            (Some(Err(error)), _) => Err(error.into()),
+            _ => Err(BanksClientError::ClientError("the unfunded transfer didn't fail the simulation")),
+        }
+    }
+
+    async fn transaction(&mut self, instructions: &[Instruction], signers: &[&Keypair]) -> std::result::Result<Transaction, BanksClientError> {
+        self.context.last_blockhash = self.context.banks_client.get_new_latest_blockhash(&self.context.last_blockhash).await?;
+        let mut all_signers = vec![&self.context.payer];
+        all_signers.extend_from_slice(signers);
+        Ok(Transaction::new_signed_with_payer(instructions, Some(&self.context.payer.pubkey()),
+                                              &all_signers, self.context.last_blockhash))
+    }
+
+    /// Creates a mint of `decimals` whose authority is the payer
+    pub async fn create_mint(&mut self, decimals: u8) -> Pubkey {
+        let mint = Keypair::new();
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        let payer = self.payer();
+        let instructions = [
+            system_instruction::create_account(&payer, &mint.pubkey(), rent.minimum_balance(spl_token::state::Mint::LEN),
+                                               spl_token::state::Mint::LEN as u64, &spl_token::ID),
+            spl_token::instruction::initialize_mint(&spl_token::ID, &mint.pubkey(), &payer, None, decimals).unwrap(),
+        ];
+        self.process(&instructions, &[&mint]).await.unwrap();
+        mint.pubkey()
+    }
+
+    /// Creates the empty associated token account of `owner` for `mint`
+    pub async fn create_associated_token_account(&mut self, owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let address = get_associated_token_address(owner, mint);
+        self.create_token_account(&address, owner, mint).await;
+        address
+    }
+
+    /// Creates an empty token account of `owner` for `mint` at `address`, e.g. a PDA of another program
+    pub async fn create_token_account(&mut self, address: &Pubkey, owner: &Pubkey, mint: &Pubkey) {
+        let mut data = vec![0; spl_token::state::Account::LEN];
+        spl_token::state::Account {
+            mint: *mint,
+            owner: *owner,
+            state: spl_token::state::AccountState::Initialized,
+            ..Default::default()
+        }.pack_into_slice(&mut data);
+        self.set_foreign_account(address, &spl_token::ID, data).await;
+    }
+
+    /// Mints `amount` of `mint` to `token_account`
+    pub async fn mint_to(&mut self, mint: &Pubkey, token_account: &Pubkey, amount: u64) {
+        let payer = self.payer();
+        let instruction = spl_token::instruction::mint_to(&spl_token::ID, mint, token_account, &payer, &[], amount).unwrap();
+        self.process(&[instruction], &[]).await.unwrap();
+    }
+
+    /// Returns the balance of `token_account`
+    pub async fn token_balance(&mut self, token_account: &Pubkey) -> u64 {
+        let account = self.context.banks_client.get_account(*token_account).await.unwrap()
+            .expect("token account doesn't exist");
+        spl_token::state::Account::unpack(&account.data).unwrap().amount
+    }
+
+    /// Fetches the vesting account derived from `seeds`
+    pub async fn vesting(&mut self, seeds: &[u8; 31]) -> Vesting {
+        let account = self.context.banks_client.get_account(find_vesting_address(seeds).0).await.unwrap()
+            .expect("vesting account doesn't exist");
+        Vesting::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Writes the account an Anchor `init` of `space` bytes allocates at `address`, zeroed and rent exempt but owned
+    /// by the system program, so that the instruction runs natively. The payer of the instruction isn't charged.
+    /// The BPF build of the program allocates its accounts itself, nothing is written when it runs, nor over an
+    /// account already created
+    pub async fn prepare_init(&mut self, address: &Pubkey, space: usize) {
+        if std::env::var("BPF_OUT_DIR").is_ok() {
+            return;
+        }
+        let existing = self.context.banks_client.get_account(*address).await.unwrap();
+        if existing.is_some_and(|account| account.owner != system_program::ID) {
+            return;
+        }
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.context.set_account(address, &Account {
+            lamports: rent.minimum_balance(space),
+            data: vec![0; space],
+            owner: system_program::ID,
+            executable: false,
+            rent_epoch: 0,
+        }.into());
+    }
+
+    /// Prepares the vesting account of `seeds` for an instruction that creates it with `number_of_schedules` schedules
+    pub async fn prepare_vesting(&mut self, seeds: &[u8; 31], number_of_schedules: u32) {
+        self.prepare_init(&find_vesting_address(seeds).0, crate::calc_vesting_account_size(number_of_schedules)).await;
+    }
+
+    /// Allocates the vesting account of `seeds` with `number_of_schedules` empty schedules through `init`
+    pub async fn allocate_vesting(&mut self, seeds: &[u8; 31], number_of_schedules: u32) {
+        self.prepare_vesting(seeds, number_of_schedules).await;
+        let payer = self.payer();
+        self.process(&[integration::init(*seeds, number_of_schedules, payer)], &[]).await.unwrap();
+    }
+
+    /// Writes `vesting` as the vesting account of `seeds`, for a contract with more schedules than a `create`
+    /// transaction can carry. No instruction validates it and the escrow isn't funded
+    pub async fn set_vesting(&mut self, seeds: &[u8; 31], vesting: &Vesting) {
+        let size = crate::calc_vesting_account_size(vesting.schedule.len() as u32);
+        let mut data = vec![0; size];
+        vesting.try_serialize(&mut data.as_mut_slice()).unwrap();
+
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.context.set_account(&find_vesting_address(seeds).0, &Account {
+            lamports: rent.minimum_balance(size),
+            data,
+            owner: crate::ID,
+            executable: false,
+            rent_epoch: 0,
+        }.into());
+    }
+
+    /// Delegates the governance power of the contract of `seeds` to `delegate` through `set_vote_delegate`, signed by
+    /// the owner of its destination
+    pub async fn delegate_votes(&mut self, seeds: [u8; 31], destination_token: &Pubkey, destination_authority: &Keypair,
+                                delegate: &Pubkey) -> std::result::Result<(), BanksClientError> {
+        let vote_delegation = integration::find_vote_delegation_address(&find_vesting_address(&seeds).0).0;
+        self.prepare_init(&vote_delegation, crate::VOTE_DELEGATION_ACCOUNT_SIZE).await;
+        let instruction = integration::set_vote_delegate(seeds, *destination_token, destination_authority.pubkey(), *delegate,
+                                                         self.payer());
+        self.process(&[instruction], &[destination_authority]).await
+    }
+
+    /// Grows the vesting account of `seeds` by `number_of_schedules` schedules as `fund_next_period` does, with a
+    /// realloc natively unavailable
+    pub async fn reserve_schedules(&mut self, seeds: &[u8; 31], number_of_schedules: usize) {
+        let address = find_vesting_address(seeds).0;
+        let mut account = self.context.banks_client.get_account(address).await.unwrap().expect("vesting doesn't exist");
+        account.data.resize(account.data.len() + number_of_schedules * crate::SCHEDULE_SIZE, 0);
+        account.lamports = self.context.banks_client.get_rent().await.unwrap().minimum_balance(account.data.len());
+        self.context.set_account(&address, &account.into());
+    }
+
+    /// Writes a vesting account of `seeds` in the layout of the first release, paying `destination_token` the
+    /// `(release_time, amount)` tranches of `schedule`. The account is sized for the current layout, as
+    /// `migrate_vesting_layout` grows it, with a realloc natively unavailable
+    pub async fn write_legacy_vesting(&mut self, seeds: &[u8; 31], destination_token: &Pubkey, mint: &Pubkey, schedule: &[(u64, u64)]) {
+        let mut data = Vesting::discriminator().to_vec();
+        data.extend_from_slice(destination_token.as_ref());
+        data.extend_from_slice(mint.as_ref());
+        data.push(1);
+        data.extend_from_slice(&(schedule.len() as u32).to_le_bytes());
+        for (release_time, amount) in schedule {
+            data.extend_from_slice(&release_time.to_le_bytes());
+            data.extend_from_slice(&amount.to_le_bytes());
+        }
+        data.resize((crate::VESTING_ACCOUNT_BASE_SIZE + schedule.len() as u64 * crate::VESTING_SCHEDULE_SIZE) as usize, 0);
+        let lamports = self.context.banks_client.get_rent().await.unwrap().minimum_balance(data.len());
+        let account = Account { lamports, data, owner: crate::ID, executable: false, rent_epoch: 0 };
+        self.context.set_account(&find_vesting_address(seeds).0, &account.into());
+    }
+
+    /// Writes the program config of `admin` as `create_config` creates it, which natively can't check the upgrade
+    /// authority of the program
+    pub async fn set_program_config(&mut self, admin: &Pubkey) {
+        let address = Pubkey::find_program_address(&[crate::CONFIG_SEED.as_bytes()], &crate::ID).0;
+        let config = crate::ProgramConfig { admin: *admin, incident_authority: *admin, build_hash: None };
+        self.set_program_account(&address, &config, crate::PROGRAM_CONFIG_ACCOUNT_SIZE).await;
+    }
+
+    /// Writes the test clock the program reads with the `test-clock` feature, `set_test_clock` creating it through
+    /// a CPI natively unavailable
+    pub async fn set_test_clock(&mut self, unix_timestamp: i64) {
+        let address = crate::test_clock::test_clock_address().0;
+        self.set_program_account(&address, &crate::test_clock::TestClock { unix_timestamp }, crate::test_clock::TEST_CLOCK_ACCOUNT_SIZE).await;
+    }
+
+    /// Backs the contract of `seeds` with `collateral_amount` of `guarantor_token` through `create_guarantee`, escrowed
+    /// in `collateral_token`
+    pub async fn create_guarantee(&mut self, seeds: [u8; 31], granter: &Keypair, guarantor: &Keypair, guarantor_token: &Pubkey,
+                                  collateral_token: &Pubkey, mint: &Pubkey, promised_amount: u64, deadline: i64,
+                                  collateral_amount: u64) -> std::result::Result<(), BanksClientError> {
+        let guarantee = integration::find_guarantee_address(&find_vesting_address(&seeds).0).0;
+        self.prepare_init(&guarantee, crate::GUARANTEE_ACCOUNT_SIZE).await;
+        let instruction = integration::create_guarantee(seeds, *collateral_token, *mint, *guarantor_token, guarantor.pubkey(),
+                                                        granter.pubkey(), self.payer(), promised_amount, deadline, collateral_amount);
+        self.process(&[instruction], &[guarantor, granter]).await
+    }
+
+    /// Registers `realm`, owned by `governance_program`, through `create_registrar` and returns the registrar address
+    pub async fn create_registrar(&mut self, realm: &Pubkey, governance_program: &Pubkey, mint: &Pubkey)
+                                  -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_registrar_address(realm, mint).0;
+        self.prepare_init(&address, crate::governance::REGISTRAR_ACCOUNT_SIZE).await;
+        let instruction = integration::create_registrar(*realm, *governance_program, *mint, self.payer());
+        self.process(&[instruction], &[]).await?;
+        Ok(address)
+    }
+
+    /// Creates the voter weight record of `owner` in `registrar` through `create_voter_weight_record` and returns its
+    /// address
+    pub async fn create_voter_weight_record(&mut self, registrar: &Pubkey, owner: &Pubkey) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_voter_weight_record_address(registrar, owner).0;
+        self.prepare_init(&address, crate::governance::VOTER_WEIGHT_RECORD_ACCOUNT_SIZE).await;
+        let instruction = integration::create_voter_weight_record(*registrar, *owner, self.payer());
+        self.process(&[instruction], &[]).await?;
+        Ok(address)
+    }
+
+    /// Returns the voter weight record at `address`
+    pub async fn voter_weight_record(&mut self, address: &Pubkey) -> crate::governance::VoterWeightRecord {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("voter weight record doesn't exist");
+        crate::governance::VoterWeightRecord::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the max voter weight record of `registrar` through `create_max_voter_weight_record` and returns its
+    /// address
+    pub async fn create_max_voter_weight_record(&mut self, registrar: &Pubkey) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_max_voter_weight_record_address(registrar).0;
+        self.prepare_init(&address, crate::governance::MAX_VOTER_WEIGHT_RECORD_ACCOUNT_SIZE).await;
+        let instruction = integration::create_max_voter_weight_record(*registrar, self.payer());
+        self.process(&[instruction], &[]).await?;
+        Ok(address)
+    }
+
+    /// Returns the max voter weight record at `address`
+    pub async fn max_voter_weight_record(&mut self, address: &Pubkey) -> crate::governance::MaxVoterWeightRecord {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("max voter weight record doesn't exist");
+        crate::governance::MaxVoterWeightRecord::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Mints the receipt of the contract of `seeds` to `beneficiary` through `create_receipt`, signed by the creator,
+    /// and returns the receipt token account of the beneficiary
+    pub async fn create_receipt(&mut self, seeds: [u8; 31], destination_token: &Pubkey, beneficiary: &Pubkey, creator: &Keypair)
+                                -> std::result::Result<Pubkey, BanksClientError> {
+        let receipt_mint = integration::find_receipt_mint_address(&find_vesting_address(&seeds).0).0;
+        let receipt_token = get_associated_token_address(beneficiary, &receipt_mint);
+        self.prepare_init(&receipt_mint, spl_token::state::Mint::LEN).await;
+        self.prepare_init(&receipt_token, spl_token::state::Account::LEN).await;
+        let instruction = integration::create_receipt(seeds, *destination_token, *beneficiary, creator.pubkey(), self.payer());
+        self.process(&[instruction], &[creator]).await?;
+        Ok(receipt_token)
+    }
+
+    /// Offers the contract of `seeds` for `price` through `list_position`, signed by the owner of its destination
+    pub async fn list_position(&mut self, seeds: [u8; 31], destination_token: &Pubkey, seller: &Keypair, seller_payment_token: &Pubkey,
+                               price: u64) -> std::result::Result<(), BanksClientError> {
+        self.prepare_init(&integration::find_listing_address(&find_vesting_address(&seeds).0).0, crate::LISTING_ACCOUNT_SIZE).await;
+        let instruction = integration::list_position(seeds, *destination_token, *seller_payment_token, seller.pubkey(), self.payer(), price);
+        self.process(&[instruction], &[seller]).await
+    }
+
+    /// Unlocks the contract of `seeds` through `unlock_with_receipt` and returns the address of the claim receipt
+    pub async fn unlock_with_receipt(&mut self, seeds: [u8; 31], vesting_token: &Pubkey, mint: &Pubkey, destination_token: &Pubkey)
+                                     -> std::result::Result<Pubkey, BanksClientError> {
+        let claim_count = self.vesting(&seeds).await.claim_count;
+        let address = integration::find_claim_receipt_address(&find_vesting_address(&seeds).0, claim_count).0;
+        self.prepare_init(&address, crate::CLAIM_RECEIPT_ACCOUNT_SIZE).await;
+        let instruction = integration::unlock_with_receipt(seeds, *vesting_token, *mint, *destination_token, claim_count, self.payer());
+        self.process(&[instruction], &[]).await?;
+        Ok(address)
+    }
+
+    /// Returns the claim receipt at `address`
+    pub async fn claim_receipt(&mut self, address: &Pubkey) -> crate::ClaimReceipt {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("claim receipt doesn't exist");
+        crate::ClaimReceipt::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Replaces the claim hook allowlist with `programs` through `set_claim_hook_allowlist`, signed by the admin of
+    /// the program config
+    pub async fn allowlist_claim_hooks(&mut self, admin: &Keypair, programs: Vec<Pubkey>) -> std::result::Result<(), BanksClientError> {
+        self.prepare_init(&integration::find_claim_hook_allowlist_address().0, crate::CLAIM_HOOK_ALLOWLIST_ACCOUNT_SIZE).await;
+        let instruction = integration::set_claim_hook_allowlist(admin.pubkey(), programs);
+        self.process(&[instruction], &[admin]).await
+    }
+
+    /// Creates the merkle distributor of `seeds` through `create_distributor`, escrowing `total_amount` from
+    /// `source_token` in `distributor_token`
+    pub async fn create_distributor(&mut self, seeds: [u8; 31], source_token: &Pubkey, source_authority: &Keypair, mint: &Pubkey,
+                                    distributor_token: &Pubkey, root: [u8; 32], total_amount: u64)
+                                    -> std::result::Result<(), BanksClientError> {
+        self.prepare_init(&integration::find_distributor_address(&seeds).0, crate::MERKLE_DISTRIBUTOR_ACCOUNT_SIZE).await;
+        let instruction = integration::create_distributor(seeds, root, total_amount, *distributor_token, *mint, *source_token,
+                                                          source_authority.pubkey(), self.payer());
+        self.process(&[instruction], &[source_authority]).await
+    }
+
+    /// Prepares the claim status of `claimant` in `distributor`, which its first claim creates
+    pub async fn prepare_claim_status(&mut self, distributor: &Pubkey, claimant: &Pubkey) {
+        let address = integration::find_claim_status_address(distributor, claimant).0;
+        self.prepare_init(&address, crate::CLAIM_STATUS_ACCOUNT_SIZE).await;
+    }
+
+    /// Creates the shared vault of `authority` for `mint` through `create_shared_vault`, escrowing in `vault_token`
+    pub async fn create_shared_vault(&mut self, authority: &Keypair, mint: &Pubkey, vault_token: &Pubkey)
+                                     -> std::result::Result<(), BanksClientError> {
+        let address = integration::find_shared_vault_address(&authority.pubkey(), mint).0;
+        self.prepare_init(&address, crate::SHARED_VAULT_ACCOUNT_SIZE).await;
+        let instruction = integration::create_shared_vault(*vault_token, *mint, authority.pubkey(), self.payer());
+        self.process(&[instruction], &[authority]).await
+    }
+
+    /// Creates the schedule template `name` of `authority` through `create_schedule_template` and returns its address
+    pub async fn create_schedule_template(&mut self, authority: &Keypair, name: &str, tranches: Vec<crate::TemplateTranche>)
+                                          -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_schedule_template_address(&authority.pubkey(), name).0;
+        self.prepare_init(&address, crate::calc_schedule_template_size(tranches.len())).await;
+        let instruction = integration::create_schedule_template(authority.pubkey(), name, tranches, self.payer());
+        self.process(&[instruction], &[authority]).await?;
+        Ok(address)
+    }
+
+    /// Creates the cohort `name` of `admin` for `mint`, without schedule template, through `create_cohort` and returns
+    /// its address
+    pub async fn create_cohort(&mut self, admin: &Keypair, name: &str, mint: &Pubkey) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_cohort_address(&admin.pubkey(), name).0;
+        self.prepare_init(&address, crate::COHORT_ACCOUNT_SIZE).await;
+        let instruction = integration::create_cohort(admin.pubkey(), name, *mint, None, self.payer());
+        self.process(&[instruction], &[admin]).await?;
+        Ok(address)
+    }
+
+    /// Returns the cohort at `address`
+    pub async fn cohort(&mut self, address: &Pubkey) -> crate::Cohort {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("cohort doesn't exist");
+        crate::Cohort::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the round `name` of `creator` for `mint` through `create_round` and returns its address
+    pub async fn create_round(&mut self, creator: &Keypair, name: &str, mint: &Pubkey) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_round_address(&creator.pubkey(), name).0;
+        self.prepare_init(&address, crate::ROUND_ACCOUNT_SIZE).await;
+        let instruction = integration::create_round(creator.pubkey(), name, *mint, self.payer());
+        self.process(&[instruction], &[creator]).await?;
+        Ok(address)
+    }
+
+    /// Returns the round at `address`
+    pub async fn round(&mut self, address: &Pubkey) -> crate::Round {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("round doesn't exist");
+        crate::Round::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the budget `name` of `org`, escrowing in `budget_token`, through `create_budget` and returns its address
+    pub async fn create_budget(&mut self, org: &Keypair, name: &str, mint: &Pubkey, budget_token: &Pubkey,
+                               allocation_limit: u64, admins: Vec<Pubkey>) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_budget_address(&org.pubkey(), name).0;
+        self.prepare_init(&address, crate::BUDGET_ACCOUNT_SIZE).await;
+        let instruction = integration::create_budget(org.pubkey(), name, *budget_token, *mint, allocation_limit, admins, self.payer());
+        self.process(&[instruction], &[org]).await?;
+        Ok(address)
+    }
+
+    /// Creates the rollup of `org` for `beneficiary` in `mint` through `create_rollup` and returns its address
+    pub async fn create_rollup(&mut self, org: &Keypair, beneficiary: &Pubkey, mint: &Pubkey)
+                               -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_rollup_address(&org.pubkey(), beneficiary).0;
+        self.prepare_init(&address, crate::ROLLUP_ACCOUNT_SIZE).await;
+        let instruction = integration::create_rollup(org.pubkey(), *beneficiary, *mint, self.payer());
+        self.process(&[instruction], &[org]).await?;
+        Ok(address)
+    }
+
+    /// Returns the rollup at `address`
+    pub async fn rollup(&mut self, address: &Pubkey) -> crate::Rollup {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("rollup doesn't exist");
+        crate::Rollup::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the unlock queue of `mint` through `create_unlock_queue` and returns its address
+    pub async fn create_unlock_queue(&mut self, mint: &Pubkey) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_unlock_queue_address(mint).0;
+        self.prepare_init(&address, crate::UNLOCK_QUEUE_ACCOUNT_SIZE).await;
+        let instruction = integration::create_unlock_queue(*mint, self.payer());
+        self.process(&[instruction], &[]).await?;
+        Ok(address)
+    }
+
+    /// Returns the unlock queue at `address`
+    pub async fn unlock_queue(&mut self, address: &Pubkey) -> crate::UnlockQueue {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("unlock queue doesn't exist");
+        crate::UnlockQueue::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the partner config of the authority of `config` with its terms, through `create_partner_config` followed
+    /// by `set_partner_config` for the defaults the builder leaves out
+    pub async fn create_partner_config(&mut self, authority: &Keypair, config: &crate::PartnerConfig)
+                                       -> std::result::Result<(), BanksClientError> {
+        let address = integration::find_partner_config_address(&authority.pubkey()).0;
+        self.prepare_init(&address, crate::PARTNER_CONFIG_ACCOUNT_SIZE).await;
+        let instructions = [
+            integration::create_partner_config(authority.pubkey(), config.fee_recipient, config.fee_bps, config.branding, self.payer()),
+            integration::set_partner_config(authority.pubkey(), config),
+        ];
+        self.process(&instructions, &[authority]).await
+    }
+
+    /// Prepares the stats of `partner_config` in `mint`, which the first `create_with_partner` creates, and returns
+    /// their address
+    pub async fn prepare_partner_stats(&mut self, partner_config: &Pubkey, mint: &Pubkey) -> Pubkey {
+        let address = integration::find_partner_stats_address(partner_config, mint).0;
+        self.prepare_init(&address, crate::PARTNER_STATS_ACCOUNT_SIZE).await;
+        address
+    }
+
+    /// Returns the partner stats at `address`
+    pub async fn partner_stats(&mut self, address: &Pubkey) -> crate::PartnerStats {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("partner stats don't exist");
+        crate::PartnerStats::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the rent sponsorship pool of `creator` through `create_rent_sponsorship_pool`, depositing `lamports` the
+    /// payer first sends the creator, and returns its address
+    pub async fn create_rent_sponsorship_pool(&mut self, creator: &Keypair, lamports: u64) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_rent_sponsorship_pool_address(&creator.pubkey()).0;
+        self.prepare_init(&address, crate::RENT_SPONSORSHIP_POOL_ACCOUNT_SIZE).await;
+        let instructions = [
+            system_instruction::transfer(&self.payer(), &creator.pubkey(), lamports),
+            integration::create_rent_sponsorship_pool(creator.pubkey(), lamports),
+        ];
+        self.process(&instructions, &[creator]).await?;
+        Ok(address)
+    }
+
+    /// Returns the rent sponsorship pool at `address`
+    pub async fn rent_sponsorship_pool(&mut self, address: &Pubkey) -> crate::RentSponsorshipPool {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("pool doesn't exist");
+        crate::RentSponsorshipPool::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Returns the lamports of `address`, 0 when it doesn't exist
+    pub async fn lamports(&mut self, address: &Pubkey) -> u64 {
+        self.context.banks_client.get_balance(*address).await.unwrap()
+    }
+
+    /// Creates the grant index of `creator` for `beneficiary` through `create_grant_index` and returns its address
+    pub async fn create_grant_index(&mut self, creator: &Keypair, beneficiary: &Pubkey) -> std::result::Result<Pubkey, BanksClientError> {
+        let address = integration::find_grant_index_address(&creator.pubkey(), beneficiary).0;
+        self.prepare_init(&address, crate::GRANT_INDEX_ACCOUNT_SIZE).await;
+        let instruction = integration::create_grant_index(creator.pubkey(), *beneficiary, self.payer());
+        self.process(&[instruction], &[creator]).await?;
+        Ok(address)
+    }
+
+    /// Returns the grant index at `address`
+    pub async fn grant_index(&mut self, address: &Pubkey) -> crate::GrantIndex {
+        let account = self.context.banks_client.get_account(*address).await.unwrap().expect("grant index doesn't exist");
+        crate::GrantIndex::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    /// Creates the vested airdrop of `seeds` along `schedule_template` through `create_airdrop`, escrowing
+    /// `total_amount` from `source_token` in `airdrop_token`
+    pub async fn create_airdrop(&mut self, seeds: [u8; 31], source_token: &Pubkey, source_authority: &Keypair, mint: &Pubkey,
+                                airdrop_token: &Pubkey, schedule_template: &Pubkey, root: [u8; 32], start: u64, total_amount: u64)
+                                -> std::result::Result<(), BanksClientError> {
+        self.prepare_init(&integration::find_airdrop_address(&seeds).0, crate::VESTED_AIRDROP_ACCOUNT_SIZE).await;
+        let instruction = integration::create_airdrop(seeds, *airdrop_token, *schedule_template, *mint, *source_token,
+                                                      source_authority.pubkey(), self.payer(), root, start, total_amount);
+        self.process(&[instruction], &[source_authority]).await
+    }
+
+    /// Writes a trading Pyth v2 price account quoting `price * 10^expo`, published at `publish_time`, and returns its
+    /// address. Only the fields `pyth::load_price` reads are set
+    pub async fn set_pyth_price(&mut self, price: i64, expo: i32, publish_time: i64) -> Pubkey {
+        let mut data = vec![0; 240];
+        data[0..4].copy_from_slice(&0xa1b2c3d4u32.to_le_bytes());
+        data[4..8].copy_from_slice(&2u32.to_le_bytes());
+        data[8..12].copy_from_slice(&3u32.to_le_bytes());
+        data[20..24].copy_from_slice(&expo.to_le_bytes());
+        data[96..104].copy_from_slice(&publish_time.to_le_bytes());
+        data[208..216].copy_from_slice(&price.to_le_bytes());
+        data[224..228].copy_from_slice(&1u32.to_le_bytes());
+
+        let address = Pubkey::new_unique();
+        self.set_foreign_account(&address, &Pubkey::new_unique(), data).await;
+        address
+    }
+
+    /// Writes a rent exempt account holding `data` owned by `owner`, e.g. the state of a program a test stands in for
+    pub async fn set_foreign_account(&mut self, address: &Pubkey, owner: &Pubkey, data: Vec<u8>) {
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.context.set_account(address, &Account {
+            lamports: rent.minimum_balance(data.len()),
+            data,
+            owner: *owner,
+            executable: false,
+            rent_epoch: 0,
+        }.into());
+    }
+
+    /// Opens the funding escrow of `seeds` through `open_funding_escrow`, depositing `amount` from `funder_token` in
+    /// `escrow_token` for `issuer`
+    pub async fn open_funding_escrow(&mut self, seeds: [u8; 31], funder_token: &Pubkey, funder: &Keypair, mint: &Pubkey,
+                                     escrow_token: &Pubkey, issuer: &Pubkey, amount: u64) -> std::result::Result<(), BanksClientError> {
+        self.prepare_init(&integration::find_funding_escrow_address(&seeds).0, crate::FUNDING_ESCROW_ACCOUNT_SIZE).await;
+        let instruction = integration::open_funding_escrow(seeds, *escrow_token, *mint, *funder_token, funder.pubkey(), *issuer, amount);
+        self.process(&[instruction], &[funder]).await
+    }
+
+    /// Returns the claim status of `claimant` in `distributor`
+    pub async fn claim_status(&mut self, distributor: &Pubkey, claimant: &Pubkey) -> crate::ClaimStatus {
+        let address = integration::find_claim_status_address(distributor, claimant).0;
+        let account = self.context.banks_client.get_account(address).await.unwrap().expect("claim status doesn't exist");
+        crate::ClaimStatus::try_deserialize(&mut account.data.as_slice()).unwrap()
+    }
+
+    async fn set_program_account<T: AccountSerialize>(&mut self, address: &Pubkey, account: &T, size: usize) {
+        let mut data = vec![0; size];
+        account.try_serialize(&mut data.as_mut_slice()).unwrap();
+
+        let rent = self.context.banks_client.get_rent().await.unwrap();
+        self.context.set_account(address, &Account {
+            lamports: rent.minimum_balance(size),
+            data,
+            owner: crate::ID,
+            executable: false,
+            rent_epoch: 0,
+        }.into());
+    }
+
+    /// Allocates, escrows and funds an irrevocable contract paid from `source_token`, owned by `source_authority`.
+    /// Returns the escrow, the associated token account of the vesting account
+    pub async fn create_grant(
+        &mut self,
+        seeds: [u8; 31],
+        source_token: &Pubkey,
+        source_authority: &Keypair,
+        mint: &Pubkey,
+        destination_token: &Pubkey,
+        schedules: Vec<Schedule>,
+    ) -> std::result::Result<Pubkey, BanksClientError> {
+        self.allocate_vesting(&seeds, schedules.len() as u32).await;
+        let vesting_token = self.create_associated_token_account(&find_vesting_address(&seeds).0, mint).await;
+        let instruction = integration::create(seeds, vesting_token, *source_token, source_authority.pubkey(), *mint,
+                                              *destination_token, schedules);
+        self.process(&[instruction], &[source_authority]).await?;
+        Ok(vesting_token)
+    }
+
+    /// Like `create_grant`, for a contract `revoker` can revoke
+    pub async fn create_revocable_grant(
+        &mut self,
+        seeds: [u8; 31],
+        source_token: &Pubkey,
+        source_authority: &Keypair,
+        mint: &Pubkey,
+        destination_token: &Pubkey,
+        schedules: Vec<Schedule>,
+        revoker: &Pubkey,
+    ) -> std::result::Result<Pubkey, BanksClientError> {
+        self.allocate_vesting(&seeds, schedules.len() as u32).await;
+        let vesting_token = self.create_associated_token_account(&find_vesting_address(&seeds).0, mint).await;
+        let mut instruction = integration::create(seeds, vesting_token, *source_token, source_authority.pubkey(), *mint,
+                                                  *destination_token, vec![]);
+        instruction.data = crate::instruction::Create {
+            seeds,
+            mint_address: *mint,
+            destination_token_address: *destination_token,
+            schedules,
+            min_claim_amount: 0,
+            revoker: Some(*revoker),
+            soulbound_receipt: false,
+            unlock_policy: crate::UnlockPolicy::Anyone,
+        }.data();
+        self.process(&[instruction], &[source_authority]).await?;
+        Ok(vesting_token)
+    }
+
+    /// Returns the clock of the bank
+    pub async fn clock(&mut self) -> Clock {
+        self.context.banks_client.get_sysvar::<Clock>().await.unwrap()
+    }
+
+    /// Returns the unix timestamp the program compares release times with
+    pub async fn now(&mut self) -> i64 {
+        self.clock().await.unix_timestamp
+    }
+
+    /// Moves the bank to the next slot and sets the clock to `timestamp`. Moving back in time is allowed, the
+    /// program only reads the clock
+    pub async fn warp_to_timestamp(&mut self, timestamp: i64) {
+        let slot = self.clock().await.slot;
+        self.context.warp_to_slot(slot + 1).unwrap();
+        let mut clock = self.clock().await;
+        clock.unix_timestamp = timestamp;
+        self.context.set_sysvar(&clock);
+    }
+
+    /// Moves the clock `seconds` forward
+    pub async fn warp_forward(&mut self, seconds: i64) {
+        let now = self.now().await;
+        self.warp_to_timestamp(now + seconds).await;
+    }
+}
+
+/// Returns the vesting account state `init` leaves before `create`, every field zeroed
+pub fn empty_vesting() -> Vesting {
+    let mut data = vec![0; crate::calc_vesting_account_size(0)];
+    data[..8].copy_from_slice(&Vesting::discriminator());
+    Vesting::try_deserialize(&mut data.as_slice()).unwrap()
+}
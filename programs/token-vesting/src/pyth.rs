@@ -0,0 +1,87 @@
+//! Minimal reader for Pyth v2 price accounts.
+//!
+//! Only the header and the aggregate price are decoded, at the offsets of the `PriceAccount`
+//! layout published by Pyth.
+
+use anchor_lang::prelude::*;
+use std::convert::{TryFrom, TryInto};
+
+const MAGIC: u32 = 0xa1b2c3d4;
+const VERSION_2: u32 = 2;
+const ACCOUNT_TYPE_PRICE: u32 = 3;
+const STATUS_TRADING: u32 = 1;
+
+const EXPO_OFFSET: usize = 20;
+const TIMESTAMP_OFFSET: usize = 96;
+const AGG_PRICE_OFFSET: usize = 208;
+const AGG_CONF_OFFSET: usize = 216;
+const AGG_STATUS_OFFSET: usize = 224;
+const PRICE_ACCOUNT_MIN_LEN: usize = 240;
+
+/// The aggregate price of a Pyth price account, `price * 10^expo`
+pub struct Price {
+    pub price: i64,
+    pub conf: u64,
+    pub expo: i32,
+    pub publish_time: i64,
+}
+
+/// Reads the current aggregate price, failing unless the feed is trading
+pub fn load_price(price_account: &AccountInfo) -> Result<Price> {
+    let data = price_account.try_borrow_data()?;
+    require!(data.len() >= PRICE_ACCOUNT_MIN_LEN, crate::VestingError::InvalidPriceFeed);
+    require!(read_u32(&data, 0) == MAGIC, crate::VestingError::InvalidPriceFeed);
+    require!(read_u32(&data, 4) == VERSION_2, crate::VestingError::InvalidPriceFeed);
+    require!(read_u32(&data, 8) == ACCOUNT_TYPE_PRICE, crate::VestingError::InvalidPriceFeed);
+    require!(read_u32(&data, AGG_STATUS_OFFSET) == STATUS_TRADING, crate::VestingError::PriceUnavailable);
+
+    Ok(Price {
+        price: read_u64(&data, AGG_PRICE_OFFSET) as i64,
+        conf: read_u64(&data, AGG_CONF_OFFSET),
+        expo: read_u32(&data, EXPO_OFFSET) as i32,
+        publish_time: read_u64(&data, TIMESTAMP_OFFSET) as i64,
+    })
+}
+
+/// Compares `price * 10^expo` with `other_price * 10^other_expo`
+pub fn cmp_prices(price: i64, expo: i32, other_price: i64, other_expo: i32) -> Option<std::cmp::Ordering> {
+    let (price, other_price) = if expo >= other_expo {
+        ((price as i128).checked_mul(10i128.checked_pow((expo - other_expo) as u32)?)?, other_price as i128)
+    } else {
+        (price as i128, (other_price as i128).checked_mul(10i128.checked_pow((other_expo - expo) as u32)?)?)
+    };
+    Some(price.cmp(&other_price))
+}
+
+/// Converts `price * 10^expo` into units of `10^target_expo`, truncating any extra precision
+pub fn scale_price(price: i64, expo: i32, target_expo: i32) -> Option<i128> {
+    if expo >= target_expo {
+        (price as i128).checked_mul(10i128.checked_pow((expo - target_expo) as u32)?)
+    } else {
+        (price as i128).checked_div(10i128.checked_pow((target_expo - expo) as u32)?)
+    }
+}
+
+/// Converts `usd_amount`, in units of `10^-usd_decimals` dollars, into base units of a token with `token_decimals`
+/// priced `price * 10^expo` dollars, rounding down. None unless the price is positive
+pub fn token_amount(usd_amount: u64, usd_decimals: u8, price: i64, expo: i32, token_decimals: u8) -> Option<u64> {
+    if price <= 0 {
+        return None;
+    }
+    // usd_amount * 10^(token_decimals - usd_decimals - expo) / price
+    let exponent = token_decimals as i32 - usd_decimals as i32 - expo;
+    let (numerator, denominator) = if exponent >= 0 {
+        ((usd_amount as i128).checked_mul(10i128.checked_pow(exponent as u32)?)?, price as i128)
+    } else {
+        (usd_amount as i128, (price as i128).checked_mul(10i128.checked_pow((-exponent) as u32)?)?)
+    };
+    u64::try_from(numerator / denominator).ok()
+}
+
+fn read_u32(data: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+}
+
+fn read_u64(data: &[u8], offset: usize) -> u64 {
+    u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+}
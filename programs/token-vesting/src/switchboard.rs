@@ -0,0 +1,44 @@
+//! Minimal reader for Switchboard Functions accounts.
+//!
+//! The `switchboard-solana` crate targets a newer Anchor, so the enclave quote is read at the offsets
+//! of the packed `FunctionAccountData` layout of the Switchboard attestation program.
+
+use anchor_lang::prelude::*;
+use std::convert::TryInto;
+
+pub mod attestation_program {
+    anchor_lang::declare_id!("sbattyXrzedoNATfc4L31wC9Mhxsi1BmFhTiN8gDshx");
+}
+
+const FUNCTION_DISCRIMINATOR: [u8; 8] = [76, 139, 47, 44, 240, 182, 148, 200];
+
+const ENCLAVE_SIGNER_OFFSET: usize = 392;
+const MR_ENCLAVE_OFFSET: usize = 424;
+const VERIFICATION_STATUS_OFFSET: usize = 456;
+const VALID_UNTIL_OFFSET: usize = 465;
+
+const VERIFICATION_SUCCESS: u8 = 1 << 2;
+const VERIFICATION_OVERRIDE: u8 = 1 << 3;
+
+/// Checks that `enclave_signer` is the signer of the currently verified enclave of the function,
+/// i.e. that the instruction was submitted by a valid run of the function
+pub fn validate_enclave_signer(function: &AccountInfo, enclave_signer: &AccountInfo, timestamp: i64) -> Result<bool> {
+    require!(*function.owner == attestation_program::ID, crate::VestingError::InvalidSwitchboardFunction);
+    let data = function.try_borrow_data()?;
+    require!(data.len() >= VALID_UNTIL_OFFSET + 8, crate::VestingError::InvalidSwitchboardFunction);
+    require!(data[..8] == FUNCTION_DISCRIMINATOR, crate::VestingError::InvalidSwitchboardFunction);
+
+    if data[MR_ENCLAVE_OFFSET..MR_ENCLAVE_OFFSET + 32] == [0u8; 32] {
+        return Ok(false);
+    }
+    if !enclave_signer.is_signer || data[ENCLAVE_SIGNER_OFFSET..ENCLAVE_SIGNER_OFFSET + 32] != enclave_signer.key.to_bytes() {
+        return Ok(false);
+    }
+
+    let valid_until = i64::from_le_bytes(data[VALID_UNTIL_OFFSET..VALID_UNTIL_OFFSET + 8].try_into().unwrap());
+    Ok(match data[VERIFICATION_STATUS_OFFSET] {
+        VERIFICATION_OVERRIDE => true,
+        VERIFICATION_SUCCESS => valid_until > timestamp,
+        _ => false,
+    })
+}
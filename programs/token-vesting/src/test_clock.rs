@@ -0,0 +1,130 @@
+//! Mock clock for deterministic localnet and CI tests, enabled by the `test-clock` feature.
+//!
+//! Time dependent instructions read the time through `unix_timestamp`. With the feature it is the time of the
+//! program owned `["test-clock"]` account, set by `set_test_clock`, when that account is passed as the last remaining
+//! account, after the event accounts. Otherwise, and always without the feature, it is the time of the Clock sysvar.
+//! Slots still come from the Clock sysvar.
+//!
+//! `set_test_clock` only exists with the feature. It isn't an instruction of the program module, so it doesn't show
+//! in the IDL: the fallback of the program handles it, signed by the admin of the program config. Without the feature
+//! it fails with `TestClockDisabled`.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::clock::UnixTimestamp;
+use anchor_lang::solana_program::hash::hash;
+
+pub const TEST_CLOCK_SEED: &[u8] = b"test-clock";
+
+pub const TEST_CLOCK_ACCOUNT_SIZE: usize =
+    8 // discriminator
+    + std::mem::size_of::<i64>(); // unix_timestamp
+
+#[account]
+pub struct TestClock {
+    pub unix_timestamp: i64,
+}
+
+/// Returns the test clock PDA and its bump
+pub fn test_clock_address() -> (Pubkey, u8) {
+    Pubkey::find_program_address(&[TEST_CLOCK_SEED], &crate::ID)
+}
+
+/// Returns the instruction discriminator of `set_test_clock`, the one Anchor would give it
+pub fn set_test_clock_discriminator() -> [u8; 8] {
+    let mut discriminator = [0; 8];
+    discriminator.copy_from_slice(&hash(b"global:set_test_clock").to_bytes()[..8]);
+    discriminator
+}
+
+/// Returns the current time: the time of the test clock when it is passed last with the `test-clock` feature,
+/// otherwise the time of the Clock sysvar
+#[cfg(feature = "test-clock")]
+pub fn unix_timestamp(remaining_accounts: &[AccountInfo]) -> Result<UnixTimestamp> {
+    match remaining_accounts.last() {
+        Some(account) if is_test_clock(account) => {
+            require!(*account.owner == crate::ID, ErrorCode::AccountOwnedByWrongProgram);
+            Ok(TestClock::try_deserialize(&mut &account.data.borrow()[..])?.unix_timestamp)
+        }
+        _ => Ok(Clock::get()?.unix_timestamp),
+    }
+}
+
+/// Returns the current time, the time of the Clock sysvar without the `test-clock` feature
+#[cfg(not(feature = "test-clock"))]
+pub fn unix_timestamp(_remaining_accounts: &[AccountInfo]) -> Result<UnixTimestamp> {
+    Ok(Clock::get()?.unix_timestamp)
+}
+
+/// Returns the remaining accounts of an instruction without the trailing test clock
+#[cfg(feature = "test-clock")]
+pub fn instruction_accounts<'a, 'info>(accounts: &'a [AccountInfo<'info>]) -> &'a [AccountInfo<'info>] {
+    match accounts {
+        [rest @ .., clock] if is_test_clock(clock) => rest,
+        _ => accounts,
+    }
+}
+
+/// Returns the remaining accounts of an instruction, which never end with a test clock without the `test-clock` feature
+#[cfg(not(feature = "test-clock"))]
+pub fn instruction_accounts<'a, 'info>(accounts: &'a [AccountInfo<'info>]) -> &'a [AccountInfo<'info>] {
+    accounts
+}
+
+#[cfg(feature = "test-clock")]
+fn is_test_clock(account: &AccountInfo) -> bool {
+    account.key() == test_clock_address().0
+}
+
+/// Handles `set_test_clock`, none when `data` isn't one
+#[cfg(feature = "test-clock")]
+pub fn handle_instruction(program_id: &Pubkey, accounts: &[AccountInfo], data: &[u8]) -> Option<Result<()>> {
+    let args = data.strip_prefix(&set_test_clock_discriminator()[..])?;
+    Some(set_test_clock(program_id, accounts, args))
+}
+
+/// Fails `set_test_clock` with `TestClockDisabled`, none when `data` isn't one
+#[cfg(not(feature = "test-clock"))]
+pub fn handle_instruction(_program_id: &Pubkey, _accounts: &[AccountInfo], data: &[u8]) -> Option<Result<()>> {
+    data.starts_with(&set_test_clock_discriminator()).then(|| err!(crate::VestingError::TestClockDisabled))
+}
+
+/// Sets the time of the test clock, creating it at the expense of the payer on its first use. Only the admin of the
+/// program config can set it.
+///
+/// Accounts: the test clock (writable), the program config, its admin (signer), the payer (writable, signer) and the
+/// system program. The instruction data is the discriminator followed by the unix timestamp, an i64 LE
+#[cfg(feature = "test-clock")]
+fn set_test_clock(program_id: &Pubkey, accounts: &[AccountInfo], args: &[u8]) -> Result<()> {
+    use std::convert::TryInto;
+
+    let (test_clock, config, admin, payer, system_program) = match accounts {
+        [test_clock, config, admin, payer, system_program, ..] => (test_clock, config, admin, payer, system_program),
+        _ => return err!(ErrorCode::AccountNotEnoughKeys),
+    };
+    let unix_timestamp = i64::from_le_bytes(args.try_into().map_err(|_| ErrorCode::InstructionDidNotDeserialize)?);
+
+    let config_address = Pubkey::find_program_address(&[crate::CONFIG_SEED.as_bytes()], program_id).0;
+    require!(config.key() == config_address, ErrorCode::ConstraintSeeds);
+    let config = Account::<crate::ProgramConfig>::try_from(config)?;
+    require!(admin.is_signer && admin.key() == config.admin, crate::VestingError::InvalidConfigAdmin);
+
+    let (address, bump) = test_clock_address();
+    require!(test_clock.key() == address, ErrorCode::ConstraintSeeds);
+    if test_clock.data_is_empty() {
+        let ix = anchor_lang::solana_program::system_instruction::create_account(
+            payer.key,
+            test_clock.key,
+            Rent::get()?.minimum_balance(TEST_CLOCK_ACCOUNT_SIZE),
+            TEST_CLOCK_ACCOUNT_SIZE as u64,
+            program_id,
+        );
+        anchor_lang::solana_program::program::invoke_signed(
+            &ix,
+            &[payer.clone(), test_clock.clone(), system_program.clone()],
+            &[&[TEST_CLOCK_SEED, &[bump]]],
+        )?;
+    }
+    require!(test_clock.owner == program_id, ErrorCode::AccountOwnedByWrongProgram);
+    let mut data = test_clock.try_borrow_mut_data()?;
+    TestClock { unix_timestamp }.try_serialize(&mut &mut data[..])
+}
@@ -0,0 +1,49 @@
+//! CPI wrappers of token program instructions `anchor-spl` 0.22 doesn't provide, following its API.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::solana_program::program::invoke_signed;
+
+#[derive(Clone)]
+pub struct TransferChecked<'info> {
+    pub from: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub to: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+}
+
+impl ToAccountMetas for TransferChecked<'_> {
+    fn to_account_metas(&self, is_signer: Option<bool>) -> Vec<AccountMeta> {
+        vec![
+            AccountMeta::new(self.from.key(), false),
+            AccountMeta::new_readonly(self.mint.key(), false),
+            AccountMeta::new(self.to.key(), false),
+            AccountMeta::new_readonly(self.authority.key(), is_signer.unwrap_or(true)),
+        ]
+    }
+}
+
+impl<'info> ToAccountInfos<'info> for TransferChecked<'info> {
+    fn to_account_infos(&self) -> Vec<AccountInfo<'info>> {
+        vec![self.from.clone(), self.mint.clone(), self.to.clone(), self.authority.clone()]
+    }
+}
+
+/// Transfers `amount` after the token program checked the mint and its `decimals`
+pub fn transfer_checked<'info>(ctx: CpiContext<'_, '_, '_, 'info, TransferChecked<'info>>, amount: u64, decimals: u8) -> Result<()> {
+    let ix = spl_token::instruction::transfer_checked(
+        ctx.program.key,
+        ctx.accounts.from.key,
+        ctx.accounts.mint.key,
+        ctx.accounts.to.key,
+        ctx.accounts.authority.key,
+        &[],
+        amount,
+        decimals,
+    )?;
+    invoke_signed(
+        &ix,
+        &[ctx.accounts.from, ctx.accounts.mint, ctx.accounts.to, ctx.accounts.authority, ctx.program],
+        ctx.signer_seeds,
+    ).map_err(Into::into)
+}
@@ -0,0 +1,119 @@
+//! CPI helpers for non-transferable Token-2022 receipts.
+//!
+//! `anchor-spl` only wraps the original token program, so the few Token-2022 and associated token
+//! account instructions needed are packed by hand following the `spl-token-2022` instruction layout.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+use anchor_lang::solana_program::system_instruction;
+
+anchor_lang::declare_id!("TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb");
+
+// A mint with extensions is padded to the token account length, followed by the account type
+// and the NonTransferable extension (type and length, no data)
+pub const NON_TRANSFERABLE_MINT_SIZE: usize = 165 + 1 + 2 + 2;
+
+const SET_AUTHORITY: u8 = 6;
+const MINT_TO: u8 = 7;
+const INITIALIZE_MINT_2: u8 = 20;
+const INITIALIZE_NON_TRANSFERABLE_MINT: u8 = 32;
+const AUTHORITY_TYPE_MINT_TOKENS: u8 = 0;
+
+pub struct SoulboundReceiptAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    /// The owner's associated token account of the mint, created by the helper
+    pub receipt_token: AccountInfo<'info>,
+    pub owner: AccountInfo<'info>,
+    /// Freeze authority of the receipt mint, signing the mint of the single receipt token
+    pub authority: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+}
+
+/// Returns the associated token account of `owner` for a Token-2022 `mint`
+pub fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(
+        &[owner.as_ref(), ID.as_ref(), mint.as_ref()],
+        &anchor_spl::associated_token::ID,
+    ).0
+}
+
+/// Creates a non-transferable mint of decimals 0 at the `mint` PDA and mints its only token to the owner.
+/// The mint authority is removed afterwards while `authority` stays the freeze authority of the mint.
+pub fn mint_soulbound_receipt(
+    accounts: &SoulboundReceiptAccounts,
+    mint_signer_seeds: &[&[&[u8]]],
+    authority_signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    let lamports = Rent::get()?.minimum_balance(NON_TRANSFERABLE_MINT_SIZE);
+    invoke_signed(
+        &system_instruction::create_account(
+            accounts.payer.key, accounts.mint.key, lamports, NON_TRANSFERABLE_MINT_SIZE as u64, &ID),
+        &[accounts.payer.clone(), accounts.mint.clone(), accounts.system_program.clone()],
+        mint_signer_seeds,
+    )?;
+
+    invoke_token(accounts, vec![INITIALIZE_NON_TRANSFERABLE_MINT], vec![AccountMeta::new(accounts.mint.key(), false)], &[])?;
+
+    let mut data = vec![INITIALIZE_MINT_2, 0];
+    data.extend_from_slice(accounts.authority.key.as_ref());
+    data.push(1);
+    data.extend_from_slice(accounts.authority.key.as_ref());
+    invoke_token(accounts, data, vec![AccountMeta::new(accounts.mint.key(), false)], &[])?;
+
+    let ix = Instruction {
+        program_id: accounts.associated_token_program.key(),
+        accounts: vec![
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new(accounts.receipt_token.key(), false),
+            AccountMeta::new_readonly(accounts.owner.key(), false),
+            AccountMeta::new_readonly(accounts.mint.key(), false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        data: vec![],
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.payer.clone(),
+            accounts.receipt_token.clone(),
+            accounts.owner.clone(),
+            accounts.mint.clone(),
+            accounts.system_program.clone(),
+            accounts.token_program.clone(),
+            accounts.associated_token_program.clone(),
+        ],
+        &[],
+    )?;
+
+    let mut data = vec![MINT_TO];
+    data.extend_from_slice(&1u64.to_le_bytes());
+    invoke_token(accounts, data, vec![
+        AccountMeta::new(accounts.mint.key(), false),
+        AccountMeta::new(accounts.receipt_token.key(), false),
+        AccountMeta::new_readonly(accounts.authority.key(), true),
+    ], authority_signer_seeds)?;
+
+    invoke_token(accounts, vec![SET_AUTHORITY, AUTHORITY_TYPE_MINT_TOKENS, 0], vec![
+        AccountMeta::new(accounts.mint.key(), false),
+        AccountMeta::new_readonly(accounts.authority.key(), true),
+    ], authority_signer_seeds)
+}
+
+fn invoke_token(accounts: &SoulboundReceiptAccounts, data: Vec<u8>, metas: Vec<AccountMeta>, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    let ix = Instruction { program_id: ID, accounts: metas, data };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.mint.clone(),
+            accounts.receipt_token.clone(),
+            accounts.authority.clone(),
+            accounts.token_program.clone(),
+        ],
+        signer_seeds,
+    ).map_err(Into::into)
+}
@@ -0,0 +1,76 @@
+//! CPI helpers for the yield vaults and staking programs escrowed tokens can be deposited into.
+//!
+//! Any program exposing Anchor style `deposit(amount)`, `withdraw(amount)` and `redeem(shares)`
+//! instructions over the accounts of [`VaultAccounts`] (in that order) can be used as a vault.
+//! `deposit` and `withdraw` are denominated in the underlying token, `redeem` burns share tokens.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::hash::hash;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::invoke_signed;
+
+pub struct VaultAccounts<'info> {
+    pub vault_program: AccountInfo<'info>,
+    pub vault: AccountInfo<'info>,
+    /// The vault token account holding the underlying tokens
+    pub vault_reserve: AccountInfo<'info>,
+    pub share_mint: AccountInfo<'info>,
+    /// The share token account owned by the vesting account
+    pub share_token: AccountInfo<'info>,
+    /// The underlying token account tokens are deposited from or withdrawn to
+    pub underlying_token: AccountInfo<'info>,
+    pub authority: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+}
+
+pub fn deposit(accounts: &VaultAccounts, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    invoke_vault("deposit", accounts, amount, signer_seeds)
+}
+
+pub fn withdraw(accounts: &VaultAccounts, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    invoke_vault("withdraw", accounts, amount, signer_seeds)
+}
+
+pub fn redeem(accounts: &VaultAccounts, shares: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    invoke_vault("redeem", accounts, shares, signer_seeds)
+}
+
+fn invoke_vault(name: &str, accounts: &VaultAccounts, amount: u64, signer_seeds: &[&[&[u8]]]) -> Result<()> {
+    let mut data = sighash(name).to_vec();
+    data.extend_from_slice(&amount.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: accounts.vault_program.key(),
+        accounts: vec![
+            AccountMeta::new(accounts.vault.key(), false),
+            AccountMeta::new(accounts.vault_reserve.key(), false),
+            AccountMeta::new(accounts.share_mint.key(), false),
+            AccountMeta::new(accounts.share_token.key(), false),
+            AccountMeta::new(accounts.underlying_token.key(), false),
+            AccountMeta::new_readonly(accounts.authority.key(), true),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.vault.clone(),
+            accounts.vault_reserve.clone(),
+            accounts.share_mint.clone(),
+            accounts.share_token.clone(),
+            accounts.underlying_token.clone(),
+            accounts.authority.clone(),
+            accounts.token_program.clone(),
+            accounts.vault_program.clone(),
+        ],
+        signer_seeds,
+    ).map_err(Into::into)
+}
+
+fn sighash(name: &str) -> [u8; 8] {
+    let preimage = format!("global:{}", name);
+    let mut sighash = [0u8; 8];
+    sighash.copy_from_slice(&hash(preimage.as_bytes()).to_bytes()[..8]);
+    sighash
+}
@@ -0,0 +1,16 @@
+//! Structured logs of the decisions `create` and `unlock` take, for debugging devnet deployments.
+//!
+//! `verbose_log!` logs one `event key=value ...` line with `msg!` when the `verbose-logs` feature is enabled and
+//! compiles to nothing otherwise, its values not even being evaluated, so production builds keep their compute and
+//! log budget. Events are named after the instruction and the decision, e.g.
+//! `unlock.transfer amount=250 escrow_balance=1000 final_sweep=false`.
+
+/// Logs `$event` followed by `key=value` pairs under the `verbose-logs` feature
+macro_rules! verbose_log {
+    ($event:literal $(, $key:ident = $value:expr)* $(,)?) => {
+        #[cfg(feature = "verbose-logs")]
+        anchor_lang::prelude::msg!(concat!($event $(, " ", stringify!($key), "={}")*) $(, $value)*);
+    };
+}
+
+pub(crate) use verbose_log;
@@ -0,0 +1,157 @@
+//! CPI helper bridging native SPL tokens through the Wormhole token bridge.
+//!
+//! The Wormhole SDK crates target a newer Anchor, so `transfer_native` is packed by hand following
+//! the borsh encoded token bridge instruction enum. The token bridge pulls the tokens through its
+//! `authority_signer` PDA, which has to be approved as a delegate of the source account first.
+
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::instruction::{AccountMeta, Instruction};
+use anchor_lang::solana_program::program::{invoke, invoke_signed};
+use anchor_lang::solana_program::{system_instruction, sysvar};
+use std::convert::TryInto;
+
+pub mod token_bridge_program {
+    anchor_lang::declare_id!("wormDTUJ6AWPNvk59vGQbDvGJmqbDTdgWgAqcLBCgUb");
+}
+
+pub mod core_bridge_program {
+    anchor_lang::declare_id!("worm2ZoG2kUd4vFXhvjh93UUH596ayRfgQ2MgjNMTth");
+}
+
+const TRANSFER_NATIVE: u8 = 5;
+// BridgeData: guardian_set_index u32, last_lamports u64, config.guardian_set_expiration_time u32, config.fee u64
+const BRIDGE_FEE_OFFSET: usize = 16;
+
+pub struct TransferNativeAccounts<'info> {
+    pub payer: AccountInfo<'info>,
+    pub config: AccountInfo<'info>,
+    pub from: AccountInfo<'info>,
+    pub mint: AccountInfo<'info>,
+    pub custody: AccountInfo<'info>,
+    pub authority_signer: AccountInfo<'info>,
+    pub custody_signer: AccountInfo<'info>,
+    pub bridge: AccountInfo<'info>,
+    /// A new account signing the transaction, holding the posted message
+    pub message: AccountInfo<'info>,
+    pub emitter: AccountInfo<'info>,
+    pub sequence: AccountInfo<'info>,
+    pub fee_collector: AccountInfo<'info>,
+    pub clock: AccountInfo<'info>,
+    pub rent: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+    pub core_bridge_program: AccountInfo<'info>,
+    pub token_program: AccountInfo<'info>,
+    pub token_bridge_program: AccountInfo<'info>,
+}
+
+/// Returns the token bridge PDA approved to pull tokens out of the source account
+pub fn authority_signer() -> Pubkey {
+    Pubkey::find_program_address(&[b"authority_signer"], &token_bridge_program::ID).0
+}
+
+/// Returns the token bridge config
+pub fn config() -> Pubkey {
+    Pubkey::find_program_address(&[b"config"], &token_bridge_program::ID).0
+}
+
+/// Returns the token bridge account holding the bridged tokens of `mint`
+pub fn custody(mint: &Pubkey) -> Pubkey {
+    Pubkey::find_program_address(&[mint.as_ref()], &token_bridge_program::ID).0
+}
+
+/// Returns the token bridge PDA owning the custody accounts
+pub fn custody_signer() -> Pubkey {
+    Pubkey::find_program_address(&[b"custody_signer"], &token_bridge_program::ID).0
+}
+
+/// Returns the token bridge PDA emitting the transfer messages
+pub fn emitter() -> Pubkey {
+    Pubkey::find_program_address(&[b"emitter"], &token_bridge_program::ID).0
+}
+
+/// Returns the core bridge state, holding the message fee
+pub fn bridge() -> Pubkey {
+    Pubkey::find_program_address(&[b"Bridge"], &core_bridge_program::ID).0
+}
+
+/// Returns the core bridge sequence of the token bridge emitter
+pub fn sequence() -> Pubkey {
+    Pubkey::find_program_address(&[b"Sequence", emitter().as_ref()], &core_bridge_program::ID).0
+}
+
+/// Returns the core bridge account collecting the message fees
+pub fn fee_collector() -> Pubkey {
+    Pubkey::find_program_address(&[b"fee_collector"], &core_bridge_program::ID).0
+}
+
+/// Pays the core bridge message fee from the payer, then bridges `amount` of `from` to `recipient_address`
+/// on `recipient_chain`, without relayer fee
+pub fn transfer_native(accounts: &TransferNativeAccounts, nonce: u32, amount: u64, recipient_address: [u8; 32], recipient_chain: u16) -> Result<()> {
+    require!(*accounts.bridge.owner == core_bridge_program::ID, crate::VestingError::InvalidBridgeAccounts);
+    let fee = {
+        let data = accounts.bridge.try_borrow_data()?;
+        let fee = data.get(BRIDGE_FEE_OFFSET..BRIDGE_FEE_OFFSET + 8).ok_or(crate::VestingError::InvalidBridgeAccounts)?;
+        u64::from_le_bytes(fee.try_into().unwrap())
+    };
+    if fee > 0 {
+        invoke(
+            &system_instruction::transfer(accounts.payer.key, accounts.fee_collector.key, fee),
+            &[accounts.payer.clone(), accounts.fee_collector.clone(), accounts.system_program.clone()],
+        )?;
+    }
+
+    let mut data = vec![TRANSFER_NATIVE];
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&0u64.to_le_bytes()); // relayer fee
+    data.extend_from_slice(&recipient_address);
+    data.extend_from_slice(&recipient_chain.to_le_bytes());
+
+    let ix = Instruction {
+        program_id: token_bridge_program::ID,
+        accounts: vec![
+            AccountMeta::new(accounts.payer.key(), true),
+            AccountMeta::new_readonly(accounts.config.key(), false),
+            AccountMeta::new(accounts.from.key(), false),
+            AccountMeta::new(accounts.mint.key(), false),
+            AccountMeta::new(accounts.custody.key(), false),
+            AccountMeta::new_readonly(accounts.authority_signer.key(), false),
+            AccountMeta::new_readonly(accounts.custody_signer.key(), false),
+            AccountMeta::new(accounts.bridge.key(), false),
+            AccountMeta::new(accounts.message.key(), true),
+            AccountMeta::new_readonly(accounts.emitter.key(), false),
+            AccountMeta::new(accounts.sequence.key(), false),
+            AccountMeta::new(accounts.fee_collector.key(), false),
+            AccountMeta::new_readonly(sysvar::clock::ID, false),
+            AccountMeta::new_readonly(sysvar::rent::ID, false),
+            AccountMeta::new_readonly(accounts.system_program.key(), false),
+            AccountMeta::new_readonly(accounts.core_bridge_program.key(), false),
+            AccountMeta::new_readonly(accounts.token_program.key(), false),
+        ],
+        data,
+    };
+    invoke_signed(
+        &ix,
+        &[
+            accounts.payer.clone(),
+            accounts.config.clone(),
+            accounts.from.clone(),
+            accounts.mint.clone(),
+            accounts.custody.clone(),
+            accounts.authority_signer.clone(),
+            accounts.custody_signer.clone(),
+            accounts.bridge.clone(),
+            accounts.message.clone(),
+            accounts.emitter.clone(),
+            accounts.sequence.clone(),
+            accounts.fee_collector.clone(),
+            accounts.clock.clone(),
+            accounts.rent.clone(),
+            accounts.system_program.clone(),
+            accounts.core_bridge_program.clone(),
+            accounts.token_program.clone(),
+            accounts.token_bridge_program.clone(),
+        ],
+        &[],
+    ).map_err(Into::into)
+}
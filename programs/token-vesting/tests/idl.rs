@@ -0,0 +1,93 @@
+//! The IDL Anchor parses from the program source, checked to describe everything the program stores, emits and
+//! fails with, so clients generated from it decode every account, event and error.
+
+use anchor_syn::idl::file::parse;
+use anchor_syn::idl::{Idl, IdlType};
+use anchor_syn::parser::context::CrateContext;
+use std::collections::HashSet;
+
+const LIB: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/lib.rs");
+
+fn idl() -> Idl {
+    parse(LIB, env!("CARGO_PKG_VERSION").to_string(), true, false).unwrap().expect("the program module")
+}
+
+fn collect_defined(value: &serde_json::Value, defined: &mut HashSet<String>) {
+    match value {
+        serde_json::Value::Object(map) => {
+            if let Some(serde_json::Value::String(name)) = map.get("defined") {
+                defined.insert(name.clone());
+            }
+            map.values().for_each(|v| collect_defined(v, defined));
+        }
+        serde_json::Value::Array(values) => values.iter().for_each(|v| collect_defined(v, defined)),
+        _ => {}
+    }
+}
+
+#[test]
+fn defined_types_resolve() {
+    let idl = idl();
+    let mut defined = HashSet::new();
+    collect_defined(&serde_json::to_value(&idl).unwrap(), &mut defined);
+    let declared: HashSet<String> = idl.accounts.iter().chain(&idl.types).map(|t| t.name.clone()).collect();
+    let unresolved: Vec<_> = defined.difference(&declared).collect();
+    assert!(unresolved.is_empty(), "types missing from the IDL: {:?}", unresolved);
+}
+
+#[test]
+fn accounts_events_and_types_are_listed_once() {
+    let idl = idl();
+    let ctx = CrateContext::parse(LIB).unwrap();
+    let accounts: HashSet<String> = idl.accounts.iter().map(|a| a.name.clone()).collect();
+    let events: HashSet<String> = idl.events.as_ref().unwrap().iter().map(|e| e.name.clone()).collect();
+    let types: HashSet<String> = idl.types.iter().map(|t| t.name.clone()).collect();
+    assert_eq!(accounts.len(), idl.accounts.len(), "duplicate account names");
+    assert_eq!(types.len(), idl.types.len(), "duplicate type names");
+    assert!(accounts.is_disjoint(&types), "accounts also listed as types");
+
+    for item in ctx.structs() {
+        let name = item.ident.to_string();
+        for attr in &item.attrs {
+            let attr_name = attr.path.segments.last().unwrap().ident.to_string();
+            let tokens = attr.tokens.to_string();
+            match attr_name.as_str() {
+                "account" | "zero_copy" => assert!(accounts.contains(&name), "account {} missing from the IDL", name),
+                "event" => assert!(events.contains(&name), "event {} missing from the IDL", name),
+                "derive" if tokens.contains("AnchorSerialize") => assert!(types.contains(&name), "type {} missing from the IDL", name),
+                _ => {}
+            }
+        }
+    }
+    for item in ctx.enums() {
+        let name = item.ident.to_string();
+        if item.attrs.iter().any(|a| a.path.is_ident("derive") && a.tokens.to_string().contains("AnchorSerialize")) {
+            assert!(types.contains(&name), "type {} missing from the IDL", name);
+        }
+    }
+}
+
+#[test]
+fn errors_are_listed_in_order() {
+    let idl = idl();
+    let ctx = CrateContext::parse(LIB).unwrap();
+    let error_code = ctx.enums().find(|e| e.ident == "VestingError").unwrap();
+    let errors = idl.errors.unwrap();
+    assert_eq!(errors.len(), error_code.variants.len());
+    for (i, (error, variant)) in errors.iter().zip(&error_code.variants).enumerate() {
+        assert_eq!(error.code, 6000 + i as u32);
+        assert_eq!(error.name, variant.ident.to_string());
+        assert!(error.msg.is_some(), "error {} has no message", error.name);
+    }
+}
+
+#[test]
+fn constants_are_listed_with_client_types() {
+    let idl = idl();
+    let ctx = CrateContext::parse(LIB).unwrap();
+    let marked = ctx.consts().filter(|c| c.attrs.iter().any(|a| a.path.is_ident("constant"))).count();
+    assert_eq!(idl.constants.len(), marked);
+    for constant in &idl.constants {
+        assert!(!matches!(constant.ty, IdlType::Defined(_)), "constant {} has no IDL type", constant.name);
+    }
+}
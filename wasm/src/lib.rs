@@ -0,0 +1,109 @@
+//! WASM bindings of the schedule math, for web frontends previewing claims.
+//!
+//! The math module of the program is compiled in from its source rather than depended upon, which would pull
+//! Anchor and the Solana runtime into the bundle, so previews use exactly the program's arithmetic.
+//! Build with `wasm-pack build wasm`. Amounts and times are `bigint` on the JavaScript side.
+
+use wasm_bindgen::prelude::*;
+
+#[path = "../../programs/token-vesting/src/math.rs"]
+mod math;
+
+#[derive(Clone)]
+struct Tranche {
+    release_time: u64,
+    amount: u64,
+    is_gated: bool,
+}
+
+impl math::Tranche for Tranche {
+    fn release_time(&self) -> u64 {
+        self.release_time
+    }
+
+    fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    fn is_gated(&self) -> bool {
+        self.is_gated
+    }
+}
+
+/// The tranches of a contract, as read from its account
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct Schedule {
+    tranches: Vec<Tranche>,
+}
+
+#[wasm_bindgen]
+impl Schedule {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> Schedule {
+        Schedule::default()
+    }
+
+    /// Appends a tranche, gated when its release condition hasn't been met yet
+    pub fn push(&mut self, release_time: u64, amount: u64, is_gated: bool) {
+        self.tranches.push(Tranche { release_time, amount, is_gated });
+    }
+
+    /// The amount the tranches still hold, undefined on overflow
+    #[wasm_bindgen(js_name = totalAmount)]
+    pub fn total_amount(&self) -> Option<u64> {
+        math::total_amount(&self.tranches)
+    }
+
+    /// The amount released at `timestamp` and not claimed yet
+    #[wasm_bindgen(js_name = releasedAmount)]
+    pub fn released_amount(&self, timestamp: i64) -> u64 {
+        math::released_amount(&self.tranches, timestamp)
+    }
+
+    /// The amount still locked at `timestamp`
+    #[wasm_bindgen(js_name = lockedAmount)]
+    pub fn locked_amount(&self, timestamp: i64) -> u64 {
+        math::locked_amount(&self.tranches, timestamp)
+    }
+
+    /// The amount a claim at `timestamp` releases, 0 below the minimum claim of the contract
+    #[wasm_bindgen(js_name = claimableAmount)]
+    pub fn claimable_amount(&self, min_claim_amount: u64, timestamp: i64) -> u64 {
+        math::claimable_amount(&self.tranches, min_claim_amount, timestamp)
+    }
+
+    /// The release time of the next locked tranche at `timestamp`, undefined once everything is released
+    #[wasm_bindgen(js_name = nextReleaseTime)]
+    pub fn next_release_time(&self, timestamp: i64) -> Option<u64> {
+        math::next_locked_tranche(&self.tranches, timestamp).map(|(release_time, _)| release_time)
+    }
+
+    /// The amount released at the next release time, tranches sharing it summed
+    #[wasm_bindgen(js_name = nextReleaseAmount)]
+    pub fn next_release_amount(&self, timestamp: i64) -> Option<u64> {
+        math::next_locked_tranche(&self.tranches, timestamp).map(|(_, amount)| amount)
+    }
+}
+
+/// The amount of `total` released linearly between `start` and `end` at `timestamp`, floored
+#[wasm_bindgen(js_name = linearReleased)]
+pub fn linear_released(total: u64, start: u64, end: u64, timestamp: u64) -> u64 {
+    math::linear_released(total, start, end, timestamp)
+}
+
+/// Generates `count` linear tranches of `total` every `interval` seconds after `start`, flattened as
+/// `[release_time, amount, ...]`
+#[wasm_bindgen(js_name = linearTranches)]
+pub fn linear_tranches(total: u64, start: u64, interval: u64, count: u64) -> Vec<u64> {
+    math::linear_tranches(total, start, interval, count)
+        .flat_map(|(release_time, amount)| vec![release_time, amount])
+        .collect()
+}
+
+/// Splits `total` across the weights of a schedule template as `create_from_template` does, the last part taking the
+/// rounding rest
+#[wasm_bindgen(js_name = splitByWeights)]
+pub fn split_by_weights(total: u64, weights: Vec<u64>) -> Vec<u64> {
+    math::split_by_weights(total, &weights).collect()
+}